@@ -7,10 +7,14 @@ use futuresdr::blocks::Copy;
 use futuresdr::blocks::Head;
 use futuresdr::blocks::NullSource;
 use futuresdr::blocks::VectorSinkBuilder;
+#[cfg(feature = "chain_scheduler")]
+use futuresdr::runtime::scheduler::ChainScheduler;
+use futuresdr::runtime::scheduler::Scheduler;
+use futuresdr::runtime::scheduler::SmolScheduler;
 use futuresdr::runtime::Flowgraph;
 use futuresdr::runtime::Runtime;
 
-fn run_fg(n_samp: u64) -> Result<()> {
+fn build_fg(n_samp: u64) -> Result<Flowgraph> {
     let mut fg = Flowgraph::new();
 
     let null_source = fg.add_block(NullSource::<f32>::new());
@@ -22,51 +26,67 @@ fn run_fg(n_samp: u64) -> Result<()> {
     fg.connect_stream(head, "out", copy, "in")?;
     fg.connect_stream(copy, "out", vect_sink, "in")?;
 
-    Runtime::new().run(fg)?;
+    Ok(fg)
+}
+
+fn run_fg<S: Scheduler, F: Fn() -> S>(scheduler: &F, n_samp: u64) -> Result<()> {
+    let fg = build_fg(n_samp)?;
+    Runtime::with_scheduler(scheduler()).run(fg)?;
     Ok(())
 }
 
-fn run_fg_timed(n_samp: u64, iters: u64) -> Result<Duration> {
+fn run_fg_timed<S: Scheduler, F: Fn() -> S>(
+    scheduler: &F,
+    n_samp: u64,
+    iters: u64,
+) -> Result<Duration> {
     let mut duration = Duration::from_secs(0);
     for _ in 0..iters {
-        let mut fg = Flowgraph::new();
-
-        let null_source = fg.add_block(NullSource::<f32>::new());
-        let head = fg.add_block(Head::<f32>::new(n_samp));
-        let copy = fg.add_block(Copy::<f32>::new());
-        let vect_sink = fg.add_block(VectorSinkBuilder::<f32>::new().build());
-
-        fg.connect_stream(null_source, "out", head, "in")?;
-        fg.connect_stream(head, "out", copy, "in")?;
-        fg.connect_stream(copy, "out", vect_sink, "in")?;
+        let fg = build_fg(n_samp)?;
+        let runtime = Runtime::with_scheduler(scheduler());
 
         let now = Instant::now();
-        Runtime::new().run(fg)?;
+        runtime.run(fg)?;
         duration += now.elapsed();
     }
 
     Ok(duration)
 }
 
-pub fn flowgraph(c: &mut Criterion) {
+fn bench_scheduler<S: Scheduler, F: Fn() -> S>(c: &mut Criterion, name: &str, scheduler: F) {
     let n_samp = 123456;
 
-    let mut group = c.benchmark_group("flowgraph");
+    let mut group = c.benchmark_group(name);
 
     group.throughput(criterion::Throughput::Elements(n_samp));
 
     group.bench_function(format!("overall-{n_samp}"), |b| {
         b.iter(|| {
-            run_fg(black_box(n_samp)).unwrap();
+            run_fg(&scheduler, black_box(n_samp)).unwrap();
         });
     });
 
     group.bench_function(format!("run-{n_samp}"), |b| {
-        b.iter_custom(|iters: u64| run_fg_timed(black_box(n_samp), black_box(iters)).unwrap());
+        b.iter_custom(|iters: u64| {
+            run_fg_timed(&scheduler, black_box(n_samp), black_box(iters)).unwrap()
+        });
     });
 
     group.finish();
 }
 
+pub fn flowgraph(c: &mut Criterion) {
+    bench_scheduler(c, "flowgraph", SmolScheduler::default);
+}
+
+/// Run with `cargo bench --bench flowgraph --features chain_scheduler`
+#[cfg(feature = "chain_scheduler")]
+pub fn flowgraph_chain(c: &mut Criterion) {
+    bench_scheduler(c, "flowgraph-chain", ChainScheduler::default);
+}
+
+#[cfg(not(feature = "chain_scheduler"))]
 criterion_group!(benches, flowgraph);
+#[cfg(feature = "chain_scheduler")]
+criterion_group!(benches, flowgraph, flowgraph_chain);
 criterion_main!(benches);
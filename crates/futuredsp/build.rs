@@ -1,7 +1,18 @@
-use rustc_version::{version_meta, Channel};
+use rustc_version::{version_meta, Channel, Version};
 
 fn main() {
-    match version_meta().unwrap().channel {
+    for cfg in [
+        "RUSTC_IS_STABLE",
+        "RUSTC_IS_BETA",
+        "RUSTC_IS_NIGHTLY",
+        "RUSTC_IS_DEV",
+        "RUSTC_HAS_AVX512",
+    ] {
+        println!("cargo:rustc-check-cfg=cfg({})", cfg);
+    }
+
+    let version_meta = version_meta().unwrap();
+    match version_meta.channel {
         Channel::Stable => {
             println!("cargo:rustc-cfg=RUSTC_IS_STABLE");
         }
@@ -15,4 +26,8 @@ fn main() {
             println!("cargo:rustc-cfg=RUSTC_IS_DEV");
         }
     }
+    // AVX-512 intrinsics are stable since Rust 1.89
+    if version_meta.semver >= Version::new(1, 89, 0) {
+        println!("cargo:rustc-cfg=RUSTC_HAS_AVX512");
+    }
 }
@@ -0,0 +1,391 @@
+//! FFT-based FIR filters
+//!
+//! For long filters, it is considerably cheaper to filter in the frequency domain than to
+//! compute the convolution directly. The kernel in this module implements the overlap-save
+//! method: blocks of `fft_size` input samples are transformed, multiplied with the spectrum
+//! of the taps, and transformed back, yielding `fft_size - num_taps + 1` output samples per
+//! block.
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+use crate::{ComputationStatus, StatefulUnaryKernel};
+use num_complex::Complex;
+use rustfft::{Fft, FftPlanner};
+
+/// An FFT-based (overlap-save) FIR filter.
+///
+/// The filter produces the same output as a
+/// [`NonResamplingFirKernel`](crate::fir::NonResamplingFirKernel) with the same taps, i.e.,
+/// `num_taps - 1` samples less than it consumes. Since the filter operates on blocks of
+/// samples, the input is buffered internally. Output samples are, therefore, only available
+/// once a full block is accumulated. Call [`flush`](Self::flush) at the end of the stream to
+/// process the last, incomplete block.
+///
+/// Implementations of this core exist for the following combinations:
+/// - `f32` samples, `f32` taps.
+/// - `Complex<f32>` samples, `f32` taps.
+/// - `Complex<f32>` samples, `Complex<f32>` taps.
+///
+/// Example usage:
+/// ```
+/// use futuredsp::StatefulUnaryKernel;
+/// use futuredsp::fftfir::FftFirKernel;
+///
+/// let mut fir = FftFirKernel::<f32, f32>::with_fft_size(vec![1.0, 2.0, 3.0], 8);
+///
+/// let input = [1.0, 2.0, 3.0, 4.0];
+/// let mut output = [0.0; 2];
+/// fir.work(&input, &mut output);
+/// fir.flush();
+/// fir.work(&[], &mut output);
+/// assert_eq!(output, [10.0, 16.0]);
+/// ```
+pub struct FftFirKernel<SampleType, TapType> {
+    taps: Vec<TapType>,
+    fft_size: usize,
+    fft: Arc<dyn Fft<f32>>,
+    ifft: Arc<dyn Fft<f32>>,
+    taps_fft: Vec<Complex<f32>>,
+    window: Vec<Complex<f32>>,
+    filled: usize,
+    buffer: Vec<Complex<f32>>,
+    pending: core::ops::Range<usize>,
+    scratch: Vec<Complex<f32>>,
+    _sample_type: core::marker::PhantomData<SampleType>,
+}
+
+impl<SampleType, TapType> FftFirKernel<SampleType, TapType>
+where
+    TapType: Copy + Into<Complex<f32>>,
+{
+    /// Create a new FFT-based FIR filter using the given taps.
+    ///
+    /// The FFT size is chosen as a power of two of at least four times the number of taps.
+    pub fn new(taps: Vec<TapType>) -> Self {
+        let fft_size = Self::default_fft_size(taps.len());
+        Self::with_fft_size(taps, fft_size)
+    }
+
+    /// Create a new FFT-based FIR filter with the given FFT size. Panics if the FFT size is
+    /// smaller than the number of taps.
+    pub fn with_fft_size(taps: Vec<TapType>, fft_size: usize) -> Self {
+        assert!(!taps.is_empty(), "the filter needs at least one tap");
+        assert!(
+            fft_size >= taps.len(),
+            "FFT size must not be smaller than the number of taps"
+        );
+
+        let mut planner = FftPlanner::new();
+        let mut kernel = Self {
+            taps: Vec::new(),
+            fft_size,
+            fft: planner.plan_fft_forward(fft_size),
+            ifft: planner.plan_fft_inverse(fft_size),
+            taps_fft: Vec::new(),
+            window: vec![Complex::new(0.0, 0.0); fft_size],
+            filled: 0,
+            buffer: vec![Complex::new(0.0, 0.0); fft_size],
+            pending: 0..0,
+            scratch: Vec::new(),
+            _sample_type: core::marker::PhantomData,
+        };
+        kernel.set_taps(taps);
+        kernel
+    }
+
+    fn default_fft_size(num_taps: usize) -> usize {
+        core::cmp::max(64, (4 * num_taps).next_power_of_two())
+    }
+
+    /// Update the taps of the filter.
+    ///
+    /// Buffered samples are kept. If the number of taps changes, the output, therefore,
+    /// loses or repeats samples once. If the new taps do not fit in the current FFT size,
+    /// the FFT size is increased.
+    pub fn set_taps(&mut self, taps: Vec<TapType>) {
+        assert!(!taps.is_empty(), "the filter needs at least one tap");
+
+        if taps.len() > self.fft_size {
+            self.fft_size = Self::default_fft_size(taps.len());
+            let mut planner = FftPlanner::new();
+            self.fft = planner.plan_fft_forward(self.fft_size);
+            self.ifft = planner.plan_fft_inverse(self.fft_size);
+            self.window.resize(self.fft_size, Complex::new(0.0, 0.0));
+            // pending samples refer to the old buffer
+            let pending: Vec<Complex<f32>> = self.buffer[self.pending.clone()].to_vec();
+            self.buffer.resize(self.fft_size, Complex::new(0.0, 0.0));
+            self.buffer[..pending.len()].copy_from_slice(&pending);
+            self.pending = 0..pending.len();
+        }
+        let scratch_len = core::cmp::max(
+            self.fft.get_inplace_scratch_len(),
+            self.ifft.get_inplace_scratch_len(),
+        );
+        self.scratch.resize(scratch_len, Complex::new(0.0, 0.0));
+
+        // scale the spectrum to account for the unnormalized inverse FFT
+        let scale = 1.0 / self.fft_size as f32;
+        self.taps_fft.clear();
+        self.taps_fft
+            .extend(taps.iter().map(|t| (*t).into() * scale));
+        self.taps_fft.resize(self.fft_size, Complex::new(0.0, 0.0));
+        self.fft
+            .process_with_scratch(&mut self.taps_fft, &mut self.scratch);
+        self.taps = taps;
+    }
+
+    /// Taps of the filter.
+    pub fn taps(&self) -> &[TapType] {
+        &self.taps
+    }
+
+    /// FFT size of the filter.
+    pub fn fft_size(&self) -> usize {
+        self.fft_size
+    }
+
+    /// Process the buffered samples, even if they do not fill a complete block.
+    ///
+    /// This should be called at the end of the stream. The resulting samples are returned by
+    /// subsequent calls to `work()`.
+    pub fn flush(&mut self) {
+        if self.pending.is_empty() && self.filled >= self.taps.len() {
+            let filled = self.filled;
+            self.window[filled..].fill(Complex::new(0.0, 0.0));
+            self.filled = self.fft_size;
+            self.process_block();
+            self.pending.end -= self.fft_size - filled;
+            self.filled = 0;
+        }
+    }
+
+    /// Filter the current window, making its output pending and retaining the overlap.
+    fn process_block(&mut self) {
+        let overlap = self.taps.len() - 1;
+        self.buffer.copy_from_slice(&self.window);
+        self.fft
+            .process_with_scratch(&mut self.buffer, &mut self.scratch);
+        for (b, t) in self.buffer.iter_mut().zip(self.taps_fft.iter()) {
+            *b *= t;
+        }
+        self.ifft
+            .process_with_scratch(&mut self.buffer, &mut self.scratch);
+        self.pending = overlap..self.fft_size;
+
+        self.window.copy_within(self.fft_size - overlap.., 0);
+        self.filled = overlap;
+    }
+
+    fn work_impl<S: Copy>(
+        &mut self,
+        i: &[S],
+        o: &mut [S],
+        to_complex: impl Fn(S) -> Complex<f32>,
+        from_complex: impl Fn(Complex<f32>) -> S,
+    ) -> (usize, usize, ComputationStatus) {
+        let mut consumed = 0;
+        let mut produced = 0;
+
+        loop {
+            let n = core::cmp::min(self.pending.len(), o.len() - produced);
+            for (y, x) in o[produced..produced + n]
+                .iter_mut()
+                .zip(self.buffer[self.pending.clone()].iter())
+            {
+                *y = from_complex(*x);
+            }
+            produced += n;
+            self.pending.start += n;
+            if !self.pending.is_empty() {
+                break;
+            }
+
+            let n = core::cmp::min(self.fft_size - self.filled, i.len() - consumed);
+            for (w, x) in self.window[self.filled..self.filled + n]
+                .iter_mut()
+                .zip(i[consumed..consumed + n].iter())
+            {
+                *w = to_complex(*x);
+            }
+            consumed += n;
+            self.filled += n;
+            if self.filled < self.fft_size {
+                break;
+            }
+            self.process_block();
+        }
+
+        let status = if !self.pending.is_empty() || consumed < i.len() {
+            ComputationStatus::InsufficientOutput
+        } else if produced == o.len() {
+            ComputationStatus::BothSufficient
+        } else {
+            ComputationStatus::InsufficientInput
+        };
+        (consumed, produced, status)
+    }
+}
+
+impl StatefulUnaryKernel<f32, f32> for FftFirKernel<f32, f32> {
+    fn work(&mut self, input: &[f32], output: &mut [f32]) -> (usize, usize, ComputationStatus) {
+        self.work_impl(input, output, |x| Complex::new(x, 0.0), |x| x.re)
+    }
+}
+
+impl<TapType> StatefulUnaryKernel<Complex<f32>, Complex<f32>>
+    for FftFirKernel<Complex<f32>, TapType>
+where
+    TapType: Copy + Into<Complex<f32>> + Send,
+{
+    fn work(
+        &mut self,
+        input: &[Complex<f32>],
+        output: &mut [Complex<f32>],
+    ) -> (usize, usize, ComputationStatus) {
+        self.work_impl(input, output, |x| x, |x| x)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fir::NonResamplingFirKernel;
+    use crate::UnaryKernel;
+
+    fn samples(n: usize) -> Vec<f32> {
+        let mut state = 1u32;
+        (0..n)
+            .map(|_| {
+                state = state.wrapping_mul(1664525).wrapping_add(1013904223);
+                (state >> 8) as f32 / (1u32 << 24) as f32 - 0.5
+            })
+            .collect()
+    }
+
+    /// Run the kernel with small, unaligned buffers.
+    fn run<S: Copy + Default>(kernel: &mut impl StatefulUnaryKernel<S, S>, input: &[S]) -> Vec<S> {
+        let mut output = Vec::new();
+        let mut buf = [S::default(); 23];
+        let mut pos = 0;
+        while pos < input.len() {
+            let end = core::cmp::min(pos + 37, input.len());
+            let (consumed, produced, _) = kernel.work(&input[pos..end], &mut buf);
+            output.extend_from_slice(&buf[..produced]);
+            pos += consumed;
+        }
+        output
+    }
+
+    fn drain<S: Copy + Default>(kernel: &mut impl StatefulUnaryKernel<S, S>) -> Vec<S> {
+        let mut output = Vec::new();
+        let mut buf = [S::default(); 23];
+        loop {
+            let (_, produced, status) = kernel.work(&[], &mut buf);
+            output.extend_from_slice(&buf[..produced]);
+            if status.produced_all_samples() {
+                return output;
+            }
+        }
+    }
+
+    #[test]
+    fn real_matches_fir() {
+        let taps = samples(101);
+        let input = samples(3000);
+        for fft_size in [101, 128, 512, 4096] {
+            let mut fft = FftFirKernel::<f32, f32>::with_fft_size(taps.clone(), fft_size);
+            let mut output = run(&mut fft, &input);
+            fft.flush();
+            output.extend(drain(&mut fft));
+
+            let fir = NonResamplingFirKernel::<f32, f32, _, _>::new(taps.clone());
+            let mut expected = vec![0.0; input.len() - taps.len() + 1];
+            fir.work(&input, &mut expected);
+
+            assert_eq!(output.len(), expected.len());
+            for (a, b) in output.iter().zip(expected.iter()) {
+                assert!((a - b).abs() < 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    fn complex_matches_fir() {
+        let input: Vec<Complex<f32>> = samples(4000)
+            .chunks(2)
+            .map(|c| Complex::new(c[0], c[1]))
+            .collect();
+        let real_taps = samples(64);
+        let complex_taps: Vec<Complex<f32>> = samples(128)
+            .chunks(2)
+            .map(|c| Complex::new(c[0], c[1]))
+            .collect();
+
+        let mut fft = FftFirKernel::<Complex<f32>, f32>::new(real_taps.clone());
+        let mut output = run(&mut fft, &input);
+        fft.flush();
+        output.extend(drain(&mut fft));
+        let fir = NonResamplingFirKernel::<Complex<f32>, Complex<f32>, _, _>::new(real_taps);
+        let mut expected = vec![Complex::new(0.0, 0.0); input.len() - 63];
+        fir.work(&input, &mut expected);
+        assert_eq!(output.len(), expected.len());
+        for (a, b) in output.iter().zip(expected.iter()) {
+            assert!((a - b).norm() < 1e-4);
+        }
+
+        let mut fft = FftFirKernel::<Complex<f32>, Complex<f32>>::new(complex_taps.clone());
+        let mut output = run(&mut fft, &input);
+        fft.flush();
+        output.extend(drain(&mut fft));
+        let mut expected = vec![Complex::new(0.0, 0.0); input.len() - 63];
+        for (k, y) in expected.iter_mut().enumerate() {
+            for (t, tap) in complex_taps.iter().enumerate() {
+                *y += input[k + 63 - t] * tap;
+            }
+        }
+        assert_eq!(output.len(), expected.len());
+        for (a, b) in output.iter().zip(expected.iter()) {
+            assert!((a - b).norm() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn short_input() {
+        let mut fft = FftFirKernel::<f32, f32>::new(vec![1.0; 8]);
+        let mut output = [0.0; 4];
+        assert_eq!(
+            fft.work(&[1.0; 5], &mut output),
+            (5, 0, ComputationStatus::InsufficientInput)
+        );
+        fft.flush();
+        assert_eq!(fft.work(&[], &mut output).1, 0);
+
+        let mut fft = FftFirKernel::<f32, f32>::new(vec![1.0; 8]);
+        fft.work(&[1.0; 10], &mut output);
+        fft.flush();
+        let (_, produced, status) = fft.work(&[], &mut output);
+        assert_eq!(produced, 3);
+        assert_eq!(status, ComputationStatus::InsufficientInput);
+        for y in output[..3].iter() {
+            assert!((y - 8.0).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn set_taps() {
+        let mut fft = FftFirKernel::<f32, f32>::with_fft_size(vec![1.0; 4], 16);
+        let input = vec![1.0; 100];
+        let mut output = run(&mut fft, &input);
+        output.extend(drain(&mut fft));
+        assert!(output.iter().all(|y| (y - 4.0).abs() < 1e-4));
+
+        // does not fit into the FFT size anymore
+        fft.set_taps(vec![2.0; 32]);
+        assert_eq!(fft.fft_size(), 128);
+        let mut output = run(&mut fft, &vec![1.0; 1000]);
+        fft.flush();
+        output.extend(drain(&mut fft));
+        assert!(!output.is_empty());
+        assert!(output.iter().all(|y| (y - 64.0).abs() < 1e-3));
+    }
+}
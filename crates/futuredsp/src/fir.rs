@@ -1,8 +1,11 @@
 //! FIR filters
+use alloc::vec::Vec;
+use core::any::TypeId;
 use core::cmp::Ordering;
 #[cfg(not(RUSTC_IS_STABLE))]
 use core::intrinsics::{fadd_fast, fmul_fast};
 
+use crate::simd::{self, Isa};
 use crate::{ComputationStatus, TapsAccessor, UnaryKernel};
 use num_complex::Complex;
 use num_traits::{Float, Zero};
@@ -12,7 +15,13 @@ use num_traits::{Float, Zero};
 ///
 /// Implementations of this core exist for the following combinations:
 /// - `f32` samples, `f32` taps.
-/// - `Complex<f32>` samples, `f32` taps.
+/// - `f64` samples, `f64` taps.
+/// - `Complex<T>` samples, `T` taps.
+/// - `Complex<T>` samples, `Complex<T>` taps.
+/// - `T` samples, `Complex<T>` taps, `Complex<T>` output.
+///
+/// The implementations for `f32` and `Complex<f32>` samples are vectorized with the best
+/// instruction set supported by the CPU, see [`simd`](crate::simd).
 ///
 /// Example usage:
 /// ```
@@ -30,6 +39,8 @@ where
     TA: TapsAccessor<TapType = TT>,
 {
     taps: TA,
+    reversed_taps: Vec<TT>,
+    isa: Isa,
     _input_type: core::marker::PhantomData<InputType>,
     _output_type: core::marker::PhantomData<OutputType>,
 }
@@ -40,12 +51,60 @@ where
 {
     /// Create a new non-resampling FIR filter using the given taps.
     pub fn new(taps: TA) -> Self {
+        // Safety: the index is smaller than the number of taps
+        let reversed_taps = (0..taps.num_taps())
+            .rev()
+            .map(|t| unsafe { taps.get(t) })
+            .collect();
         Self {
             taps,
+            reversed_taps,
+            isa: Isa::detect(),
             _input_type: core::marker::PhantomData,
             _output_type: core::marker::PhantomData,
         }
     }
+
+    /// Use the given instruction set instead of the detected one. Panics if it is not
+    /// supported by the CPU.
+    pub fn with_isa(mut self, isa: Isa) -> Self {
+        assert!(isa.is_supported(), "{:?} is not supported by the CPU", isa);
+        self.isa = isa;
+        self
+    }
+
+    /// Instruction set of the filter.
+    pub fn isa(&self) -> Isa {
+        self.isa
+    }
+}
+
+/// Multiply-accumulate, using fast-math intrinsics if available.
+#[cfg(not(RUSTC_IS_STABLE))]
+#[inline(always)]
+fn mac<T: Float>(accum: T, sample: T, tap: T) -> T {
+    unsafe { fadd_fast(accum, fmul_fast(sample, tap)) }
+}
+
+/// Multiply-accumulate, using fast-math intrinsics if available.
+#[cfg(RUSTC_IS_STABLE)]
+#[inline(always)]
+fn mac<T: Float>(accum: T, sample: T, tap: T) -> T {
+    accum + sample * tap
+}
+
+/// Number of samples a non-resampling filter with `num_taps` taps can produce.
+fn fir_num_samples(
+    num_taps: usize,
+    num_inputs: usize,
+    num_outputs: usize,
+) -> (usize, ComputationStatus) {
+    let num_producable_samples = (num_inputs + 1).saturating_sub(num_taps);
+    match num_producable_samples.cmp(&num_outputs) {
+        Ordering::Greater => (num_outputs, ComputationStatus::InsufficientOutput),
+        Ordering::Equal => (num_producable_samples, ComputationStatus::BothSufficient),
+        Ordering::Less => (num_producable_samples, ComputationStatus::InsufficientInput),
+    }
 }
 
 /// Internal helper function to abstract away everything but the core computation.
@@ -69,12 +128,7 @@ where
     OutputType: Copy,
     TapsType::TapType: Copy,
 {
-    let num_producable_samples = (i.len() + 1).saturating_sub(taps.num_taps());
-    let (n, status) = match num_producable_samples.cmp(&o.len()) {
-        Ordering::Greater => (o.len(), ComputationStatus::InsufficientOutput),
-        Ordering::Equal => (num_producable_samples, ComputationStatus::BothSufficient),
-        Ordering::Less => (num_producable_samples, ComputationStatus::InsufficientInput),
-    };
+    let (n, status) = fir_num_samples(taps.num_taps(), i.len(), o.len());
 
     unsafe {
         for k in 0..n {
@@ -93,73 +147,75 @@ where
     (n, n, status)
 }
 
-#[cfg(not(RUSTC_IS_STABLE))]
-impl<TA: TapsAccessor<TapType = f32>> UnaryKernel<f32, f32>
-    for NonResamplingFirKernel<f32, f32, TA, f32>
-{
-    fn work(&self, i: &[f32], o: &mut [f32]) -> (usize, usize, ComputationStatus) {
-        fir_kernel_core(
-            &self.taps,
-            i,
-            o,
-            || 0.0,
-            |accum, sample, tap| unsafe { fadd_fast(accum, fmul_fast(sample, tap)) },
-        )
+/// Vectorized counterpart of [fir_kernel_core()], calling `kernel` with the reversed
+/// taps.
+fn fir_kernel_simd<InputType, OutputType, TapType>(
+    isa: Isa,
+    reversed_taps: &[TapType],
+    i: &[InputType],
+    o: &mut [OutputType],
+    kernel: fn(Isa, &[InputType], &[TapType], &mut [OutputType]),
+) -> (usize, usize, ComputationStatus) {
+    let (n, status) = fir_num_samples(reversed_taps.len(), i.len(), o.len());
+    if n > 0 {
+        kernel(isa, i, reversed_taps, &mut o[..n]);
     }
+    (n, n, status)
 }
 
-#[cfg(RUSTC_IS_STABLE)]
-impl<TA: TapsAccessor<TapType = f32>> UnaryKernel<f32, f32>
-    for NonResamplingFirKernel<f32, f32, TA, f32>
-{
-    fn work(&self, i: &[f32], o: &mut [f32]) -> (usize, usize, ComputationStatus) {
-        fir_kernel_core(
-            &self.taps,
-            i,
-            o,
-            || 0.0,
-            |accum, sample, tap| accum + sample * tap,
-        )
+/// Reinterpret a slice of `A` as a slice of `B`, if both are the same type. This selects
+/// the vectorized kernels in the implementations that are generic over the sample type.
+fn cast<A: 'static, B: 'static>(s: &[A]) -> Option<&[B]> {
+    if TypeId::of::<A>() == TypeId::of::<B>() {
+        // Safety: `A` and `B` are the same type
+        Some(unsafe { core::slice::from_raw_parts(s.as_ptr() as *const B, s.len()) })
+    } else {
+        None
     }
 }
 
-#[cfg(not(RUSTC_IS_STABLE))]
-impl<TA: TapsAccessor<TapType = f64>> UnaryKernel<f64, f64>
-    for NonResamplingFirKernel<f64, f64, TA, f64>
+/// Mutable counterpart of [cast()].
+fn cast_mut<A: 'static, B: 'static>(s: &mut [A]) -> Option<&mut [B]> {
+    if TypeId::of::<A>() == TypeId::of::<B>() {
+        // Safety: `A` and `B` are the same type
+        Some(unsafe { core::slice::from_raw_parts_mut(s.as_mut_ptr() as *mut B, s.len()) })
+    } else {
+        None
+    }
+}
+
+impl<TA: TapsAccessor<TapType = f32>> UnaryKernel<f32, f32>
+    for NonResamplingFirKernel<f32, f32, TA, f32>
 {
-    fn work(&self, i: &[f64], o: &mut [f64]) -> (usize, usize, ComputationStatus) {
-        fir_kernel_core(
-            &self.taps,
-            i,
-            o,
-            || 0.0,
-            |accum, sample, tap| unsafe { fadd_fast(accum, fmul_fast(sample, tap)) },
-        )
+    fn work(&self, i: &[f32], o: &mut [f32]) -> (usize, usize, ComputationStatus) {
+        if self.isa != Isa::Scalar {
+            return fir_kernel_simd(self.isa, &self.reversed_taps, i, o, simd::fir_ff);
+        }
+        fir_kernel_core(&self.taps, i, o, || 0.0, mac)
     }
 }
 
-#[cfg(RUSTC_IS_STABLE)]
 impl<TA: TapsAccessor<TapType = f64>> UnaryKernel<f64, f64>
     for NonResamplingFirKernel<f64, f64, TA, f64>
 {
     fn work(&self, i: &[f64], o: &mut [f64]) -> (usize, usize, ComputationStatus) {
-        fir_kernel_core(
-            &self.taps,
-            i,
-            o,
-            || 0.0,
-            |accum, sample, tap| accum + sample * tap,
-        )
+        fir_kernel_core(&self.taps, i, o, || 0.0, mac)
     }
 }
 
-#[cfg(not(RUSTC_IS_STABLE))]
 impl<TA: TapsAccessor<TapType = T>, T> UnaryKernel<Complex<T>, Complex<T>>
     for NonResamplingFirKernel<Complex<T>, Complex<T>, TA, T>
 where
-    T: Float + Send + Sync + Copy + Zero,
+    T: Float + Send + Sync + Copy + Zero + 'static,
 {
     fn work(&self, i: &[Complex<T>], o: &mut [Complex<T>]) -> (usize, usize, ComputationStatus) {
+        if self.isa != Isa::Scalar {
+            if let (Some(taps), Some(i), Some(o)) =
+                (cast(&self.reversed_taps[..]), cast(i), cast_mut(o))
+            {
+                return fir_kernel_simd(self.isa, taps, i, o, simd::fir_cf);
+            }
+        }
         fir_kernel_core(
             &self.taps,
             i,
@@ -169,20 +225,26 @@ where
                 re: T::zero(),
             },
             |accum, sample, tap| Complex {
-                re: unsafe { fadd_fast(accum.re, fmul_fast(sample.re, tap)) },
-                im: unsafe { fadd_fast(accum.im, fmul_fast(sample.im, tap)) },
+                re: mac(accum.re, sample.re, tap),
+                im: mac(accum.im, sample.im, tap),
             },
         )
     }
 }
 
-#[cfg(RUSTC_IS_STABLE)]
-impl<TA: TapsAccessor<TapType = T>, T> UnaryKernel<Complex<T>, Complex<T>>
-    for NonResamplingFirKernel<Complex<T>, Complex<T>, TA, T>
+impl<TA: TapsAccessor<TapType = Complex<T>>, T> UnaryKernel<Complex<T>, Complex<T>>
+    for NonResamplingFirKernel<Complex<T>, Complex<T>, TA, Complex<T>>
 where
-    T: Float + Send + Sync + Copy + Zero,
+    T: Float + Send + Sync + Copy + Zero + 'static,
 {
     fn work(&self, i: &[Complex<T>], o: &mut [Complex<T>]) -> (usize, usize, ComputationStatus) {
+        if self.isa != Isa::Scalar {
+            if let (Some(taps), Some(i), Some(o)) =
+                (cast(&self.reversed_taps[..]), cast(i), cast_mut(o))
+            {
+                return fir_kernel_simd(self.isa, taps, i, o, simd::fir_cc);
+            }
+        }
         fir_kernel_core(
             &self.taps,
             i,
@@ -191,20 +253,24 @@ where
                 im: T::zero(),
                 re: T::zero(),
             },
-            |accum, sample, tap| Complex {
-                re: accum.re + sample.re * tap,
-                im: accum.im + sample.im * tap,
-            },
+            |accum, sample, tap| accum + sample * tap,
         )
     }
 }
 
-impl<TA: TapsAccessor<TapType = Complex<T>>, T> UnaryKernel<Complex<T>, Complex<T>>
-    for NonResamplingFirKernel<Complex<T>, Complex<T>, TA, Complex<T>>
+impl<TA: TapsAccessor<TapType = Complex<T>>, T> UnaryKernel<T, Complex<T>>
+    for NonResamplingFirKernel<T, Complex<T>, TA, Complex<T>>
 where
-    T: Float + Send + Sync + Copy + Zero,
+    T: Float + Send + Sync + Copy + Zero + 'static,
 {
-    fn work(&self, i: &[Complex<T>], o: &mut [Complex<T>]) -> (usize, usize, ComputationStatus) {
+    fn work(&self, i: &[T], o: &mut [Complex<T>]) -> (usize, usize, ComputationStatus) {
+        if self.isa != Isa::Scalar {
+            if let (Some(taps), Some(i), Some(o)) =
+                (cast(&self.reversed_taps[..]), cast(i), cast_mut(o))
+            {
+                return fir_kernel_simd(self.isa, taps, i, o, simd::fir_fc);
+            }
+        }
         fir_kernel_core(
             &self.taps,
             i,
@@ -213,7 +279,10 @@ where
                 im: T::zero(),
                 re: T::zero(),
             },
-            |accum, sample, tap| accum + sample * tap,
+            |accum, sample, tap| Complex {
+                re: mac(accum.re, sample, tap.re),
+                im: mac(accum.im, sample, tap.im),
+            },
         )
     }
 }
@@ -232,7 +301,14 @@ where
 ///
 /// Implementations of this core exist for the following combinations:
 /// - `f32` samples, `f32` taps.
+/// - `f64` samples, `f64` taps.
 /// - `Complex<f32>` samples, `f32` taps.
+/// - `Complex<f64>` samples, `f64` taps.
+/// - `Complex<T>` samples, `Complex<T>` taps.
+/// - `T` samples, `Complex<T>` taps, `Complex<T>` output.
+///
+/// The implementations for `f32` and `Complex<f32>` samples are vectorized with the best
+/// instruction set supported by the CPU, see [`simd`](crate::simd).
 ///
 /// Example usage:
 /// ```
@@ -255,6 +331,8 @@ where
     interp: usize,
     decim: usize,
     taps: TA,
+    banks: Vec<TT>,
+    isa: Isa,
     _input_type: core::marker::PhantomData<InputType>,
     _output_type: core::marker::PhantomData<OutputType>,
 }
@@ -267,14 +345,70 @@ where
     pub fn new(interp: usize, decim: usize, taps: TA) -> Self {
         // Ensure number of taps is divisible by interp
         assert!(taps.num_taps() % interp == 0);
+        // Taps of the polyphase components one after another, each in reversed order
+        let num_taps = taps.num_taps() / interp;
+        let mut banks = Vec::with_capacity(taps.num_taps());
+        for bank in 0..interp {
+            for t in 0..num_taps {
+                // Safety: the index is smaller than the number of taps
+                banks.push(unsafe { taps.get(interp * (num_taps - t - 1) + bank) });
+            }
+        }
         Self {
             interp,
             decim,
             taps,
+            banks,
+            isa: Isa::detect(),
             _input_type: core::marker::PhantomData,
             _output_type: core::marker::PhantomData,
         }
     }
+
+    /// Use the given instruction set instead of the detected one. Panics if it is not
+    /// supported by the CPU.
+    pub fn with_isa(mut self, isa: Isa) -> Self {
+        assert!(isa.is_supported(), "{:?} is not supported by the CPU", isa);
+        self.isa = isa;
+        self
+    }
+
+    /// Instruction set of the filter.
+    pub fn isa(&self) -> Isa {
+        self.isa
+    }
+}
+
+/// Number of samples a resampling filter with `num_taps` taps (in total) can consume and
+/// produce.
+fn resampling_num_samples(
+    interp: usize,
+    decim: usize,
+    num_taps: usize,
+    num_inputs: usize,
+    num_outputs: usize,
+) -> (usize, usize, ComputationStatus) {
+    // Assume same number of taps in all filters
+    let num_taps = num_taps / interp;
+    let num_producable_samples =
+        ((num_inputs + 1).saturating_sub(num_taps) * interp).saturating_sub(1) / decim;
+    // Ensure it is divisible by interpolation factor to avoid keeping track of state
+    let num_producable_samples = (num_producable_samples / interp) * interp;
+    let (num_producable_samples, status) = match num_producable_samples.cmp(&num_outputs) {
+        Ordering::Greater => (
+            (num_outputs / interp) * interp,
+            ComputationStatus::InsufficientOutput,
+        ),
+        Ordering::Equal => (num_producable_samples, ComputationStatus::BothSufficient),
+        Ordering::Less => (num_producable_samples, ComputationStatus::InsufficientInput),
+    };
+    // Compute number of input samples to consume
+    //let n = num_producable_samples.saturating_sub(1) * decim / interp + 1;
+    let n = (num_producable_samples / interp) * decim;
+    // Assert state is 0 so that we do not need to keep track of the state
+    debug_assert!(((num_producable_samples * decim) % interp) == 0);
+
+    (n, num_producable_samples, status)
 }
 
 /// Internal helper function to abstract away everything but the core computation.
@@ -300,23 +434,9 @@ where
     OutputType: Copy,
     TapsType::TapType: Copy,
 {
-    // Assume same number of taps in all filters
     let num_taps = taps.num_taps() / interp;
-    let num_producable_samples =
-        ((i.len() + 1).saturating_sub(num_taps) * interp).saturating_sub(1) / decim;
-    // Ensure it is divisible by interpolation factor to avoid keeping track of state
-    let num_producable_samples = (num_producable_samples / interp) * interp;
-    let (num_producable_samples, status) = match num_producable_samples.cmp(&o.len()) {
-        Ordering::Greater => (
-            (o.len() / interp) * interp,
-            ComputationStatus::InsufficientOutput,
-        ),
-        Ordering::Equal => (num_producable_samples, ComputationStatus::BothSufficient),
-        Ordering::Less => (num_producable_samples, ComputationStatus::InsufficientInput),
-    };
-    // Compute number of input samples to consume
-    //let n = num_producable_samples.saturating_sub(1) * decim / interp + 1;
-    let n = (num_producable_samples / interp) * decim;
+    let (n, num_producable_samples, status) =
+        resampling_num_samples(interp, decim, taps.num_taps(), i.len(), o.len());
 
     unsafe {
         for k in 0..num_producable_samples {
@@ -330,16 +450,55 @@ where
             *o.get_unchecked_mut(k) = sum;
         }
     }
-    // Assert state is 0 so that we do not need to keep track of the state
-    debug_assert!(((num_producable_samples * decim) % interp) == 0);
 
     (n, num_producable_samples, status)
 }
 
+/// Vectorized resampling kernel, see [simd::polyphase_ff()].
+type ResamplingKernel<InputType, TapType, OutputType> =
+    fn(Isa, usize, usize, &[InputType], &[TapType], &mut [OutputType]);
+
+/// Vectorized counterpart of [resampling_fir_kernel_core()], calling `kernel` with the
+/// taps of the polyphase components.
+fn resampling_fir_kernel_simd<InputType, OutputType, TapType>(
+    isa: Isa,
+    interp: usize,
+    decim: usize,
+    banks: &[TapType],
+    i: &[InputType],
+    o: &mut [OutputType],
+    kernel: ResamplingKernel<InputType, TapType, OutputType>,
+) -> (usize, usize, ComputationStatus) {
+    let (n, num_producable_samples, status) =
+        resampling_num_samples(interp, decim, banks.len(), i.len(), o.len());
+    if num_producable_samples > 0 {
+        kernel(
+            isa,
+            interp,
+            decim,
+            i,
+            banks,
+            &mut o[..num_producable_samples],
+        );
+    }
+    (n, num_producable_samples, status)
+}
+
 impl<TA: TapsAccessor<TapType = f32>> UnaryKernel<f32, f32>
     for PolyphaseResamplingFirKernel<f32, f32, TA, f32>
 {
     fn work(&self, i: &[f32], o: &mut [f32]) -> (usize, usize, ComputationStatus) {
+        if self.isa != Isa::Scalar {
+            return resampling_fir_kernel_simd(
+                self.isa,
+                self.interp,
+                self.decim,
+                &self.banks,
+                i,
+                o,
+                simd::polyphase_ff,
+            );
+        }
         resampling_fir_kernel_core(
             self.interp,
             self.decim,
@@ -360,6 +519,17 @@ impl<TA: TapsAccessor<TapType = f32>> UnaryKernel<Complex<f32>, Complex<f32>>
         i: &[Complex<f32>],
         o: &mut [Complex<f32>],
     ) -> (usize, usize, ComputationStatus) {
+        if self.isa != Isa::Scalar {
+            return resampling_fir_kernel_simd(
+                self.isa,
+                self.interp,
+                self.decim,
+                &self.banks,
+                i,
+                o,
+                simd::polyphase_cf,
+            );
+        }
         resampling_fir_kernel_core(
             self.interp,
             self.decim,
@@ -414,6 +584,77 @@ impl<TA: TapsAccessor<TapType = f64>> UnaryKernel<Complex<f64>, Complex<f64>>
     }
 }
 
+impl<TA: TapsAccessor<TapType = Complex<T>>, T> UnaryKernel<Complex<T>, Complex<T>>
+    for PolyphaseResamplingFirKernel<Complex<T>, Complex<T>, TA, Complex<T>>
+where
+    T: Float + Send + Sync + Copy + Zero + 'static,
+{
+    fn work(&self, i: &[Complex<T>], o: &mut [Complex<T>]) -> (usize, usize, ComputationStatus) {
+        if self.isa != Isa::Scalar {
+            if let (Some(banks), Some(i), Some(o)) = (cast(&self.banks[..]), cast(i), cast_mut(o)) {
+                return resampling_fir_kernel_simd(
+                    self.isa,
+                    self.interp,
+                    self.decim,
+                    banks,
+                    i,
+                    o,
+                    simd::polyphase_cc,
+                );
+            }
+        }
+        resampling_fir_kernel_core(
+            self.interp,
+            self.decim,
+            &self.taps,
+            i,
+            o,
+            || Complex {
+                im: T::zero(),
+                re: T::zero(),
+            },
+            |accum, sample, tap| accum + sample * tap,
+        )
+    }
+}
+
+impl<TA: TapsAccessor<TapType = Complex<T>>, T> UnaryKernel<T, Complex<T>>
+    for PolyphaseResamplingFirKernel<T, Complex<T>, TA, Complex<T>>
+where
+    T: Float + Send + Sync + Copy + Zero + 'static,
+{
+    fn work(&self, i: &[T], o: &mut [Complex<T>]) -> (usize, usize, ComputationStatus) {
+        if self.isa != Isa::Scalar {
+            if let (Some(banks), Some(i), Some(o)) = (cast(&self.banks[..]), cast(i), cast_mut(o)) {
+                return resampling_fir_kernel_simd(
+                    self.isa,
+                    self.interp,
+                    self.decim,
+                    banks,
+                    i,
+                    o,
+                    simd::polyphase_fc,
+                );
+            }
+        }
+        resampling_fir_kernel_core(
+            self.interp,
+            self.decim,
+            &self.taps,
+            i,
+            o,
+            || Complex {
+                im: T::zero(),
+                re: T::zero(),
+            },
+            |accum, sample, tap| Complex {
+                re: accum.re + sample * tap.re,
+                im: accum.im + sample * tap.im,
+            },
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -588,4 +829,218 @@ mod tests {
         assert_eq!(output[0], 4.0);
         assert_eq!(output[1], 13.0);
     }
+
+    /// Deterministic samples in [-1, 1)
+    fn samples(n: usize, seed: u32) -> Vec<f32> {
+        let mut state = seed;
+        (0..n)
+            .map(|_| {
+                state = state.wrapping_mul(1664525).wrapping_add(1013904223);
+                (state >> 8) as f32 / (1 << 23) as f32 - 1.0
+            })
+            .collect()
+    }
+
+    fn complex_samples(n: usize, seed: u32) -> Vec<Complex<f32>> {
+        samples(2 * n, seed)
+            .chunks(2)
+            .map(|x| Complex::new(x[0], x[1]))
+            .collect()
+    }
+
+    fn simd_isas() -> Vec<Isa> {
+        Isa::ALL
+            .iter()
+            .copied()
+            .filter(|isa| *isa != Isa::Scalar && isa.is_supported())
+            .collect()
+    }
+
+    #[test]
+    fn simd_fir_kernel() {
+        for isa in simd_isas() {
+            for num_taps in [1, 2, 3, 7, 8, 15, 16, 17, 33, 64, 67] {
+                let taps = samples(num_taps, 1);
+                let input = samples(300, 3);
+                let complex_input = complex_samples(300, 4);
+
+                let scalar = NonResamplingFirKernel::<f32, f32, _, _>::new(taps.clone())
+                    .with_isa(Isa::Scalar);
+                let simd =
+                    NonResamplingFirKernel::<f32, f32, _, _>::new(taps.clone()).with_isa(isa);
+                let mut expected = [0.0; 256];
+                let mut output = [0.0; 256];
+                let res = scalar.work(&input, &mut expected);
+                assert_eq!(simd.work(&input, &mut output), res);
+                for (x, y) in output.iter().zip(expected.iter()) {
+                    assert!((x - y).abs() < 1e-4, "{:?}: {} != {}", isa, x, y);
+                }
+
+                let scalar =
+                    NonResamplingFirKernel::<Complex<f32>, Complex<f32>, _, _>::new(taps.clone())
+                        .with_isa(Isa::Scalar);
+                let simd =
+                    NonResamplingFirKernel::<Complex<f32>, Complex<f32>, _, _>::new(taps.clone())
+                        .with_isa(isa);
+                let mut expected = [Complex::new(0.0, 0.0); 256];
+                let mut output = [Complex::new(0.0, 0.0); 256];
+                let res = scalar.work(&complex_input, &mut expected);
+                assert_eq!(simd.work(&complex_input, &mut output), res);
+                for (x, y) in output.iter().zip(expected.iter()) {
+                    assert!((x - y).norm() < 1e-4, "{:?}: {} != {}", isa, x, y);
+                }
+            }
+            simd_complex_taps::<1>(isa);
+            simd_complex_taps::<2>(isa);
+            simd_complex_taps::<3>(isa);
+            simd_complex_taps::<8>(isa);
+            simd_complex_taps::<9>(isa);
+            simd_complex_taps::<17>(isa);
+            simd_complex_taps::<64>(isa);
+        }
+    }
+
+    fn simd_complex_taps<const N: usize>(isa: Isa) {
+        let taps: [Complex<f32>; N] = complex_samples(N, 2).try_into().unwrap();
+        let input = complex_samples(300, 4);
+
+        let scalar = NonResamplingFirKernel::<Complex<f32>, Complex<f32>, _, _>::new(taps)
+            .with_isa(Isa::Scalar);
+        let simd =
+            NonResamplingFirKernel::<Complex<f32>, Complex<f32>, _, _>::new(taps).with_isa(isa);
+        let mut expected = [Complex::new(0.0, 0.0); 256];
+        let mut output = [Complex::new(0.0, 0.0); 256];
+        let res = scalar.work(&input, &mut expected);
+        assert_eq!(simd.work(&input, &mut output), res);
+        for (x, y) in output.iter().zip(expected.iter()) {
+            assert!((x - y).norm() < 1e-4, "{:?}: {} != {}", isa, x, y);
+        }
+
+        let input = samples(300, 3);
+        let scalar =
+            NonResamplingFirKernel::<f32, Complex<f32>, _, _>::new(taps).with_isa(Isa::Scalar);
+        let simd = NonResamplingFirKernel::<f32, Complex<f32>, _, _>::new(taps).with_isa(isa);
+        let mut expected = [Complex::new(0.0, 0.0); 256];
+        let mut output = [Complex::new(0.0, 0.0); 256];
+        let res = scalar.work(&input, &mut expected);
+        assert_eq!(simd.work(&input, &mut output), res);
+        for (x, y) in output.iter().zip(expected.iter()) {
+            assert!((x - y).norm() < 1e-4, "{:?}: {} != {}", isa, x, y);
+        }
+    }
+
+    #[test]
+    fn complex_taps_f64() {
+        let taps = [Complex::new(1.0, 1.0), Complex::new(0.0, 2.0)];
+        let kernel = NonResamplingFirKernel::<f64, Complex<f64>, _, _>::new(taps);
+        let mut output = [Complex::new(0.0, 0.0); 2];
+        assert_eq!(
+            kernel.work(&[1.0, 2.0, 3.0], &mut output),
+            (2, 2, ComputationStatus::BothSufficient)
+        );
+        assert_eq!(output, [Complex::new(2.0, 4.0), Complex::new(3.0, 7.0)]);
+
+        let kernel =
+            PolyphaseResamplingFirKernel::<Complex<f64>, Complex<f64>, _, _>::new(1, 1, taps);
+        let input = [
+            Complex::new(1.0, 0.0),
+            Complex::new(0.0, 1.0),
+            Complex::new(1.0, 0.0),
+        ];
+        let mut output = [Complex::new(0.0, 0.0); 1];
+        assert_eq!(
+            kernel.work(&input, &mut output),
+            (1, 1, ComputationStatus::BothSufficient)
+        );
+        assert_eq!(output, [Complex::new(-1.0, 3.0)]);
+    }
+
+    #[test]
+    fn simd_resampling_fir_kernel() {
+        for isa in simd_isas() {
+            for (interp, decim) in [(1, 1), (1, 3), (2, 1), (3, 2), (5, 7), (16, 1)] {
+                let taps = samples(interp * 21, 1);
+                let input = samples(300, 3);
+                let complex_input = complex_samples(300, 4);
+
+                let scalar = PolyphaseResamplingFirKernel::<f32, f32, _, _>::new(
+                    interp,
+                    decim,
+                    taps.clone(),
+                )
+                .with_isa(Isa::Scalar);
+                let simd = PolyphaseResamplingFirKernel::<f32, f32, _, _>::new(
+                    interp,
+                    decim,
+                    taps.clone(),
+                )
+                .with_isa(isa);
+                let mut expected = [0.0; 512];
+                let mut output = [0.0; 512];
+                let res = scalar.work(&input, &mut expected);
+                assert_eq!(simd.work(&input, &mut output), res);
+                for (x, y) in output.iter().zip(expected.iter()) {
+                    assert!((x - y).abs() < 1e-4, "{:?}: {} != {}", isa, x, y);
+                }
+
+                let scalar = PolyphaseResamplingFirKernel::<Complex<f32>, Complex<f32>, _, _>::new(
+                    interp,
+                    decim,
+                    taps.clone(),
+                )
+                .with_isa(Isa::Scalar);
+                let simd = PolyphaseResamplingFirKernel::<Complex<f32>, Complex<f32>, _, _>::new(
+                    interp,
+                    decim,
+                    taps.clone(),
+                )
+                .with_isa(isa);
+                let mut expected = [Complex::new(0.0, 0.0); 512];
+                let mut output = [Complex::new(0.0, 0.0); 512];
+                let res = scalar.work(&complex_input, &mut expected);
+                assert_eq!(simd.work(&complex_input, &mut output), res);
+                for (x, y) in output.iter().zip(expected.iter()) {
+                    assert!((x - y).norm() < 1e-4, "{:?}: {} != {}", isa, x, y);
+                }
+
+                // divisible by all interpolation factors
+                let complex_taps: [Complex<f32>; 240] = complex_samples(240, 2).try_into().unwrap();
+                let scalar = PolyphaseResamplingFirKernel::<Complex<f32>, Complex<f32>, _, _>::new(
+                    interp,
+                    decim,
+                    complex_taps,
+                )
+                .with_isa(Isa::Scalar);
+                let simd = PolyphaseResamplingFirKernel::<Complex<f32>, Complex<f32>, _, _>::new(
+                    interp,
+                    decim,
+                    complex_taps,
+                )
+                .with_isa(isa);
+                let res = scalar.work(&complex_input, &mut expected);
+                assert_eq!(simd.work(&complex_input, &mut output), res);
+                for (x, y) in output.iter().zip(expected.iter()) {
+                    assert!((x - y).norm() < 1e-4, "{:?}: {} != {}", isa, x, y);
+                }
+
+                let scalar = PolyphaseResamplingFirKernel::<f32, Complex<f32>, _, _>::new(
+                    interp,
+                    decim,
+                    complex_taps,
+                )
+                .with_isa(Isa::Scalar);
+                let simd = PolyphaseResamplingFirKernel::<f32, Complex<f32>, _, _>::new(
+                    interp,
+                    decim,
+                    complex_taps,
+                )
+                .with_isa(isa);
+                let res = scalar.work(&input, &mut expected);
+                assert_eq!(simd.work(&input, &mut output), res);
+                for (x, y) in output.iter().zip(expected.iter()) {
+                    assert!((x - y).norm() < 1e-4, "{:?}: {} != {}", isa, x, y);
+                }
+            }
+        }
+    }
 }
@@ -693,3 +693,803 @@ pub mod kaiser {
         }
     }
 }
+
+/// FIR filter design methods based on the Parks-McClellan algorithm, which uses the Remez
+/// exchange algorithm to find the optimal equiripple filter. For a given number of taps, the
+/// resulting filters minimize the maximum weighted error w.r.t. the desired response. They
+/// have (generalized) linear phase.
+///
+/// The algorithm is described in:
+/// - J. H. McClellan, T. W. Parks, and L. R. Rabiner "A Computer Program for Designing
+///   Optimum FIR Linear Phase Digital Filters," IEEE Transactions on Audio and
+///   Electroacoustics, vol. AU-21, no. 6, December 1973.
+/// - A. V. Oppenheim and R. W. Schafer "Digital Signal Processing," 3rd Edition.
+pub mod remez {
+    extern crate alloc;
+    use alloc::vec::Vec;
+    use core::f64::consts::PI;
+    use num_traits::FromPrimitive;
+
+    const GRID_DENSITY: usize = 16;
+    const MAX_ITERATIONS: usize = 40;
+
+    /// Type of the filter to design.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum FilterType {
+        /// Filter with even symmetric taps and piecewise constant gain per band, i.e.,
+        /// lowpass, highpass, bandpass, bandstop, or multiband filters.
+        Multiband,
+        /// Differentiator with odd symmetric taps. The desired response in a band with gain
+        /// `g` is `j * g * 2 * pi * f`. The error is weighted relative to the desired response.
+        Differentiator,
+        /// Hilbert transformer with odd symmetric taps. The desired response in a band with
+        /// gain `g` is `-j * g`.
+        Hilbert,
+    }
+
+    /// Frequency band of a filter specification.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct Band {
+        /// Lower edge frequency (in cycles/sample).
+        pub start: f64,
+        /// Upper edge frequency (in cycles/sample).
+        pub end: f64,
+        /// Desired gain in the band.
+        pub gain: f64,
+        /// Weight of the error in the band.
+        pub weight: f64,
+    }
+
+    impl Band {
+        /// Create a band from `start` to `end` (in cycles/sample) with the given `gain` and
+        /// error `weight`.
+        pub fn new(start: f64, end: f64, gain: f64, weight: f64) -> Self {
+            Self {
+                start,
+                end,
+                gain,
+                weight,
+            }
+        }
+    }
+
+    /// Designs an equiripple FIR filter with `num_taps` taps for the given bands, using the
+    /// Parks-McClellan algorithm. Frequencies between the bands are don't care regions.
+    ///
+    /// Filters with even symmetry and an even number of taps have zero gain at 1/2, i.e.,
+    /// highpass and bandstop filters require an odd number of taps. Filters with odd
+    /// symmetry and an odd number of taps have zero gain at 0 and 1/2.
+    ///
+    /// The filter taps are constructed internally as `f64` and then casted to the generic type
+    /// `T` using [`num_traits::FromPrimitive::from_f64()`].
+    ///
+    /// Example usage:
+    /// ```
+    /// use futuredsp::firdes::remez::{self, Band, FilterType};
+    ///
+    /// // lowpass with passband up to 0.1, stopband from 0.15, and a 10 times larger
+    /// // weight for the stopband
+    /// let bands = [Band::new(0.0, 0.1, 1.0, 1.0), Band::new(0.15, 0.5, 0.0, 10.0)];
+    /// let taps = remez::design::<f32>(61, &bands, FilterType::Multiband);
+    /// ```
+    pub fn design<T: FromPrimitive>(
+        num_taps: usize,
+        bands: &[Band],
+        filter_type: FilterType,
+    ) -> Vec<T> {
+        remez(num_taps, bands, filter_type)
+            .0
+            .iter()
+            .map(|x| T::from_f64(*x).unwrap())
+            .collect()
+    }
+
+    /// Designs an equiripple lowpass FIR filter with cutoff frequency `cutoff` and transition
+    /// width `transition_bw` (in cycles/sample). The filter uses the minimum number of taps
+    /// that keeps the ripple in passband and stopband below `max_ripple`.
+    ///
+    /// Example usage:
+    /// ```
+    /// use futuredsp::firdes;
+    ///
+    /// let sampling_freq = 10_000;
+    /// // 2000 Hz cutoff frequency and 500 Hz transtion band
+    /// let cutoff = 2_000.0 / sampling_freq as f64;
+    /// let transition_bw = 500.0 / sampling_freq as f64;
+    /// let max_ripple = 0.001;
+    /// let taps = firdes::remez::lowpass::<f32>(cutoff, transition_bw, max_ripple);
+    /// ```
+    pub fn lowpass<T: FromPrimitive>(cutoff: f64, transition_bw: f64, max_ripple: f64) -> Vec<T> {
+        assert!(cutoff > 0.0, "cutoff must be greater than 0");
+        assert!(transition_bw > 0.0, "transition_bw must be greater than 0");
+        assert!(
+            cutoff + transition_bw < 1.0 / 2.0,
+            "cutoff+transition_bw must be less than 1/2"
+        );
+        let bands = [
+            Band::new(0.0, cutoff, 1.0, 1.0),
+            Band::new(cutoff + transition_bw, 0.5, 0.0, 1.0),
+        ];
+        minimum_length(&bands, transition_bw, max_ripple, false)
+    }
+
+    /// Designs an equiripple highpass FIR filter with cutoff frequency `cutoff` and transition
+    /// width `transition_bw` (in cycles/sample). The filter uses the minimum (odd) number of
+    /// taps that keeps the ripple in passband and stopband below `max_ripple`.
+    ///
+    /// Example usage:
+    /// ```
+    /// use futuredsp::firdes;
+    ///
+    /// let sampling_freq = 10_000;
+    /// // 4000 Hz cutoff frequency and 500 Hz transtion band
+    /// let cutoff = 4_000.0 / sampling_freq as f64;
+    /// let transition_bw = 500.0 / sampling_freq as f64;
+    /// let max_ripple = 0.001;
+    /// let taps = firdes::remez::highpass::<f32>(cutoff, transition_bw, max_ripple);
+    /// ```
+    pub fn highpass<T: FromPrimitive>(cutoff: f64, transition_bw: f64, max_ripple: f64) -> Vec<T> {
+        assert!(cutoff < 0.5, "cutoff must be less than 1/2");
+        assert!(transition_bw > 0.0, "transition_bw must be greater than 0");
+        assert!(
+            cutoff - transition_bw > 0.0,
+            "cutoff-transition_bw must be greater than 0"
+        );
+        let bands = [
+            Band::new(0.0, cutoff - transition_bw, 0.0, 1.0),
+            Band::new(cutoff, 0.5, 1.0, 1.0),
+        ];
+        minimum_length(&bands, transition_bw, max_ripple, true)
+    }
+
+    /// Designs an equiripple bandpass FIR filter with passband from `lower_cutoff` to
+    /// `higher_cutoff` and transition widths `transition_bw` (in cycles/sample). The filter
+    /// uses the minimum number of taps that keeps the ripple in passband and stopbands below
+    /// `max_ripple`.
+    ///
+    /// Example usage:
+    /// ```
+    /// use futuredsp::firdes;
+    ///
+    /// let sampling_freq = 10_000;
+    /// // 1000 Hz lower cutoff frequency, 4000 Hz higher cutoff frequency,
+    /// // and 500 Hz transtion bands
+    /// let lower_cutoff = 1_000.0 / sampling_freq as f64;
+    /// let higher_cutoff = 4_000.0 / sampling_freq as f64;
+    /// let transition_bw = 500.0 / sampling_freq as f64;
+    /// let max_ripple = 0.001;
+    /// let taps = firdes::remez::bandpass::<f32>(lower_cutoff, higher_cutoff, transition_bw, max_ripple);
+    /// ```
+    pub fn bandpass<T: FromPrimitive>(
+        lower_cutoff: f64,
+        higher_cutoff: f64,
+        transition_bw: f64,
+        max_ripple: f64,
+    ) -> Vec<T> {
+        assert!(
+            higher_cutoff > lower_cutoff,
+            "higher_cutoff must be greater than lower_cutoff"
+        );
+        assert!(transition_bw > 0.0, "transition_bw must be greater than 0");
+        assert!(
+            lower_cutoff - transition_bw > 0.0,
+            "lower_cutoff-transition_bw must be greater than 0"
+        );
+        assert!(
+            higher_cutoff + transition_bw < 1.0 / 2.0,
+            "higher_cutoff+transition_bw must be less than 1/2"
+        );
+        let bands = [
+            Band::new(0.0, lower_cutoff - transition_bw, 0.0, 1.0),
+            Band::new(lower_cutoff, higher_cutoff, 1.0, 1.0),
+            Band::new(higher_cutoff + transition_bw, 0.5, 0.0, 1.0),
+        ];
+        minimum_length(&bands, transition_bw, max_ripple, false)
+    }
+
+    /// Designs an equiripple bandstop FIR filter with stopband from `lower_cutoff` to
+    /// `higher_cutoff` and transition widths `transition_bw` (in cycles/sample). The filter
+    /// uses the minimum (odd) number of taps that keeps the ripple in passbands and stopband
+    /// below `max_ripple`.
+    ///
+    /// Example usage:
+    /// ```
+    /// use futuredsp::firdes;
+    ///
+    /// let sampling_freq = 10_000;
+    /// // 1000 Hz lower cutoff frequency, 4000 Hz higher cutoff frequency,
+    /// // and 500 Hz transtion bands
+    /// let lower_cutoff = 1_000.0 / sampling_freq as f64;
+    /// let higher_cutoff = 4_000.0 / sampling_freq as f64;
+    /// let transition_bw = 500.0 / sampling_freq as f64;
+    /// let max_ripple = 0.001;
+    /// let taps = firdes::remez::bandstop::<f32>(lower_cutoff, higher_cutoff, transition_bw, max_ripple);
+    /// ```
+    pub fn bandstop<T: FromPrimitive>(
+        lower_cutoff: f64,
+        higher_cutoff: f64,
+        transition_bw: f64,
+        max_ripple: f64,
+    ) -> Vec<T> {
+        assert!(
+            higher_cutoff > lower_cutoff,
+            "higher_cutoff must be greater than lower_cutoff"
+        );
+        assert!(transition_bw > 0.0, "transition_bw must be greater than 0");
+        assert!(
+            lower_cutoff - transition_bw > 0.0,
+            "lower_cutoff-transition_bw must be greater than 0"
+        );
+        assert!(
+            higher_cutoff + transition_bw < 1.0 / 2.0,
+            "higher_cutoff+transition_bw must be less than 1/2"
+        );
+        let bands = [
+            Band::new(0.0, lower_cutoff - transition_bw, 1.0, 1.0),
+            Band::new(lower_cutoff, higher_cutoff, 0.0, 1.0),
+            Band::new(higher_cutoff + transition_bw, 0.5, 1.0, 1.0),
+        ];
+        minimum_length(&bands, transition_bw, max_ripple, true)
+    }
+
+    /// Designs an equiripple differentiator with `num_taps` taps that approximates the
+    /// derivative up to frequency `bandwidth` (in cycles/sample), i.e., a response of
+    /// `j * 2 * pi * f`. Above `bandwidth`, the response is don't care.
+    ///
+    /// Differentiators with an even number of taps are more accurate, but have a delay of
+    /// half a sample.
+    ///
+    /// Example usage:
+    /// ```
+    /// use futuredsp::firdes;
+    ///
+    /// let taps = firdes::remez::differentiator::<f32>(32, 0.4);
+    /// ```
+    pub fn differentiator<T: FromPrimitive>(num_taps: usize, bandwidth: f64) -> Vec<T> {
+        assert!(
+            bandwidth > 0.0 && bandwidth <= 0.5,
+            "bandwidth must be in (0, 1/2]"
+        );
+        let bands = [Band::new(0.0, bandwidth, 1.0, 1.0)];
+        design(num_taps, &bands, FilterType::Differentiator)
+    }
+
+    /// Designs an equiripple Hilbert transformer with `num_taps` taps and transition width
+    /// `transition_bw` (in cycles/sample) at 0 and, for an odd number of taps, at 1/2.
+    ///
+    /// Hilbert transformers with an odd number of taps have every other tap equal to zero and
+    /// an integer delay.
+    ///
+    /// Example usage:
+    /// ```
+    /// use futuredsp::firdes;
+    ///
+    /// let taps = firdes::remez::hilbert::<f32>(31, 0.02);
+    /// ```
+    pub fn hilbert<T: FromPrimitive>(num_taps: usize, transition_bw: f64) -> Vec<T> {
+        assert!(
+            transition_bw > 0.0 && transition_bw < 0.25,
+            "transition_bw must be in (0, 1/4)"
+        );
+        let end = if num_taps % 2 == 1 {
+            0.5 - transition_bw
+        } else {
+            0.5
+        };
+        let bands = [Band::new(transition_bw, end, 1.0, 1.0)];
+        design(num_taps, &bands, FilterType::Hilbert)
+    }
+
+    /// Searches the minimum number of taps for which the maximum weighted error of the
+    /// design is below `max_ripple`, starting from an estimate.
+    fn minimum_length<T: FromPrimitive>(
+        bands: &[Band],
+        transition_bw: f64,
+        max_ripple: f64,
+        odd: bool,
+    ) -> Vec<T> {
+        assert!(
+            max_ripple > 0.0 && max_ripple < 1.0,
+            "max_ripple must be in (0, 1)"
+        );
+        let step = if odd { 2 } else { 1 };
+        let adjust = |n: usize| if odd { n | 1 } else { n };
+
+        // Estimate from J. F. Kaiser, "Nonrecursive Digital Filter Design using the
+        // I_0-sinh Window Function"
+        let ripple_db = -20.0 * max_ripple.log10();
+        let estimate = ((ripple_db - 13.0) / (14.6 * transition_bw)).ceil() + 1.0;
+        let mut num_taps = adjust(estimate.max(3.0) as usize);
+
+        let (mut taps, deviation) = remez(num_taps, bands, FilterType::Multiband);
+        if deviation <= max_ripple {
+            // shorten as long as the spec is met
+            while num_taps > step + 2 {
+                let (t, d) = remez(num_taps - step, bands, FilterType::Multiband);
+                if d > max_ripple {
+                    break;
+                }
+                num_taps -= step;
+                taps = t;
+            }
+        } else {
+            // lengthen until the spec is met
+            let limit = 4 * num_taps;
+            loop {
+                num_taps += step;
+                let (t, d) = remez(num_taps, bands, FilterType::Multiband);
+                taps = t;
+                if d <= max_ripple {
+                    break;
+                }
+                if num_taps >= limit {
+                    warn!("remez: filter with {num_taps} taps does not meet the specification");
+                    break;
+                }
+            }
+        }
+
+        taps.iter().map(|x| T::from_f64(*x).unwrap()).collect()
+    }
+
+    /// Even or odd symmetry of the taps.
+    #[derive(Clone, Copy, PartialEq, Eq)]
+    enum Symmetry {
+        Even,
+        Odd,
+    }
+
+    /// Lagrange interpolation of the amplitude response through the extremal frequencies in
+    /// barycentric form.
+    struct Interpolator {
+        ad: Vec<f64>,
+        x: Vec<f64>,
+        y: Vec<f64>,
+        delta: f64,
+    }
+
+    impl Interpolator {
+        fn new(ext: &[usize], grid: &[f64], desired: &[f64], weight: &[f64]) -> Self {
+            let r = ext.len() - 1;
+            let x: Vec<f64> = ext.iter().map(|e| (2.0 * PI * grid[*e]).cos()).collect();
+
+            // products are interleaved to avoid over- and underflows
+            let ld = (r - 1) / 15 + 1;
+            let ad: Vec<f64> = (0..=r)
+                .map(|i| {
+                    let mut denom = 1.0;
+                    for j in 0..ld {
+                        for k in (j..=r).step_by(ld) {
+                            if k != i {
+                                denom *= 2.0 * (x[i] - x[k]);
+                            }
+                        }
+                    }
+                    if denom.abs() < 0.00001 {
+                        denom = 0.00001;
+                    }
+                    1.0 / denom
+                })
+                .collect();
+
+            let mut numer = 0.0;
+            let mut denom = 0.0;
+            let mut sign = 1.0;
+            for i in 0..=r {
+                numer += ad[i] * desired[ext[i]];
+                denom += sign * ad[i] / weight[ext[i]];
+                sign = -sign;
+            }
+            let delta = numer / denom;
+
+            let mut sign = 1.0;
+            let y = (0..=r)
+                .map(|i| {
+                    let v = desired[ext[i]] - sign * delta / weight[ext[i]];
+                    sign = -sign;
+                    v
+                })
+                .collect();
+
+            Self { ad, x, y, delta }
+        }
+
+        fn amplitude(&self, freq: f64) -> f64 {
+            let xc = (2.0 * PI * freq).cos();
+            let mut numer = 0.0;
+            let mut denom = 0.0;
+            for i in 0..self.x.len() {
+                let c = xc - self.x[i];
+                if c.abs() < 1.0e-7 {
+                    return self.y[i];
+                }
+                let c = self.ad[i] / c;
+                denom += c;
+                numer += c * self.y[i];
+            }
+            numer / denom
+        }
+    }
+
+    /// Find the `r + 1` alternating extrema of the error function with the largest errors.
+    fn search(r: usize, error: &[f64]) -> Vec<usize> {
+        let n = error.len();
+        let mut ext = Vec::new();
+
+        if (error[0] > 0.0 && error[0] > error[1]) || (error[0] < 0.0 && error[0] < error[1]) {
+            ext.push(0);
+        }
+        for i in 1..n - 1 {
+            if (error[i] >= error[i - 1] && error[i] > error[i + 1] && error[i] > 0.0)
+                || (error[i] <= error[i - 1] && error[i] < error[i + 1] && error[i] < 0.0)
+            {
+                ext.push(i);
+            }
+        }
+        let j = n - 1;
+        if (error[j] > 0.0 && error[j] > error[j - 1])
+            || (error[j] < 0.0 && error[j] < error[j - 1])
+        {
+            ext.push(j);
+        }
+
+        while ext.len() > r + 1 {
+            // remove the smaller of two non-alternating extrema or, if all alternate, the
+            // smaller of the first and the last extremum
+            let k = ext.len();
+            let smaller = |a: usize, b: usize| {
+                if error[ext[a]].abs() < error[ext[b]].abs() {
+                    a
+                } else {
+                    b
+                }
+            };
+            let del = (1..k)
+                .find(|j| (error[ext[*j]] > 0.0) == (error[ext[j - 1]] > 0.0))
+                .map(|j| smaller(j - 1, j))
+                .unwrap_or_else(|| smaller(k - 1, 0));
+            ext.remove(del);
+        }
+        ext
+    }
+
+    /// Run the Parks-McClellan algorithm, returning the taps and the maximum weighted error.
+    fn remez(num_taps: usize, bands: &[Band], filter_type: FilterType) -> (Vec<f64>, f64) {
+        assert!(num_taps > 2, "num_taps must be greater than 2");
+        assert!(!bands.is_empty(), "at least one band must be specified");
+        for (i, b) in bands.iter().enumerate() {
+            assert!(
+                b.start >= 0.0 && b.start < b.end && b.end <= 0.5,
+                "band edges must be increasing and in [0, 1/2]"
+            );
+            assert!(b.weight > 0.0, "band weights must be greater than 0");
+            if i > 0 {
+                assert!(
+                    bands[i - 1].end < b.start,
+                    "bands must be ordered and must not overlap"
+                );
+            }
+        }
+
+        let symmetry = match filter_type {
+            FilterType::Multiband => Symmetry::Even,
+            _ => Symmetry::Odd,
+        };
+        let odd_taps = num_taps % 2 == 1;
+        let mut r = num_taps / 2;
+        if odd_taps && symmetry == Symmetry::Even {
+            r += 1;
+        }
+
+        // dense frequency grid with desired response and weights
+        let delf = 0.5 / (GRID_DENSITY * r) as f64;
+        let mut grid = Vec::new();
+        let mut desired = Vec::new();
+        let mut weight = Vec::new();
+        for (i, b) in bands.iter().enumerate() {
+            let mut start = b.start;
+            if i == 0 && symmetry == Symmetry::Odd && start < delf {
+                // odd symmetric filters have zero gain at 0
+                start = delf;
+            }
+            let mut end = b.end;
+            if (symmetry == Symmetry::Odd) == odd_taps && end > 0.5 - delf {
+                // odd symmetric filters with an odd number of taps and even symmetric filters
+                // with an even number of taps have zero gain at 1/2
+                end = 0.5 - delf;
+            }
+            if start >= end {
+                continue;
+            }
+            let k = (((end - start) / delf + 0.5) as usize).max(1);
+            for j in 0..=k {
+                let f = start + (end - start) * j as f64 / k as f64;
+                grid.push(f);
+                if filter_type == FilterType::Differentiator {
+                    desired.push(b.gain * 2.0 * PI * f);
+                    if b.gain.abs() > 0.0001 {
+                        weight.push(b.weight / f);
+                    } else {
+                        weight.push(b.weight);
+                    }
+                } else {
+                    desired.push(b.gain);
+                    weight.push(b.weight);
+                }
+            }
+        }
+        assert!(
+            grid.len() > r + 1,
+            "bands are too narrow for the number of taps"
+        );
+
+        // express the amplitude response as a polynomial in cos(2 pi f)
+        for i in 0..grid.len() {
+            let c = match (symmetry, odd_taps) {
+                (Symmetry::Even, true) => 1.0,
+                (Symmetry::Even, false) => (PI * grid[i]).cos(),
+                (Symmetry::Odd, true) => (2.0 * PI * grid[i]).sin(),
+                (Symmetry::Odd, false) => (PI * grid[i]).sin(),
+            };
+            desired[i] /= c;
+            weight[i] *= c;
+        }
+
+        // initial guess of the extremal frequencies
+        let n = grid.len();
+        let mut ext: Vec<usize> = (0..=r).map(|i| i * (n - 1) / r).collect();
+        let mut converged = false;
+        for _ in 0..MAX_ITERATIONS {
+            let interp = Interpolator::new(&ext, &grid, &desired, &weight);
+            let error: Vec<f64> = (0..n)
+                .map(|i| weight[i] * (desired[i] - interp.amplitude(grid[i])))
+                .collect();
+            let new_ext = search(r, &error);
+            if new_ext.len() < r + 1 {
+                break;
+            }
+            ext = new_ext;
+
+            let (min, max) = ext.iter().fold((f64::MAX, 0.0f64), |(min, max), e| {
+                (min.min(error[*e].abs()), max.max(error[*e].abs()))
+            });
+            if (max - min) / max < 0.0001 {
+                converged = true;
+                break;
+            }
+        }
+        if !converged {
+            warn!("remez: design with {num_taps} taps did not converge");
+        }
+        let interp = Interpolator::new(&ext, &grid, &desired, &weight);
+
+        // sample the amplitude response and compute the taps through frequency sampling
+        let amplitude: Vec<f64> = (0..=num_taps / 2)
+            .map(|i| {
+                let f = i as f64 / num_taps as f64;
+                let c = match (symmetry, odd_taps) {
+                    (Symmetry::Even, true) => 1.0,
+                    (Symmetry::Even, false) => (PI * f).cos(),
+                    (Symmetry::Odd, true) => (2.0 * PI * f).sin(),
+                    (Symmetry::Odd, false) => (PI * f).sin(),
+                };
+                interp.amplitude(f) * c
+            })
+            .collect();
+        let mut taps = frequency_sampling(num_taps, &amplitude, symmetry);
+
+        // taps above approximate -j A(f), so that the differentiator is j 2 pi f
+        if filter_type == FilterType::Differentiator {
+            for t in taps.iter_mut() {
+                *t = -*t;
+            }
+        }
+
+        (taps, interp.delta.abs())
+    }
+
+    /// Compute the taps of a linear phase filter from samples of its amplitude response
+    fn frequency_sampling(num_taps: usize, amplitude: &[f64], symmetry: Symmetry) -> Vec<f64> {
+        let n = num_taps as f64;
+        let m = (n - 1.0) / 2.0;
+        let half = (num_taps - 1) / 2;
+        (0..num_taps)
+            .map(|i| {
+                let x = 2.0 * PI * (i as f64 - m) / n;
+                let mut val = match symmetry {
+                    Symmetry::Even => amplitude[0],
+                    Symmetry::Odd if num_taps % 2 == 1 => 0.0,
+                    Symmetry::Odd => amplitude[num_taps / 2] * (PI * (i as f64 - m)).sin(),
+                };
+                for (k, a) in amplitude.iter().enumerate().take(half + 1).skip(1) {
+                    val += match symmetry {
+                        Symmetry::Even => 2.0 * a * (x * k as f64).cos(),
+                        Symmetry::Odd => 2.0 * a * (x * k as f64).sin(),
+                    };
+                }
+                val / n
+            })
+            .collect()
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::firdes::kaiser;
+
+        /// Frequency response at `freq` (in cycles/sample) as real and imaginary part
+        fn response(taps: &[f64], freq: f64) -> (f64, f64) {
+            taps.iter()
+                .enumerate()
+                .fold((0.0, 0.0), |(re, im), (n, t)| {
+                    let x = 2.0 * PI * freq * n as f64;
+                    (re + t * x.cos(), im - t * x.sin())
+                })
+        }
+
+        fn magnitude(taps: &[f64], freq: f64) -> f64 {
+            let (re, im) = response(taps, freq);
+            (re * re + im * im).sqrt()
+        }
+
+        /// Check the magnitude response in `[start, end]` against `gain`
+        fn check_band(taps: &[f64], start: f64, end: f64, gain: f64, tol: f64) {
+            for i in 0..=200 {
+                let f = start + (end - start) * i as f64 / 200.0;
+                let m = magnitude(taps, f);
+                assert!(
+                    (m - gain).abs() < tol,
+                    "abs({} - {}) < {} (freq {})",
+                    m,
+                    gain,
+                    tol,
+                    f
+                );
+            }
+        }
+
+        fn check_symmetry(taps: &[f64], sign: f64) {
+            let n = taps.len();
+            for i in 0..n {
+                assert!((taps[i] - sign * taps[n - 1 - i]).abs() < 1e-9);
+            }
+        }
+
+        #[test]
+        fn lowpass_spec() {
+            let cutoff = 0.2;
+            let transition_bw = 0.05;
+            let max_ripple = 0.01;
+            let taps = lowpass::<f64>(cutoff, transition_bw, max_ripple);
+            check_symmetry(&taps, 1.0);
+            // allow for deviations between the points of the dense grid
+            let tol = max_ripple * 1.05;
+            check_band(&taps, 0.0, cutoff, 1.0, tol);
+            check_band(&taps, cutoff + transition_bw, 0.5, 0.0, tol);
+
+            // one tap less does not meet the spec
+            let (_, deviation) = remez(
+                taps.len() - 1,
+                &[
+                    Band::new(0.0, cutoff, 1.0, 1.0),
+                    Band::new(cutoff + transition_bw, 0.5, 0.0, 1.0),
+                ],
+                FilterType::Multiband,
+            );
+            assert!(deviation > max_ripple);
+
+            let kaiser = kaiser::lowpass::<f64>(cutoff, transition_bw, max_ripple);
+            assert!(taps.len() < kaiser.len());
+        }
+
+        #[test]
+        fn highpass_spec() {
+            let cutoff = 0.4;
+            let transition_bw = 0.03;
+            let max_ripple = 0.02;
+            let taps = highpass::<f64>(cutoff, transition_bw, max_ripple);
+            assert_eq!(taps.len() % 2, 1);
+            check_symmetry(&taps, 1.0);
+            let tol = max_ripple * 1.05;
+            check_band(&taps, 0.0, cutoff - transition_bw, 0.0, tol);
+            check_band(&taps, cutoff, 0.5, 1.0, tol);
+
+            let kaiser = kaiser::highpass::<f64>(cutoff, transition_bw, max_ripple);
+            assert!(taps.len() < kaiser.len());
+        }
+
+        #[test]
+        fn bandpass_spec() {
+            let lower_cutoff = 0.2;
+            let higher_cutoff = 0.3;
+            let transition_bw = 0.05;
+            let max_ripple = 0.001;
+            let taps = bandpass::<f64>(lower_cutoff, higher_cutoff, transition_bw, max_ripple);
+            check_symmetry(&taps, 1.0);
+            let tol = max_ripple * 1.05;
+            check_band(&taps, 0.0, lower_cutoff - transition_bw, 0.0, tol);
+            check_band(&taps, lower_cutoff, higher_cutoff, 1.0, tol);
+            check_band(&taps, higher_cutoff + transition_bw, 0.5, 0.0, tol);
+
+            let kaiser =
+                kaiser::bandpass::<f64>(lower_cutoff, higher_cutoff, transition_bw, max_ripple);
+            assert!(taps.len() < kaiser.len());
+        }
+
+        #[test]
+        fn bandstop_spec() {
+            let lower_cutoff = 0.1;
+            let higher_cutoff = 0.2;
+            let transition_bw = 0.04;
+            let max_ripple = 0.01;
+            let taps = bandstop::<f64>(lower_cutoff, higher_cutoff, transition_bw, max_ripple);
+            assert_eq!(taps.len() % 2, 1);
+            check_symmetry(&taps, 1.0);
+            let tol = max_ripple * 1.05;
+            check_band(&taps, 0.0, lower_cutoff - transition_bw, 1.0, tol);
+            check_band(&taps, lower_cutoff, higher_cutoff, 0.0, tol);
+            check_band(&taps, higher_cutoff + transition_bw, 0.5, 1.0, tol);
+        }
+
+        #[test]
+        fn weights() {
+            let bands = [
+                Band::new(0.0, 0.1, 1.0, 1.0),
+                Band::new(0.15, 0.5, 0.0, 10.0),
+            ];
+            let (taps, deviation) = remez(41, &bands, FilterType::Multiband);
+            check_band(&taps, 0.0, 0.1, 1.0, deviation * 1.05);
+            check_band(&taps, 0.15, 0.5, 0.0, deviation / 10.0 * 1.05);
+
+            // the stopband ripple is attained
+            let stop = (0..=200)
+                .map(|i| magnitude(&taps, 0.15 + 0.35 * i as f64 / 200.0))
+                .fold(0.0, f64::max);
+            assert!(stop > deviation / 10.0 * 0.9);
+        }
+
+        #[test]
+        fn differentiator_response() {
+            for num_taps in [31, 32] {
+                let bandwidth = 0.4;
+                let taps = differentiator::<f64>(num_taps, bandwidth);
+                check_symmetry(&taps, -1.0);
+
+                let delay = (num_taps - 1) as f64 / 2.0;
+                for i in 1..=40 {
+                    let f = bandwidth * i as f64 / 40.0;
+                    // remove the linear phase of the delay
+                    let (re, im) = response(&taps, f);
+                    let x = 2.0 * PI * f * delay;
+                    let (re, im) = (re * x.cos() - im * x.sin(), re * x.sin() + im * x.cos());
+                    let expected = 2.0 * PI * f;
+                    assert!(re.abs() < 1e-9);
+                    assert!(
+                        (im - expected).abs() < 0.01 * expected,
+                        "abs({} - {}) (freq {})",
+                        im,
+                        expected,
+                        f
+                    );
+                }
+            }
+        }
+
+        #[test]
+        fn hilbert_response() {
+            let num_taps = 31;
+            let transition_bw = 0.03;
+            let taps = hilbert::<f64>(num_taps, transition_bw);
+            check_symmetry(&taps, -1.0);
+
+            // every other tap is zero and the tap after the center is about 2/pi
+            let center = num_taps / 2;
+            for i in (center % 2..num_taps).step_by(2) {
+                assert!(taps[i].abs() < 1e-6);
+            }
+            assert!((taps[center + 1] - 2.0 / PI).abs() < 0.02);
+
+            check_band(&taps, transition_bw, 0.5 - transition_bw, 1.0, 0.05);
+        }
+    }
+}
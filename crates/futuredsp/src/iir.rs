@@ -1,10 +1,11 @@
 //! IIR filters
-use core::ops::{AddAssign, Mul};
+use core::ops::{Add, AddAssign, Mul, Sub};
 
 use crate::{ComputationStatus, StatefulUnaryKernel, TapsAccessor};
 
 extern crate alloc;
 use alloc::vec::Vec;
+use num_complex::Complex;
 use num_traits::Zero;
 
 /// An IIR filter.
@@ -144,10 +145,9 @@ where
         }
 
         // Update the memory
-        for idx in 1..memory.len() {
-            memory[idx] = memory[idx - 1];
-        }
         if !memory.is_empty() {
+            let len = memory.len();
+            memory.copy_within(0..len - 1, 1);
             memory[0] = *o;
         }
 
@@ -169,6 +169,147 @@ where
     )
 }
 
+/// Coefficients of a second-order section (biquad).
+///
+/// The section computes
+/// ```text
+/// y[k] = b0 * x[k] + b1 * x[k-1] + b2 * x[k-2] - a1 * y[k-1] - a2 * y[k-2]
+/// ```
+/// A first-order section has `b2` and `a2` set to zero. Sections can be designed with
+/// [`iirdes`](crate::iirdes).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Biquad<TapType> {
+    /// Feed-forward coefficient of `x[k]`.
+    pub b0: TapType,
+    /// Feed-forward coefficient of `x[k-1]`.
+    pub b1: TapType,
+    /// Feed-forward coefficient of `x[k-2]`.
+    pub b2: TapType,
+    /// Feedback coefficient of `y[k-1]`.
+    pub a1: TapType,
+    /// Feedback coefficient of `y[k-2]`.
+    pub a2: TapType,
+}
+
+/// An IIR filter, implemented as cascade of second-order sections (biquads).
+///
+/// Compared to the direct form of [IirKernel], the cascade is numerically stable also
+/// for filters of high order. Each section is implemented in transposed direct form II.
+///
+/// Calling `work()` on this struct always produces exactly as many samples as
+/// it consumes. Note that this kernel is stateful, and thus implements the
+/// [StatefulUnaryKernel] trait.
+///
+/// Implementations of this core exist for `f32` and `f64` taps with real or complex
+/// samples of the same precision.
+///
+/// Example usage:
+/// ```
+/// use futuredsp::StatefulUnaryKernel;
+/// use futuredsp::iir::{Biquad, SosKernel};
+///
+/// // y[k] = x[k] + 0.5 * y[k-1]
+/// let section = Biquad { b0: 1.0, b1: 0.0, b2: 0.0, a1: -0.5, a2: 0.0 };
+/// let mut iir = SosKernel::<f32, f32>::new(vec![section]);
+///
+/// let input = [1.0, 0.0, 0.0];
+/// let mut output = [0.0; 3];
+/// iir.work(&input, &mut output);
+/// assert_eq!(output, [1.0, 0.5, 0.25]);
+/// ```
+pub struct SosKernel<SampleType, TapType> {
+    sections: Vec<Biquad<TapType>>,
+    state: Vec<[SampleType; 2]>,
+}
+
+impl<SampleType: Zero + Copy, TapType> SosKernel<SampleType, TapType> {
+    /// Create a cascade of the given sections
+    pub fn new(sections: Vec<Biquad<TapType>>) -> Self {
+        let state = vec![[SampleType::zero(); 2]; sections.len()];
+        Self { sections, state }
+    }
+
+    /// Sections of the filter
+    pub fn sections(&self) -> &[Biquad<TapType>] {
+        &self.sections
+    }
+
+    /// Reset the state of the filter
+    pub fn reset(&mut self) {
+        for s in self.state.iter_mut() {
+            *s = [SampleType::zero(); 2];
+        }
+    }
+}
+
+impl StatefulUnaryKernel<f32, f32> for SosKernel<f32, f32> {
+    fn work(&mut self, input: &[f32], output: &mut [f32]) -> (usize, usize, ComputationStatus) {
+        sos_work(&self.sections, &mut self.state, input, output)
+    }
+}
+
+impl StatefulUnaryKernel<f64, f64> for SosKernel<f64, f64> {
+    fn work(&mut self, input: &[f64], output: &mut [f64]) -> (usize, usize, ComputationStatus) {
+        sos_work(&self.sections, &mut self.state, input, output)
+    }
+}
+
+impl StatefulUnaryKernel<Complex<f32>, Complex<f32>> for SosKernel<Complex<f32>, f32> {
+    fn work(
+        &mut self,
+        input: &[Complex<f32>],
+        output: &mut [Complex<f32>],
+    ) -> (usize, usize, ComputationStatus) {
+        sos_work(&self.sections, &mut self.state, input, output)
+    }
+}
+
+impl StatefulUnaryKernel<Complex<f64>, Complex<f64>> for SosKernel<Complex<f64>, f64> {
+    fn work(
+        &mut self,
+        input: &[Complex<f64>],
+        output: &mut [Complex<f64>],
+    ) -> (usize, usize, ComputationStatus) {
+        sos_work(&self.sections, &mut self.state, input, output)
+    }
+}
+
+#[inline(always)]
+fn sos_work<S, T>(
+    sections: &[Biquad<T>],
+    state: &mut [[S; 2]],
+    i: &[S],
+    o: &mut [S],
+) -> (usize, usize, ComputationStatus)
+where
+    S: Copy + Add<Output = S> + Sub<Output = S> + Mul<T, Output = S>,
+    T: Copy,
+{
+    let n = core::cmp::min(i.len(), o.len());
+    for (x, y) in i.iter().zip(o.iter_mut()) {
+        let mut v = *x;
+        for (b, s) in sections.iter().zip(state.iter_mut()) {
+            let out = v * b.b0 + s[0];
+            s[0] = v * b.b1 - out * b.a1 + s[1];
+            s[1] = v * b.b2 - out * b.a2;
+            v = out;
+        }
+        *y = v;
+    }
+
+    (
+        n,
+        n,
+        if i.len() == o.len() {
+            ComputationStatus::BothSufficient
+        } else if i.len() > o.len() {
+            ComputationStatus::InsufficientOutput
+        } else {
+            ComputationStatus::InsufficientInput
+        },
+    )
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -230,4 +371,66 @@ mod test {
         assert_eq!(iir.feed(10.0), Some(17.5));
         assert_eq!(iir.feed(10.0), Some(18.75));
     }
+
+    #[test]
+    fn test_iir_a_taps_memory() {
+        // y[k] = x[k] + y[k-3]
+        let mut iir = IirKernel::<f32, f32, _>::new(vec![0.0, 0.0, 1.0], vec![1.0]);
+
+        let input = [0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0];
+        let mut output = [0.0; 10];
+        let (n_consumed, n_produced, _status) = iir.work(&input, &mut output);
+        assert_eq!(n_consumed, 10);
+        assert_eq!(n_produced, 10);
+        assert_eq!(output, [0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn test_sos_impulse_response() {
+        // two sections, y[k] = x[k] + 0.5 * y[k-1] each
+        let section = Biquad {
+            b0: 1.0,
+            b1: 0.0,
+            b2: 0.0,
+            a1: -0.5,
+            a2: 0.0,
+        };
+        let mut iir = SosKernel::<f64, f64>::new(vec![section, section]);
+
+        let input = [1.0, 0.0, 0.0, 0.0];
+        let mut output = [0.0; 4];
+        let (n_consumed, n_produced, status) = iir.work(&input, &mut output);
+        assert_eq!(n_consumed, 4);
+        assert_eq!(n_produced, 4);
+        assert_eq!(status, ComputationStatus::BothSufficient);
+        // (k + 1) * 0.5^k
+        assert_eq!(output, [1.0, 1.0, 0.75, 0.5]);
+    }
+
+    #[test]
+    fn test_sos_state() {
+        let section = Biquad {
+            b0: 0.2,
+            b1: 0.3,
+            b2: 0.1,
+            a1: -0.4,
+            a2: 0.2,
+        };
+        let input: Vec<f32> = (0..20).map(|x| x as f32).collect();
+
+        let mut iir = SosKernel::<f32, f32>::new(vec![section]);
+        let mut expected = vec![0.0; 20];
+        iir.work(&input, &mut expected);
+
+        // processing in chunks yields the same output
+        iir.reset();
+        let mut output = vec![0.0; 20];
+        let (n, _, status) = iir.work(&input, &mut output[..7]);
+        assert_eq!(n, 7);
+        assert_eq!(status, ComputationStatus::InsufficientOutput);
+        let (_, m, status) = iir.work(&input[n..], &mut output[n..]);
+        assert_eq!(m, 13);
+        assert_eq!(status, ComputationStatus::BothSufficient);
+        assert_eq!(output, expected);
+    }
 }
@@ -0,0 +1,880 @@
+//! Methods for designing IIR filters
+//!
+//! Filters are designed from analog prototypes (Butterworth, Chebyshev I/II, elliptic, and
+//! Bessel), which are transformed to the requested lowpass, highpass, bandpass, or bandstop
+//! response and discretized with the bilinear transform. Frequencies are given in
+//! cycles/sample and are pre-warped, so that the digital filter has the specified edges.
+//!
+//! The result is a [`Zpk`] filter, which can be converted to direct-form taps for
+//! [`IirKernel`](crate::iir::IirKernel) or to second-order sections for
+//! [`SosKernel`](crate::iir::SosKernel). Direct-form filters of high order are numerically
+//! unstable, so second-order sections are the better choice in most cases.
+//!
+//! Example usage:
+//! ```
+//! use futuredsp::iir::SosKernel;
+//! use futuredsp::iirdes::{self, Prototype};
+//! use futuredsp::StatefulUnaryKernel;
+//!
+//! // 6th order elliptic lowpass with 0.5 dB ripple, 60 dB attenuation, and cutoff at 0.1
+//! let filter = iirdes::lowpass(
+//!     Prototype::Elliptic {
+//!         ripple_db: 0.5,
+//!         attenuation_db: 60.0,
+//!     },
+//!     6,
+//!     0.1,
+//! );
+//! let mut kernel = SosKernel::<f32, f32>::new(filter.sos());
+//!
+//! let input = [1.0, 0.0, 0.0, 0.0];
+//! let mut output = [0.0; 4];
+//! kernel.work(&input, &mut output);
+//! ```
+extern crate alloc;
+use alloc::vec::Vec;
+use core::f64::consts::PI;
+use num_complex::Complex;
+use num_traits::FromPrimitive;
+
+use crate::iir::Biquad;
+
+type C64 = Complex<f64>;
+
+/// Tolerance to consider a root real.
+const REAL_TOL: f64 = 1e-10;
+
+/// Analog prototype filter.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Prototype {
+    /// Maximally flat Butterworth filter. The gain at the cutoff frequency is -3 dB.
+    Butterworth,
+    /// Chebyshev type I filter with equiripple passband. The cutoff frequency is the edge
+    /// of the passband, where the gain is `-ripple_db`.
+    Chebyshev1 {
+        /// Passband ripple in dB.
+        ripple_db: f64,
+    },
+    /// Chebyshev type II filter with equiripple stopband. The cutoff frequency is the edge
+    /// of the stopband, where the gain is `-attenuation_db`.
+    Chebyshev2 {
+        /// Minimum stopband attenuation in dB.
+        attenuation_db: f64,
+    },
+    /// Elliptic (Cauer) filter with equiripple passband and stopband. The cutoff frequency
+    /// is the edge of the passband, where the gain is `-ripple_db`.
+    Elliptic {
+        /// Passband ripple in dB.
+        ripple_db: f64,
+        /// Minimum stopband attenuation in dB.
+        attenuation_db: f64,
+    },
+    /// Bessel filter with maximally flat group delay. The gain at the cutoff frequency is
+    /// -3 dB.
+    Bessel,
+}
+
+/// Filter given by its zeros, poles, and gain.
+///
+/// The transfer function is `gain * prod(z - zeros) / prod(z - poles)`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Zpk {
+    /// Zeros of the filter.
+    pub zeros: Vec<Complex<f64>>,
+    /// Poles of the filter.
+    pub poles: Vec<Complex<f64>>,
+    /// Gain of the filter.
+    pub gain: f64,
+}
+
+impl Zpk {
+    /// Frequency response of the digital filter at `freq` (in cycles/sample).
+    pub fn frequency_response(&self, freq: f64) -> Complex<f64> {
+        let z = C64::from_polar(1.0, 2.0 * PI * freq);
+        let num: C64 = self.zeros.iter().map(|q| z - q).product();
+        let den: C64 = self.poles.iter().map(|p| z - p).product();
+        num / den * self.gain
+    }
+
+    /// Coefficients `(b, a)` of the transfer function polynomials in `z^-1`, with
+    /// `a[0] = 1`.
+    pub fn coefficients(&self) -> (Vec<f64>, Vec<f64>) {
+        let b = poly(&self.zeros).iter().map(|x| x * self.gain).collect();
+        let a = poly(&self.poles);
+        (b, a)
+    }
+
+    /// Direct-form taps `(a_taps, b_taps)` as expected by
+    /// [`IirKernel::new()`](crate::iir::IirKernel::new).
+    ///
+    /// The feedback taps are the negated denominator coefficients without the leading one.
+    /// The filter taps are constructed internally as `f64` and then casted to the generic
+    /// type `T` using [`num_traits::FromPrimitive::from_f64()`].
+    pub fn direct_form<T: FromPrimitive>(&self) -> (Vec<T>, Vec<T>) {
+        let (b, a) = self.coefficients();
+        let a_taps = a[1..].iter().map(|x| T::from_f64(-x).unwrap()).collect();
+        let b_taps = b.iter().map(|x| T::from_f64(*x).unwrap()).collect();
+        (a_taps, b_taps)
+    }
+
+    /// Second-order sections for [`SosKernel`](crate::iir::SosKernel).
+    ///
+    /// Poles are paired with their complex conjugate and with the closest zeros. The
+    /// sections are ordered by increasing distance of their poles to the unit circle and the
+    /// gain is applied in the first section. A filter with odd order has one first-order
+    /// section.
+    pub fn sos<T: FromPrimitive>(&self) -> Vec<Biquad<T>> {
+        let mut pole_pairs = pairs(&self.poles);
+        let mut zero_pairs = pairs(&self.zeros);
+
+        // sections with poles close to the unit circle get the closest zeros
+        let dist = |p: &Vec<C64>| (1.0 - p[0].norm()).abs();
+        pole_pairs.sort_by(|a, b| dist(b).partial_cmp(&dist(a)).unwrap());
+        let mut sections = Vec::new();
+        for poles in pole_pairs.into_iter().rev() {
+            let zeros = if zero_pairs.is_empty() {
+                Vec::new()
+            } else {
+                let same_order = zero_pairs.iter().any(|z| z.len() == poles.len());
+                let i = zero_pairs
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, z)| !same_order || z.len() == poles.len())
+                    .min_by(|(_, a), (_, b)| {
+                        (a[0] - poles[0])
+                            .norm()
+                            .partial_cmp(&(b[0] - poles[0]).norm())
+                            .unwrap()
+                    })
+                    .map(|(i, _)| i)
+                    .unwrap();
+                zero_pairs.remove(i)
+            };
+            sections.push((zeros, poles));
+        }
+        // remaining zeros get sections without poles
+        sections.extend(zero_pairs.into_iter().map(|z| (z, Vec::new())));
+        sections.reverse();
+
+        sections
+            .iter()
+            .enumerate()
+            .map(|(i, (zeros, poles))| {
+                let gain = if i == 0 { self.gain } else { 1.0 };
+                let mut b = poly(zeros);
+                let mut a = poly(poles);
+                b.resize(3, 0.0);
+                a.resize(3, 0.0);
+                Biquad {
+                    b0: T::from_f64(gain * b[0]).unwrap(),
+                    b1: T::from_f64(gain * b[1]).unwrap(),
+                    b2: T::from_f64(gain * b[2]).unwrap(),
+                    a1: T::from_f64(a[1]).unwrap(),
+                    a2: T::from_f64(a[2]).unwrap(),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Designs a lowpass IIR filter of order `order` with cutoff frequency `cutoff` (in
+/// cycles/sample).
+///
+/// Example usage:
+/// ```
+/// use futuredsp::iirdes::{self, Prototype};
+///
+/// let sampling_freq = 10_000;
+/// // 2000 Hz cutoff frequency
+/// let cutoff = 2_000.0 / sampling_freq as f64;
+/// let filter = iirdes::lowpass(Prototype::Butterworth, 4, cutoff);
+/// let sections = filter.sos::<f32>();
+/// ```
+pub fn lowpass(prototype: Prototype, order: usize, cutoff: f64) -> Zpk {
+    assert!(cutoff > 0.0 && cutoff < 0.5, "cutoff must be in (0, 1/2)");
+    let analog = analog_prototype(prototype, order);
+    bilinear(lp2lp(analog, prewarp(cutoff)))
+}
+
+/// Designs a highpass IIR filter of order `order` with cutoff frequency `cutoff` (in
+/// cycles/sample).
+///
+/// Example usage:
+/// ```
+/// use futuredsp::iirdes::{self, Prototype};
+///
+/// let sampling_freq = 10_000;
+/// // 4000 Hz cutoff frequency
+/// let cutoff = 4_000.0 / sampling_freq as f64;
+/// let filter = iirdes::highpass(Prototype::Chebyshev1 { ripple_db: 1.0 }, 5, cutoff);
+/// let sections = filter.sos::<f32>();
+/// ```
+pub fn highpass(prototype: Prototype, order: usize, cutoff: f64) -> Zpk {
+    assert!(cutoff > 0.0 && cutoff < 0.5, "cutoff must be in (0, 1/2)");
+    let analog = analog_prototype(prototype, order);
+    bilinear(lp2hp(analog, prewarp(cutoff)))
+}
+
+/// Designs a bandpass IIR filter with passband from `lower_cutoff` to `higher_cutoff` (in
+/// cycles/sample). The prototype has order `order`, the resulting filter has order
+/// `2 * order`.
+///
+/// Example usage:
+/// ```
+/// use futuredsp::iirdes::{self, Prototype};
+///
+/// let sampling_freq = 10_000;
+/// // 1000 Hz lower and 2000 Hz higher cutoff frequency
+/// let lower_cutoff = 1_000.0 / sampling_freq as f64;
+/// let higher_cutoff = 2_000.0 / sampling_freq as f64;
+/// let filter = iirdes::bandpass(Prototype::Butterworth, 4, lower_cutoff, higher_cutoff);
+/// let sections = filter.sos::<f32>();
+/// ```
+pub fn bandpass(prototype: Prototype, order: usize, lower_cutoff: f64, higher_cutoff: f64) -> Zpk {
+    assert!(lower_cutoff > 0.0, "lower_cutoff must be greater than 0");
+    assert!(
+        higher_cutoff > lower_cutoff,
+        "higher_cutoff must be greater than lower_cutoff"
+    );
+    assert!(higher_cutoff < 0.5, "higher_cutoff must be less than 1/2");
+    let analog = analog_prototype(prototype, order);
+    let (wo, bw) = band(lower_cutoff, higher_cutoff);
+    bilinear(lp2bp(analog, wo, bw))
+}
+
+/// Designs a bandstop IIR filter with stopband from `lower_cutoff` to `higher_cutoff` (in
+/// cycles/sample). The prototype has order `order`, the resulting filter has order
+/// `2 * order`.
+///
+/// Example usage:
+/// ```
+/// use futuredsp::iirdes::{self, Prototype};
+///
+/// let sampling_freq = 10_000;
+/// // 1000 Hz lower and 2000 Hz higher cutoff frequency
+/// let lower_cutoff = 1_000.0 / sampling_freq as f64;
+/// let higher_cutoff = 2_000.0 / sampling_freq as f64;
+/// let filter = iirdes::bandstop(Prototype::Chebyshev2 { attenuation_db: 40.0 }, 4, lower_cutoff, higher_cutoff);
+/// let sections = filter.sos::<f32>();
+/// ```
+pub fn bandstop(prototype: Prototype, order: usize, lower_cutoff: f64, higher_cutoff: f64) -> Zpk {
+    assert!(lower_cutoff > 0.0, "lower_cutoff must be greater than 0");
+    assert!(
+        higher_cutoff > lower_cutoff,
+        "higher_cutoff must be greater than lower_cutoff"
+    );
+    assert!(higher_cutoff < 0.5, "higher_cutoff must be less than 1/2");
+    let analog = analog_prototype(prototype, order);
+    let (wo, bw) = band(lower_cutoff, higher_cutoff);
+    bilinear(lp2bs(analog, wo, bw))
+}
+
+/// Analog lowpass prototype of order `order` with a cutoff frequency of 1 rad/s.
+///
+/// The zeros and poles are in the s-plane.
+pub fn analog_prototype(prototype: Prototype, order: usize) -> Zpk {
+    assert!(order > 0, "order must be greater than 0");
+    match prototype {
+        Prototype::Butterworth => butterworth(order),
+        Prototype::Chebyshev1 { ripple_db } => {
+            assert!(ripple_db > 0.0, "ripple_db must be greater than 0");
+            chebyshev1(order, ripple_db)
+        }
+        Prototype::Chebyshev2 { attenuation_db } => {
+            assert!(
+                attenuation_db > 0.0,
+                "attenuation_db must be greater than 0"
+            );
+            chebyshev2(order, attenuation_db)
+        }
+        Prototype::Elliptic {
+            ripple_db,
+            attenuation_db,
+        } => {
+            assert!(ripple_db > 0.0, "ripple_db must be greater than 0");
+            assert!(
+                attenuation_db > ripple_db,
+                "attenuation_db must be greater than ripple_db"
+            );
+            elliptic(order, ripple_db, attenuation_db)
+        }
+        Prototype::Bessel => {
+            assert!(order <= 25, "order of Bessel filters must be at most 25");
+            bessel(order)
+        }
+    }
+}
+
+/// Analog frequency of the digital frequency `freq` for the bilinear transform with
+/// `s = 2 (z - 1) / (z + 1)`.
+fn prewarp(freq: f64) -> f64 {
+    2.0 * (PI * freq).tan()
+}
+
+/// Center frequency and bandwidth of a band in the analog domain.
+fn band(lower: f64, higher: f64) -> (f64, f64) {
+    let w1 = prewarp(lower);
+    let w2 = prewarp(higher);
+    ((w1 * w2).sqrt(), w2 - w1)
+}
+
+/// Set the gain, so that the prototype has gain `dc` at 0.
+fn with_dc_gain(zeros: Vec<C64>, poles: Vec<C64>, dc: f64) -> Zpk {
+    let num: C64 = poles.iter().map(|p| -p).product();
+    let den: C64 = zeros.iter().map(|z| -z).product();
+    Zpk {
+        gain: dc * (num / den).re,
+        zeros,
+        poles,
+    }
+}
+
+fn butterworth(order: usize) -> Zpk {
+    let n = order as f64;
+    let poles = (0..order)
+        .map(|k| {
+            let m = -n + 1.0 + 2.0 * k as f64;
+            -C64::from_polar(1.0, PI * m / (2.0 * n))
+        })
+        .collect();
+    with_dc_gain(Vec::new(), poles, 1.0)
+}
+
+fn chebyshev1(order: usize, ripple_db: f64) -> Zpk {
+    let n = order as f64;
+    let eps = (10f64.powf(0.1 * ripple_db) - 1.0).sqrt();
+    let mu = (1.0 / eps).asinh() / n;
+    let poles = (0..order)
+        .map(|k| {
+            let theta = PI * (-n + 1.0 + 2.0 * k as f64) / (2.0 * n);
+            -C64::new(mu, theta).sinh()
+        })
+        .collect();
+    let dc = if order % 2 == 1 {
+        1.0
+    } else {
+        1.0 / (1.0 + eps * eps).sqrt()
+    };
+    with_dc_gain(Vec::new(), poles, dc)
+}
+
+fn chebyshev2(order: usize, attenuation_db: f64) -> Zpk {
+    let n = order as f64;
+    let de = 1.0 / (10f64.powf(0.1 * attenuation_db) - 1.0).sqrt();
+    let mu = (1.0 / de).asinh() / n;
+
+    // zeros on the imaginary axis, skipping the one at infinity for odd orders
+    let zeros = (0..order)
+        .map(|k| -n + 1.0 + 2.0 * k as f64)
+        .filter(|m| *m != 0.0)
+        .map(|m| C64::new(0.0, 1.0 / (m * PI / (2.0 * n)).sin()))
+        .collect();
+    let poles = (0..order)
+        .map(|k| {
+            let m = -n + 1.0 + 2.0 * k as f64;
+            let p = -C64::from_polar(1.0, PI * m / (2.0 * n));
+            C64::new(mu.sinh() * p.re, mu.cosh() * p.im).inv()
+        })
+        .collect();
+    with_dc_gain(zeros, poles, 1.0)
+}
+
+fn elliptic(order: usize, ripple_db: f64, attenuation_db: f64) -> Zpk {
+    if order == 1 {
+        // first-order elliptic filters are Chebyshev filters
+        return chebyshev1(order, ripple_db);
+    }
+    let n = order as f64;
+    let ep = (10f64.powf(0.1 * ripple_db) - 1.0).sqrt();
+    let es = (10f64.powf(0.1 * attenuation_db) - 1.0).sqrt();
+    let k1 = ep / es;
+    let k = elliptic::degree(order, k1);
+
+    let v0 = (-C64::i() * elliptic::asn(C64::i() / ep, k1) / n).re;
+    let mut zeros = Vec::new();
+    let mut poles = Vec::new();
+    for i in 1..=order / 2 {
+        let u = C64::new((2 * i - 1) as f64 / n, 0.0);
+        let z = C64::i() / (elliptic::cd(u, k) * k);
+        let p = C64::i() * elliptic::cd(u - C64::i() * v0, k);
+        zeros.extend([z, z.conj()]);
+        poles.extend([p, p.conj()]);
+    }
+    if order % 2 == 1 {
+        let p = C64::i() * elliptic::sn(C64::i() * v0, k);
+        poles.push(C64::new(p.re, 0.0));
+    }
+    let dc = if order % 2 == 1 {
+        1.0
+    } else {
+        1.0 / (1.0 + ep * ep).sqrt()
+    };
+    with_dc_gain(zeros, poles, dc)
+}
+
+fn bessel(order: usize) -> Zpk {
+    // poles of the delay-normalized filter, scaled to -3 dB at 1 rad/s
+    let poles = bessel_poles(order);
+    let proto = with_dc_gain(Vec::new(), poles, 1.0);
+    let mag = |w: f64| {
+        let s = C64::new(0.0, w);
+        (proto.poles.iter().map(|p| s - p).product::<C64>().inv() * proto.gain).norm()
+    };
+    let half = core::f64::consts::FRAC_1_SQRT_2;
+    let mut low = 0.0;
+    let mut high = 1.0;
+    while mag(high) > half {
+        high *= 2.0;
+    }
+    for _ in 0..100 {
+        let mid = (low + high) / 2.0;
+        if mag(mid) > half {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    let w = (low + high) / 2.0;
+    let poles = proto.poles.iter().map(|p| p / w).collect();
+    with_dc_gain(Vec::new(), poles, 1.0)
+}
+
+/// Poles of the Bessel filter with unit group delay at 0, i.e., the roots of the reverse
+/// Bessel polynomial.
+fn bessel_poles(order: usize) -> Vec<C64> {
+    // coefficients a_k = (2n - k)! / (2^(n - k) k! (n - k)!), computed recursively from
+    // a_n = 1 to keep them in range
+    let n = order;
+    let mut coeffs = vec![0.0; n + 1];
+    coeffs[n] = 1.0;
+    for k in (0..n).rev() {
+        coeffs[k] = coeffs[k + 1] * (2 * n - k) as f64 * (k + 1) as f64 / (2 * (n - k)) as f64;
+    }
+    roots(&coeffs)
+}
+
+/// Roots of the monic polynomial with coefficients in ascending order, using the
+/// Durand-Kerner method.
+fn roots(coeffs: &[f64]) -> Vec<C64> {
+    let n = coeffs.len() - 1;
+    let eval = |x: C64| {
+        coeffs
+            .iter()
+            .rev()
+            .fold(C64::new(0.0, 0.0), |acc, c| acc * x + c)
+    };
+    let radius = coeffs[0].abs().powf(1.0 / n as f64).max(1.0);
+    let mut roots: Vec<C64> = (0..n)
+        .map(|k| C64::from_polar(radius, 2.0 * PI * k as f64 / n as f64 + 0.4))
+        .collect();
+    for _ in 0..1000 {
+        let mut delta: f64 = 0.0;
+        for i in 0..n {
+            let den: C64 = (0..n)
+                .filter(|j| *j != i)
+                .map(|j| roots[i] - roots[j])
+                .product();
+            let step = eval(roots[i]) / den;
+            roots[i] -= step;
+            delta = delta.max(step.norm() / roots[i].norm().max(1.0));
+        }
+        if delta < 1e-15 {
+            break;
+        }
+    }
+    // clean up round-off for real roots
+    roots
+        .into_iter()
+        .map(|r| {
+            if r.im.abs() < REAL_TOL * r.norm().max(1.0) {
+                C64::new(r.re, 0.0)
+            } else {
+                r
+            }
+        })
+        .collect()
+}
+
+/// Jacobi elliptic functions and their inverses, normalized to the quarter period, using
+/// Landen transformations.
+///
+/// The method is described in:
+/// - S. J. Orfanidis "Lecture Notes on Elliptic Filter Design," 2006.
+mod elliptic {
+    use super::C64;
+    use alloc::vec::Vec;
+    use core::f64::consts::PI;
+
+    /// Descending Landen sequence of moduli.
+    fn landen(k: f64) -> Vec<f64> {
+        let mut v = Vec::new();
+        let mut k = k;
+        while k > 1e-15 && v.len() < 20 {
+            k = (k / (1.0 + (1.0 - k * k).sqrt())).powi(2);
+            v.push(k);
+        }
+        v
+    }
+
+    /// `cd(u K, k)`
+    pub fn cd(u: C64, k: f64) -> C64 {
+        let mut w = (u * PI / 2.0).cos();
+        for v in landen(k).iter().rev() {
+            w = w * (1.0 + v) / (w * w * *v + 1.0);
+        }
+        w
+    }
+
+    /// `sn(u K, k)`
+    pub fn sn(u: C64, k: f64) -> C64 {
+        let mut w = (u * PI / 2.0).sin();
+        for v in landen(k).iter().rev() {
+            w = w * (1.0 + v) / (w * w * *v + 1.0);
+        }
+        w
+    }
+
+    /// Inverse of [`cd`], i.e., `u` with `cd(u K, k) = w`.
+    pub fn acd(w: C64, k: f64) -> C64 {
+        let mut w = w;
+        let mut prev = k;
+        for v in landen(k) {
+            w = w / ((-w * w * prev * prev + 1.0).sqrt() + 1.0) * 2.0 / (1.0 + v);
+            prev = v;
+        }
+        w.acos() * 2.0 / PI
+    }
+
+    /// Inverse of [`sn`], i.e., `u` with `sn(u K, k) = w`.
+    pub fn asn(w: C64, k: f64) -> C64 {
+        -acd(w, k) + 1.0
+    }
+
+    /// Solve the degree equation for the selectivity modulus of an elliptic filter of
+    /// order `order` with discrimination modulus `k1`.
+    pub fn degree(order: usize, k1: f64) -> f64 {
+        let n = order as f64;
+        let k1p = (1.0 - k1 * k1).sqrt();
+        let prod: f64 = (1..=order / 2)
+            .map(|i| sn(C64::new((2 * i - 1) as f64 / n, 0.0), k1p).re.powi(4))
+            .product();
+        let kp = k1p.powi(order as i32) * prod;
+        (1.0 - kp * kp).sqrt()
+    }
+}
+
+/// Scale the cutoff of a lowpass prototype to `wo`.
+fn lp2lp(zpk: Zpk, wo: f64) -> Zpk {
+    let degree = zpk.poles.len() as i32 - zpk.zeros.len() as i32;
+    Zpk {
+        zeros: zpk.zeros.iter().map(|z| z * wo).collect(),
+        poles: zpk.poles.iter().map(|p| p * wo).collect(),
+        gain: zpk.gain * wo.powi(degree),
+    }
+}
+
+/// Transform a lowpass prototype to a highpass with cutoff `wo`.
+fn lp2hp(zpk: Zpk, wo: f64) -> Zpk {
+    let degree = zpk.poles.len() - zpk.zeros.len();
+    let num: C64 = zpk.zeros.iter().map(|z| -z).product();
+    let den: C64 = zpk.poles.iter().map(|p| -p).product();
+    let mut zeros: Vec<C64> = zpk.zeros.iter().map(|z| wo / z).collect();
+    zeros.resize(zeros.len() + degree, C64::new(0.0, 0.0));
+    Zpk {
+        zeros,
+        poles: zpk.poles.iter().map(|p| wo / p).collect(),
+        gain: zpk.gain * (num / den).re,
+    }
+}
+
+/// Map the roots `r` of a lowpass to the roots of a band filter, solving
+/// `x^2 - r x + wo^2 = 0`.
+fn band_roots(roots: &[C64], wo: f64) -> Vec<C64> {
+    let mut v = Vec::new();
+    for r in roots {
+        let d = (r * r - wo * wo).sqrt();
+        v.push(r + d);
+        v.push(r - d);
+    }
+    v
+}
+
+/// Transform a lowpass prototype to a bandpass with center `wo` and bandwidth `bw`.
+fn lp2bp(zpk: Zpk, wo: f64, bw: f64) -> Zpk {
+    let degree = zpk.poles.len() - zpk.zeros.len();
+    let z: Vec<C64> = zpk.zeros.iter().map(|z| z * bw / 2.0).collect();
+    let p: Vec<C64> = zpk.poles.iter().map(|p| p * bw / 2.0).collect();
+    let mut zeros = band_roots(&z, wo);
+    zeros.resize(zeros.len() + degree, C64::new(0.0, 0.0));
+    Zpk {
+        zeros,
+        poles: band_roots(&p, wo),
+        gain: zpk.gain * bw.powi(degree as i32),
+    }
+}
+
+/// Transform a lowpass prototype to a bandstop with center `wo` and bandwidth `bw`.
+fn lp2bs(zpk: Zpk, wo: f64, bw: f64) -> Zpk {
+    let degree = zpk.poles.len() - zpk.zeros.len();
+    let num: C64 = zpk.zeros.iter().map(|z| -z).product();
+    let den: C64 = zpk.poles.iter().map(|p| -p).product();
+    let z: Vec<C64> = zpk.zeros.iter().map(|z| bw / 2.0 / z).collect();
+    let p: Vec<C64> = zpk.poles.iter().map(|p| bw / 2.0 / p).collect();
+    let mut zeros = band_roots(&z, wo);
+    for _ in 0..degree {
+        zeros.push(C64::new(0.0, wo));
+        zeros.push(C64::new(0.0, -wo));
+    }
+    Zpk {
+        zeros,
+        poles: band_roots(&p, wo),
+        gain: zpk.gain * (num / den).re,
+    }
+}
+
+/// Bilinear transform from the s-plane to the z-plane with `s = 2 (z - 1) / (z + 1)`.
+fn bilinear(zpk: Zpk) -> Zpk {
+    let fs2 = 2.0;
+    let degree = zpk.poles.len() - zpk.zeros.len();
+    let num: C64 = zpk.zeros.iter().map(|z| fs2 - z).product();
+    let den: C64 = zpk.poles.iter().map(|p| fs2 - p).product();
+    let mut zeros: Vec<C64> = zpk.zeros.iter().map(|z| (fs2 + z) / (fs2 - z)).collect();
+    zeros.resize(zeros.len() + degree, C64::new(-1.0, 0.0));
+    Zpk {
+        zeros,
+        poles: zpk.poles.iter().map(|p| (fs2 + p) / (fs2 - p)).collect(),
+        gain: zpk.gain * (num / den).re,
+    }
+}
+
+/// Group roots in complex conjugate pairs and pairs of real roots. If the number of real
+/// roots is odd, the last group has only one root.
+fn pairs(roots: &[C64]) -> Vec<Vec<C64>> {
+    let is_real = |r: &C64| r.im.abs() <= REAL_TOL * r.norm().max(1.0);
+    let mut real: Vec<C64> = roots
+        .iter()
+        .filter(|r| is_real(r))
+        .map(|r| C64::new(r.re, 0.0))
+        .collect();
+    real.sort_by(|a, b| a.re.partial_cmp(&b.re).unwrap());
+
+    let mut v: Vec<Vec<C64>> = roots
+        .iter()
+        .filter(|r| !is_real(r) && r.im > 0.0)
+        .map(|r| vec![*r, r.conj()])
+        .collect();
+    v.extend(real.chunks(2).map(|c| c.to_vec()));
+    v
+}
+
+/// Real coefficients of the polynomial with the given roots, highest power first.
+fn poly(roots: &[C64]) -> Vec<f64> {
+    let mut c = vec![C64::new(1.0, 0.0)];
+    for r in roots {
+        c.push(C64::new(0.0, 0.0));
+        for i in (1..c.len()).rev() {
+            let prev = c[i - 1];
+            c[i] -= prev * r;
+        }
+    }
+    c.iter().map(|x| x.re).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::iir::{IirKernel, SosKernel};
+    use crate::StatefulUnaryKernel;
+
+    fn gain_db(zpk: &Zpk, freq: f64) -> f64 {
+        20.0 * zpk.frequency_response(freq).norm().log10()
+    }
+
+    fn assert_close(a: f64, b: f64, tol: f64) {
+        assert!((a - b).abs() < tol, "abs({} - {}) < {}", a, b, tol);
+    }
+
+    #[test]
+    fn butterworth_coefficients() {
+        // Test coefficients generated using scipy:
+        // ```
+        // b, a = scipy.signal.butter(2, 0.2)
+        // ```
+        let (b, a) = lowpass(Prototype::Butterworth, 2, 0.1).coefficients();
+        let test_b = [0.06745527, 0.13491055, 0.06745527];
+        let test_a = [1.0, -1.1429805, 0.4128016];
+        for (x, y) in b.iter().zip(test_b.iter()) {
+            assert_close(*x, *y, 1e-6);
+        }
+        for (x, y) in a.iter().zip(test_a.iter()) {
+            assert_close(*x, *y, 1e-6);
+        }
+    }
+
+    #[test]
+    fn bessel_poles_accuracy() {
+        let poles = bessel_poles(2);
+        assert_close(poles[0].re, -1.5, 1e-9);
+        assert_close(poles[0].im.abs(), 0.75f64.sqrt(), 1e-9);
+
+        let mut poles = bessel_poles(3);
+        poles.sort_by(|a, b| a.re.partial_cmp(&b.re).unwrap());
+        assert_close(poles[0].re, -2.3222, 1e-4);
+        assert_close(poles[0].im, 0.0, 1e-9);
+        assert_close(poles[1].re, -1.8389, 1e-4);
+        assert_close(poles[1].im.abs(), 1.7544, 1e-4);
+    }
+
+    #[test]
+    fn lowpass_cutoff() {
+        let cutoff = 0.15;
+        let cases = [
+            (Prototype::Butterworth, -3.0103),
+            (Prototype::Chebyshev1 { ripple_db: 1.0 }, -1.0),
+            (
+                Prototype::Chebyshev2 {
+                    attenuation_db: 40.0,
+                },
+                -40.0,
+            ),
+            (
+                Prototype::Elliptic {
+                    ripple_db: 0.5,
+                    attenuation_db: 50.0,
+                },
+                -0.5,
+            ),
+            (Prototype::Bessel, -3.0103),
+        ];
+        for (prototype, gain) in cases {
+            for order in 1..8 {
+                let filter = lowpass(prototype, order, cutoff);
+                assert_eq!(filter.poles.len(), order);
+                assert!(filter.poles.iter().all(|p| p.norm() < 1.0));
+                assert_close(gain_db(&filter, cutoff), gain, 1e-3);
+            }
+        }
+    }
+
+    #[test]
+    fn lowpass_ripple() {
+        let cutoff = 0.1;
+        let stopband = 0.2;
+        let ripple_db = 0.5;
+        let attenuation_db = 50.0;
+        let filters = [
+            lowpass(Prototype::Chebyshev1 { ripple_db }, 5, cutoff),
+            lowpass(
+                Prototype::Elliptic {
+                    ripple_db,
+                    attenuation_db,
+                },
+                5,
+                cutoff,
+            ),
+        ];
+        for filter in filters.iter() {
+            for i in 0..=100 {
+                let gain = gain_db(filter, cutoff * i as f64 / 100.0);
+                assert!(gain < 1e-9 && gain > -ripple_db - 1e-9, "{}", gain);
+            }
+        }
+        // the elliptic filter reaches the stopband faster
+        let stop = gain_db(&filters[1], stopband);
+        assert!(stop < gain_db(&filters[0], stopband));
+        for i in 0..=100 {
+            let freq = stopband + (0.5 - stopband) * i as f64 / 100.0;
+            assert!(gain_db(&filters[1], freq) < -attenuation_db + 1e-6);
+        }
+
+        let filter = lowpass(Prototype::Chebyshev2 { attenuation_db }, 5, 0.2);
+        for i in 0..=100 {
+            let freq = 0.2 + 0.3 * i as f64 / 100.0;
+            assert!(gain_db(&filter, freq) < -attenuation_db + 1e-6);
+        }
+        assert_close(gain_db(&filter, 0.0), 0.0, 1e-9);
+    }
+
+    #[test]
+    fn highpass_response() {
+        let filter = highpass(Prototype::Butterworth, 4, 0.2);
+        assert_close(gain_db(&filter, 0.5), 0.0, 1e-9);
+        assert_close(gain_db(&filter, 0.2), -3.0103, 1e-3);
+        assert!(gain_db(&filter, 0.05) < -40.0);
+    }
+
+    #[test]
+    fn bandpass_response() {
+        let filter = bandpass(Prototype::Chebyshev1 { ripple_db: 1.0 }, 4, 0.1, 0.2);
+        assert_eq!(filter.poles.len(), 8);
+        assert_close(gain_db(&filter, 0.1), -1.0, 1e-3);
+        assert_close(gain_db(&filter, 0.2), -1.0, 1e-3);
+        assert!(gain_db(&filter, 0.15) > -1.0 - 1e-9);
+        assert!(gain_db(&filter, 0.02) < -40.0);
+        assert!(gain_db(&filter, 0.4) < -40.0);
+    }
+
+    #[test]
+    fn bandstop_response() {
+        let filter = bandstop(Prototype::Butterworth, 4, 0.1, 0.2);
+        assert_eq!(filter.poles.len(), 8);
+        assert_close(gain_db(&filter, 0.0), 0.0, 1e-9);
+        assert_close(gain_db(&filter, 0.5), 0.0, 1e-9);
+        assert_close(gain_db(&filter, 0.1), -3.0103, 1e-3);
+        assert_close(gain_db(&filter, 0.2), -3.0103, 1e-3);
+        assert!(gain_db(&filter, 0.15) < -60.0);
+    }
+
+    #[test]
+    fn sos_matches_direct_form() {
+        let filter = lowpass(Prototype::Chebyshev1 { ripple_db: 1.0 }, 5, 0.2);
+        let (a_taps, b_taps) = filter.direct_form::<f64>();
+        let mut iir = IirKernel::<f64, f64, _>::new(a_taps, b_taps);
+        let mut sos = SosKernel::<f64, f64>::new(filter.sos());
+        assert_eq!(sos.sections().len(), 3);
+
+        // the direct-form kernel looks ahead by `b_taps.len() - 1` samples and fills its
+        // memory with the first inputs
+        let delay = filter.zeros.len();
+        let mut iir_in = [0.0; 64];
+        let mut sos_in = [0.0; 64];
+        iir_in[10 + delay] = 1.0;
+        sos_in[10] = 1.0;
+        let mut iir_out = [0.0; 64];
+        let mut sos_out = [0.0; 64];
+        let (_, n, _) = iir.work(&iir_in, &mut iir_out);
+        assert_eq!(n, 64 - delay);
+        sos.work(&sos_in, &mut sos_out);
+        for (x, y) in iir_out[..n].iter().zip(sos_out.iter()) {
+            assert_close(*x, *y, 1e-9);
+        }
+        assert!(sos_out[10..20].iter().any(|x| x.abs() > 0.1));
+    }
+
+    #[test]
+    fn sos_high_order_stable() {
+        let filter = lowpass(
+            Prototype::Elliptic {
+                ripple_db: 0.1,
+                attenuation_db: 80.0,
+            },
+            12,
+            0.02,
+        );
+        let sections = filter.sos::<f64>();
+        assert_eq!(sections.len(), 6);
+        for s in sections.iter() {
+            // the poles of a biquad are stable, iff a2 < 1 and |a1| < 1 + a2
+            assert!(s.a2.abs() < 1.0 && s.a1.abs() < 1.0 + s.a2);
+        }
+
+        // the step response of the cascade settles at the dc gain
+        let dc = filter.frequency_response(0.0).norm();
+        let mut sos = SosKernel::<f64, f64>::new(sections);
+        let input = vec![1.0; 16384];
+        let mut output = vec![0.0; 16384];
+        sos.work(&input, &mut output);
+        assert_close(output[16383], dc, 1e-6);
+        assert!(output.iter().all(|x| x.is_finite() && x.abs() < 2.0));
+    }
+}
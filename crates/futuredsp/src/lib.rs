@@ -9,10 +9,19 @@ extern crate log;
 #[macro_use]
 extern crate alloc;
 
+#[cfg(feature = "std")]
+extern crate std;
+
+#[cfg(feature = "fft")]
+pub mod fftfir;
 pub mod fir;
 pub mod firdes;
 pub mod iir;
+pub mod iirdes;
 pub mod math;
+#[cfg(feature = "fft")]
+pub mod pfb;
+pub mod simd;
 pub mod windows;
 
 mod tapsaccessor;
@@ -0,0 +1,532 @@
+//! Polyphase filterbanks
+//!
+//! A channelizer splits a wideband signal into `num_channels` equally spaced channels. Channel
+//! `c` is centered at `c / num_channels` cycles/sample, i.e., channels with an index larger
+//! than `num_channels / 2` correspond to negative frequencies. A synthesizer combines the
+//! channels again.
+//!
+//! Both are based on a lowpass prototype filter with a cutoff of `1 / (2 * num_channels)` and
+//! unit gain, which can be designed with
+//! [`firdes::kaiser::multirate`](crate::firdes::kaiser::multirate):
+//! ```
+//! use futuredsp::firdes;
+//! use futuredsp::pfb::{PfbChannelizerKernel, PfbSynthesizerKernel};
+//!
+//! let taps = firdes::kaiser::multirate::<f32>(1, 8, 12, 0.0001);
+//! let channelizer = PfbChannelizerKernel::new(8, &taps, 1);
+//! let synthesizer = PfbSynthesizerKernel::new(8, &taps, 1);
+//! ```
+//!
+//! The filterbanks are critically sampled (`oversampling = 1`), i.e., each channel is sampled
+//! with `1 / num_channels` of the wideband sample rate, or oversampled by an integer factor,
+//! typically `2`, which avoids aliasing at the channel edges.
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+use crate::ComputationStatus;
+use num_complex::Complex;
+use rustfft::{Fft, FftPlanner};
+
+/// Pad the taps with zeros to a multiple of `n` and split them in `n` polyphase branches.
+///
+/// Branch `k` holds `taps[m * n + k]` in reversed order, i.e., with increasing `m` towards
+/// the front.
+fn branches(taps: &[f32], n: usize, scale: f32) -> (usize, Vec<f32>) {
+    let len = taps.chunks(n).len();
+    let mut branches = Vec::with_capacity(len * n);
+    for k in 0..n {
+        for m in (0..len).rev() {
+            branches.push(taps.get(m * n + k).copied().unwrap_or(0.0) * scale);
+        }
+    }
+    (len, branches)
+}
+
+/// Number of steps that can be computed with the given inputs and outputs.
+fn num_steps(num_producable: usize, num_outputs: usize) -> (usize, ComputationStatus) {
+    match num_producable.cmp(&num_outputs) {
+        core::cmp::Ordering::Greater => (num_outputs, ComputationStatus::InsufficientOutput),
+        core::cmp::Ordering::Equal => (num_producable, ComputationStatus::BothSufficient),
+        core::cmp::Ordering::Less => (num_producable, ComputationStatus::InsufficientInput),
+    }
+}
+
+/// A polyphase filterbank channelizer.
+///
+/// Each output sample of a channel consumes `num_channels / oversampling` input samples.
+/// Like the [`PolyphaseResamplingFirKernel`](crate::fir::PolyphaseResamplingFirKernel), the
+/// filter only produces outputs for which all input samples are available and does not
+/// consume the samples required for the next output.
+///
+/// Example usage:
+/// ```
+/// use futuredsp::firdes;
+/// use futuredsp::pfb::PfbChannelizerKernel;
+/// use num_complex::Complex;
+///
+/// let taps = firdes::kaiser::multirate::<f32>(1, 4, 12, 0.0001);
+/// let mut channelizer = PfbChannelizerKernel::new(4, &taps, 1);
+///
+/// let input = vec![Complex::new(1.0, 0.0); 1024];
+/// let mut outputs = vec![vec![Complex::new(0.0, 0.0); 256]; 4];
+/// let mut slices: Vec<Option<&mut [Complex<f32>]>> =
+///     outputs.iter_mut().map(|o| Some(o.as_mut_slice())).collect();
+/// let (_, produced, _) = channelizer.work(&input, &mut slices);
+/// // DC ends up in the first channel
+/// assert!((outputs[0][produced - 1].re - 1.0).abs() < 1e-3);
+/// assert!(outputs[1][produced - 1].norm() < 1e-3);
+/// ```
+pub struct PfbChannelizerKernel {
+    num_channels: usize,
+    oversampling: usize,
+    branch_len: usize,
+    branches: Vec<f32>,
+    ifft: Arc<dyn Fft<f32>>,
+    buffer: Vec<Complex<f32>>,
+    scratch: Vec<Complex<f32>>,
+    // time of the next output sample modulo the number of channels
+    time: usize,
+}
+
+impl PfbChannelizerKernel {
+    /// Create a channelizer with `num_channels` channels, using the given prototype
+    /// filter. Panics if `num_channels` is not a multiple of `oversampling`.
+    pub fn new(num_channels: usize, taps: &[f32], oversampling: usize) -> Self {
+        assert!(num_channels > 0, "num_channels must be greater than 0");
+        assert!(oversampling > 0, "oversampling must be greater than 0");
+        assert_eq!(
+            num_channels % oversampling,
+            0,
+            "num_channels must be a multiple of oversampling"
+        );
+        assert!(!taps.is_empty(), "the filter needs at least one tap");
+
+        let (branch_len, branches) = branches(taps, num_channels, 1.0);
+        let ifft = FftPlanner::new().plan_fft_inverse(num_channels);
+        let scratch = vec![Complex::new(0.0, 0.0); ifft.get_inplace_scratch_len()];
+        Self {
+            num_channels,
+            oversampling,
+            branch_len,
+            branches,
+            ifft,
+            buffer: vec![Complex::new(0.0, 0.0); num_channels],
+            scratch,
+            time: num_channels - 1,
+        }
+    }
+
+    /// Number of channels.
+    pub fn num_channels(&self) -> usize {
+        self.num_channels
+    }
+
+    /// Oversampling factor of the channels.
+    pub fn oversampling(&self) -> usize {
+        self.oversampling
+    }
+
+    /// Number of input samples per output sample.
+    pub fn decimation(&self) -> usize {
+        self.num_channels / self.oversampling
+    }
+
+    /// Channelize the input.
+    ///
+    /// `outputs` holds one buffer per channel. Channels without a buffer are skipped. All
+    /// buffers receive the same number of samples. Returns the number of consumed input
+    /// samples, the number of samples produced per channel, and the status of the
+    /// computation.
+    pub fn work(
+        &mut self,
+        input: &[Complex<f32>],
+        outputs: &mut [Option<&mut [Complex<f32>]>],
+    ) -> (usize, usize, ComputationStatus) {
+        assert_eq!(outputs.len(), self.num_channels, "one output per channel");
+
+        let n = self.num_channels;
+        let decimation = self.decimation();
+        let window = self.branch_len * n;
+        let num_producable = if input.len() >= window {
+            (input.len() - window) / decimation + 1
+        } else {
+            0
+        };
+        let num_outputs = outputs
+            .iter()
+            .flatten()
+            .map(|o| o.len())
+            .min()
+            .unwrap_or(usize::MAX);
+        let (steps, status) = num_steps(num_producable, num_outputs);
+
+        for s in 0..steps {
+            let w = &input[s * decimation..s * decimation + window];
+            // filter with branch `k`, rotating the result to account for the time of the
+            // output sample
+            for k in 0..n {
+                let taps = &self.branches[k * self.branch_len..(k + 1) * self.branch_len];
+                let mut sum = Complex::new(0.0, 0.0);
+                for (j, t) in taps.iter().enumerate() {
+                    sum += w[n - 1 - k + j * n] * t;
+                }
+                self.buffer[(k + n - self.time) % n] = sum;
+            }
+            self.ifft
+                .process_with_scratch(&mut self.buffer, &mut self.scratch);
+            for (o, y) in outputs.iter_mut().zip(self.buffer.iter()) {
+                if let Some(o) = o {
+                    o[s] = *y;
+                }
+            }
+            self.time = (self.time + decimation) % n;
+        }
+
+        (steps * decimation, steps, status)
+    }
+}
+
+/// A polyphase filterbank synthesizer.
+///
+/// Each input sample of the channels produces `num_channels / oversampling` output samples.
+/// The filter state is initialized with zeros, i.e., outputs are available right away.
+///
+/// Example usage:
+/// ```
+/// use futuredsp::firdes;
+/// use futuredsp::pfb::PfbSynthesizerKernel;
+/// use num_complex::Complex;
+///
+/// let taps = firdes::kaiser::multirate::<f32>(1, 4, 12, 0.0001);
+/// let mut synthesizer = PfbSynthesizerKernel::new(4, &taps, 1);
+///
+/// let input = vec![Complex::new(1.0, 0.0); 256];
+/// // only the first channel carries a signal
+/// let inputs = [Some(input.as_slice()), None, None, None];
+/// let mut output = vec![Complex::new(0.0, 0.0); 1024];
+/// let (consumed, produced, _) = synthesizer.work(&inputs, &mut output);
+/// assert_eq!(consumed, 256);
+/// assert_eq!(produced, 1024);
+/// assert!((output[1023].re - 1.0).abs() < 1e-3);
+/// ```
+pub struct PfbSynthesizerKernel {
+    num_channels: usize,
+    oversampling: usize,
+    branch_len: usize,
+    branches: Vec<f32>,
+    ifft: Arc<dyn Fft<f32>>,
+    // inverse FFTs of the last `branch_len` input vectors
+    history: Vec<Complex<f32>>,
+    pos: usize,
+    scratch: Vec<Complex<f32>>,
+    // time of the next output sample modulo the number of channels
+    time: usize,
+}
+
+impl PfbSynthesizerKernel {
+    /// Create a synthesizer with `num_channels` channels, using the given prototype filter.
+    /// Panics if `num_channels` is not a multiple of `oversampling`.
+    ///
+    /// The taps are scaled by the interpolation factor, i.e., the same taps can be used for
+    /// the channelizer and the synthesizer.
+    pub fn new(num_channels: usize, taps: &[f32], oversampling: usize) -> Self {
+        assert!(num_channels > 0, "num_channels must be greater than 0");
+        assert!(oversampling > 0, "oversampling must be greater than 0");
+        assert_eq!(
+            num_channels % oversampling,
+            0,
+            "num_channels must be a multiple of oversampling"
+        );
+        assert!(!taps.is_empty(), "the filter needs at least one tap");
+
+        let interpolation = num_channels / oversampling;
+        let (branch_len, branches) = branches(taps, interpolation, interpolation as f32);
+        let ifft = FftPlanner::new().plan_fft_inverse(num_channels);
+        let scratch = vec![Complex::new(0.0, 0.0); ifft.get_inplace_scratch_len()];
+        Self {
+            num_channels,
+            oversampling,
+            branch_len,
+            branches,
+            ifft,
+            history: vec![Complex::new(0.0, 0.0); branch_len * num_channels],
+            pos: 0,
+            scratch,
+            time: 0,
+        }
+    }
+
+    /// Number of channels.
+    pub fn num_channels(&self) -> usize {
+        self.num_channels
+    }
+
+    /// Oversampling factor of the channels.
+    pub fn oversampling(&self) -> usize {
+        self.oversampling
+    }
+
+    /// Number of output samples per input sample.
+    pub fn interpolation(&self) -> usize {
+        self.num_channels / self.oversampling
+    }
+
+    /// Combine the channels.
+    ///
+    /// `inputs` holds one buffer per channel. Channels without a buffer are treated as
+    /// zeros. The same number of samples is consumed from all buffers. Returns the number of
+    /// samples consumed per channel, the number of produced samples, and the status of the
+    /// computation.
+    pub fn work(
+        &mut self,
+        inputs: &[Option<&[Complex<f32>]>],
+        output: &mut [Complex<f32>],
+    ) -> (usize, usize, ComputationStatus) {
+        assert_eq!(inputs.len(), self.num_channels, "one input per channel");
+
+        let n = self.num_channels;
+        let interpolation = self.interpolation();
+        let num_producable = inputs
+            .iter()
+            .flatten()
+            .map(|i| i.len())
+            .min()
+            .unwrap_or(usize::MAX);
+        let (steps, status) = num_steps(num_producable, output.len() / interpolation);
+
+        for s in 0..steps {
+            // the newest vector is stored at `pos`, older ones follow
+            self.pos = (self.pos + self.branch_len - 1) % self.branch_len;
+            let u = &mut self.history[self.pos * n..(self.pos + 1) * n];
+            for (u, i) in u.iter_mut().zip(inputs.iter()) {
+                *u = i.map(|i| i[s]).unwrap_or_else(|| Complex::new(0.0, 0.0));
+            }
+            self.ifft.process_with_scratch(u, &mut self.scratch);
+
+            for r in 0..interpolation {
+                let idx = (self.time + r) % n;
+                let taps = &self.branches[r * self.branch_len..(r + 1) * self.branch_len];
+                let mut sum = Complex::new(0.0, 0.0);
+                // taps are reversed, the last one belongs to the newest vector
+                for (m, t) in taps.iter().rev().enumerate() {
+                    let slot = (self.pos + m) % self.branch_len;
+                    sum += self.history[slot * n + idx] * t;
+                }
+                output[s * interpolation + r] = sum;
+            }
+            self.time = (self.time + interpolation) % n;
+        }
+
+        (steps, steps * interpolation, status)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::firdes;
+    use crate::windows;
+    use core::f32::consts::PI;
+
+    fn samples(n: usize) -> Vec<Complex<f32>> {
+        let mut state = 1u32;
+        let mut next = move || {
+            state = state.wrapping_mul(1664525).wrapping_add(1013904223);
+            (state >> 8) as f32 / (1u32 << 24) as f32 - 0.5
+        };
+        (0..n).map(|_| Complex::new(next(), next())).collect()
+    }
+
+    fn rotator(c: usize, n: usize, t: usize) -> Complex<f32> {
+        Complex::from_polar(1.0, 2.0 * PI * ((c * t) % n) as f32 / n as f32)
+    }
+
+    fn channelize(
+        kernel: &mut PfbChannelizerKernel,
+        input: &[Complex<f32>],
+    ) -> Vec<Vec<Complex<f32>>> {
+        let mut outputs = vec![vec![Complex::new(0.0, 0.0); input.len()]; kernel.num_channels()];
+        let mut slices: Vec<Option<&mut [Complex<f32>]>> =
+            outputs.iter_mut().map(|o| Some(o.as_mut_slice())).collect();
+        let (_, produced, status) = kernel.work(input, &mut slices);
+        assert_eq!(status, ComputationStatus::InsufficientInput);
+        for o in outputs.iter_mut() {
+            o.truncate(produced);
+        }
+        outputs
+    }
+
+    fn synthesize(
+        kernel: &mut PfbSynthesizerKernel,
+        inputs: &[Vec<Complex<f32>>],
+    ) -> Vec<Complex<f32>> {
+        let slices: Vec<Option<&[Complex<f32>]>> =
+            inputs.iter().map(|i| Some(i.as_slice())).collect();
+        let mut output = vec![Complex::new(0.0, 0.0); inputs[0].len() * kernel.interpolation()];
+        let (consumed, produced, status) = kernel.work(&slices, &mut output);
+        assert_eq!(consumed, inputs[0].len());
+        assert_eq!(produced, output.len());
+        assert_eq!(status, ComputationStatus::BothSufficient);
+        output
+    }
+
+    #[test]
+    fn channelizer_matches_reference() {
+        let n = 8;
+        let taps = firdes::kaiser::multirate::<f32>(1, n, 4, 0.001);
+        let input = samples(1000);
+        for oversampling in [1, 2] {
+            let mut kernel = PfbChannelizerKernel::new(n, &taps, oversampling);
+            let outputs = channelize(&mut kernel, &input);
+            let d = n / oversampling;
+            assert_eq!(outputs[0].len(), (input.len() - taps.len()) / d + 1);
+
+            for (c, output) in outputs.iter().enumerate() {
+                for (j, y) in output.iter().enumerate() {
+                    // mix down by c / n, filter, and decimate
+                    let t = j * d + taps.len() - 1;
+                    let mut want = Complex::new(0.0, 0.0);
+                    for (l, tap) in taps.iter().enumerate() {
+                        want += input[t - l] * rotator(c, n, t - l).conj() * tap;
+                    }
+                    assert!((y - want).norm() < 1e-4);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn synthesizer_matches_reference() {
+        let n = 8;
+        let taps = firdes::kaiser::multirate::<f32>(1, n, 4, 0.001);
+        let inputs: Vec<Vec<Complex<f32>>> = (0..n).map(|_| samples(50)).collect();
+        for oversampling in [1, 2] {
+            let mut kernel = PfbSynthesizerKernel::new(n, &taps, oversampling);
+            let output = synthesize(&mut kernel, &inputs);
+            let d = n / oversampling;
+
+            for (t, y) in output.iter().enumerate() {
+                // interpolate and mix up by c / n
+                let mut want = Complex::new(0.0, 0.0);
+                for (c, input) in inputs.iter().enumerate() {
+                    for (k, x) in input.iter().enumerate().take(t / d + 1) {
+                        if let Some(tap) = taps.get(t - k * d) {
+                            want += x * rotator(c, n, t) * tap * d as f32;
+                        }
+                    }
+                }
+                assert!((y - want).norm() < 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    fn streaming() {
+        let n = 4;
+        let taps = firdes::kaiser::multirate::<f32>(1, n, 6, 0.001);
+        let input = samples(500);
+        let mut kernel = PfbChannelizerKernel::new(n, &taps, 2);
+        let expected = channelize(&mut kernel, &input);
+
+        let mut kernel = PfbChannelizerKernel::new(n, &taps, 2);
+        let mut outputs = vec![Vec::new(); n];
+        let mut pos = 0;
+        loop {
+            let end = core::cmp::min(pos + 97, input.len());
+            let mut bufs = vec![[Complex::new(0.0, 0.0); 3]; n];
+            let mut slices: Vec<Option<&mut [Complex<f32>]>> =
+                bufs.iter_mut().map(|b| Some(&mut b[..])).collect();
+            // skip the second channel
+            slices[1] = None;
+            let (consumed, produced, _) = kernel.work(&input[pos..end], &mut slices);
+            for (o, b) in outputs.iter_mut().zip(bufs.iter()) {
+                o.extend_from_slice(&b[..produced]);
+            }
+            pos += consumed;
+            if produced == 0 && end == input.len() {
+                break;
+            }
+        }
+        for (c, (have, want)) in outputs.iter().zip(expected.iter()).enumerate() {
+            if c == 1 {
+                assert!(have.iter().all(|x| *x == Complex::new(0.0, 0.0)));
+            } else {
+                assert_eq!(have, want);
+            }
+        }
+
+        // synthesize with a disabled channel in chunks
+        let mut kernel = PfbSynthesizerKernel::new(n, &taps, 1);
+        let mut zeroed = expected.clone();
+        zeroed[1].fill(Complex::new(0.0, 0.0));
+        let expected = synthesize(&mut kernel, &zeroed);
+
+        let mut kernel = PfbSynthesizerKernel::new(n, &taps, 1);
+        let mut output = Vec::new();
+        let mut pos = 0;
+        while pos < zeroed[0].len() {
+            let end = core::cmp::min(pos + 5, zeroed[0].len());
+            let mut slices: Vec<Option<&[Complex<f32>]>> =
+                zeroed.iter().map(|i| Some(&i[pos..end])).collect();
+            slices[1] = None;
+            let mut buf = [Complex::new(0.0, 0.0); 11];
+            let (consumed, produced, _) = kernel.work(&slices, &mut buf);
+            output.extend_from_slice(&buf[..produced]);
+            pos += consumed;
+        }
+        assert_eq!(output, expected);
+    }
+
+    /// Tones close to the centers of channels 1 and 6.
+    fn tones() -> Vec<Complex<f32>> {
+        (0..8000)
+            .map(|t| {
+                let t = t as f32;
+                Complex::from_polar(1.0, 2.0 * PI * (1.0 / 8.0 + 0.003) * t)
+                    + Complex::from_polar(0.5, 2.0 * PI * (6.0 / 8.0 - 0.002) * t)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn channel_power() {
+        let n = 8;
+        let taps = firdes::kaiser::multirate::<f32>(1, n, 12, 0.0001);
+        let input = tones();
+
+        for oversampling in [1, 2] {
+            let mut channelizer = PfbChannelizerKernel::new(n, &taps, oversampling);
+            let channels = channelize(&mut channelizer, &input);
+            for (c, channel) in channels.iter().enumerate() {
+                let power =
+                    channel.iter().map(|x| x.norm_sqr()).sum::<f32>() / channel.len() as f32;
+                let want = match c {
+                    1 => 1.0,
+                    6 => 0.25,
+                    _ => 0.0,
+                };
+                assert!((power - want).abs() < 1e-3);
+            }
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let n = 8;
+        // the channels are only reconstructed without a delay, if the delay of the prototype
+        // filter is half its length, i.e., for an even number of symmetric taps
+        let window = windows::kaiser(192, 8.0);
+        let taps = firdes::lowpass::<f32>(1.0 / 16.0, &window);
+        let input = tones();
+
+        for oversampling in [1, 2] {
+            let mut channelizer = PfbChannelizerKernel::new(n, &taps, oversampling);
+            let channels = channelize(&mut channelizer, &input);
+            let mut synthesizer = PfbSynthesizerKernel::new(n, &taps, oversampling);
+            let output = synthesize(&mut synthesizer, &channels);
+            for (y, x) in output.iter().zip(input.iter()).skip(2 * taps.len()) {
+                assert!((y - x).norm() < 1e-3);
+            }
+        }
+    }
+}
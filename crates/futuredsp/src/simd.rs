@@ -0,0 +1,689 @@
+//! Vectorized kernels with runtime CPU feature detection
+//!
+//! The FIR kernels in [`fir`](crate::fir) use the best instruction set supported by the CPU
+//! for `f32` and `Complex<f32>` samples with `f32` or `Complex<f32>` taps. With the `std`
+//! feature (enabled by default), the instruction set is detected at runtime. Without it, only
+//! instruction sets enabled at compile time (e.g., with `-C target-feature=+avx2`) are used.
+//!
+//! Example usage:
+//! ```
+//! use futuredsp::fir::NonResamplingFirKernel;
+//! use futuredsp::simd::Isa;
+//!
+//! let fir = NonResamplingFirKernel::<f32, f32, _, _>::new([1.0, 2.0, 3.0]);
+//! assert_eq!(fir.isa(), Isa::detect());
+//!
+//! // force the scalar implementation
+//! let fir = fir.with_isa(Isa::Scalar);
+//! ```
+use num_complex::Complex;
+
+/// Instruction set of the vectorized kernels.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Isa {
+    /// Scalar implementation, available on all CPUs.
+    Scalar,
+    /// x86 SSE.
+    Sse,
+    /// x86 AVX2 and FMA.
+    Avx2,
+    /// x86 AVX-512F. Requires Rust 1.89 or newer.
+    Avx512,
+    /// Aarch64 NEON.
+    Neon,
+}
+
+#[cfg(all(feature = "std", any(target_arch = "x86", target_arch = "x86_64")))]
+macro_rules! has_feature {
+    ($feature:tt) => {
+        std::is_x86_feature_detected!($feature)
+    };
+}
+
+#[cfg(all(feature = "std", target_arch = "aarch64"))]
+macro_rules! has_feature {
+    ($feature:tt) => {
+        std::arch::is_aarch64_feature_detected!($feature)
+    };
+}
+
+#[cfg(not(all(
+    feature = "std",
+    any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64")
+)))]
+#[allow(unused_macros)]
+macro_rules! has_feature {
+    ($feature:tt) => {
+        cfg!(target_feature = $feature)
+    };
+}
+
+impl Isa {
+    /// All instruction sets, ordered from the most to the least preferred one.
+    pub const ALL: [Isa; 5] = [Isa::Avx512, Isa::Avx2, Isa::Sse, Isa::Neon, Isa::Scalar];
+
+    /// Best instruction set supported by the CPU.
+    pub fn detect() -> Self {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(Isa::is_supported)
+            .unwrap_or(Isa::Scalar)
+    }
+
+    /// Check if the CPU supports the instruction set.
+    pub fn is_supported(&self) -> bool {
+        match self {
+            Isa::Scalar => true,
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            Isa::Sse => has_feature!("sse"),
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            Isa::Avx2 => {
+                #[cfg(feature = "std")]
+                {
+                    has_feature!("avx2") && has_feature!("fma")
+                }
+                #[cfg(not(feature = "std"))]
+                {
+                    cfg!(all(target_feature = "avx2", target_feature = "fma"))
+                }
+            }
+            #[cfg(all(RUSTC_HAS_AVX512, any(target_arch = "x86", target_arch = "x86_64")))]
+            Isa::Avx512 => has_feature!("avx512f"),
+            #[cfg(target_arch = "aarch64")]
+            Isa::Neon => has_feature!("neon"),
+            #[allow(unreachable_patterns)]
+            _ => false,
+        }
+    }
+}
+
+/// Vectorized kernels of one instruction set. The target feature is given as string
+/// literal, the module has to provide `dot_ff`, `dot_cf`, and `dot_cc`.
+macro_rules! kernels {
+    ($feature:literal) => {
+        /// `o[k] = sum_t i[k + t] * taps[t]`
+        #[target_feature(enable = $feature)]
+        pub unsafe fn fir_ff(i: &[f32], taps: &[f32], o: &mut [f32]) {
+            debug_assert!(i.len() + 1 >= o.len() + taps.len());
+            let (i, h, n) = (i.as_ptr(), taps.as_ptr(), taps.len());
+            for (k, o) in o.iter_mut().enumerate() {
+                *o = dot_ff(i.add(k), h, n);
+            }
+        }
+
+        /// `o[k] = sum_t i[k + t] * taps[t]`
+        #[target_feature(enable = $feature)]
+        pub unsafe fn fir_cf(i: &[Complex<f32>], taps: &[f32], o: &mut [Complex<f32>]) {
+            debug_assert!(i.len() + 1 >= o.len() + taps.len());
+            let (i, h, n) = (i.as_ptr() as *const f32, taps.as_ptr(), taps.len());
+            for (k, o) in o.iter_mut().enumerate() {
+                *o = dot_cf(i.add(2 * k), h, n);
+            }
+        }
+
+        /// `o[k] = sum_t i[k + t] * taps[t]`
+        #[target_feature(enable = $feature)]
+        pub unsafe fn fir_cc(i: &[Complex<f32>], taps: &[Complex<f32>], o: &mut [Complex<f32>]) {
+            debug_assert!(i.len() + 1 >= o.len() + taps.len());
+            let (i, h, n) = (
+                i.as_ptr() as *const f32,
+                taps.as_ptr() as *const f32,
+                taps.len(),
+            );
+            for (k, o) in o.iter_mut().enumerate() {
+                *o = dot_cc(i.add(2 * k), h, n);
+            }
+        }
+
+        /// `o[k] = sum_t i[k + t] * taps[t]`
+        #[target_feature(enable = $feature)]
+        pub unsafe fn fir_fc(i: &[f32], taps: &[Complex<f32>], o: &mut [Complex<f32>]) {
+            debug_assert!(i.len() + 1 >= o.len() + taps.len());
+            let (i, h, n) = (i.as_ptr(), taps.as_ptr() as *const f32, taps.len());
+            for (k, o) in o.iter_mut().enumerate() {
+                // the product is commutative, so the complex taps take the place of the samples
+                *o = dot_cf(h, i.add(k), n);
+            }
+        }
+
+        /// `o[k] = sum_t i[k * decim / interp + t] * banks[b][t]` with
+        /// `b = k * decim % interp`
+        #[target_feature(enable = $feature)]
+        pub unsafe fn polyphase_ff(
+            interp: usize,
+            decim: usize,
+            i: &[f32],
+            banks: &[f32],
+            o: &mut [f32],
+        ) {
+            let n = banks.len() / interp;
+            debug_assert!(o.is_empty() || (o.len() - 1) * decim / interp + n <= i.len());
+            let (i, h) = (i.as_ptr(), banks.as_ptr());
+            for (k, o) in o.iter_mut().enumerate() {
+                let bank = (k * decim) % interp;
+                *o = dot_ff(i.add(k * decim / interp), h.add(bank * n), n);
+            }
+        }
+
+        /// `o[k] = sum_t i[k * decim / interp + t] * banks[b][t]` with
+        /// `b = k * decim % interp`
+        #[target_feature(enable = $feature)]
+        pub unsafe fn polyphase_cf(
+            interp: usize,
+            decim: usize,
+            i: &[Complex<f32>],
+            banks: &[f32],
+            o: &mut [Complex<f32>],
+        ) {
+            let n = banks.len() / interp;
+            debug_assert!(o.is_empty() || (o.len() - 1) * decim / interp + n <= i.len());
+            let (i, h) = (i.as_ptr() as *const f32, banks.as_ptr());
+            for (k, o) in o.iter_mut().enumerate() {
+                let bank = (k * decim) % interp;
+                *o = dot_cf(i.add(2 * (k * decim / interp)), h.add(bank * n), n);
+            }
+        }
+
+        /// `o[k] = sum_t i[k * decim / interp + t] * banks[b][t]` with
+        /// `b = k * decim % interp`
+        #[target_feature(enable = $feature)]
+        pub unsafe fn polyphase_fc(
+            interp: usize,
+            decim: usize,
+            i: &[f32],
+            banks: &[Complex<f32>],
+            o: &mut [Complex<f32>],
+        ) {
+            let n = banks.len() / interp;
+            debug_assert!(o.is_empty() || (o.len() - 1) * decim / interp + n <= i.len());
+            let (i, h) = (i.as_ptr(), banks.as_ptr() as *const f32);
+            for (k, o) in o.iter_mut().enumerate() {
+                let bank = (k * decim) % interp;
+                *o = dot_cf(h.add(2 * bank * n), i.add(k * decim / interp), n);
+            }
+        }
+
+        /// `o[k] = sum_t i[k * decim / interp + t] * banks[b][t]` with
+        /// `b = k * decim % interp`
+        #[target_feature(enable = $feature)]
+        pub unsafe fn polyphase_cc(
+            interp: usize,
+            decim: usize,
+            i: &[Complex<f32>],
+            banks: &[Complex<f32>],
+            o: &mut [Complex<f32>],
+        ) {
+            let n = banks.len() / interp;
+            debug_assert!(o.is_empty() || (o.len() - 1) * decim / interp + n <= i.len());
+            let (i, h) = (i.as_ptr() as *const f32, banks.as_ptr() as *const f32);
+            for (k, o) in o.iter_mut().enumerate() {
+                let bank = (k * decim) % interp;
+                *o = dot_cc(i.add(2 * (k * decim / interp)), h.add(2 * bank * n), n);
+            }
+        }
+    };
+}
+
+/// Sum the real products of the tail that does not fill a vector.
+#[inline(always)]
+unsafe fn tail_ff(x: *const f32, h: *const f32, start: usize, n: usize) -> f32 {
+    let mut sum = 0.0;
+    for j in start..n {
+        sum += *x.add(j) * *h.add(j);
+    }
+    sum
+}
+
+/// Sum the complex-real products of the tail that does not fill a vector.
+#[inline(always)]
+unsafe fn tail_cf(x: *const f32, h: *const f32, start: usize, n: usize) -> Complex<f32> {
+    let mut sum = Complex::new(0.0, 0.0);
+    for j in start..n {
+        sum.re += *x.add(2 * j) * *h.add(j);
+        sum.im += *x.add(2 * j + 1) * *h.add(j);
+    }
+    sum
+}
+
+/// Sum the complex products of the tail that does not fill a vector.
+#[inline(always)]
+unsafe fn tail_cc(x: *const f32, h: *const f32, start: usize, n: usize) -> Complex<f32> {
+    let mut sum = Complex::new(0.0, 0.0);
+    for j in start..n {
+        let x = Complex::new(*x.add(2 * j), *x.add(2 * j + 1));
+        let h = Complex::new(*h.add(2 * j), *h.add(2 * j + 1));
+        sum += x * h;
+    }
+    sum
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+mod sse {
+    #[cfg(target_arch = "x86")]
+    use core::arch::x86::*;
+    #[cfg(target_arch = "x86_64")]
+    use core::arch::x86_64::*;
+    use num_complex::Complex;
+
+    use super::{tail_cc, tail_cf, tail_ff};
+
+    /// Sums of the even and odd lanes
+    #[inline]
+    #[target_feature(enable = "sse")]
+    unsafe fn hsum(v: __m128) -> (f32, f32) {
+        let s = _mm_add_ps(v, _mm_movehl_ps(v, v));
+        (_mm_cvtss_f32(s), _mm_cvtss_f32(_mm_shuffle_ps(s, s, 0b01)))
+    }
+
+    #[inline]
+    #[target_feature(enable = "sse")]
+    unsafe fn dot_ff(x: *const f32, h: *const f32, n: usize) -> f32 {
+        let mut acc0 = _mm_setzero_ps();
+        let mut acc1 = _mm_setzero_ps();
+        let mut j = 0;
+        while j + 8 <= n {
+            acc0 = _mm_add_ps(
+                acc0,
+                _mm_mul_ps(_mm_loadu_ps(x.add(j)), _mm_loadu_ps(h.add(j))),
+            );
+            acc1 = _mm_add_ps(
+                acc1,
+                _mm_mul_ps(_mm_loadu_ps(x.add(j + 4)), _mm_loadu_ps(h.add(j + 4))),
+            );
+            j += 8;
+        }
+        if j + 4 <= n {
+            acc0 = _mm_add_ps(
+                acc0,
+                _mm_mul_ps(_mm_loadu_ps(x.add(j)), _mm_loadu_ps(h.add(j))),
+            );
+            j += 4;
+        }
+        let (even, odd) = hsum(_mm_add_ps(acc0, acc1));
+        even + odd + tail_ff(x, h, j, n)
+    }
+
+    #[inline]
+    #[target_feature(enable = "sse")]
+    unsafe fn dot_cf(x: *const f32, h: *const f32, n: usize) -> Complex<f32> {
+        let mut acc0 = _mm_setzero_ps();
+        let mut acc1 = _mm_setzero_ps();
+        let mut j = 0;
+        while j + 4 <= n {
+            let t = _mm_loadu_ps(h.add(j));
+            let lo = _mm_unpacklo_ps(t, t);
+            let hi = _mm_unpackhi_ps(t, t);
+            acc0 = _mm_add_ps(acc0, _mm_mul_ps(_mm_loadu_ps(x.add(2 * j)), lo));
+            acc1 = _mm_add_ps(acc1, _mm_mul_ps(_mm_loadu_ps(x.add(2 * j + 4)), hi));
+            j += 4;
+        }
+        let (re, im) = hsum(_mm_add_ps(acc0, acc1));
+        Complex::new(re, im) + tail_cf(x, h, j, n)
+    }
+
+    #[inline]
+    #[target_feature(enable = "sse")]
+    unsafe fn dot_cc(x: *const f32, h: *const f32, n: usize) -> Complex<f32> {
+        let mut acc_re = _mm_setzero_ps();
+        let mut acc_im = _mm_setzero_ps();
+        let mut j = 0;
+        while j + 2 <= n {
+            let v = _mm_loadu_ps(x.add(2 * j));
+            let t = _mm_loadu_ps(h.add(2 * j));
+            acc_re = _mm_add_ps(acc_re, _mm_mul_ps(v, t));
+            acc_im = _mm_add_ps(acc_im, _mm_mul_ps(v, _mm_shuffle_ps(t, t, 0b10_11_00_01)));
+            j += 2;
+        }
+        let (rr, ii) = hsum(acc_re);
+        let (ri, ir) = hsum(acc_im);
+        Complex::new(rr - ii, ri + ir) + tail_cc(x, h, j, n)
+    }
+
+    kernels!("sse");
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+mod avx2 {
+    #[cfg(target_arch = "x86")]
+    use core::arch::x86::*;
+    #[cfg(target_arch = "x86_64")]
+    use core::arch::x86_64::*;
+    use num_complex::Complex;
+
+    use super::{tail_cc, tail_cf, tail_ff};
+
+    /// Sums of the even and odd lanes
+    #[inline]
+    #[target_feature(enable = "avx2,fma")]
+    unsafe fn hsum(v: __m256) -> (f32, f32) {
+        let v = _mm_add_ps(_mm256_castps256_ps128(v), _mm256_extractf128_ps(v, 1));
+        let s = _mm_add_ps(v, _mm_movehl_ps(v, v));
+        (_mm_cvtss_f32(s), _mm_cvtss_f32(_mm_shuffle_ps(s, s, 0b01)))
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx2,fma")]
+    unsafe fn dot_ff(x: *const f32, h: *const f32, n: usize) -> f32 {
+        let mut acc0 = _mm256_setzero_ps();
+        let mut acc1 = _mm256_setzero_ps();
+        let mut j = 0;
+        while j + 16 <= n {
+            acc0 = _mm256_fmadd_ps(_mm256_loadu_ps(x.add(j)), _mm256_loadu_ps(h.add(j)), acc0);
+            acc1 = _mm256_fmadd_ps(
+                _mm256_loadu_ps(x.add(j + 8)),
+                _mm256_loadu_ps(h.add(j + 8)),
+                acc1,
+            );
+            j += 16;
+        }
+        if j + 8 <= n {
+            acc0 = _mm256_fmadd_ps(_mm256_loadu_ps(x.add(j)), _mm256_loadu_ps(h.add(j)), acc0);
+            j += 8;
+        }
+        let (even, odd) = hsum(_mm256_add_ps(acc0, acc1));
+        even + odd + tail_ff(x, h, j, n)
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx2,fma")]
+    unsafe fn dot_cf(x: *const f32, h: *const f32, n: usize) -> Complex<f32> {
+        let idx_lo = _mm256_setr_epi32(0, 0, 1, 1, 2, 2, 3, 3);
+        let idx_hi = _mm256_setr_epi32(4, 4, 5, 5, 6, 6, 7, 7);
+        let mut acc0 = _mm256_setzero_ps();
+        let mut acc1 = _mm256_setzero_ps();
+        let mut j = 0;
+        while j + 8 <= n {
+            let t = _mm256_loadu_ps(h.add(j));
+            let lo = _mm256_permutevar8x32_ps(t, idx_lo);
+            let hi = _mm256_permutevar8x32_ps(t, idx_hi);
+            acc0 = _mm256_fmadd_ps(_mm256_loadu_ps(x.add(2 * j)), lo, acc0);
+            acc1 = _mm256_fmadd_ps(_mm256_loadu_ps(x.add(2 * j + 8)), hi, acc1);
+            j += 8;
+        }
+        let (re, im) = hsum(_mm256_add_ps(acc0, acc1));
+        Complex::new(re, im) + tail_cf(x, h, j, n)
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx2,fma")]
+    unsafe fn dot_cc(x: *const f32, h: *const f32, n: usize) -> Complex<f32> {
+        let mut acc_re = _mm256_setzero_ps();
+        let mut acc_im = _mm256_setzero_ps();
+        let mut j = 0;
+        while j + 4 <= n {
+            let v = _mm256_loadu_ps(x.add(2 * j));
+            let t = _mm256_loadu_ps(h.add(2 * j));
+            acc_re = _mm256_fmadd_ps(v, t, acc_re);
+            acc_im = _mm256_fmadd_ps(v, _mm256_permute_ps(t, 0b10_11_00_01), acc_im);
+            j += 4;
+        }
+        let (rr, ii) = hsum(acc_re);
+        let (ri, ir) = hsum(acc_im);
+        Complex::new(rr - ii, ri + ir) + tail_cc(x, h, j, n)
+    }
+
+    kernels!("avx2,fma");
+}
+
+#[cfg(all(RUSTC_HAS_AVX512, any(target_arch = "x86", target_arch = "x86_64")))]
+mod avx512 {
+    #[cfg(target_arch = "x86")]
+    use core::arch::x86::*;
+    #[cfg(target_arch = "x86_64")]
+    use core::arch::x86_64::*;
+    use num_complex::Complex;
+
+    use super::{tail_cc, tail_cf, tail_ff};
+
+    /// Sums of the even and odd lanes
+    #[inline]
+    #[target_feature(enable = "avx512f")]
+    unsafe fn hsum(v: __m512) -> (f32, f32) {
+        (
+            _mm512_mask_reduce_add_ps(0x5555, v),
+            _mm512_mask_reduce_add_ps(0xaaaa, v),
+        )
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx512f")]
+    unsafe fn dot_ff(x: *const f32, h: *const f32, n: usize) -> f32 {
+        let mut acc0 = _mm512_setzero_ps();
+        let mut acc1 = _mm512_setzero_ps();
+        let mut j = 0;
+        while j + 32 <= n {
+            acc0 = _mm512_fmadd_ps(_mm512_loadu_ps(x.add(j)), _mm512_loadu_ps(h.add(j)), acc0);
+            acc1 = _mm512_fmadd_ps(
+                _mm512_loadu_ps(x.add(j + 16)),
+                _mm512_loadu_ps(h.add(j + 16)),
+                acc1,
+            );
+            j += 32;
+        }
+        if j + 16 <= n {
+            acc0 = _mm512_fmadd_ps(_mm512_loadu_ps(x.add(j)), _mm512_loadu_ps(h.add(j)), acc0);
+            j += 16;
+        }
+        let acc = _mm512_add_ps(acc0, acc1);
+        _mm512_reduce_add_ps(acc) + tail_ff(x, h, j, n)
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx512f")]
+    unsafe fn dot_cf(x: *const f32, h: *const f32, n: usize) -> Complex<f32> {
+        let idx_lo = _mm512_setr_epi32(0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7);
+        let idx_hi = _mm512_setr_epi32(8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13, 14, 14, 15, 15);
+        let mut acc0 = _mm512_setzero_ps();
+        let mut acc1 = _mm512_setzero_ps();
+        let mut j = 0;
+        while j + 16 <= n {
+            let t = _mm512_loadu_ps(h.add(j));
+            let lo = _mm512_permutexvar_ps(idx_lo, t);
+            let hi = _mm512_permutexvar_ps(idx_hi, t);
+            acc0 = _mm512_fmadd_ps(_mm512_loadu_ps(x.add(2 * j)), lo, acc0);
+            acc1 = _mm512_fmadd_ps(_mm512_loadu_ps(x.add(2 * j + 16)), hi, acc1);
+            j += 16;
+        }
+        let (re, im) = hsum(_mm512_add_ps(acc0, acc1));
+        Complex::new(re, im) + tail_cf(x, h, j, n)
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx512f")]
+    unsafe fn dot_cc(x: *const f32, h: *const f32, n: usize) -> Complex<f32> {
+        let mut acc_re = _mm512_setzero_ps();
+        let mut acc_im = _mm512_setzero_ps();
+        let mut j = 0;
+        while j + 8 <= n {
+            let v = _mm512_loadu_ps(x.add(2 * j));
+            let t = _mm512_loadu_ps(h.add(2 * j));
+            acc_re = _mm512_fmadd_ps(v, t, acc_re);
+            acc_im = _mm512_fmadd_ps(v, _mm512_permute_ps(t, 0b10_11_00_01), acc_im);
+            j += 8;
+        }
+        let (rr, ii) = hsum(acc_re);
+        Complex::new(rr - ii, _mm512_reduce_add_ps(acc_im)) + tail_cc(x, h, j, n)
+    }
+
+    kernels!("avx512f");
+}
+
+#[cfg(target_arch = "aarch64")]
+mod neon {
+    use core::arch::aarch64::*;
+    use num_complex::Complex;
+
+    use super::{tail_cc, tail_cf, tail_ff};
+
+    /// Sums of the even and odd lanes
+    #[inline]
+    #[target_feature(enable = "neon")]
+    unsafe fn hsum(v: float32x4_t) -> (f32, f32) {
+        let s = vadd_f32(vget_low_f32(v), vget_high_f32(v));
+        (vget_lane_f32(s, 0), vget_lane_f32(s, 1))
+    }
+
+    #[inline]
+    #[target_feature(enable = "neon")]
+    unsafe fn dot_ff(x: *const f32, h: *const f32, n: usize) -> f32 {
+        let mut acc0 = vdupq_n_f32(0.0);
+        let mut acc1 = vdupq_n_f32(0.0);
+        let mut j = 0;
+        while j + 8 <= n {
+            acc0 = vfmaq_f32(acc0, vld1q_f32(x.add(j)), vld1q_f32(h.add(j)));
+            acc1 = vfmaq_f32(acc1, vld1q_f32(x.add(j + 4)), vld1q_f32(h.add(j + 4)));
+            j += 8;
+        }
+        if j + 4 <= n {
+            acc0 = vfmaq_f32(acc0, vld1q_f32(x.add(j)), vld1q_f32(h.add(j)));
+            j += 4;
+        }
+        vaddvq_f32(vaddq_f32(acc0, acc1)) + tail_ff(x, h, j, n)
+    }
+
+    #[inline]
+    #[target_feature(enable = "neon")]
+    unsafe fn dot_cf(x: *const f32, h: *const f32, n: usize) -> Complex<f32> {
+        let mut acc0 = vdupq_n_f32(0.0);
+        let mut acc1 = vdupq_n_f32(0.0);
+        let mut j = 0;
+        while j + 4 <= n {
+            let t = vld1q_f32(h.add(j));
+            acc0 = vfmaq_f32(acc0, vld1q_f32(x.add(2 * j)), vzip1q_f32(t, t));
+            acc1 = vfmaq_f32(acc1, vld1q_f32(x.add(2 * j + 4)), vzip2q_f32(t, t));
+            j += 4;
+        }
+        let (re, im) = hsum(vaddq_f32(acc0, acc1));
+        Complex::new(re, im) + tail_cf(x, h, j, n)
+    }
+
+    #[inline]
+    #[target_feature(enable = "neon")]
+    unsafe fn dot_cc(x: *const f32, h: *const f32, n: usize) -> Complex<f32> {
+        let mut acc_re = vdupq_n_f32(0.0);
+        let mut acc_im = vdupq_n_f32(0.0);
+        let mut j = 0;
+        while j + 2 <= n {
+            let v = vld1q_f32(x.add(2 * j));
+            let t = vld1q_f32(h.add(2 * j));
+            acc_re = vfmaq_f32(acc_re, v, t);
+            acc_im = vfmaq_f32(acc_im, v, vrev64q_f32(t));
+            j += 2;
+        }
+        let (rr, ii) = hsum(acc_re);
+        Complex::new(rr - ii, vaddvq_f32(acc_im)) + tail_cc(x, h, j, n)
+    }
+
+    kernels!("neon");
+}
+
+/// Dispatch a kernel to the module of the instruction set. Panics for [`Isa::Scalar`] and
+/// instruction sets that are not available for the target architecture.
+macro_rules! dispatch {
+    ($isa:expr, $kernel:ident($($arg:expr),*)) => {
+        // Safety: the kernels check that the CPU supports the instruction set
+        unsafe {
+            match $isa {
+                #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+                Isa::Sse => sse::$kernel($($arg),*),
+                #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+                Isa::Avx2 => avx2::$kernel($($arg),*),
+                #[cfg(all(RUSTC_HAS_AVX512, any(target_arch = "x86", target_arch = "x86_64")))]
+                Isa::Avx512 => avx512::$kernel($($arg),*),
+                #[cfg(target_arch = "aarch64")]
+                Isa::Neon => neon::$kernel($($arg),*),
+                isa => panic!("no vectorized kernel for {:?}", isa),
+            }
+        }
+    };
+}
+
+/// Real FIR filter with real taps. `taps` are in reversed order, i.e., `taps[0]` is
+/// multiplied with the oldest sample.
+pub(crate) fn fir_ff(isa: Isa, i: &[f32], taps: &[f32], o: &mut [f32]) {
+    debug_assert!(isa.is_supported());
+    assert!(i.len() + 1 >= o.len() + taps.len());
+    dispatch!(isa, fir_ff(i, taps, o))
+}
+
+/// Complex FIR filter with real taps. `taps` are in reversed order.
+pub(crate) fn fir_cf(isa: Isa, i: &[Complex<f32>], taps: &[f32], o: &mut [Complex<f32>]) {
+    debug_assert!(isa.is_supported());
+    assert!(i.len() + 1 >= o.len() + taps.len());
+    dispatch!(isa, fir_cf(i, taps, o))
+}
+
+/// Complex FIR filter with complex taps. `taps` are in reversed order.
+pub(crate) fn fir_cc(isa: Isa, i: &[Complex<f32>], taps: &[Complex<f32>], o: &mut [Complex<f32>]) {
+    debug_assert!(isa.is_supported());
+    assert!(i.len() + 1 >= o.len() + taps.len());
+    dispatch!(isa, fir_cc(i, taps, o))
+}
+
+/// Real FIR filter with complex taps. `taps` are in reversed order.
+pub(crate) fn fir_fc(isa: Isa, i: &[f32], taps: &[Complex<f32>], o: &mut [Complex<f32>]) {
+    debug_assert!(isa.is_supported());
+    assert!(i.len() + 1 >= o.len() + taps.len());
+    dispatch!(isa, fir_fc(i, taps, o))
+}
+
+/// Real polyphase FIR filter with real taps. `banks` holds the taps of the `interp`
+/// polyphase components one after another, each in reversed order.
+pub(crate) fn polyphase_ff(
+    isa: Isa,
+    interp: usize,
+    decim: usize,
+    i: &[f32],
+    banks: &[f32],
+    o: &mut [f32],
+) {
+    debug_assert!(isa.is_supported());
+    assert!(o.is_empty() || (o.len() - 1) * decim / interp + banks.len() / interp <= i.len());
+    dispatch!(isa, polyphase_ff(interp, decim, i, banks, o))
+}
+
+/// Complex polyphase FIR filter with real taps. `banks` holds the taps of the `interp`
+/// polyphase components one after another, each in reversed order.
+pub(crate) fn polyphase_cf(
+    isa: Isa,
+    interp: usize,
+    decim: usize,
+    i: &[Complex<f32>],
+    banks: &[f32],
+    o: &mut [Complex<f32>],
+) {
+    debug_assert!(isa.is_supported());
+    assert!(o.is_empty() || (o.len() - 1) * decim / interp + banks.len() / interp <= i.len());
+    dispatch!(isa, polyphase_cf(interp, decim, i, banks, o))
+}
+
+/// Real polyphase FIR filter with complex taps. `banks` holds the taps of the `interp`
+/// polyphase components one after another, each in reversed order.
+pub(crate) fn polyphase_fc(
+    isa: Isa,
+    interp: usize,
+    decim: usize,
+    i: &[f32],
+    banks: &[Complex<f32>],
+    o: &mut [Complex<f32>],
+) {
+    debug_assert!(isa.is_supported());
+    assert!(o.is_empty() || (o.len() - 1) * decim / interp + banks.len() / interp <= i.len());
+    dispatch!(isa, polyphase_fc(interp, decim, i, banks, o))
+}
+
+/// Complex polyphase FIR filter with complex taps. `banks` holds the taps of the `interp`
+/// polyphase components one after another, each in reversed order.
+pub(crate) fn polyphase_cc(
+    isa: Isa,
+    interp: usize,
+    decim: usize,
+    i: &[Complex<f32>],
+    banks: &[Complex<f32>],
+    o: &mut [Complex<f32>],
+) {
+    debug_assert!(isa.is_supported());
+    assert!(o.is_empty() || (o.len() - 1) * decim / interp + banks.len() / interp <= i.len());
+    dispatch!(isa, polyphase_cc(interp, decim, i, banks, o))
+}
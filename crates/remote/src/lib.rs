@@ -29,6 +29,7 @@ pub use remote::Connection;
 pub use remote::ConnectionType;
 pub use remote::Flowgraph;
 pub use remote::Handler;
+pub use remote::MessageOutput;
 pub use remote::Remote;
 
 use thiserror::Error;
@@ -54,6 +55,9 @@ pub enum Error {
     /// Error in [`http`] crate.
     #[error("HTTP error")]
     Http(#[from] http::Error),
+    /// Invalid credentials for an HTTP header.
+    #[error("Invalid credentials")]
+    Credentials(#[from] http::header::InvalidHeaderValue),
     /// Wrong [`Flowgraph`] ID.
     #[error("Wrong flowgraph id")]
     FlowgraphId(usize),
@@ -1,39 +1,140 @@
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use futures::stream::BoxStream;
 use futures::Future;
+use futures::StreamExt;
 use futuresdr_types::BlockDescription;
 use futuresdr_types::FlowgraphDescription;
+use futuresdr_types::FlowgraphEvent;
+use futuresdr_types::FlowgraphState;
+use futuresdr_types::FlowgraphStats;
 use futuresdr_types::Pmt;
+use hyper::body::HttpBody;
 use hyper::client::connect::Connect;
 use hyper::client::HttpConnector;
+use hyper::header::HeaderValue;
 use hyper::rt::Executor;
 use hyper::Body;
 use hyper::Client;
 use hyper::Request;
+use serde::de::DeserializeOwned;
 use serde::Deserialize;
 use std::pin::Pin;
+use std::time::Duration;
 
 use crate::Error;
 
+/// HTTP client with the credentials for the control port.
+#[derive(Clone, Debug)]
+struct Api<H: Connect + Clone + Send + Sync + 'static> {
+    client: Client<H>,
+    auth: Option<HeaderValue>,
+}
+
+impl<H: Connect + Clone + Send + Sync + 'static> Api<H> {
+    fn new(client: Client<H>) -> Self {
+        Self { client, auth: None }
+    }
+
+    async fn request(&self, mut req: Request<Body>) -> Result<hyper::Response<Body>, Error> {
+        if let Some(auth) = &self.auth {
+            req.headers_mut()
+                .insert(hyper::header::AUTHORIZATION, auth.clone());
+        }
+        Ok(self.client.request(req).await?)
+    }
+}
+
 async fn get<H: Connect + Clone + Send + Sync + 'static, T: for<'a> Deserialize<'a>>(
-    client: &Client<H>,
+    api: &Api<H>,
     url: String,
 ) -> Result<T, Error> {
     let url: hyper::Uri = url.parse()?;
-    let body = match client.get(url.clone()).await {
-        Ok(b) => {
-            if !b.status().is_success() {
-                return Err(Error::Endpoint(url));
-            }
-            b.into_body()
-        }
-        Err(e) => return Err(e.into()),
-    };
+    let req = Request::get(url.clone()).body(Body::empty())?;
+    let res = api.request(req).await?;
+    if !res.status().is_success() {
+        return Err(Error::Endpoint(url));
+    }
+    let body = res.into_body();
     let bytes = hyper::body::to_bytes(body).await?;
     Ok(serde_json::from_slice(&bytes)?)
 }
 
+async fn post<H: Connect + Clone + Send + Sync + 'static, T: for<'a> Deserialize<'a>>(
+    api: &Api<H>,
+    url: String,
+    pmt: &Pmt,
+) -> Result<T, Error> {
+    let json = serde_json::to_string(pmt)?;
+    let url: hyper::Uri = url.parse()?;
+    let req = Request::post(url.clone())
+        .header(hyper::header::CONTENT_TYPE, "application/json")
+        .body(Body::from(json))?;
+    let res = api.request(req).await?;
+    if !res.status().is_success() {
+        return Err(Error::Endpoint(url));
+    }
+    let body = res.into_body();
+    let bytes = hyper::body::to_bytes(body).await?;
+    Ok(serde_json::from_slice(&bytes)?)
+}
+
+/// Take the data of the next complete Server-Sent Event from the buffer.
+///
+/// Events without data, like keep-alive comments, yield an empty string.
+fn next_event(buf: &mut Vec<u8>) -> Option<String> {
+    let end = buf.windows(2).position(|w| w == b"\n\n")?;
+    let event: Vec<u8> = buf.drain(..end + 2).collect();
+    let event = String::from_utf8_lossy(&event);
+    let data: Vec<&str> = event
+        .lines()
+        .filter_map(|l| l.strip_prefix("data:"))
+        .map(|l| l.strip_prefix(' ').unwrap_or(l))
+        .collect();
+    Some(data.join("\n"))
+}
+
+async fn subscribe<
+    H: Connect + Clone + Send + Sync + 'static,
+    T: DeserializeOwned + Send + 'static,
+>(
+    api: &Api<H>,
+    url: String,
+) -> Result<BoxStream<'static, Result<T, Error>>, Error> {
+    let url: hyper::Uri = url.parse()?;
+    let req = Request::get(url.clone())
+        .header(hyper::header::ACCEPT, "text/event-stream")
+        .body(Body::empty())?;
+    let res = api.request(req).await?;
+    if !res.status().is_success() {
+        return Err(Error::Endpoint(url));
+    }
+
+    let stream = futures::stream::unfold(
+        (res.into_body(), Vec::new()),
+        |(mut body, mut buf)| async move {
+            loop {
+                if let Some(data) = next_event(&mut buf) {
+                    if data.is_empty() {
+                        continue;
+                    }
+                    let item = serde_json::from_str(&data).map_err(Error::from);
+                    return Some((item, (body, buf)));
+                }
+                match body.data().await {
+                    Some(Ok(chunk)) => buf.extend_from_slice(&chunk),
+                    Some(Err(e)) => return Some((Err(e.into()), (body, buf))),
+                    None => return None,
+                }
+            }
+        },
+    );
+    Ok(stream.boxed())
+}
+
 /// Connection to a remote runtime.
 pub struct Remote<H: Connect + Clone + Send + Sync + 'static> {
-    client: Client<H>,
+    api: Api<H>,
     url: String,
 }
 
@@ -41,7 +142,7 @@ impl Remote<HttpConnector> {
     /// Create a [`Remote`].
     pub fn new<I: Into<String>>(url: I) -> Self {
         Self {
-            client: Client::new(),
+            api: Api::new(Client::new()),
             url: url.into(),
         }
     }
@@ -49,47 +150,117 @@ impl Remote<HttpConnector> {
 
 impl<H: Connect + Clone + Send + Sync + 'static> Remote<H> {
     /// Create a [`Remote`] with an async runtime.
+    ///
+    /// Use a connector that supports TLS to connect to a control port that is served over HTTPS.
     pub fn with_runtime<E>(url: String, connector: H, executor: E) -> Self
     where
         E: Executor<Pin<Box<dyn Future<Output = ()> + Send>>> + Send + Sync + 'static,
     {
         let client = Client::builder().executor(executor).build(connector);
-        Self { client, url }
+        Self {
+            api: Api::new(client),
+            url,
+        }
+    }
+
+    /// Authenticate with a token, which is sent as `Bearer` token.
+    ///
+    /// Use the write token of the runtime to call handlers, terminate, or launch flowgraphs.
+    pub fn with_token(mut self, token: &str) -> Result<Self, Error> {
+        let mut auth = HeaderValue::from_str(&format!("Bearer {token}"))?;
+        auth.set_sensitive(true);
+        self.api.auth = Some(auth);
+        Ok(self)
+    }
+
+    /// Authenticate with user name and password, using `Basic` authorization.
+    ///
+    /// The runtime ignores the user name and expects a token as password.
+    pub fn with_basic_auth(mut self, user: &str, password: &str) -> Result<Self, Error> {
+        let credentials = BASE64.encode(format!("{user}:{password}"));
+        let mut auth = HeaderValue::from_str(&format!("Basic {credentials}"))?;
+        auth.set_sensitive(true);
+        self.api.auth = Some(auth);
+        Ok(self)
     }
 
     /// Get a specific [`Flowgraph`].
     pub async fn flowgraph(&self, id: usize) -> Result<Flowgraph<H>, Error> {
-        let fgs = self.flowgraphs().await?;
-        fgs.iter()
-            .find(|x| x.id == id)
-            .cloned()
-            .ok_or(Error::FlowgraphId(id))
+        let description = get(&self.api, format!("{}/api/fg/{}/", self.url, id))
+            .await
+            .map_err(|_| Error::FlowgraphId(id))?;
+        Ok(Flowgraph {
+            id,
+            description,
+            api: self.api.clone(),
+            url: self.url.clone(),
+        })
     }
 
     /// Get a list of all running [`Flowgraphs`](Flowgraph).
     pub async fn flowgraphs(&self) -> Result<Vec<Flowgraph<H>>, Error> {
-        let ids: Vec<usize> = get(&self.client, format!("{}/api/fg/", self.url)).await?;
         let mut v = Vec::new();
 
-        for i in ids.into_iter() {
-            let fg: FlowgraphDescription =
-                get(&self.client, format!("{}/api/fg/{}/", self.url, i)).await?;
-            v.push(fg);
+        for (id, state) in self.states().await? {
+            if state != FlowgraphState::Running {
+                continue;
+            }
+            let description: FlowgraphDescription =
+                get(&self.api, format!("{}/api/fg/{}/", self.url, id)).await?;
+            v.push(Flowgraph {
+                id,
+                description,
+                api: self.api.clone(),
+                url: self.url.clone(),
+            });
         }
 
-        let v = v
-            .into_iter()
-            .enumerate()
-            .map(|(i, f)| Flowgraph {
-                id: i,
-                description: f,
-                client: self.client.clone(),
-                url: self.url.clone(),
-            })
-            .collect();
+        Ok(v)
+    }
 
+    /// Get the [`FlowgraphState`] of all flowgraphs, including the ones that are done.
+    pub async fn states(&self) -> Result<Vec<(usize, FlowgraphState)>, Error> {
+        let ids: Vec<usize> = get(&self.api, format!("{}/api/fg/", self.url)).await?;
+        let mut v = Vec::new();
+        for id in ids.into_iter() {
+            v.push((id, self.state(id).await?));
+        }
         Ok(v)
     }
+
+    /// Get the [`FlowgraphState`] of a flowgraph.
+    pub async fn state(&self, id: usize) -> Result<FlowgraphState, Error> {
+        get(&self.api, format!("{}/api/fg/{}/state/", self.url, id)).await
+    }
+
+    /// Terminate a flowgraph.
+    pub async fn terminate(&self, id: usize) -> Result<(), Error> {
+        let _: Pmt = post(
+            &self.api,
+            format!("{}/api/fg/{}/terminate/", self.url, id),
+            &Pmt::Null,
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Get the names of the factories that can [`launch`](Self::launch) flowgraphs.
+    pub async fn factories(&self) -> Result<Vec<String>, Error> {
+        get(&self.api, format!("{}/api/factory/", self.url)).await
+    }
+
+    /// Launch a flowgraph through a factory of the runtime.
+    ///
+    /// The parameters are passed to the factory.
+    pub async fn launch(&self, factory: &str, params: Pmt) -> Result<Flowgraph<H>, Error> {
+        let id: usize = post(
+            &self.api,
+            format!("{}/api/factory/{}/", self.url, factory),
+            &params,
+        )
+        .await?;
+        self.flowgraph(id).await
+    }
 }
 
 /// A remote Flowgraph.
@@ -97,14 +268,14 @@ impl<H: Connect + Clone + Send + Sync + 'static> Remote<H> {
 pub struct Flowgraph<H: Connect + Clone + Send + Sync + 'static> {
     id: usize,
     description: FlowgraphDescription,
-    client: Client<H>,
+    api: Api<H>,
     url: String,
 }
 
 impl<H: Connect + Clone + Send + Sync + 'static> Flowgraph<H> {
     /// Update the [`Flowgraph`], getting current blocks and connections.
     pub async fn update(&mut self) -> Result<(), Error> {
-        self.description = get(&self.client, format!("{}/api/fg/{}/", self.url, self.id)).await?;
+        self.description = get(&self.api, format!("{}/api/fg/{}/", self.url, self.id)).await?;
         Ok(())
     }
 
@@ -115,7 +286,7 @@ impl<H: Connect + Clone + Send + Sync + 'static> Flowgraph<H> {
             .iter()
             .map(|d| Block {
                 description: d.clone(),
-                client: self.client.clone(),
+                api: self.api.clone(),
                 url: self.url.clone(),
                 flowgraph_id: self.id,
             })
@@ -130,7 +301,7 @@ impl<H: Connect + Clone + Send + Sync + 'static> Flowgraph<H> {
             .find(|x| x.id == id)
             .map(|d| Block {
                 description: d.clone(),
-                client: self.client.clone(),
+                api: self.api.clone(),
                 url: self.url.clone(),
                 flowgraph_id: self.id,
             })
@@ -167,6 +338,59 @@ impl<H: Connect + Clone + Send + Sync + 'static> Flowgraph<H> {
     }
 }
 
+impl<H: Connect + Clone + Send + Sync + 'static> Flowgraph<H> {
+    /// Id of the [`Flowgraph`].
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    /// Get the [`FlowgraphState`] of the [`Flowgraph`].
+    pub async fn state(&self) -> Result<FlowgraphState, Error> {
+        get(&self.api, format!("{}/api/fg/{}/state/", self.url, self.id)).await
+    }
+
+    /// Terminate the [`Flowgraph`].
+    pub async fn terminate(&self) -> Result<(), Error> {
+        let _: Pmt = post(
+            &self.api,
+            format!("{}/api/fg/{}/terminate/", self.url, self.id),
+            &Pmt::Null,
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Subscribe to the [`FlowgraphEvents`](FlowgraphEvent) of the [`Flowgraph`].
+    ///
+    /// The stream ends, once the flowgraph is done.
+    pub async fn events(&self) -> Result<BoxStream<'static, Result<FlowgraphEvent, Error>>, Error> {
+        subscribe(
+            &self.api,
+            format!("{}/api/fg/{}/events/", self.url, self.id),
+        )
+        .await
+    }
+
+    /// Subscribe to the [`FlowgraphStats`], which are pushed periodically.
+    ///
+    /// The stream ends, once the flowgraph is done.
+    pub async fn stats(
+        &self,
+        interval: Duration,
+    ) -> Result<BoxStream<'static, Result<FlowgraphStats, Error>>, Error> {
+        subscribe(
+            &self.api,
+            format!(
+                "{}/api/fg/{}/stats/stream/?interval_ms={}",
+                self.url,
+                self.id,
+                interval.as_millis()
+            ),
+        )
+        .await
+    }
+}
+
 impl<H: Connect + Clone + Send + Sync + 'static> std::fmt::Display for Flowgraph<H> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -189,11 +413,20 @@ pub enum Handler {
     Name(String),
 }
 
+/// Specify a message output port of a [`Block`]
+#[derive(Clone, Debug)]
+pub enum MessageOutput {
+    /// Numeric ID of the port
+    Id(usize),
+    /// Name of the port
+    Name(String),
+}
+
 /// A [`Block`] of a [`Flowgraph`].
 #[derive(Clone, Debug)]
 pub struct Block<H: Connect + Clone + Send + Sync + 'static> {
     description: BlockDescription,
-    client: Client<H>,
+    api: Api<H>,
     url: String,
     flowgraph_id: usize,
 }
@@ -202,7 +435,7 @@ impl<H: Connect + Clone + Send + Sync + 'static> Block<H> {
     /// Update the [`Block`], retrieving a new [`BlockDescription`] from the [`Flowgraph`].
     pub async fn update(&mut self) -> Result<(), Error> {
         self.description = get(
-            &self.client,
+            &self.api,
             format!(
                 "{}/api/fg/{}/block/{}/",
                 self.url, self.flowgraph_id, self.description.id
@@ -222,8 +455,7 @@ impl<H: Connect + Clone + Send + Sync + 'static> Block<H> {
 
     /// Call a message handler of a [`Block`] with the given [`Pmt`](futuresdr_types::Pmt).
     pub async fn callback(&self, handler: Handler, pmt: Pmt) -> Result<Pmt, Error> {
-        let json = serde_json::to_string(&pmt)?;
-        let url: hyper::Uri = match handler {
+        let url = match handler {
             Handler::Name(n) => format!(
                 "{}/api/fg/{}/block/{}/call/{}/",
                 &self.url, self.flowgraph_id, self.description.id, n
@@ -232,22 +464,33 @@ impl<H: Connect + Clone + Send + Sync + 'static> Block<H> {
                 "{}/api/fg/{}/block/{}/call/{}/",
                 &self.url, self.flowgraph_id, self.description.id, i
             ),
-        }
-        .parse()?;
-        let req = Request::post(url.clone())
-            .header(hyper::header::CONTENT_TYPE, "application/json")
-            .body(Body::from(json))?;
-        let body = match self.client.request(req).await {
-            Ok(b) => {
-                if !b.status().is_success() {
-                    return Err(Error::Endpoint(url));
-                }
-                b.into_body()
-            }
-            Err(e) => return Err(e.into()),
         };
-        let bytes = hyper::body::to_bytes(body).await?;
-        Ok(serde_json::from_slice(&bytes)?)
+        post(&self.api, url, &pmt).await
+    }
+}
+
+impl<H: Connect + Clone + Send + Sync + 'static> Block<H> {
+    /// Subscribe to the [`Pmts`](futuresdr_types::Pmt) that the [`Block`] posts to a message
+    /// output port.
+    ///
+    /// Messages are dropped, if the subscriber does not keep up. The stream ends, once the port
+    /// is finished.
+    pub async fn subscribe(
+        &self,
+        port: MessageOutput,
+    ) -> Result<BoxStream<'static, Result<Pmt, Error>>, Error> {
+        let port = match port {
+            MessageOutput::Id(i) => i.to_string(),
+            MessageOutput::Name(n) => n,
+        };
+        subscribe(
+            &self.api,
+            format!(
+                "{}/api/fg/{}/block/{}/subscribe/{}/",
+                &self.url, self.flowgraph_id, self.description.id, port
+            ),
+        )
+        .await
     }
 }
 
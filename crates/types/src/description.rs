@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
 /// Description of a `Flowgraph`.
 ///
@@ -11,6 +12,61 @@ pub struct FlowgraphDescription {
     pub stream_edges: Vec<(usize, usize, usize, usize)>,
     /// Message edges
     pub message_edges: Vec<(usize, usize, usize, usize)>,
+    /// Queues of the message edges (in the same order as the edges)
+    #[serde(default)]
+    pub message_queues: Vec<MessageQueueDescription>,
+    /// Measured latency of the stream edges (in the same order as the edges)
+    #[serde(default)]
+    pub stream_latencies: Vec<StreamLatencyDescription>,
+}
+
+/// Latency of a stream connection.
+///
+/// The latency of an item is the time from being produced until being consumed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StreamLatencyDescription {
+    /// Maximum number of items in flight, if the connection is limited
+    pub max_items: Option<usize>,
+    /// Number of measurements
+    pub measurements: u64,
+    /// Mean latency
+    pub mean: Duration,
+    /// Maximum latency
+    pub max: Duration,
+    /// Latency of the last measurement
+    pub last: Duration,
+}
+
+/// Queue of a message connection.
+///
+/// Configures what happens, if a block posts to a message output, while the receiving block does
+/// not keep up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "policy", content = "capacity")]
+pub enum MessageQueueConfig {
+    /// Wait for space in the inbox of the receiving block
+    Block,
+    /// Queue up to the given number of messages, dropping the oldest message, if full
+    DropOldest(usize),
+    /// Queue up to the given number of messages, dropping new messages, if full
+    DropNewest(usize),
+}
+
+// deriving `Default` for enums requires Rust 1.62
+#[allow(clippy::derivable_impls)]
+impl Default for MessageQueueConfig {
+    fn default() -> Self {
+        MessageQueueConfig::Block
+    }
+}
+
+/// Description of the queue of a message connection.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MessageQueueDescription {
+    /// Configuration
+    pub config: MessageQueueConfig,
+    /// Number of dropped messages
+    pub dropped: u64,
 }
 
 /// Description of a `Block`.
@@ -28,6 +84,12 @@ pub struct BlockDescription {
     pub stream_inputs: Vec<String>,
     /// Stream outputs
     pub stream_outputs: Vec<String>,
+    /// Item types of the stream inputs
+    #[serde(default)]
+    pub stream_input_types: Vec<String>,
+    /// Item types of the stream outputs
+    #[serde(default)]
+    pub stream_output_types: Vec<String>,
     /// Message inputs
     pub message_inputs: Vec<String>,
     /// Message outputs
@@ -0,0 +1,36 @@
+use serde::{Deserialize, Serialize};
+
+/// Event of a running `Flowgraph`.
+///
+/// Events are pushed to subscribers, for example, through the streaming endpoints of the REST
+/// API.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FlowgraphEvent {
+    /// Block finished
+    BlockDone {
+        /// Block Id
+        block_id: usize,
+    },
+    /// Block terminated with an error
+    BlockError {
+        /// Block Id
+        block_id: usize,
+        /// Error message
+        error: String,
+    },
+    /// Flowgraph was asked to terminate
+    Terminate,
+}
+
+/// Run state of a `Flowgraph`.
+///
+/// This enum can be serialized to be used with the REST API.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FlowgraphState {
+    /// Flowgraph is running
+    Running,
+    /// Flowgraph finished successfully
+    Finished,
+    /// Flowgraph finished with an error
+    Failed(String),
+}
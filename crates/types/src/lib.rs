@@ -13,6 +13,17 @@ use thiserror::Error;
 mod description;
 pub use description::BlockDescription;
 pub use description::FlowgraphDescription;
+pub use description::MessageQueueConfig;
+pub use description::MessageQueueDescription;
+pub use description::StreamLatencyDescription;
+mod event;
+pub use event::FlowgraphEvent;
+pub use event::FlowgraphState;
+mod stats;
+pub use stats::BlockStats;
+pub use stats::FlowgraphStats;
+pub use stats::StreamInputStats;
+pub use stats::StreamOutputStats;
 
 /// PMT Any trait
 ///
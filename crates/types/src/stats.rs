@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Runtime statistics of a `Flowgraph`.
+///
+/// This struct can be serialized to be used with the REST API.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FlowgraphStats {
+    /// Statistics of the running blocks
+    pub blocks: Vec<BlockStats>,
+}
+
+/// Runtime statistics of a `Block`.
+///
+/// This struct can be serialized to be used with the REST API.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockStats {
+    /// Id
+    pub id: usize,
+    /// Instance name
+    pub instance_name: String,
+    /// Number of calls to `work()`
+    pub work_calls: u64,
+    /// Accumulated time spent in `work()`
+    pub work_time: Duration,
+    /// Number of handled messages
+    pub messages_handled: u64,
+    /// Number of restarts after errors
+    pub restarts: usize,
+    /// Stream inputs
+    pub stream_inputs: Vec<StreamInputStats>,
+    /// Stream outputs
+    pub stream_outputs: Vec<StreamOutputStats>,
+}
+
+/// Runtime statistics of a stream input.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreamInputStats {
+    /// Name
+    pub name: String,
+    /// Number of consumed items
+    pub items_consumed: u64,
+    /// Fill level of the buffer
+    ///
+    /// Number of items that were left in the buffer after the last call to `work()`.
+    pub items_available: usize,
+}
+
+/// Runtime statistics of a stream output.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreamOutputStats {
+    /// Name
+    pub name: String,
+    /// Number of produced items
+    pub items_produced: u64,
+    /// Free space in the buffer
+    ///
+    /// Number of items that could have been written after the last call to `work()`.
+    pub space_available: usize,
+}
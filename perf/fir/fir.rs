@@ -4,10 +4,12 @@ use std::time;
 
 use futuresdr::anyhow::{Context, Result};
 use futuresdr::blocks::CopyRandBuilder;
-use futuresdr::blocks::FirBuilder;
+use futuresdr::blocks::Fir;
 use futuresdr::blocks::Head;
 use futuresdr::blocks::NullSink;
 use futuresdr::blocks::NullSource;
+use futuresdr::futuredsp::fir::NonResamplingFirKernel;
+use futuresdr::futuredsp::simd::Isa;
 use futuresdr::runtime::scheduler::FlowScheduler;
 use futuresdr::runtime::scheduler::SmolScheduler;
 use futuresdr::runtime::scheduler::TpbScheduler;
@@ -28,6 +30,20 @@ struct Args {
     max_copy: usize,
     #[clap(short = 'S', long, default_value = "smol1")]
     scheduler: String,
+    #[clap(short, long, default_value = "auto")]
+    isa: String,
+}
+
+fn parse_isa(isa: &str) -> Isa {
+    match isa {
+        "auto" => Isa::detect(),
+        "scalar" => Isa::Scalar,
+        "sse" => Isa::Sse,
+        "avx2" => Isa::Avx2,
+        "avx512" => Isa::Avx512,
+        "neon" => Isa::Neon,
+        _ => panic!("unknown instruction set"),
+    }
 }
 
 fn main() -> Result<()> {
@@ -38,7 +54,11 @@ fn main() -> Result<()> {
         samples,
         max_copy,
         scheduler,
+        isa,
     } = Args::parse();
+    let isa_name = isa;
+    let isa = parse_isa(&isa_name);
+    assert!(isa.is_supported(), "{isa:?} is not supported by the CPU");
 
     let mut fg = Flowgraph::new();
     let taps: [f32; 64] = repeat_with(rand::random::<f32>)
@@ -46,6 +66,11 @@ fn main() -> Result<()> {
         .collect::<Vec<f32>>()
         .try_into()
         .unwrap();
+    let fir = || {
+        Fir::<f32, f32, f32, _>::new(
+            NonResamplingFirKernel::<f32, f32, _, _>::new(taps.to_owned()).with_isa(isa),
+        )
+    };
 
     let mut snks = Vec::new();
 
@@ -55,14 +80,14 @@ fn main() -> Result<()> {
         fg.connect_stream(src, "out", head, "in")?;
 
         let copy = fg.add_block(CopyRandBuilder::<f32>::new().max_copy(max_copy).build());
-        let mut last = fg.add_block(FirBuilder::new::<f32, f32, _, _>(taps.to_owned()));
+        let mut last = fg.add_block(fir());
         fg.connect_stream(head, "out", copy, "in")?;
         fg.connect_stream(copy, "out", last, "in")?;
 
         for _ in 1..stages {
             let copy = fg.add_block(CopyRandBuilder::<f32>::new().max_copy(max_copy).build());
             fg.connect_stream(last, "out", copy, "in")?;
-            last = fg.add_block(FirBuilder::new::<f32, f32, _, _>(taps.to_owned()));
+            last = fg.add_block(fir());
             fg.connect_stream(copy, "out", last, "in")?;
         }
 
@@ -104,14 +129,15 @@ fn main() -> Result<()> {
     }
 
     println!(
-        "{},{},{},{},{},{},{}",
+        "{},{},{},{},{},{},{},{}",
         run,
         pipes,
         stages,
         samples,
         max_copy,
         scheduler,
-        elapsed.as_secs_f64()
+        elapsed.as_secs_f64(),
+        isa_name
     );
 
     Ok(())
@@ -3,7 +3,7 @@
 outfile=perf-data/results.csv
 rm -f ${outfile}
 
-echo "sdr,run,pipes,stages,samples,max_copy,scheduler,time" > ${outfile}
+echo "sdr,run,pipes,stages,samples,max_copy,scheduler,time,isa" > ${outfile}
 
 files=$(ls perf-data/gr_*.csv 2>/dev/null || echo)
 for f in ${files}
@@ -17,6 +17,8 @@ def conf_int(data, confidence=0.95):
 ### throughput vs stages
 d = pd.read_csv('perf-data/results.csv')
 d = d[d['max_copy'] == 512]
+isa = d[(d['sdr'] == 'fs') & (d['scheduler'] == 'smol1')]
+d = d[(d['sdr'] == 'gr') | (d['isa'] == 'auto')]
 t = d.groupby(['sdr', 'scheduler', 'stages']).agg({'time': np.mean})
 print(t.unstack(level=[0,1]))
 
@@ -49,3 +51,29 @@ ax.legend(handles, labels, handlelength=2.95)
 plt.savefig('fir_rand.pdf')
 plt.close('all')
 
+### throughput vs stages for different instruction sets
+t = isa.groupby(['isa', 'stages']).agg({'time': np.mean})
+print(t.unstack(level=0))
+
+d = isa.groupby(['isa', 'stages']).agg({'time': [np.mean, np.var, conf_int]})
+
+fig, ax = plt.subplots(1, 1)
+fig.subplots_adjust(bottom=.192, left=.11, top=.99, right=.97)
+
+for (i, label) in [('scalar', 'Scalar'), ('sse', 'SSE'), ('avx2', 'AVX2'), ('avx512', 'AVX-512')]:
+    if i not in d.index.get_level_values(0):
+        continue
+    t = d.loc[(i)].reset_index()
+    ax.errorbar(t['stages'], t[('time', 'mean')], yerr=t[('time', 'conf_int')], label=label)
+
+plt.setp(ax.get_yticklabels(), rotation=90, va="center")
+ax.set_xlabel('\#\,Stages')
+ax.set_ylabel('Execution Time (in s)')
+ax.set_ylim(0)
+
+handles, labels = ax.get_legend_handles_labels()
+handles = [x[0] for x in handles]
+ax.legend(handles, labels, handlelength=2.95)
+
+plt.savefig('fir_isa.pdf')
+plt.close('all')
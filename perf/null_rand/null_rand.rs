@@ -6,6 +6,7 @@ use futuresdr::blocks::CopyRandBuilder;
 use futuresdr::blocks::Head;
 use futuresdr::blocks::NullSink;
 use futuresdr::blocks::NullSource;
+use futuresdr::runtime::scheduler::ChainScheduler;
 use futuresdr::runtime::scheduler::FlowScheduler;
 use futuresdr::runtime::scheduler::SmolScheduler;
 use futuresdr::runtime::scheduler::TpbScheduler;
@@ -82,6 +83,11 @@ fn main() -> Result<()> {
         let now = time::Instant::now();
         fg = runtime.run(fg)?;
         elapsed = now.elapsed();
+    } else if scheduler == "chain" {
+        let runtime = Runtime::with_scheduler(ChainScheduler::default());
+        let now = time::Instant::now();
+        fg = runtime.run(fg)?;
+        elapsed = now.elapsed();
     } else {
         panic!("unknown scheduler");
     }
@@ -11,9 +11,13 @@ use crate::runtime::MessageIoBuilder;
 use crate::runtime::Pmt;
 use crate::runtime::StreamIo;
 use crate::runtime::StreamIoBuilder;
+use crate::runtime::TagPropagation;
 use crate::runtime::WorkIo;
 
 /// Automatic Gain Control Block
+///
+/// Samples are processed in place, if the output uses
+/// [`Slab::in_place`](crate::runtime::buffer::slab::Slab::in_place) and the input a Slab buffer.
 pub struct Agc<T> {
     /// Minimum value that has to be reached in order for AGC to start adjusting gain.
     squelch: f32,
@@ -71,6 +75,7 @@ where
             StreamIoBuilder::new()
                 .add_input::<T>("in")
                 .add_output::<T>("out")
+                .tag_propagation_policy(TagPropagation::OneToOne)
                 .build(),
             MessageIoBuilder::<Self>::new()
                 .add_input("gain_locked", Self::gain_locked)
@@ -179,6 +184,19 @@ where
         _mio: &mut MessageIo<Self>,
         _meta: &mut BlockMeta,
     ) -> Result<()> {
+        while sio
+            .process_in_place::<T>(0, 0, |s| {
+                for v in s.iter_mut() {
+                    if v.abs().to_f32().unwrap() > self.squelch {
+                        *v = self.scale(*v);
+                    } else {
+                        *v = T::from(0.0).unwrap();
+                    }
+                }
+            })
+            .is_some()
+        {}
+
         let i = sio.input(0).slice::<T>();
         let o = sio.output(0).slice::<T>();
 
@@ -7,6 +7,7 @@ use crate::runtime::MessageIo;
 use crate::runtime::MessageIoBuilder;
 use crate::runtime::StreamIo;
 use crate::runtime::StreamIoBuilder;
+use crate::runtime::TagPropagation;
 use crate::runtime::TypedBlock;
 use crate::runtime::WorkIo;
 
@@ -20,6 +21,8 @@ use crate::runtime::WorkIo;
 ///
 /// `out`: Output, corresponding to input with function applied
 ///
+/// To process samples in place, use [`ApplyInPlace`](crate::blocks::ApplyInPlace).
+///
 /// # Usage
 /// ```
 /// use futuresdr::blocks::Apply;
@@ -82,6 +85,7 @@ where
             StreamIoBuilder::new()
                 .add_input::<A>("in")
                 .add_output::<B>("out")
+                .tag_propagation_policy(TagPropagation::OneToOne)
                 .build(),
             MessageIoBuilder::<Self>::new().build(),
             Self {
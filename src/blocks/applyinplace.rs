@@ -0,0 +1,122 @@
+use crate::anyhow::Result;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::TagPropagation;
+use crate::runtime::TypedBlock;
+use crate::runtime::WorkIo;
+
+/// Apply a function to each sample, modifying it in place.
+///
+/// # Stream Inputs
+///
+/// `in`: Input
+///
+/// # Stream Outputs
+///
+/// `out`: Output, corresponding to input with function applied
+///
+/// Samples are processed in place, when the block is connected through
+/// [`Slab`](crate::runtime::buffer::slab::Slab) buffers and the output uses
+/// [`Slab::in_place`](crate::runtime::buffer::slab::Slab::in_place). Otherwise, they are copied
+/// to the output and modified there.
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::ApplyInPlace;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// // Double each sample
+/// let doubler = fg.add_block(ApplyInPlace::new(|i: &mut f32| *i *= 2.0));
+/// ```
+pub struct ApplyInPlace<F, A>
+where
+    F: FnMut(&mut A) + Send + 'static,
+    A: Clone + Send + 'static,
+{
+    f: F,
+    _p: std::marker::PhantomData<A>,
+}
+
+impl<F, A> ApplyInPlace<F, A>
+where
+    F: FnMut(&mut A) + Send + 'static,
+    A: Clone + Send + 'static,
+{
+    /// Create [`ApplyInPlace`] block
+    ///
+    /// ## Parameter
+    /// - `f`: Function to apply on each sample
+    pub fn new(f: F) -> Block {
+        Block::from_typed(Self::new_typed(f))
+    }
+
+    /// Create typed [`ApplyInPlace`] block
+    ///
+    /// ## Parameter
+    /// - `f`: Function to apply on each sample
+    pub fn new_typed(f: F) -> TypedBlock<Self> {
+        TypedBlock::new(
+            BlockMetaBuilder::new("ApplyInPlace").build(),
+            StreamIoBuilder::new()
+                .add_input::<A>("in")
+                .add_output::<A>("out")
+                .tag_propagation_policy(TagPropagation::OneToOne)
+                .build(),
+            MessageIoBuilder::<Self>::new().build(),
+            Self {
+                f,
+                _p: std::marker::PhantomData,
+            },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl<F, A> Kernel for ApplyInPlace<F, A>
+where
+    F: FnMut(&mut A) + Send + 'static,
+    A: Clone + Send + 'static,
+{
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let f = &mut self.f;
+        while sio
+            .process_in_place::<A>(0, 0, |s| s.iter_mut().for_each(&mut *f))
+            .is_some()
+        {}
+
+        let i = sio.input(0).slice::<A>();
+        let o = sio.output(0).slice::<A>();
+
+        let m = std::cmp::min(i.len(), o.len());
+        if m > 0 {
+            for (v, r) in i.iter().zip(o.iter_mut()) {
+                *r = v.clone();
+                f(r);
+            }
+
+            sio.input(0).consume(m);
+            sio.output(0).produce(m);
+        }
+
+        if sio.input(0).finished() && m == i.len() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
@@ -7,6 +7,7 @@ use crate::runtime::MessageIo;
 use crate::runtime::MessageIoBuilder;
 use crate::runtime::StreamIo;
 use crate::runtime::StreamIoBuilder;
+use crate::runtime::TagPropagation;
 use crate::runtime::WorkIo;
 
 /// Apply a function to each N input samples, producing M output samples.
@@ -65,6 +66,10 @@ where
             StreamIoBuilder::new()
                 .add_input::<A>("in")
                 .add_output::<B>("out")
+                .tag_propagation_policy(TagPropagation::RateChange {
+                    interp: M,
+                    decim: N,
+                })
                 .build(),
             MessageIoBuilder::<Self>::new().build(),
             ApplyNM {
@@ -7,6 +7,7 @@ use crate::runtime::MessageIo;
 use crate::runtime::MessageIoBuilder;
 use crate::runtime::StreamIo;
 use crate::runtime::StreamIoBuilder;
+use crate::runtime::TagPropagation;
 use crate::runtime::WorkIo;
 
 /// Apply a function to combine two streams into one.
@@ -64,6 +65,7 @@ where
                 .add_input::<A>("in0")
                 .add_input::<B>("in1")
                 .add_output::<C>("out")
+                .tag_propagation_policy(TagPropagation::AllToAll)
                 .build(),
             MessageIoBuilder::<Self>::new().build(),
             Combine {
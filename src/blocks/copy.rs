@@ -7,6 +7,7 @@ use crate::runtime::MessageIo;
 use crate::runtime::MessageIoBuilder;
 use crate::runtime::StreamIo;
 use crate::runtime::StreamIoBuilder;
+use crate::runtime::TagPropagation;
 use crate::runtime::WorkIo;
 
 /// Copy input samples to the output.
@@ -22,6 +23,7 @@ impl<T: core::marker::Copy + Send + 'static> Copy<T> {
             StreamIoBuilder::new()
                 .add_input::<T>("in")
                 .add_output::<T>("out")
+                .tag_propagation_policy(TagPropagation::OneToOne)
                 .build(),
             MessageIoBuilder::<Self>::new().build(),
             Copy::<T> {
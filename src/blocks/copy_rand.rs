@@ -7,6 +7,7 @@ use crate::runtime::MessageIo;
 use crate::runtime::MessageIoBuilder;
 use crate::runtime::StreamIo;
 use crate::runtime::StreamIoBuilder;
+use crate::runtime::TagPropagation;
 use crate::runtime::WorkIo;
 use std::marker::PhantomData;
 
@@ -35,6 +36,7 @@ impl<T: Copy + Send + 'static> CopyRand<T> {
             StreamIoBuilder::new()
                 .add_input::<T>("in")
                 .add_output::<T>("out")
+                .tag_propagation_policy(TagPropagation::OneToOne)
                 .build(),
             MessageIoBuilder::<Self>::new().build(),
             CopyRand::<T> {
@@ -12,6 +12,7 @@ use crate::runtime::MessageIo;
 use crate::runtime::MessageIoBuilder;
 use crate::runtime::StreamIo;
 use crate::runtime::StreamIoBuilder;
+use crate::runtime::TagPropagation;
 use crate::runtime::WorkIo;
 
 /// Compute an FFT.
@@ -79,6 +80,7 @@ impl Fft {
             StreamIoBuilder::new()
                 .add_input::<Complex32>("in")
                 .add_output::<Complex32>("out")
+                .tag_propagation_policy(TagPropagation::OneToOne)
                 .build(),
             MessageIoBuilder::<Fft>::new().build(),
             Fft {
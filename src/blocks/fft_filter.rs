@@ -0,0 +1,164 @@
+use futuredsp::fftfir::FftFirKernel;
+use futuredsp::StatefulUnaryKernel;
+use rustfft::num_complex::Complex32;
+
+use crate::anyhow::Result;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::TagPropagation;
+use crate::runtime::WorkIo;
+
+/// FFT-based FIR filter.
+///
+/// Filters the input with the overlap-save method, which is considerably faster than a
+/// [Fir](crate::blocks::FirBuilder) filter for long filters with hundreds or thousands of
+/// taps. It produces the same output as the [Fir](crate::blocks::FirBuilder) filter, i.e.,
+/// `num_taps - 1` samples less than it consumes.
+///
+/// Supported combinations are `f32` samples with `f32` taps and [Complex32] samples with
+/// `f32` or [Complex32] taps. See the [futuredsp docs](futuredsp::fftfir) for details.
+///
+/// # Inputs
+///
+/// `in`: Input
+///
+/// # Outputs
+///
+/// `out`: Output
+///
+/// # Message Handler
+///
+/// `taps`: Update the taps with a [`Pmt::VecF32`] or a `Vec<TapType>` wrapped in a
+/// [`Pmt::Any`].
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::FftFilter;
+/// use futuresdr::futuredsp::firdes;
+/// use futuresdr::num_complex::Complex32;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let taps = firdes::kaiser::lowpass::<f32>(0.05, 0.005, 0.001);
+/// let filter = fg.add_block(FftFilter::<Complex32, f32>::new(taps));
+/// ```
+pub struct FftFilter<SampleType, TapType>
+where
+    SampleType: 'static + Send,
+    TapType: 'static + Send,
+{
+    kernel: FftFirKernel<SampleType, TapType>,
+    flushed: bool,
+}
+
+impl<SampleType, TapType> FftFilter<SampleType, TapType>
+where
+    SampleType: 'static + Send,
+    TapType: 'static + Send + Sync + Copy + From<f32> + Into<Complex32>,
+    FftFirKernel<SampleType, TapType>: StatefulUnaryKernel<SampleType, SampleType>,
+{
+    /// Create FFT filter block, choosing the FFT size based on the number of taps
+    pub fn new(taps: Vec<TapType>) -> Block {
+        Self::with_kernel(FftFirKernel::new(taps))
+    }
+
+    /// Create FFT filter block with the given FFT size
+    ///
+    /// The filter produces `fft_size - num_taps + 1` samples per FFT.
+    pub fn with_fft_size(taps: Vec<TapType>, fft_size: usize) -> Block {
+        Self::with_kernel(FftFirKernel::with_fft_size(taps, fft_size))
+    }
+
+    fn with_kernel(kernel: FftFirKernel<SampleType, TapType>) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("FftFilter").build(),
+            StreamIoBuilder::new()
+                .add_input::<SampleType>("in")
+                .add_output::<SampleType>("out")
+                .tag_propagation_policy(TagPropagation::OneToOne)
+                .build(),
+            MessageIoBuilder::<Self>::new()
+                .add_input("taps", Self::taps)
+                .build(),
+            FftFilter {
+                kernel,
+                flushed: false,
+            },
+        )
+    }
+
+    #[message_handler]
+    async fn taps(
+        &mut self,
+        _io: &mut WorkIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        let taps = match &p {
+            Pmt::VecF32(v) => v.iter().map(|t| TapType::from(*t)).collect(),
+            Pmt::Any(a) => match a.downcast_ref::<Vec<TapType>>() {
+                Some(v) => v.clone(),
+                None => return Ok(Pmt::InvalidValue),
+            },
+            _ => return Ok(Pmt::InvalidValue),
+        };
+        if taps.is_empty() {
+            return Ok(Pmt::InvalidValue);
+        }
+        self.kernel.set_taps(taps);
+        Ok(Pmt::Ok)
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl<SampleType, TapType> Kernel for FftFilter<SampleType, TapType>
+where
+    SampleType: 'static + Send,
+    TapType: 'static + Send + Sync + Copy + From<f32> + Into<Complex32>,
+    FftFirKernel<SampleType, TapType>: StatefulUnaryKernel<SampleType, SampleType>,
+{
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<SampleType>();
+        let o = sio.output(0).slice::<SampleType>();
+
+        let (consumed, mut produced, mut status) = self.kernel.work(i, o);
+
+        // process the last, incomplete block, once all samples are consumed
+        if sio.input(0).finished()
+            && consumed == i.len()
+            && status.produced_all_samples()
+            && !self.flushed
+        {
+            self.kernel.flush();
+            self.flushed = true;
+            let (_, p, s) = self.kernel.work(&[], &mut o[produced..]);
+            produced += p;
+            status = s;
+        }
+
+        sio.input(0).consume(consumed);
+        sio.output(0).produce(produced);
+
+        if self.flushed && status.produced_all_samples() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
@@ -7,6 +7,7 @@ use crate::runtime::MessageIo;
 use crate::runtime::MessageIoBuilder;
 use crate::runtime::StreamIo;
 use crate::runtime::StreamIoBuilder;
+use crate::runtime::TagPropagation;
 use crate::runtime::WorkIo;
 use futuredsp::fir::*;
 use futuredsp::firdes;
@@ -41,6 +42,7 @@ where
             StreamIoBuilder::new()
                 .add_input::<InputType>("in")
                 .add_output::<OutputType>("out")
+                .tag_propagation_policy(TagPropagation::OneToOne)
                 .build(),
             MessageIoBuilder::<Fir<InputType, OutputType, TapType, Core>>::new().build(),
             Fir {
@@ -178,11 +180,13 @@ impl FirBuilder {
         PolyphaseResamplingFirKernel<InputType, OutputType, Taps, TapType>:
             UnaryKernel<InputType, OutputType> + Send,
     {
-        Fir::<
+        let mut block = Fir::<
             InputType,
             OutputType,
             TapType,
             PolyphaseResamplingFirKernel<InputType, OutputType, Taps, TapType>,
-        >::new(PolyphaseResamplingFirKernel::new(interp, decim, taps))
+        >::new(PolyphaseResamplingFirKernel::new(interp, decim, taps));
+        block.set_tag_propagation(TagPropagation::RateChange { interp, decim }.into_fn());
+        block
     }
 }
@@ -7,6 +7,7 @@ use crate::runtime::MessageIo;
 use crate::runtime::MessageIoBuilder;
 use crate::runtime::StreamIo;
 use crate::runtime::StreamIoBuilder;
+use crate::runtime::TagPropagation;
 use crate::runtime::WorkIo;
 
 /// Copies only a given number of samples and stops.
@@ -41,6 +42,7 @@ impl<T: Copy + Send + 'static> Head<T> {
             StreamIoBuilder::new()
                 .add_input::<T>("in")
                 .add_output::<T>("out")
+                .tag_propagation_policy(TagPropagation::OneToOne)
                 .build(),
             MessageIoBuilder::new().build(),
             Head::<T> {
@@ -7,9 +7,13 @@ use crate::runtime::MessageIo;
 use crate::runtime::MessageIoBuilder;
 use crate::runtime::StreamIo;
 use crate::runtime::StreamIoBuilder;
+use crate::runtime::TagPropagation;
 use crate::runtime::WorkIo;
+use futuredsp::iir::Biquad;
 use futuredsp::iir::IirKernel;
+use futuredsp::iir::SosKernel;
 use futuredsp::{StatefulUnaryKernel, TapsAccessor};
+use rustfft::num_traits::Zero;
 
 /// IIR filter.
 pub struct Iir<InputType, OutputType, TapType, Core>
@@ -39,6 +43,7 @@ where
             StreamIoBuilder::new()
                 .add_input::<InputType>("in")
                 .add_output::<OutputType>("out")
+                .tag_propagation_policy(TagPropagation::OneToOne)
                 .build(),
             MessageIoBuilder::<Iir<InputType, OutputType, TapType, Core>>::new().build(),
             Iir {
@@ -122,6 +127,19 @@ where
 ///
 /// let iir = fg.add_block(IirBuilder::new::<f32, f32, f32, [f32; 3]>([1.0, 2.0, 3.0], [4.0, 5.0, 6.0]));
 /// ```
+///
+/// Filters of high order should be implemented as cascade of second-order sections, which
+/// can be designed with [futuredsp::iirdes]:
+/// ```
+/// use futuresdr::blocks::IirBuilder;
+/// use futuresdr::futuredsp::iirdes::{self, Prototype};
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let filter = iirdes::lowpass(Prototype::Butterworth, 8, 0.1);
+/// let iir = fg.add_block(IirBuilder::new_sos::<f32, f32>(filter.sos()));
+/// ```
 pub struct IirBuilder {
     //
 }
@@ -140,4 +158,16 @@ impl IirBuilder {
             IirKernel::new(a_taps, b_taps),
         )
     }
+
+    /// Create IIR filter as cascade of second-order sections
+    pub fn new_sos<SampleType, TapType>(sections: Vec<Biquad<TapType>>) -> Block
+    where
+        SampleType: 'static + Send + Copy + Zero,
+        TapType: 'static + Send,
+        SosKernel<SampleType, TapType>: StatefulUnaryKernel<SampleType, SampleType> + Send,
+    {
+        Iir::<SampleType, SampleType, TapType, SosKernel<SampleType, TapType>>::new(SosKernel::new(
+            sections,
+        ))
+    }
 }
@@ -1,8 +1,8 @@
-use async_io::Timer;
 use std::time::Duration;
 use std::time::Instant;
 
 use crate::anyhow::Result;
+use crate::runtime::clock;
 use crate::runtime::Block;
 use crate::runtime::BlockMeta;
 use crate::runtime::BlockMetaBuilder;
@@ -32,15 +32,11 @@ impl MessageSource {
             MessageSource {
                 message,
                 interval,
-                t_last: Instant::now(),
+                t_last: clock::now(),
                 n_messages,
             },
         )
     }
-
-    async fn sleep(dur: Duration) {
-        Timer::after(dur).await;
-    }
 }
 
 #[doc(hidden)]
@@ -53,7 +49,7 @@ impl Kernel for MessageSource {
         mio: &mut MessageIo<Self>,
         _b: &mut BlockMeta,
     ) -> Result<()> {
-        let now = Instant::now();
+        let now = clock::now();
 
         if now >= self.t_last + self.interval {
             mio.post(0, self.message.clone()).await;
@@ -66,9 +62,7 @@ impl Kernel for MessageSource {
             }
         }
 
-        io.block_on(MessageSource::sleep(
-            self.t_last + self.interval - Instant::now(),
-        ));
+        io.block_on(clock::sleep_until(self.t_last + self.interval));
 
         Ok(())
     }
@@ -79,7 +73,7 @@ impl Kernel for MessageSource {
         _mio: &mut MessageIo<Self>,
         _b: &mut BlockMeta,
     ) -> Result<()> {
-        self.t_last = Instant::now();
+        self.t_last = clock::now();
         Ok(())
     }
 }
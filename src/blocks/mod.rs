@@ -3,6 +3,7 @@
 //! | Block | Usage | WebAssembly? |
 //! |---|---|---|
 //! | [Apply] | Apply a function to each sample. | ✅ |
+//! | [ApplyInPlace] | Apply a function to each sample, modifying it in place. | ✅ |
 //! | [ApplyIntoIter] | Apply a function on each input sample to create an iterator and output its values. | ✅ |
 //! | [ApplyNM] | Apply a function to each N input samples, producing M output samples. | ✅ |
 //! | [Combine] | Apply a function to combine two streams into one. | ✅ |
@@ -17,8 +18,11 @@
 //! |---|---|---|
 //! | [Agc](Agc) | Automatic Gain Control | ✅ |
 //! | [Fft](Fft) | Compute an FFT. | ✅ |
+//! | [FftFilter] | FFT-based FIR filter for long filters. | ✅ |
 //! | [Fir](FirBuilder) | FIR filter and resampler. | ✅ |
 //! | [Iir](IirBuilder) | IIR filter. | ✅ |
+//! | [PfbChannelizer](PfbChannelizerBuilder) | Polyphase filterbank channelizer. | ✅ |
+//! | [PfbSynthesizer](PfbSynthesizerBuilder) | Polyphase filterbank synthesizer. | ✅ |
 //!
 //! ## Misc
 //! | Block | Usage | WebAssembly? |
@@ -57,6 +61,8 @@
 //! | [ChannelSource] | Push samples through a channel into a stream connection. | ✅ |
 //! | [FileSink] | Write samples to a file. | ❌ |
 //! | [FileSource] | Read samples from a file. | ❌ |
+//! | [ShmSink] | Push samples into a shared-memory segment for another process (Unix). | ❌ |
+//! | [ShmSource] | Read samples from a shared-memory segment of another process (Unix). | ❌ |
 //! | [TcpSource] | Reads samples from a TCP socket. | ❌ |
 //! | [TcpSink] | Push samples into a TCP socket. | ❌ |
 //! | [WebsocketSink] | Push samples in a WebSocket. | ❌ |
@@ -107,6 +113,9 @@ pub use apply::Apply;
 mod applynm;
 pub use applynm::ApplyNM;
 
+mod applyinplace;
+pub use applyinplace::ApplyInPlace;
+
 mod applyintoiter;
 pub use applyintoiter::ApplyIntoIter;
 
@@ -138,6 +147,8 @@ pub use fir::FirBuilder;
 mod fft;
 pub use fft::Fft;
 pub use fft::FftDirection;
+mod fft_filter;
+pub use fft_filter::FftFilter;
 
 #[cfg(not(target_arch = "wasm32"))]
 mod file_sink;
@@ -178,6 +189,10 @@ mod null_sink;
 pub use null_sink::NullSink;
 mod null_source;
 pub use null_source::NullSource;
+mod pfb;
+pub use pfb::{PfbChannelizer, PfbChannelizerBuilder, PfbSynthesizer, PfbSynthesizerBuilder};
+
+pub(crate) mod registry;
 
 /// Seify hardware driver blocks
 #[cfg(feature = "seify")]
@@ -195,6 +210,15 @@ mod sink;
 pub use sink::Sink;
 mod source;
 pub use source::Source;
+#[cfg(all(unix, not(target_arch = "wasm32")))]
+mod shm_sink;
+#[cfg(all(unix, not(target_arch = "wasm32")))]
+pub use shm_sink::ShmSink;
+#[cfg(all(unix, not(target_arch = "wasm32")))]
+mod shm_source;
+#[cfg(all(unix, not(target_arch = "wasm32")))]
+pub use shm_source::ShmSource;
+
 mod split;
 pub use split::Split;
 
@@ -0,0 +1,389 @@
+use futuredsp::firdes;
+use futuredsp::pfb::PfbChannelizerKernel;
+use futuredsp::pfb::PfbSynthesizerKernel;
+use rustfft::num_complex::Complex32;
+
+use crate::anyhow::Result;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::TagPropagation;
+use crate::runtime::WorkIo;
+
+/// Channel index of an `enable` or `disable` message.
+fn channel_index(p: &Pmt, num_channels: usize) -> Option<usize> {
+    let c = match p {
+        Pmt::Usize(c) => *c,
+        Pmt::U32(c) => *c as usize,
+        Pmt::U64(c) => *c as usize,
+        _ => return None,
+    };
+    if c < num_channels {
+        Some(c)
+    } else {
+        None
+    }
+}
+
+/// Default prototype filter of the filterbanks.
+fn default_taps(num_channels: usize) -> Vec<f32> {
+    firdes::kaiser::multirate::<f32>(1, num_channels, 12, 0.0001)
+}
+
+/// Polyphase filterbank channelizer.
+///
+/// Splits the input into `num_channels` equally spaced channels. Channel `c` is centered at
+/// `c / num_channels` of the sample rate, i.e., channels with an index larger than
+/// `num_channels / 2` correspond to negative frequencies. The channels are critically sampled
+/// with `1 / num_channels` of the input sample rate or, if oversampled, with twice that
+/// rate. See the [futuredsp docs](futuredsp::pfb) for details.
+///
+/// # Inputs
+///
+/// `in`: Input samples (Complex32)
+///
+/// # Outputs
+///
+/// `out0`, `out1`, ...: Channels (Complex32)
+///
+/// # Message Handler
+///
+/// `enable`: Enable the output channel with the given index.
+/// `disable`: Disable the output channel with the given index. No samples are computed for
+/// or written to disabled outputs.
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::PfbChannelizerBuilder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let channelizer = fg.add_block(PfbChannelizerBuilder::new(8).oversampled().build());
+/// ```
+pub struct PfbChannelizer {
+    kernel: PfbChannelizerKernel,
+    enabled: Vec<bool>,
+}
+
+impl PfbChannelizer {
+    /// Create PFB channelizer block
+    pub fn new(num_channels: usize) -> Block {
+        PfbChannelizerBuilder::new(num_channels).build()
+    }
+
+    #[message_handler]
+    async fn enable(
+        &mut self,
+        _io: &mut WorkIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        if let Some(c) = channel_index(&p, self.enabled.len()) {
+            self.enabled[c] = true;
+            Ok(Pmt::Ok)
+        } else {
+            Ok(Pmt::InvalidValue)
+        }
+    }
+
+    #[message_handler]
+    async fn disable(
+        &mut self,
+        _io: &mut WorkIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        if let Some(c) = channel_index(&p, self.enabled.len()) {
+            self.enabled[c] = false;
+            Ok(Pmt::Ok)
+        } else {
+            Ok(Pmt::InvalidValue)
+        }
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for PfbChannelizer {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<Complex32>();
+        let mut outputs: Vec<Option<&mut [Complex32]>> = self
+            .enabled
+            .iter()
+            .enumerate()
+            .map(|(c, e)| e.then(|| sio.output(c).slice::<Complex32>()))
+            .collect();
+
+        let (consumed, produced, status) = self.kernel.work(i, &mut outputs);
+
+        sio.input(0).consume(consumed);
+        for (c, e) in self.enabled.iter().enumerate() {
+            if *e {
+                sio.output(c).produce(produced);
+            }
+        }
+
+        if sio.input(0).finished() && status.produced_all_samples() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+/// Build a [PfbChannelizer].
+///
+/// By default, the channelizer is critically sampled and uses a prototype filter designed
+/// with [`firdes::kaiser::multirate`](futuredsp::firdes::kaiser::multirate).
+pub struct PfbChannelizerBuilder {
+    num_channels: usize,
+    taps: Vec<f32>,
+    oversampling: usize,
+    enabled: Vec<bool>,
+}
+
+impl PfbChannelizerBuilder {
+    /// Create PFB channelizer builder
+    pub fn new(num_channels: usize) -> PfbChannelizerBuilder {
+        PfbChannelizerBuilder {
+            num_channels,
+            taps: default_taps(num_channels),
+            oversampling: 1,
+            enabled: vec![true; num_channels],
+        }
+    }
+    /// Prototype filter with a cutoff of `1 / (2 * num_channels)`
+    #[must_use]
+    pub fn taps(mut self, taps: Vec<f32>) -> PfbChannelizerBuilder {
+        self.taps = taps;
+        self
+    }
+    /// Oversample channels by a factor of two
+    #[must_use]
+    pub fn oversampled(mut self) -> PfbChannelizerBuilder {
+        self.oversampling = 2;
+        self
+    }
+    /// Disable output channel
+    #[must_use]
+    pub fn disable(mut self, channel: usize) -> PfbChannelizerBuilder {
+        self.enabled[channel] = false;
+        self
+    }
+    /// Build PFB channelizer
+    pub fn build(self) -> Block {
+        let mut sio = StreamIoBuilder::new().add_input::<Complex32>("in");
+        for c in 0..self.num_channels {
+            sio = sio.add_output::<Complex32>(format!("out{c}").as_str());
+        }
+
+        Block::new(
+            BlockMetaBuilder::new("PfbChannelizer").build(),
+            sio.tag_propagation_policy(TagPropagation::RateChange {
+                interp: self.oversampling,
+                decim: self.num_channels,
+            })
+            .build(),
+            MessageIoBuilder::<PfbChannelizer>::new()
+                .add_input("enable", PfbChannelizer::enable)
+                .add_input("disable", PfbChannelizer::disable)
+                .build(),
+            PfbChannelizer {
+                kernel: PfbChannelizerKernel::new(self.num_channels, &self.taps, self.oversampling),
+                enabled: self.enabled,
+            },
+        )
+    }
+}
+
+/// Polyphase filterbank synthesizer.
+///
+/// Combines `num_channels` channels, sampled with `1 / num_channels` of the output sample
+/// rate or, if oversampled, with twice that rate, into one stream. Channel `c` is shifted to
+/// `c / num_channels` of the output sample rate. See the
+/// [futuredsp docs](futuredsp::pfb) for details.
+///
+/// # Inputs
+///
+/// `in0`, `in1`, ...: Channels (Complex32)
+///
+/// # Outputs
+///
+/// `out`: Output samples (Complex32)
+///
+/// # Message Handler
+///
+/// `enable`: Enable the input channel with the given index.
+/// `disable`: Disable the input channel with the given index. Disabled inputs are not read
+/// and treated as zeros.
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::PfbSynthesizerBuilder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let synthesizer = fg.add_block(PfbSynthesizerBuilder::new(8).disable(7).build());
+/// ```
+pub struct PfbSynthesizer {
+    kernel: PfbSynthesizerKernel,
+    enabled: Vec<bool>,
+}
+
+impl PfbSynthesizer {
+    /// Create PFB synthesizer block
+    pub fn new(num_channels: usize) -> Block {
+        PfbSynthesizerBuilder::new(num_channels).build()
+    }
+
+    #[message_handler]
+    async fn enable(
+        &mut self,
+        _io: &mut WorkIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        if let Some(c) = channel_index(&p, self.enabled.len()) {
+            self.enabled[c] = true;
+            Ok(Pmt::Ok)
+        } else {
+            Ok(Pmt::InvalidValue)
+        }
+    }
+
+    #[message_handler]
+    async fn disable(
+        &mut self,
+        _io: &mut WorkIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        if let Some(c) = channel_index(&p, self.enabled.len()) {
+            self.enabled[c] = false;
+            Ok(Pmt::Ok)
+        } else {
+            Ok(Pmt::InvalidValue)
+        }
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for PfbSynthesizer {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let inputs: Vec<Option<&[Complex32]>> = self
+            .enabled
+            .iter()
+            .enumerate()
+            .map(|(c, e)| e.then(|| sio.input(c).slice::<Complex32>()))
+            .collect();
+        let o = sio.output(0).slice::<Complex32>();
+
+        let (consumed, produced, status) = self.kernel.work(&inputs, o);
+
+        let mut finished = false;
+        for (c, i) in inputs.iter().enumerate() {
+            if let Some(i) = i {
+                sio.input(c).consume(consumed);
+                finished |= sio.input(c).finished() && consumed == i.len();
+            }
+        }
+        sio.output(0).produce(produced);
+
+        if finished && status.produced_all_samples() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+/// Build a [PfbSynthesizer].
+///
+/// By default, the synthesizer is critically sampled and uses a prototype filter designed
+/// with [`firdes::kaiser::multirate`](futuredsp::firdes::kaiser::multirate).
+pub struct PfbSynthesizerBuilder {
+    num_channels: usize,
+    taps: Vec<f32>,
+    oversampling: usize,
+    enabled: Vec<bool>,
+}
+
+impl PfbSynthesizerBuilder {
+    /// Create PFB synthesizer builder
+    pub fn new(num_channels: usize) -> PfbSynthesizerBuilder {
+        PfbSynthesizerBuilder {
+            num_channels,
+            taps: default_taps(num_channels),
+            oversampling: 1,
+            enabled: vec![true; num_channels],
+        }
+    }
+    /// Prototype filter with a cutoff of `1 / (2 * num_channels)` and unit gain
+    #[must_use]
+    pub fn taps(mut self, taps: Vec<f32>) -> PfbSynthesizerBuilder {
+        self.taps = taps;
+        self
+    }
+    /// Channels are oversampled by a factor of two
+    #[must_use]
+    pub fn oversampled(mut self) -> PfbSynthesizerBuilder {
+        self.oversampling = 2;
+        self
+    }
+    /// Disable input channel
+    #[must_use]
+    pub fn disable(mut self, channel: usize) -> PfbSynthesizerBuilder {
+        self.enabled[channel] = false;
+        self
+    }
+    /// Build PFB synthesizer
+    pub fn build(self) -> Block {
+        let mut sio = StreamIoBuilder::new();
+        for c in 0..self.num_channels {
+            sio = sio.add_input::<Complex32>(format!("in{c}").as_str());
+        }
+
+        Block::new(
+            BlockMetaBuilder::new("PfbSynthesizer").build(),
+            sio.add_output::<Complex32>("out")
+                .tag_propagation_policy(TagPropagation::RateChange {
+                    interp: self.num_channels / self.oversampling,
+                    decim: 1,
+                })
+                .build(),
+            MessageIoBuilder::<PfbSynthesizer>::new()
+                .add_input("enable", PfbSynthesizer::enable)
+                .add_input("disable", PfbSynthesizer::disable)
+                .build(),
+            PfbSynthesizer {
+                kernel: PfbSynthesizerKernel::new(self.num_channels, &self.taps, self.oversampling),
+                enabled: self.enabled,
+            },
+        )
+    }
+}
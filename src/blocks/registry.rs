@@ -0,0 +1,362 @@
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Duration;
+
+use crate::anyhow::{anyhow, bail, Result};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::blocks::BlobToUdp;
+use crate::blocks::ConsoleSink;
+use crate::blocks::Copy;
+use crate::blocks::CopyRand;
+use crate::blocks::Fft;
+use crate::blocks::FftDirection;
+use crate::blocks::FftFilter;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::blocks::FileSink;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::blocks::FileSource;
+use crate::blocks::FirBuilder;
+use crate::blocks::Head;
+use crate::blocks::IirBuilder;
+use crate::blocks::MessageBurst;
+use crate::blocks::MessageCopy;
+use crate::blocks::MessageSink;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::blocks::MessageSourceBuilder;
+use crate::blocks::NullSink;
+use crate::blocks::NullSource;
+use crate::blocks::PfbChannelizerBuilder;
+use crate::blocks::PfbSynthesizerBuilder;
+#[cfg(all(unix, not(target_arch = "wasm32")))]
+use crate::blocks::ShmSink;
+#[cfg(all(unix, not(target_arch = "wasm32")))]
+use crate::blocks::ShmSource;
+use crate::blocks::TagDebug;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::blocks::TcpSink;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::blocks::TcpSource;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::blocks::Throttle;
+use crate::blocks::VectorSinkBuilder;
+use crate::blocks::VectorSource;
+use crate::num_complex::{Complex, Complex32, Complex64};
+use crate::runtime::BlockRegistry;
+use crate::runtime::Pmt;
+
+/// Instantiate a generic block with the type given by the `item_type` parameter.
+macro_rules! typed {
+    ($p:expr, $t:ident => $body:expr) => {
+        match string($p, "item_type")?.as_str() {
+            "u8" => {
+                type $t = u8;
+                $body
+            }
+            "u16" => {
+                type $t = u16;
+                $body
+            }
+            "u32" => {
+                type $t = u32;
+                $body
+            }
+            "u64" => {
+                type $t = u64;
+                $body
+            }
+            "i8" => {
+                type $t = i8;
+                $body
+            }
+            "i16" => {
+                type $t = i16;
+                $body
+            }
+            "i32" => {
+                type $t = i32;
+                $body
+            }
+            "i64" => {
+                type $t = i64;
+                $body
+            }
+            "f32" => {
+                type $t = f32;
+                $body
+            }
+            "f64" => {
+                type $t = f64;
+                $body
+            }
+            "Complex32" => {
+                type $t = Complex32;
+                $body
+            }
+            "Complex64" => {
+                type $t = Complex64;
+                $body
+            }
+            t => bail!("unsupported item type {t}"),
+        }
+    };
+}
+
+fn get<'a>(p: &'a Pmt, name: &str) -> Option<&'a Pmt> {
+    match p {
+        Pmt::MapStrPmt(m) => m.get(name),
+        _ => None,
+    }
+}
+
+fn required<'a>(p: &'a Pmt, name: &str) -> Result<&'a Pmt> {
+    get(p, name).ok_or_else(|| anyhow!("missing parameter {name}"))
+}
+
+fn string(p: &Pmt, name: &str) -> Result<String> {
+    match required(p, name)? {
+        Pmt::String(s) => Ok(s.clone()),
+        v => bail!("parameter {name} is not a string ({v:?})"),
+    }
+}
+
+fn float(p: &Pmt, name: &str) -> Result<f64> {
+    required(p, name)?
+        .clone()
+        .try_into()
+        .map_err(|_| anyhow!("parameter {name} is not a number"))
+}
+
+fn integer(p: &Pmt, name: &str) -> Result<u64> {
+    match required(p, name)? {
+        Pmt::U32(v) => Ok(*v as u64),
+        Pmt::U64(v) => Ok(*v),
+        Pmt::Usize(v) => Ok(*v as u64),
+        Pmt::F64(v) if v.fract() == 0.0 && *v >= 0.0 => Ok(*v as u64),
+        v => bail!("parameter {name} is not an unsigned integer ({v:?})"),
+    }
+}
+
+fn number(p: &Pmt) -> Option<f64> {
+    match p {
+        Pmt::U32(v) => Some(*v as f64),
+        Pmt::U64(v) => Some(*v as f64),
+        Pmt::Usize(v) => Some(*v as f64),
+        Pmt::F32(v) => Some(*v as f64),
+        Pmt::F64(v) => Some(*v),
+        _ => None,
+    }
+}
+
+/// Item that can be given as element of an array parameter.
+///
+/// Complex items are given as `[re, im]` or as real number.
+trait Item: Sized {
+    fn from_pmt(p: &Pmt) -> Option<Self>;
+}
+
+macro_rules! real_item {
+    ($($t:ty),*) => {
+        $(impl Item for $t {
+            fn from_pmt(p: &Pmt) -> Option<Self> {
+                number(p).map(|v| v as $t)
+            }
+        })*
+    };
+}
+
+real_item!(u8, u16, u32, u64, i8, i16, i32, i64, f32, f64);
+
+macro_rules! complex_item {
+    ($($t:ty),*) => {
+        $(impl Item for Complex<$t> {
+            fn from_pmt(p: &Pmt) -> Option<Self> {
+                match p {
+                    Pmt::VecPmt(v) if v.len() == 2 => Some(Self::new(
+                        number(&v[0])? as $t,
+                        number(&v[1])? as $t,
+                    )),
+                    Pmt::VecF32(v) if v.len() == 2 => Some(Self::new(v[0] as $t, v[1] as $t)),
+                    p => number(p).map(|v| Self::new(v as $t, 0.0)),
+                }
+            }
+        })*
+    };
+}
+
+complex_item!(f32, f64);
+
+fn array<T: Item>(p: &Pmt, name: &str) -> Result<Vec<T>> {
+    let items = match required(p, name)? {
+        Pmt::VecPmt(v) => v.iter().map(T::from_pmt).collect(),
+        Pmt::VecF32(v) => v.iter().map(|x| T::from_pmt(&Pmt::F32(*x))).collect(),
+        Pmt::VecU64(v) => v.iter().map(|x| T::from_pmt(&Pmt::U64(*x))).collect(),
+        _ => None,
+    };
+    items.ok_or_else(|| anyhow!("parameter {name} is not an array of numbers"))
+}
+
+fn boolean(p: &Pmt, name: &str, default: bool) -> Result<bool> {
+    match get(p, name) {
+        None => Ok(default),
+        Some(Pmt::Bool(b)) => Ok(*b),
+        Some(v) => bail!("parameter {name} is not a boolean ({v:?})"),
+    }
+}
+
+/// Register the built-in blocks
+pub(crate) fn register_builtin(r: &mut BlockRegistry) {
+    r.register("NullSource", |p| typed!(p, T => Ok(NullSource::<T>::new())));
+    r.register("NullSink", |p| typed!(p, T => Ok(NullSink::<T>::new())));
+    r.register("Copy", |p| typed!(p, T => Ok(Copy::<T>::new())));
+    r.register("CopyRand", |p| {
+        let max_copy = integer(p, "max_copy")? as usize;
+        typed!(p, T => Ok(CopyRand::<T>::new(max_copy)))
+    });
+    r.register(
+        "VectorSource",
+        |p| typed!(p, T => Ok(VectorSource::<T>::new(array::<T>(p, "items")?))),
+    );
+    r.register("VectorSink", |p| {
+        typed!(p, T => {
+            let mut b = VectorSinkBuilder::<T>::new();
+            if get(p, "capacity").is_some() {
+                b = b.init_capacity(integer(p, "capacity")? as usize);
+            }
+            Ok(b.build())
+        })
+    });
+    r.register("Head", |p| {
+        let n = integer(p, "n_items")?;
+        typed!(p, T => Ok(Head::<T>::new(n)))
+    });
+    r.register("ConsoleSink", |p| {
+        let sep = if get(p, "sep").is_some() {
+            string(p, "sep")?
+        } else {
+            ", ".to_string()
+        };
+        typed!(p, T => Ok(ConsoleSink::<T>::new(sep)))
+    });
+    r.register("TagDebug", |p| {
+        let name = string(p, "name")?;
+        typed!(p, T => Ok(TagDebug::<T>::new(name)))
+    });
+    #[cfg(not(target_arch = "wasm32"))]
+    r.register("Throttle", |p| {
+        let rate = float(p, "rate")?;
+        typed!(p, T => Ok(Throttle::<T>::new(rate)))
+    });
+    #[cfg(not(target_arch = "wasm32"))]
+    r.register("FileSource", |p| {
+        let file_name = string(p, "file_name")?;
+        let repeat = boolean(p, "repeat", false)?;
+        typed!(p, T => Ok(FileSource::<T>::new(file_name, repeat)))
+    });
+    #[cfg(not(target_arch = "wasm32"))]
+    r.register("FileSink", |p| {
+        let file_name = string(p, "file_name")?;
+        typed!(p, T => Ok(FileSink::<T>::new(file_name)))
+    });
+    r.register("Fft", |p| {
+        let len = integer(p, "len")? as usize;
+        let direction = if boolean(p, "inverse", false)? {
+            FftDirection::Inverse
+        } else {
+            FftDirection::Forward
+        };
+        let fft_shift = boolean(p, "fft_shift", false)?;
+        let normalize = if get(p, "normalize").is_some() {
+            Some(float(p, "normalize")? as f32)
+        } else {
+            None
+        };
+        Ok(Fft::with_options(len, direction, fft_shift, normalize))
+    });
+    r.register("Fir", |p| {
+        let taps = array::<f32>(p, "taps")?;
+        match string(p, "item_type")?.as_str() {
+            "f32" => Ok(FirBuilder::new::<f32, f32, f32, _>(taps)),
+            "Complex32" => Ok(FirBuilder::new::<Complex32, Complex32, f32, _>(taps)),
+            t => bail!("unsupported item type {t}"),
+        }
+    });
+    r.register("FftFilter", |p| {
+        let taps = array::<f32>(p, "taps")?;
+        let fft_size = if get(p, "fft_size").is_some() {
+            Some(integer(p, "fft_size")? as usize)
+        } else {
+            None
+        };
+        match (string(p, "item_type")?.as_str(), fft_size) {
+            ("f32", None) => Ok(FftFilter::<f32, f32>::new(taps)),
+            ("f32", Some(n)) => Ok(FftFilter::<f32, f32>::with_fft_size(taps, n)),
+            ("Complex32", None) => Ok(FftFilter::<Complex32, f32>::new(taps)),
+            ("Complex32", Some(n)) => Ok(FftFilter::<Complex32, f32>::with_fft_size(taps, n)),
+            (t, _) => bail!("unsupported item type {t}"),
+        }
+    });
+    r.register("Iir", |p| match string(p, "item_type")?.as_str() {
+        "f32" => Ok(IirBuilder::new::<f32, f32, f32, _>(
+            array::<f32>(p, "a_taps")?,
+            array::<f32>(p, "b_taps")?,
+        )),
+        "f64" => Ok(IirBuilder::new::<f64, f64, f64, _>(
+            array::<f64>(p, "a_taps")?,
+            array::<f64>(p, "b_taps")?,
+        )),
+        t => bail!("unsupported item type {t}"),
+    });
+    r.register("PfbChannelizer", |p| {
+        let mut b = PfbChannelizerBuilder::new(integer(p, "num_channels")? as usize);
+        if get(p, "taps").is_some() {
+            b = b.taps(array(p, "taps")?);
+        }
+        if boolean(p, "oversampled", false)? {
+            b = b.oversampled();
+        }
+        Ok(b.build())
+    });
+    r.register("PfbSynthesizer", |p| {
+        let mut b = PfbSynthesizerBuilder::new(integer(p, "num_channels")? as usize);
+        if get(p, "taps").is_some() {
+            b = b.taps(array(p, "taps")?);
+        }
+        if boolean(p, "oversampled", false)? {
+            b = b.oversampled();
+        }
+        Ok(b.build())
+    });
+    #[cfg(not(target_arch = "wasm32"))]
+    r.register("TcpSource", |p| {
+        Ok(TcpSource::new(integer(p, "port")? as u32))
+    });
+    #[cfg(not(target_arch = "wasm32"))]
+    r.register("TcpSink", |p| Ok(TcpSink::new(integer(p, "port")? as u32)));
+    #[cfg(not(target_arch = "wasm32"))]
+    r.register("BlobToUdp", |p| Ok(BlobToUdp::new(string(p, "remote")?)));
+    #[cfg(all(unix, not(target_arch = "wasm32")))]
+    r.register("ShmSource", |p| {
+        let name = string(p, "name")?;
+        typed!(p, T => Ok(ShmSource::<T>::new(name)))
+    });
+    #[cfg(all(unix, not(target_arch = "wasm32")))]
+    r.register("ShmSink", |p| {
+        let name = string(p, "name")?;
+        typed!(p, T => Ok(ShmSink::<T>::new(name)))
+    });
+    r.register("MessageSink", |_| Ok(MessageSink::new()));
+    r.register("MessageCopy", |_| Ok(MessageCopy::new()));
+    r.register("MessageBurst", |p| {
+        let message = get(p, "message").cloned().unwrap_or(Pmt::Null);
+        Ok(MessageBurst::new(message, integer(p, "n_messages")?))
+    });
+    #[cfg(not(target_arch = "wasm32"))]
+    r.register("MessageSource", |p| {
+        let message = get(p, "message").cloned().unwrap_or(Pmt::Null);
+        let interval = Duration::from_secs_f64(float(p, "interval")?);
+        let mut b = MessageSourceBuilder::new(message, interval);
+        if get(p, "n_messages").is_some() {
+            b = b.n_messages(integer(p, "n_messages")? as usize);
+        }
+        Ok(b.build())
+    });
+}
@@ -24,6 +24,10 @@ use crate::runtime::WorkIo;
 use super::builder::BuilderType;
 
 /// Seify Sink block
+///
+/// Items tagged with [`Tag::TX_TIME`] start a burst that is transmitted at the given hardware
+/// time. [`Tag::TX_SOB`] and [`Tag::TX_EOB`] mark the first and the last item of a burst.
+/// Alternatively, a `NamedUsize("burst_start", len)` tag sends the next `len` items as one burst.
 pub struct Sink<D: DeviceTrait + Clone> {
     channels: Vec<usize>,
     dev: Device<D>,
@@ -159,20 +163,8 @@ impl<D: DeviceTrait + Clone> Kernel for Sink<D> {
             return Ok(());
         }
 
-        let t = sio.input(0).tags().iter().find_map(|x| match x {
-            ItemTag {
-                index,
-                tag: Tag::NamedUsize(n, len),
-            } => {
-                if *index == 0 && n == "burst_start" {
-                    debug_assert!(*len <= streamer.mtu().unwrap());
-                    Some(*len)
-                } else {
-                    None
-                }
-            }
-            _ => None,
-        });
+        let t = burst_start(sio.input(0).tags());
+        debug_assert!(t.map(|len| len <= streamer.mtu().unwrap()).unwrap_or(true));
 
         io.finished = sio.inputs().iter().any(|x| x.finished());
 
@@ -188,8 +180,9 @@ impl<D: DeviceTrait + Clone> Kernel for Sink<D> {
                 0
             }
         } else {
-            // send in non-burst mode
-            let ret = streamer.write(&bufs, None, false, 2_000_000)?;
+            let burst = next_burst(sio.input(0).tags(), min_in_len);
+            let bufs: Vec<&[Complex32]> = bufs.iter().map(|b| &b[0..burst.len]).collect();
+            let ret = streamer.write(&bufs, burst.at_ns, burst.end, 2_000_000)?;
             if ret != min_in_len {
                 io.call_again = true;
             }
@@ -229,6 +222,57 @@ impl<D: DeviceTrait + Clone> Kernel for Sink<D> {
     }
 }
 
+/// Length of a burst, started with a `NamedUsize("burst_start", len)` tag on the first item
+fn burst_start(tags: &[ItemTag]) -> Option<usize> {
+    tags.iter().find_map(|x| match x {
+        ItemTag {
+            index: 0,
+            tag: Tag::NamedUsize(n, len),
+        } if n == "burst_start" => Some(*len),
+        _ => None,
+    })
+}
+
+/// Items to write in one call to the streamer
+#[derive(Debug, PartialEq, Eq)]
+struct Burst {
+    len: usize,
+    at_ns: Option<i64>,
+    end: bool,
+}
+
+/// Split `n` available items at the next burst boundary, given by tx_time/tx_sob/tx_eob tags
+fn next_burst(tags: &[ItemTag], n: usize) -> Burst {
+    let mut at_ns = None;
+    let mut len = n;
+    let mut end = false;
+    for x in tags.iter().filter(|x| x.index < n) {
+        match &x.tag {
+            Tag::NamedI64(name, t) if name == Tag::TX_TIME => {
+                if x.index == 0 {
+                    at_ns = Some(*t);
+                } else {
+                    len = len.min(x.index);
+                }
+            }
+            Tag::String(name) if name == Tag::TX_SOB && x.index > 0 => {
+                len = len.min(x.index);
+            }
+            _ => {}
+        }
+    }
+    if let Some(eob) = tags
+        .iter()
+        .filter(|x| x.index < len && matches!(&x.tag, Tag::String(n) if n == Tag::TX_EOB))
+        .map(|x| x.index)
+        .min()
+    {
+        len = eob + 1;
+        end = true;
+    }
+    Burst { len, at_ns, end }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 type Sched = crate::runtime::scheduler::SmolScheduler;
 #[cfg(target_arch = "wasm32")]
@@ -250,3 +294,72 @@ impl SinkBuilder {
         Builder::with_scheduler(BuilderType::Sink, scheduler)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tag(index: usize, tag: Tag) -> ItemTag {
+        ItemTag { index, tag }
+    }
+
+    fn burst(len: usize, at_ns: Option<i64>, end: bool) -> Burst {
+        Burst { len, at_ns, end }
+    }
+
+    #[test]
+    fn continuous() {
+        assert_eq!(next_burst(&[], 100), burst(100, None, false));
+        assert_eq!(
+            next_burst(&[tag(3, Tag::Id(1))], 100),
+            burst(100, None, false)
+        );
+    }
+
+    #[test]
+    fn timed_burst() {
+        let tags = [
+            tag(0, Tag::NamedI64(Tag::TX_TIME.to_string(), 1234)),
+            tag(9, Tag::String(Tag::TX_EOB.to_string())),
+        ];
+        assert_eq!(next_burst(&tags, 100), burst(10, Some(1234), true));
+        // end of burst not available yet
+        assert_eq!(next_burst(&tags, 5), burst(5, Some(1234), false));
+    }
+
+    #[test]
+    fn split_at_next_burst() {
+        let tags = [
+            tag(0, Tag::String(Tag::TX_SOB.to_string())),
+            tag(20, Tag::NamedI64(Tag::TX_TIME.to_string(), 1234)),
+            tag(50, Tag::String(Tag::TX_SOB.to_string())),
+        ];
+        assert_eq!(next_burst(&tags, 100), burst(20, None, false));
+        assert_eq!(next_burst(&tags[2..], 100), burst(50, None, false));
+        // tags of items that are not available are ignored
+        assert_eq!(next_burst(&tags[1..], 10), burst(10, None, false));
+    }
+
+    #[test]
+    fn end_before_next_burst() {
+        let tags = [
+            tag(4, Tag::String(Tag::TX_EOB.to_string())),
+            tag(5, Tag::NamedI64(Tag::TX_TIME.to_string(), 1234)),
+            tag(8, Tag::String(Tag::TX_EOB.to_string())),
+        ];
+        assert_eq!(next_burst(&tags, 100), burst(5, None, true));
+    }
+
+    #[test]
+    fn legacy_burst_start() {
+        assert_eq!(burst_start(&[]), None);
+        assert_eq!(
+            burst_start(&[tag(0, Tag::NamedUsize("burst_start".to_string(), 42))]),
+            Some(42)
+        );
+        assert_eq!(
+            burst_start(&[tag(1, Tag::NamedUsize("burst_start".to_string(), 42))]),
+            None
+        );
+    }
+}
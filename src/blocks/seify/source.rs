@@ -18,14 +18,97 @@ use crate::runtime::MessageIoBuilder;
 use crate::runtime::Pmt;
 use crate::runtime::StreamIo;
 use crate::runtime::StreamIoBuilder;
+use crate::runtime::Tag;
 use crate::runtime::WorkIo;
 
 /// Seify Source block
+///
+/// Tags the first item of the stream and the first item after a reconfiguration (frequency,
+/// sample rate, or config command) or a read error (e.g., an overflow) with
+/// [`Tag::RX_TIME`], [`Tag::RX_RATE`], and [`Tag::RX_FREQ`]. Use a
+/// [`SampleClock`](crate::runtime::SampleClock) to compute the time of any item.
+///
+/// The time of the first item is the start time of the builder. If no start time is set or
+/// samples got lost after a read error, the host time is used instead, since the device time is
+/// not available. On WebAssembly, no [`Tag::RX_TIME`] is emitted in these cases.
 pub struct Source<D: DeviceTrait + Clone> {
     channels: Vec<usize>,
     dev: Device<D>,
     streamer: Option<D::RxStreamer>,
     start_time: Option<i64>,
+    tags: RxTags,
+}
+
+/// Time, sample rate, and frequency tags of received items
+///
+/// The time of an item is derived from a reference item with a known time.
+#[derive(Debug)]
+struct RxTags {
+    produced: u64,
+    // absolute offset, time
+    reference: Option<(u64, i64)>,
+    rate: f64,
+    freq: f64,
+    pending: bool,
+}
+
+impl RxTags {
+    fn new() -> Self {
+        RxTags {
+            produced: 0,
+            reference: None,
+            rate: 1.0,
+            freq: 0.0,
+            pending: false,
+        }
+    }
+
+    /// Stream starts at `start_time` or, if not set, at the host time
+    fn start(&mut self, start_time: Option<i64>, host_time: Option<i64>, rate: f64, freq: f64) {
+        self.rate = rate;
+        self.freq = freq;
+        self.reference = start_time.or(host_time).map(|t| (self.produced, t));
+        self.pending = true;
+    }
+
+    fn time_of(&self, offset: u64) -> Option<i64> {
+        let (ref_offset, ref_time) = self.reference?;
+        let delta = (offset as f64 - ref_offset as f64) * 1e9 / self.rate;
+        Some(ref_time + delta.round() as i64)
+    }
+
+    /// Sample rate or frequency changed
+    fn reconfigure(&mut self, rate: f64, freq: f64) {
+        // keep the time of the current item, before the sample rate changes
+        self.reference = self.time_of(self.produced).map(|t| (self.produced, t));
+        self.rate = rate;
+        self.freq = freq;
+        self.pending = true;
+    }
+
+    /// Read failed, fall back to the host time
+    fn read_error(&mut self, host_time: Option<i64>) {
+        // samples might be lost, once the stream is running
+        if self.reference.map(|r| r.0 < self.produced).unwrap_or(true) {
+            self.reference = host_time.map(|t| (self.produced, t));
+            self.pending = true;
+        }
+    }
+
+    /// Tags of the first of `n` received items
+    fn produce(&mut self, n: usize) -> Vec<Tag> {
+        let mut tags = Vec::new();
+        if self.pending && n > 0 {
+            if let Some(t) = self.time_of(self.produced) {
+                tags.push(Tag::NamedI64(Tag::RX_TIME.to_string(), t));
+            }
+            tags.push(Tag::NamedF64(Tag::RX_RATE.to_string(), self.rate));
+            tags.push(Tag::NamedF64(Tag::RX_FREQ.to_string(), self.freq));
+            self.pending = false;
+        }
+        self.produced += n as u64;
+        tags
+    }
 }
 
 impl<D: DeviceTrait + Clone> Source<D> {
@@ -56,10 +139,19 @@ impl<D: DeviceTrait + Clone> Source<D> {
                 dev,
                 start_time,
                 streamer: None,
+                tags: RxTags::new(),
             },
         )
     }
 
+    /// Read sample rate and frequency back from the device and emit new tags
+    fn update_config(&mut self) -> Result<()> {
+        let rate = self.dev.sample_rate(Rx, self.channels[0])?;
+        let freq = self.dev.frequency(Rx, self.channels[0])?;
+        self.tags.reconfigure(rate, freq);
+        Ok(())
+    }
+
     #[message_handler]
     fn cmd_handler(
         &mut self,
@@ -70,6 +162,7 @@ impl<D: DeviceTrait + Clone> Source<D> {
     ) -> Result<Pmt> {
         let c: Config = p.try_into()?;
         c.apply(&self.dev, &self.channels, Rx)?;
+        self.update_config()?;
         Ok(Pmt::Ok)
     }
 
@@ -90,6 +183,7 @@ impl<D: DeviceTrait + Clone> Source<D> {
                 _ => return Ok(Pmt::InvalidValue),
             };
         }
+        self.update_config()?;
         Ok(Pmt::Ok)
     }
 
@@ -130,6 +224,7 @@ impl<D: DeviceTrait + Clone> Source<D> {
                 _ => return Ok(Pmt::InvalidValue),
             };
         }
+        self.update_config()?;
         Ok(Pmt::Ok)
     }
 }
@@ -145,6 +240,7 @@ impl<D: DeviceTrait + Clone> Kernel for Source<D> {
         _meta: &mut BlockMeta,
     ) -> Result<()> {
         let outs = sio.outputs_mut();
+        let n_outs = outs.len();
         let mut bufs: Vec<&mut [Complex32]> =
             outs.iter_mut().map(|b| b.slice::<Complex32>()).collect();
 
@@ -156,10 +252,18 @@ impl<D: DeviceTrait + Clone> Kernel for Source<D> {
             return Ok(());
         }
 
-        if let Ok(len) = streamer.read(&mut bufs, 1_000_000) {
-            for i in 0..outs.len() {
-                sio.output(i).produce(len);
+        match streamer.read(&mut bufs, 1_000_000) {
+            Ok(len) => {
+                let tags = self.tags.produce(len);
+                for i in 0..n_outs {
+                    let o = sio.output(i);
+                    for t in tags.iter() {
+                        o.add_tag(0, t.clone());
+                    }
+                    o.produce(len);
+                }
             }
+            Err(_) => self.tags.read_error(host_time()),
         }
         io.call_again = true;
         Ok(())
@@ -177,6 +281,10 @@ impl<D: DeviceTrait + Clone> Kernel for Source<D> {
             .context("no stream")?
             .activate_at(self.start_time)?;
 
+        let rate = self.dev.sample_rate(Rx, self.channels[0])?;
+        let freq = self.dev.frequency(Rx, self.channels[0])?;
+        self.tags.start(self.start_time, host_time(), rate, freq);
+
         Ok(())
     }
 
@@ -191,6 +299,19 @@ impl<D: DeviceTrait + Clone> Kernel for Source<D> {
     }
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+fn host_time() -> Option<i64> {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .ok()
+        .map(|d| d.as_nanos() as i64)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn host_time() -> Option<i64> {
+    None
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 type Sched = crate::runtime::scheduler::SmolScheduler;
 #[cfg(target_arch = "wasm32")]
@@ -212,3 +333,81 @@ impl SourceBuilder {
         Builder::with_scheduler(BuilderType::Source, scheduler)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(tags: &[Tag]) -> Option<i64> {
+        tags.iter().find_map(|t| match t {
+            Tag::NamedI64(n, t) if n == Tag::RX_TIME => Some(*t),
+            _ => None,
+        })
+    }
+
+    fn rate(tags: &[Tag]) -> Option<f64> {
+        tags.iter().find_map(|t| match t {
+            Tag::NamedF64(n, r) if n == Tag::RX_RATE => Some(*r),
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn start_time() {
+        let mut t = RxTags::new();
+        t.start(Some(1_000), Some(5), 1e6, 100e6);
+
+        let tags = t.produce(10);
+        assert_eq!(tags.len(), 3);
+        assert_eq!(time(&tags), Some(1_000));
+        assert_eq!(rate(&tags), Some(1e6));
+        assert!(tags
+            .iter()
+            .any(|t| matches!(t, Tag::NamedF64(n, f) if n == Tag::RX_FREQ && *f == 100e6)));
+
+        assert!(t.produce(10).is_empty());
+        assert_eq!(t.time_of(20), Some(1_000 + 20_000));
+    }
+
+    #[test]
+    fn host_time_fallback() {
+        let mut t = RxTags::new();
+        t.start(None, Some(42), 1e6, 0.0);
+        assert!(t.produce(0).is_empty());
+        assert_eq!(time(&t.produce(10)), Some(42));
+
+        // without host time, only rate and frequency are tagged
+        let mut t = RxTags::new();
+        t.start(None, None, 1e6, 0.0);
+        let tags = t.produce(10);
+        assert_eq!(tags.len(), 2);
+        assert_eq!(time(&tags), None);
+    }
+
+    #[test]
+    fn reconfigure() {
+        let mut t = RxTags::new();
+        t.start(Some(0), None, 1e6, 0.0);
+        t.produce(1000);
+
+        t.reconfigure(2e6, 1e9);
+        let tags = t.produce(2000);
+        assert_eq!(time(&tags), Some(1_000_000));
+        assert_eq!(rate(&tags), Some(2e6));
+        assert_eq!(t.time_of(3000), Some(2_000_000));
+    }
+
+    #[test]
+    fn read_error() {
+        let mut t = RxTags::new();
+        t.start(Some(100), None, 1e6, 0.0);
+
+        // no samples lost before the first item
+        t.read_error(Some(7));
+        assert_eq!(time(&t.produce(10)), Some(100));
+
+        t.read_error(Some(7));
+        assert_eq!(time(&t.produce(10)), Some(7));
+        assert!(t.produce(10).is_empty());
+    }
+}
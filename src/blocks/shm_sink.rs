@@ -0,0 +1,143 @@
+use std::sync::Arc;
+
+use crate::anyhow::Result;
+use crate::runtime::buffer::shm::Segment;
+use crate::runtime::config;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Push samples and tags into a named POSIX shared-memory segment.
+///
+/// The sink creates the segment, replacing a stale segment with the same name, and removes it,
+/// when the flowgraph terminates. A [ShmSource](super::ShmSource) in another flowgraph, possibly
+/// in another process on the same host, attaches to the segment. The sink waits for at least
+/// one source to attach, before it starts writing, and is throttled by the slowest source. If the
+/// input is finished, while no source is attached, the remaining samples are dropped.
+///
+/// `NamedAny` tags cannot be shared between processes and are dropped.
+///
+/// # Inputs
+///
+/// `in`: Input
+///
+/// # Outputs
+///
+/// No outputs.
+///
+/// # Usage
+/// ```no_run
+/// use futuresdr::blocks::ShmSink;
+/// use futuresdr::runtime::Flowgraph;
+/// use num_complex::Complex;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let sink = fg.add_block(ShmSink::<Complex<f32>>::new("futuresdr-rx"));
+/// ```
+pub struct ShmSink<T: Send + 'static> {
+    name: String,
+    min_bytes: usize,
+    segment: Option<Arc<Segment>>,
+    _type: std::marker::PhantomData<T>,
+}
+
+impl<T: Send + 'static> ShmSink<T> {
+    /// Create ShmSink block
+    pub fn new<S: Into<String>>(name: S) -> Block {
+        Self::with_size(name, config::config().buffer_size)
+    }
+
+    /// Create ShmSink block with a minimum size of the shared-memory ring in bytes
+    pub fn with_size<S: Into<String>>(name: S, min_bytes: usize) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("ShmSink").build(),
+            StreamIoBuilder::new().add_input::<T>("in").build(),
+            MessageIoBuilder::new().build(),
+            ShmSink::<T> {
+                name: name.into(),
+                min_bytes,
+                segment: None,
+                _type: std::marker::PhantomData,
+            },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl<T: Send + 'static> Kernel for ShmSink<T> {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let segment = self.segment.as_ref().unwrap();
+        let seq = segment.reader_seq();
+
+        if segment.readers() == 0 {
+            if sio.input(0).finished() {
+                io.finished = true;
+            } else {
+                io.block_on(segment.wait_reader(seq));
+            }
+            return Ok(());
+        }
+
+        let item_size = std::mem::size_of::<T>();
+        let i = sio.input(0).slice_unchecked::<u8>();
+        let available = i.len() / item_size;
+        let (ptr, space) = segment.space();
+        let n = std::cmp::min(available, space / item_size);
+
+        if n > 0 {
+            unsafe {
+                std::ptr::copy_nonoverlapping(i.as_ptr(), ptr, n * item_size);
+            }
+            segment.produce(n * item_size, sio.input(0).tags());
+            sio.input(0).consume(n);
+        }
+
+        if n < available {
+            io.block_on(segment.wait_reader(seq));
+        } else if sio.input(0).finished() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        self.segment = Some(Segment::create(
+            &self.name,
+            std::mem::size_of::<T>(),
+            self.min_bytes,
+        )?);
+        Ok(())
+    }
+
+    async fn deinit(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        if let Some(s) = self.segment.take() {
+            s.close();
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,134 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::anyhow::Result;
+use crate::runtime::buffer::shm::Segment;
+use crate::runtime::clock;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Read samples and tags from a named POSIX shared-memory segment.
+///
+/// The segment is created by a [ShmSink](super::ShmSink) in another flowgraph, possibly in
+/// another process on the same host. The source waits for the segment to appear and receives
+/// all samples that are written after it attached. It finishes, once the sink is finished and
+/// all samples are read.
+///
+/// # Inputs
+///
+/// No inputs.
+///
+/// # Outputs
+///
+/// `out`: Output
+///
+/// # Usage
+/// ```no_run
+/// use futuresdr::blocks::ShmSource;
+/// use futuresdr::runtime::Flowgraph;
+/// use num_complex::Complex;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let source = fg.add_block(ShmSource::<Complex<f32>>::new("futuresdr-rx"));
+/// ```
+pub struct ShmSource<T: Send + 'static> {
+    name: String,
+    segment: Option<(Arc<Segment>, usize)>,
+    _type: std::marker::PhantomData<T>,
+}
+
+impl<T: Send + 'static> ShmSource<T> {
+    /// Create ShmSource block
+    pub fn new<S: Into<String>>(name: S) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("ShmSource").build(),
+            StreamIoBuilder::new().add_output::<T>("out").build(),
+            MessageIoBuilder::new().build(),
+            ShmSource::<T> {
+                name: name.into(),
+                segment: None,
+                _type: std::marker::PhantomData,
+            },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl<T: Send + 'static> Kernel for ShmSource<T> {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let (segment, slot) = self.segment.as_ref().unwrap();
+        let seq = segment.writer_seq();
+        let done = segment.writer_done();
+
+        let item_size = std::mem::size_of::<T>();
+        let (ptr, bytes, tags) = segment.readable(*slot);
+        let available = bytes / item_size;
+        let o = sio.output(0).slice_unchecked::<u8>();
+        let n = std::cmp::min(available, o.len() / item_size);
+
+        if n > 0 {
+            unsafe {
+                std::ptr::copy_nonoverlapping(ptr, o.as_mut_ptr(), n * item_size);
+            }
+            for t in tags.into_iter().filter(|t| t.index < n) {
+                sio.output(0).add_tag(t.index, t.tag);
+            }
+            segment.consume(*slot, n * item_size);
+            sio.output(0).produce(n);
+        }
+
+        if n == available {
+            if done {
+                io.finished = true;
+            } else {
+                io.block_on(segment.wait_writer(seq));
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let segment = loop {
+            if let Some(s) = Segment::open(&self.name, std::mem::size_of::<T>())? {
+                break s;
+            }
+            clock::sleep(Duration::from_millis(10)).await;
+        };
+        let slot = segment.attach()?;
+        self.segment = Some((segment, slot));
+        Ok(())
+    }
+
+    async fn deinit(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        if let Some((segment, slot)) = self.segment.take() {
+            segment.detach(slot);
+        }
+        Ok(())
+    }
+}
@@ -7,6 +7,7 @@ use crate::runtime::MessageIo;
 use crate::runtime::MessageIoBuilder;
 use crate::runtime::StreamIo;
 use crate::runtime::StreamIoBuilder;
+use crate::runtime::TagPropagation;
 use crate::runtime::WorkIo;
 
 /// Apply a function to split a stream.
@@ -38,6 +39,7 @@ where
                 .add_input::<A>("in")
                 .add_output::<B>("out0")
                 .add_output::<C>("out1")
+                .tag_propagation_policy(TagPropagation::AllToAll)
                 .build(),
             MessageIoBuilder::<Self>::new().build(),
             Split {
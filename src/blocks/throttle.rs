@@ -1,4 +1,5 @@
 use crate::anyhow::Result;
+use crate::runtime::clock;
 use crate::runtime::Block;
 use crate::runtime::BlockMeta;
 use crate::runtime::BlockMetaBuilder;
@@ -7,8 +8,8 @@ use crate::runtime::MessageIo;
 use crate::runtime::MessageIoBuilder;
 use crate::runtime::StreamIo;
 use crate::runtime::StreamIoBuilder;
+use crate::runtime::TagPropagation;
 use crate::runtime::WorkIo;
-use async_io::Timer;
 use std::time::Duration;
 use std::time::Instant;
 
@@ -48,11 +49,12 @@ impl<T: Copy + Send + 'static> Throttle<T> {
             StreamIoBuilder::new()
                 .add_input::<T>("in")
                 .add_output::<T>("out")
+                .tag_propagation_policy(TagPropagation::OneToOne)
                 .build(),
             MessageIoBuilder::<Self>::new().build(),
             Throttle::<T> {
                 rate,
-                t_init: Instant::now(),
+                t_init: clock::now(),
                 n_items: 0,
                 _type: std::marker::PhantomData,
             },
@@ -73,7 +75,7 @@ impl<T: Copy + Send + 'static> Kernel for Throttle<T> {
         let i = sio.input(0).slice::<T>();
         let o = sio.output(0).slice::<T>();
 
-        let now = Instant::now();
+        let now = clock::now();
         let target_items = (now - self.t_init).as_secs_f64() * self.rate;
         let target_items = target_items.floor() as usize;
         let remaining_items = target_items - self.n_items;
@@ -94,9 +96,7 @@ impl<T: Copy + Send + 'static> Kernel for Throttle<T> {
             io.finished = true;
         }
 
-        io.block_on(async {
-            Timer::after(Duration::from_millis(100)).await;
-        });
+        io.block_on(clock::sleep(Duration::from_millis(100)));
 
         Ok(())
     }
@@ -107,7 +107,7 @@ impl<T: Copy + Send + 'static> Kernel for Throttle<T> {
         _mio: &mut MessageIo<Self>,
         _meta: &mut BlockMeta,
     ) -> Result<()> {
-        self.t_init = Instant::now();
+        self.t_init = clock::now();
         self.n_items = 0;
         Ok(())
     }
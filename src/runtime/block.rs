@@ -4,23 +4,33 @@ use futures::future::Either;
 use futures::prelude::*;
 use futures::FutureExt;
 use std::any::Any;
+use std::collections::VecDeque;
 use std::fmt;
 use std::future::Future;
 use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
 
 use crate::anyhow::{Context, Result};
 use crate::runtime::BlockDescription;
+use crate::runtime::BlockError;
 use crate::runtime::BlockMessage;
 use crate::runtime::BlockMeta;
+use crate::runtime::BlockPhase;
+use crate::runtime::BlockStats;
 use crate::runtime::Error;
 use crate::runtime::FlowgraphMessage;
 use crate::runtime::MessageIo;
 use crate::runtime::MessageOutput;
+use crate::runtime::MessageQueue;
 use crate::runtime::Pmt;
 use crate::runtime::PortId;
+use crate::runtime::RestartPolicy;
+use crate::runtime::SchedulingHints;
 use crate::runtime::StreamInput;
 use crate::runtime::StreamIo;
 use crate::runtime::StreamOutput;
+use crate::runtime::TagPropagationFn;
 
 /// Work IO
 ///
@@ -104,13 +114,13 @@ pub trait BlockT: Send + Any {
     fn set_instance_name(&mut self, name: &str);
     fn type_name(&self) -> &str;
     fn is_blocking(&self) -> bool;
+    fn restart_policy(&self) -> RestartPolicy;
+    fn set_restart_policy(&mut self, policy: RestartPolicy);
+    fn scheduling_hints(&self) -> SchedulingHints;
+    fn set_scheduling_hints(&mut self, hints: SchedulingHints);
 
     // ##### STREAM IO
-    #[allow(clippy::type_complexity)]
-    fn set_tag_propagation(
-        &mut self,
-        f: Box<dyn FnMut(&mut [StreamInput], &mut [StreamOutput]) + Send + 'static>,
-    );
+    fn set_tag_propagation(&mut self, f: TagPropagationFn);
     fn stream_inputs(&self) -> &Vec<StreamInput>;
     fn stream_input(&self, id: usize) -> &StreamInput;
     fn stream_input_name_to_id(&self, name: &str) -> Option<usize>;
@@ -120,6 +130,7 @@ pub trait BlockT: Send + Any {
 
     // ##### MESSAGE IO
     fn message_input_name_to_id(&self, name: &str) -> Option<usize>;
+    fn message_input_names(&self) -> Vec<String>;
     fn message_outputs(&self) -> &Vec<MessageOutput>;
     fn message_output_name_to_id(&self, name: &str) -> Option<usize>;
 }
@@ -148,31 +159,54 @@ impl<T: Kernel + Send + 'static> TypedBlock<T> {
     }
 }
 
+/// Counters of the block run loop
+#[derive(Debug, Default)]
+struct RunStats {
+    work_calls: u64,
+    work_time: Duration,
+    messages_handled: u64,
+    restarts: usize,
+}
+
+/// Outcome of handling a block error
+enum Recovery {
+    /// Block was restarted and keeps running
+    Restarted,
+    /// Block shuts down gracefully
+    Finish,
+    /// Error has to be reported to the flowgraph
+    Fail(BlockPhase, crate::anyhow::Error),
+}
+
 pub(crate) struct TypedBlockWrapper<T> {
     pub(crate) inner: Option<TypedBlock<T>>,
 }
 
 impl<T: Kernel + Send + 'static> TypedBlockWrapper<T> {
-    async fn call_handler(
+    /// Call a message handler
+    ///
+    /// Returns an error, if the handler failed, and [`Error::InvalidHandler`], if there is no
+    /// such handler.
+    pub(crate) async fn call_handler(
         io: &mut WorkIo,
         mio: &mut MessageIo<T>,
         meta: &mut BlockMeta,
         kernel: &mut T,
         id: PortId,
         p: Pmt,
-    ) -> std::result::Result<Pmt, Error> {
+    ) -> Result<std::result::Result<Pmt, Error>> {
         let id = match id {
             PortId::Index(i) => {
                 if i < mio.inputs().len() {
                     i
                 } else {
-                    return Err(Error::InvalidHandler(PortId::Index(i)));
+                    return Ok(Err(Error::InvalidHandler(PortId::Index(i))));
                 }
             }
             PortId::Name(n) => match mio.input_name_to_id(&n) {
                 Some(s) => s,
                 None => {
-                    return Err(Error::InvalidHandler(PortId::Name(n)));
+                    return Ok(Err(Error::InvalidHandler(PortId::Name(n))));
                 }
             },
         };
@@ -181,7 +215,190 @@ impl<T: Kernel + Send + 'static> TypedBlockWrapper<T> {
         }
         let h = mio.input(id).get_handler();
         let f = (h)(kernel, io, mio, meta, p);
-        f.await.or(Err(Error::HandlerError))
+        Ok(Ok(f.await?))
+    }
+
+    /// Handle an error according to the [`RestartPolicy`] of the block
+    async fn recover(
+        sio: &mut StreamIo,
+        mio: &mut MessageIo<T>,
+        meta: &mut BlockMeta,
+        kernel: &mut T,
+        stats: &mut RunStats,
+        mut phase: BlockPhase,
+        mut error: crate::anyhow::Error,
+    ) -> Recovery {
+        let (max_restarts, backoff) = match meta.restart_policy() {
+            RestartPolicy::Terminate => return Recovery::Fail(phase, error),
+            RestartPolicy::Finish => {
+                warn!(
+                    "{}: {phase} failed, finishing block ({:?})",
+                    meta.instance_name().unwrap(),
+                    error
+                );
+                return Recovery::Finish;
+            }
+            RestartPolicy::Restart {
+                max_restarts,
+                backoff,
+            } => (max_restarts, backoff),
+        };
+
+        loop {
+            if stats.restarts >= max_restarts {
+                return Recovery::Fail(phase, error);
+            }
+
+            let delay = backoff.saturating_mul(1 << stats.restarts.min(16));
+            stats.restarts += 1;
+            warn!(
+                "{}: {phase} failed, restarting in {:?} ({}/{}) ({:?})",
+                meta.instance_name().unwrap(),
+                delay,
+                stats.restarts,
+                max_restarts,
+                error
+            );
+
+            if phase != BlockPhase::Init {
+                if let Err(e) = kernel.deinit(sio, mio, meta).await {
+                    warn!(
+                        "{}: Error in deinit() during restart ({:?})",
+                        meta.instance_name().unwrap(),
+                        e
+                    );
+                }
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            crate::runtime::clock::sleep(delay).await;
+
+            match kernel.init(sio, mio, meta).await {
+                Ok(()) => return Recovery::Restarted,
+                Err(e) => {
+                    phase = BlockPhase::Init;
+                    error = e;
+                }
+            }
+        }
+    }
+
+    /// Report an error to the flowgraph, handing the block back
+    async fn report_error(
+        mut block: TypedBlock<T>,
+        block_id: usize,
+        main_inbox: &mut Sender<FlowgraphMessage>,
+        phase: BlockPhase,
+        error: crate::anyhow::Error,
+    ) -> Result<()> {
+        // the flowgraph might keep running, so let the neighbors shut down. Blocks that fail in
+        // deinit() already notified them before.
+        if phase != BlockPhase::Deinit {
+            join_all(
+                block
+                    .sio
+                    .inputs_mut()
+                    .iter_mut()
+                    .map(|i| i.notify_finished()),
+            )
+            .await;
+            join_all(
+                block
+                    .sio
+                    .outputs_mut()
+                    .iter_mut()
+                    .map(|o| o.notify_finished()),
+            )
+            .await;
+            join_all(
+                block
+                    .mio
+                    .outputs_mut()
+                    .iter_mut()
+                    .map(|o| o.notify_finished()),
+            )
+            .await;
+        }
+
+        let error = BlockError {
+            block_id,
+            instance_name: block.meta.instance_name().unwrap().to_string(),
+            phase,
+            error,
+        };
+        main_inbox
+            .send(FlowgraphMessage::BlockError {
+                block_id,
+                block: Block(Box::new(TypedBlockWrapper { inner: Some(block) })),
+                error,
+            })
+            .await?;
+        Ok(())
+    }
+
+    fn description(
+        block_id: usize,
+        sio: &StreamIo,
+        mio: &MessageIo<T>,
+        meta: &BlockMeta,
+    ) -> BlockDescription {
+        let stream_inputs: Vec<String> =
+            sio.inputs().iter().map(|x| x.name().to_string()).collect();
+        let stream_outputs: Vec<String> =
+            sio.outputs().iter().map(|x| x.name().to_string()).collect();
+        let stream_input_types: Vec<String> = sio
+            .inputs()
+            .iter()
+            .map(|x| x.type_name().to_string())
+            .collect();
+        let stream_output_types: Vec<String> = sio
+            .outputs()
+            .iter()
+            .map(|x| x.type_name().to_string())
+            .collect();
+        let message_inputs: Vec<String> = mio.input_names();
+        let message_outputs: Vec<String> =
+            mio.outputs().iter().map(|x| x.name().to_string()).collect();
+
+        BlockDescription {
+            id: block_id,
+            type_name: meta.type_name().to_string(),
+            instance_name: meta.instance_name().unwrap().to_string(),
+            stream_inputs,
+            stream_outputs,
+            stream_input_types,
+            stream_output_types,
+            message_inputs,
+            message_outputs,
+            blocking: meta.is_blocking(),
+        }
+    }
+
+    fn connect_message_output(
+        mio: &mut MessageIo<T>,
+        src_port: usize,
+        dst_port: usize,
+        dst_inbox: Sender<BlockMessage>,
+        queue: Option<Arc<MessageQueue>>,
+    ) {
+        match queue {
+            Some(queue) => mio
+                .output_mut(src_port)
+                .connect_with_queue(dst_port, dst_inbox, queue),
+            None => mio.output_mut(src_port).connect(dst_port, dst_inbox),
+        }
+    }
+
+    fn stats(block_id: usize, sio: &StreamIo, meta: &BlockMeta, stats: &RunStats) -> BlockStats {
+        BlockStats {
+            id: block_id,
+            instance_name: meta.instance_name().unwrap().to_string(),
+            work_calls: stats.work_calls,
+            work_time: stats.work_time,
+            messages_handled: stats.messages_handled,
+            restarts: stats.restarts,
+            stream_inputs: sio.inputs().iter().map(|x| x.stats()).collect(),
+            stream_outputs: sio.outputs().iter().map(|x| x.stats()).collect(),
+        }
     }
 
     async fn run_impl(
@@ -201,6 +418,7 @@ impl<T: Kernel + Send + 'static> TypedBlockWrapper<T> {
             finished: false,
             block_on: None,
         };
+        let mut stats = RunStats::default();
 
         // setup phase
         loop {
@@ -208,40 +426,96 @@ impl<T: Kernel + Send + 'static> TypedBlockWrapper<T> {
                 BlockMessage::Initialize => {
                     if let Err(e) = kernel.init(&mut sio, &mut mio, &mut meta).await {
                         error!(
-                            "{}: Error during initialization. Terminating.",
-                            meta.instance_name().unwrap()
+                            "{}: Error during initialization. ({:?})",
+                            meta.instance_name().unwrap(),
+                            e
                         );
-                        main_inbox
-                            .send(FlowgraphMessage::BlockError {
-                                block_id,
-                                block: Block(Box::new(TypedBlockWrapper {
-                                    inner: Some(TypedBlock {
+                        match Self::recover(
+                            &mut sio,
+                            &mut mio,
+                            &mut meta,
+                            &mut kernel,
+                            &mut stats,
+                            BlockPhase::Init,
+                            e,
+                        )
+                        .await
+                        {
+                            Recovery::Restarted => {}
+                            Recovery::Finish => work_io.finished = true,
+                            Recovery::Fail(phase, e) => {
+                                return Self::report_error(
+                                    TypedBlock {
                                         sio,
                                         mio,
                                         meta,
                                         kernel,
-                                    }),
-                                })),
-                            })
-                            .await?;
-                        return Err(e);
-                    } else {
-                        main_inbox.send(FlowgraphMessage::Initialized).await?;
+                                    },
+                                    block_id,
+                                    &mut main_inbox,
+                                    phase,
+                                    e,
+                                )
+                                .await;
+                            }
+                        }
                     }
+                    main_inbox.send(FlowgraphMessage::Initialized).await?;
                     break;
                 }
-                BlockMessage::StreamOutputInit { src_port, writer } => {
+                BlockMessage::StreamOutputInit {
+                    src_port,
+                    writer,
+                    latency,
+                } => {
                     sio.output(src_port).init(writer);
+                    sio.output(src_port).set_latency_probes(latency);
                 }
-                BlockMessage::StreamInputInit { dst_port, reader } => {
+                BlockMessage::StreamInputInit {
+                    dst_port,
+                    reader,
+                    latency,
+                } => {
                     sio.input(dst_port).set_reader(reader);
+                    sio.input(dst_port).set_latency_probe(latency);
                 }
                 BlockMessage::MessageOutputConnect {
                     src_port,
                     dst_port,
                     dst_inbox,
+                    queue,
                 } => {
-                    mio.output_mut(src_port).connect(dst_port, dst_inbox);
+                    Self::connect_message_output(&mut mio, src_port, dst_port, dst_inbox, queue);
+                }
+                BlockMessage::MessageOutputDisconnect {
+                    src_port,
+                    dst_port,
+                    dst_inbox,
+                } => {
+                    mio.output_mut(src_port).disconnect(dst_port, &dst_inbox);
+                }
+                BlockMessage::BlockDescription { tx } => {
+                    tx.send(Self::description(block_id, &sio, &mio, &meta))
+                        .unwrap();
+                }
+                BlockMessage::BlockStats { tx } => {
+                    let _ = tx.send(Self::stats(block_id, &sio, &meta, &stats));
+                }
+                // a neighbor that failed during init
+                BlockMessage::StreamInputDone { input_id } => {
+                    sio.input(input_id).finish();
+                }
+                BlockMessage::StreamOutputDone { .. } => {
+                    work_io.finished = true;
+                }
+                BlockMessage::Dequeue { queue, .. } => {
+                    while let Some(data) = queue.pop() {
+                        warn!(
+                            "{} unhandled message during init {:?}",
+                            meta.instance_name().unwrap(),
+                            data
+                        );
+                    }
                 }
                 t => warn!(
                     "{} unhandled message during init {:?}",
@@ -253,33 +527,74 @@ impl<T: Kernel + Send + 'static> TypedBlockWrapper<T> {
 
         let inbox = inbox.peekable();
         futures::pin_mut!(inbox);
+        // message queues that are drained, once the inbox is empty
+        let mut dequeue: VecDeque<(PortId, Arc<MessageQueue>)> = VecDeque::new();
 
         // main loop
         loop {
             // ================== non blocking
             loop {
-                match inbox.next().now_or_never() {
+                let msg = match inbox.next().now_or_never() {
+                    Some(Some(BlockMessage::Dequeue { port_id, queue })) => {
+                        dequeue.push_back((port_id, queue));
+                        continue;
+                    }
+                    None => match dequeue.front() {
+                        Some((port_id, queue)) => match queue.pop() {
+                            Some(data) => Some(Some(BlockMessage::Call {
+                                port_id: port_id.clone(),
+                                data,
+                            })),
+                            None => {
+                                dequeue.pop_front();
+                                continue;
+                            }
+                        },
+                        None => None,
+                    },
+                    m => m,
+                };
+                match msg {
                     Some(Some(BlockMessage::Notify)) => {}
                     Some(Some(BlockMessage::BlockDescription { tx })) => {
-                        let stream_inputs: Vec<String> =
-                            sio.inputs().iter().map(|x| x.name().to_string()).collect();
-                        let stream_outputs: Vec<String> =
-                            sio.outputs().iter().map(|x| x.name().to_string()).collect();
-                        let message_inputs: Vec<String> = mio.input_names();
-                        let message_outputs: Vec<String> =
-                            mio.outputs().iter().map(|x| x.name().to_string()).collect();
-
-                        let description = BlockDescription {
-                            id: block_id,
-                            type_name: meta.type_name().to_string(),
-                            instance_name: meta.instance_name().unwrap().to_string(),
-                            stream_inputs,
-                            stream_outputs,
-                            message_inputs,
-                            message_outputs,
-                            blocking: meta.is_blocking(),
-                        };
-                        tx.send(description).unwrap();
+                        tx.send(Self::description(block_id, &sio, &mio, &meta))
+                            .unwrap();
+                    }
+                    Some(Some(BlockMessage::BlockStats { tx })) => {
+                        let _ = tx.send(Self::stats(block_id, &sio, &meta, &stats));
+                    }
+                    Some(Some(BlockMessage::StreamOutputInit {
+                        src_port,
+                        writer,
+                        latency,
+                    })) => {
+                        sio.output(src_port).init(writer);
+                        sio.output(src_port).set_latency_probes(latency);
+                    }
+                    Some(Some(BlockMessage::StreamInputInit {
+                        dst_port,
+                        reader,
+                        latency,
+                    })) => {
+                        sio.input(dst_port).set_reader(reader);
+                        sio.input(dst_port).set_latency_probe(latency);
+                    }
+                    Some(Some(BlockMessage::MessageOutputConnect {
+                        src_port,
+                        dst_port,
+                        dst_inbox,
+                        queue,
+                    })) => {
+                        Self::connect_message_output(
+                            &mut mio, src_port, dst_port, dst_inbox, queue,
+                        );
+                    }
+                    Some(Some(BlockMessage::MessageOutputDisconnect {
+                        src_port,
+                        dst_port,
+                        dst_inbox,
+                    })) => {
+                        mio.output_mut(src_port).disconnect(dst_port, &dst_inbox);
                     }
                     Some(Some(BlockMessage::StreamInputDone { input_id })) => {
                         sio.input(input_id).finish();
@@ -288,6 +603,7 @@ impl<T: Kernel + Send + 'static> TypedBlockWrapper<T> {
                         work_io.finished = true;
                     }
                     Some(Some(BlockMessage::Call { port_id, data })) => {
+                        stats.messages_handled += 1;
                         match Self::call_handler(
                             &mut work_io,
                             &mut mio,
@@ -298,36 +614,53 @@ impl<T: Kernel + Send + 'static> TypedBlockWrapper<T> {
                         )
                         .await
                         {
-                            Err(Error::InvalidHandler(port_id)) => {
+                            Ok(Err(Error::InvalidHandler(port_id))) => {
                                 error!(
                                     "{}: BlockMessage::Call -> Invalid Handler {port_id:?}.",
                                     meta.instance_name().unwrap(),
                                 );
                             }
-                            Err(Error::HandlerError) => {
+                            Err(e) => {
                                 error!(
-                                    "{}: BlockMessage::Call -> HandlerError. Terminating.",
+                                    "{}: BlockMessage::Call -> HandlerError. ({:?})",
                                     meta.instance_name().unwrap(),
+                                    e
                                 );
-                                main_inbox
-                                    .send(FlowgraphMessage::BlockError {
-                                        block_id,
-                                        block: Block(Box::new(TypedBlockWrapper {
-                                            inner: Some(TypedBlock {
+                                match Self::recover(
+                                    &mut sio,
+                                    &mut mio,
+                                    &mut meta,
+                                    &mut kernel,
+                                    &mut stats,
+                                    BlockPhase::Handler,
+                                    e,
+                                )
+                                .await
+                                {
+                                    Recovery::Restarted => {}
+                                    Recovery::Finish => work_io.finished = true,
+                                    Recovery::Fail(phase, e) => {
+                                        return Self::report_error(
+                                            TypedBlock {
                                                 sio,
                                                 mio,
                                                 meta,
                                                 kernel,
-                                            }),
-                                        })),
-                                    })
-                                    .await?;
-                                return Err(Error::HandlerError.into());
+                                            },
+                                            block_id,
+                                            &mut main_inbox,
+                                            phase,
+                                            e,
+                                        )
+                                        .await;
+                                    }
+                                }
                             }
                             _ => {}
                         }
                     }
                     Some(Some(BlockMessage::Callback { port_id, data, tx })) => {
+                        stats.messages_handled += 1;
                         match Self::call_handler(
                             &mut work_io,
                             &mut mio,
@@ -338,28 +671,44 @@ impl<T: Kernel + Send + 'static> TypedBlockWrapper<T> {
                         )
                         .await
                         {
-                            Err(Error::HandlerError) => {
+                            Err(e) => {
                                 error!(
-                                    "{}: Error in callback. Terminating.",
+                                    "{}: Error in callback. ({:?})",
                                     meta.instance_name().unwrap(),
+                                    e
                                 );
                                 let _ = tx.send(Err(Error::InvalidHandler(port_id)));
-                                main_inbox
-                                    .send(FlowgraphMessage::BlockError {
-                                        block_id,
-                                        block: Block(Box::new(TypedBlockWrapper {
-                                            inner: Some(TypedBlock {
+                                match Self::recover(
+                                    &mut sio,
+                                    &mut mio,
+                                    &mut meta,
+                                    &mut kernel,
+                                    &mut stats,
+                                    BlockPhase::Handler,
+                                    e,
+                                )
+                                .await
+                                {
+                                    Recovery::Restarted => {}
+                                    Recovery::Finish => work_io.finished = true,
+                                    Recovery::Fail(phase, e) => {
+                                        return Self::report_error(
+                                            TypedBlock {
                                                 sio,
                                                 mio,
                                                 meta,
                                                 kernel,
-                                            }),
-                                        })),
-                                    })
-                                    .await?;
-                                return Err(Error::HandlerError.into());
+                                            },
+                                            block_id,
+                                            &mut main_inbox,
+                                            phase,
+                                            e,
+                                        )
+                                        .await;
+                                    }
+                                }
                             }
-                            res => {
+                            Ok(res) => {
                                 let _ = tx.send(res);
                             }
                         }
@@ -402,20 +751,19 @@ impl<T: Kernel + Send + 'static> TypedBlockWrapper<T> {
                             meta.instance_name().unwrap(),
                             e
                         );
-                        main_inbox
-                            .send(FlowgraphMessage::BlockError {
-                                block_id,
-                                block: Block(Box::new(TypedBlockWrapper {
-                                    inner: Some(TypedBlock {
-                                        sio,
-                                        mio,
-                                        meta,
-                                        kernel,
-                                    }),
-                                })),
-                            })
-                            .await?;
-                        return Err(e);
+                        return Self::report_error(
+                            TypedBlock {
+                                sio,
+                                mio,
+                                meta,
+                                kernel,
+                            },
+                            block_id,
+                            &mut main_inbox,
+                            BlockPhase::Deinit,
+                            e,
+                        )
+                        .await;
                     }
                 };
             }
@@ -442,29 +790,61 @@ impl<T: Kernel + Send + 'static> TypedBlockWrapper<T> {
 
             // ================== work
             work_io.call_again = false;
-            if let Err(e) = kernel
+            #[cfg(not(target_arch = "wasm32"))]
+            let start = std::time::Instant::now();
+            let ret = kernel
                 .work(&mut work_io, &mut sio, &mut mio, &mut meta)
-                .await
+                .await;
+            stats.work_calls += 1;
+            #[cfg(not(target_arch = "wasm32"))]
             {
+                stats.work_time += start.elapsed();
+            }
+            if let Err(e) = ret {
                 error!(
-                    "{}: Error in work(). Terminating. ({:?})",
+                    "{}: Error in work(). ({:?})",
                     meta.instance_name().unwrap(),
                     e
                 );
-                main_inbox
-                    .send(FlowgraphMessage::BlockError {
-                        block_id,
-                        block: Block(Box::new(TypedBlockWrapper {
-                            inner: Some(TypedBlock {
+                match Self::recover(
+                    &mut sio,
+                    &mut mio,
+                    &mut meta,
+                    &mut kernel,
+                    &mut stats,
+                    BlockPhase::Work,
+                    e,
+                )
+                .await
+                {
+                    Recovery::Restarted => {
+                        // keep what work() consumed and produced before it failed
+                        sio.commit();
+                        work_io.block_on = None;
+                        work_io.call_again = true;
+                        continue;
+                    }
+                    Recovery::Finish => {
+                        sio.commit();
+                        work_io.finished = true;
+                        continue;
+                    }
+                    Recovery::Fail(phase, e) => {
+                        return Self::report_error(
+                            TypedBlock {
                                 sio,
                                 mio,
                                 meta,
                                 kernel,
-                            }),
-                        })),
-                    })
-                    .await?;
-                return Err(e);
+                            },
+                            block_id,
+                            &mut main_inbox,
+                            phase,
+                            e,
+                        )
+                        .await;
+                    }
+                }
             }
             sio.commit();
 
@@ -500,6 +880,28 @@ impl<T: Kernel + Send + 'static> BlockT for TypedBlockWrapper<T> {
     fn is_blocking(&self) -> bool {
         self.inner.as_ref().map(|i| i.meta.is_blocking()).unwrap()
     }
+    fn restart_policy(&self) -> RestartPolicy {
+        self.inner
+            .as_ref()
+            .map(|i| i.meta.restart_policy())
+            .unwrap()
+    }
+    fn set_restart_policy(&mut self, policy: RestartPolicy) {
+        if let Some(i) = self.inner.as_mut() {
+            i.meta.set_restart_policy(policy)
+        }
+    }
+    fn scheduling_hints(&self) -> SchedulingHints {
+        self.inner
+            .as_ref()
+            .map(|i| i.meta.scheduling_hints().clone())
+            .unwrap()
+    }
+    fn set_scheduling_hints(&mut self, hints: SchedulingHints) {
+        if let Some(i) = self.inner.as_mut() {
+            i.meta.set_scheduling_hints(hints)
+        }
+    }
 
     // ##### KERNEL
     async fn run(
@@ -521,10 +923,7 @@ impl<T: Kernel + Send + 'static> BlockT for TypedBlockWrapper<T> {
     }
 
     // ##### STREAM IO
-    fn set_tag_propagation(
-        &mut self,
-        f: Box<dyn FnMut(&mut [StreamInput], &mut [StreamOutput]) + Send + 'static>,
-    ) {
+    fn set_tag_propagation(&mut self, f: TagPropagationFn) {
         if let Some(i) = self.inner.as_mut() {
             i.sio.set_tag_propagation(f)
         }
@@ -561,6 +960,9 @@ impl<T: Kernel + Send + 'static> BlockT for TypedBlockWrapper<T> {
             .map(|i| i.mio.input_name_to_id(name))
             .unwrap()
     }
+    fn message_input_names(&self) -> Vec<String> {
+        self.inner.as_ref().map(|i| i.mio.input_names()).unwrap()
+    }
     fn message_outputs(&self) -> &Vec<MessageOutput> {
         self.inner.as_ref().map(|i| i.mio.outputs()).unwrap()
     }
@@ -633,6 +1035,22 @@ impl Block {
     pub fn is_blocking(&self) -> bool {
         self.0.is_blocking()
     }
+    /// Get restart policy (see [`BlockMeta::restart_policy`])
+    pub fn restart_policy(&self) -> RestartPolicy {
+        self.0.restart_policy()
+    }
+    /// Set restart policy (see [`BlockMeta::set_restart_policy`])
+    pub fn set_restart_policy(&mut self, policy: RestartPolicy) {
+        self.0.set_restart_policy(policy)
+    }
+    /// Get scheduling hints (see [`BlockMeta::scheduling_hints`])
+    pub fn scheduling_hints(&self) -> SchedulingHints {
+        self.0.scheduling_hints()
+    }
+    /// Set scheduling hints (see [`BlockMeta::set_scheduling_hints`])
+    pub fn set_scheduling_hints(&mut self, hints: SchedulingHints) {
+        self.0.set_scheduling_hints(hints)
+    }
 
     pub(crate) async fn run(
         mut self,
@@ -645,11 +1063,10 @@ impl Block {
 
     // ##### STREAM IO
     /// Set tag propagation function
-    #[allow(clippy::type_complexity)]
-    pub fn set_tag_propagation(
-        &mut self,
-        f: Box<dyn FnMut(&mut [StreamInput], &mut [StreamOutput]) + Send + 'static>,
-    ) {
+    ///
+    /// Use [`TagPropagation::into_fn`](crate::runtime::TagPropagation::into_fn) for built-in
+    /// policies.
+    pub fn set_tag_propagation(&mut self, f: TagPropagationFn) {
         self.0.set_tag_propagation(f);
     }
     /// Get stream input ports
@@ -682,6 +1099,10 @@ impl Block {
     pub fn message_input_name_to_id(&self, name: &str) -> Option<usize> {
         self.0.message_input_name_to_id(name)
     }
+    /// Get names of message input ports
+    pub fn message_input_names(&self) -> Vec<String> {
+        self.0.message_input_names()
+    }
     /// Get message output ports
     pub fn message_outputs(&self) -> &Vec<MessageOutput> {
         self.0.message_outputs()
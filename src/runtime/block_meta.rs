@@ -1,16 +1,99 @@
+use std::time::Duration;
+
+/// What to do, if a block fails
+///
+/// The policy applies to errors in `init()`, `work()`, and message handlers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RestartPolicy {
+    /// Report the error to the flowgraph, which is terminated by default
+    Terminate,
+    /// Log the error and shut the block down, as if it finished
+    ///
+    /// The error is not reported to the flowgraph.
+    Finish,
+    /// Restart the block up to `max_restarts` times
+    ///
+    /// The block is restarted by calling `deinit()` and `init()`, keeping its stream and message
+    /// connections. Before each restart, the block waits for `backoff`, which is doubled with
+    /// every attempt. Once the limit is reached, the error is reported to the flowgraph.
+    ///
+    /// Items that `work()` consumed or produced before it failed are committed before the
+    /// restart.
+    Restart {
+        /// Maximum number of restarts
+        max_restarts: usize,
+        /// Delay before the first restart
+        backoff: Duration,
+    },
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        RestartPolicy::Terminate
+    }
+}
+
+/// Priority class of the thread that runs a block
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Priority {
+    /// Priority of the executor threads
+    Normal,
+    /// Raised priority within the normal scheduling class (e.g., a negative nice value)
+    High,
+    /// Realtime scheduling class (e.g., `SCHED_FIFO`)
+    ///
+    /// Usually requires elevated privileges.
+    Realtime,
+}
+
+impl Default for Priority {
+    fn default() -> Self {
+        Priority::Normal
+    }
+}
+
+/// Hints for the scheduler, where and how to run a block
+///
+/// Schedulers try to honor the hints and log a warning, if a hint cannot be satisfied.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct SchedulingHints {
+    /// CPU cores, the block may run on
+    pub cores: Option<Vec<usize>>,
+    /// Priority class
+    pub priority: Priority,
+    /// Run the block on its own thread
+    pub dedicated_thread: bool,
+}
+
+impl SchedulingHints {
+    /// Check, if any hint is set
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
 /// Block metadata
 pub struct BlockMeta {
     type_name: String,
     instance_name: Option<String>,
     blocking: bool,
+    restart_policy: RestartPolicy,
+    scheduling_hints: SchedulingHints,
 }
 
 impl BlockMeta {
-    fn new(type_name: String, blocking: bool) -> BlockMeta {
+    fn new(
+        type_name: String,
+        blocking: bool,
+        restart_policy: RestartPolicy,
+        scheduling_hints: SchedulingHints,
+    ) -> BlockMeta {
         BlockMeta {
             type_name,
             instance_name: None,
             blocking,
+            restart_policy,
+            scheduling_hints,
         }
     }
     /// Name of block type
@@ -31,12 +114,32 @@ impl BlockMeta {
     pub fn is_blocking(&self) -> bool {
         self.blocking
     }
+    /// Restart policy
+    pub fn restart_policy(&self) -> RestartPolicy {
+        self.restart_policy
+    }
+    /// Set restart policy
+    pub fn set_restart_policy(&mut self, policy: RestartPolicy) {
+        self.restart_policy = policy;
+    }
+    /// Scheduling hints
+    pub fn scheduling_hints(&self) -> &SchedulingHints {
+        &self.scheduling_hints
+    }
+    /// Set scheduling hints
+    ///
+    /// Hints are only considered, when the block is spawned.
+    pub fn set_scheduling_hints(&mut self, hints: SchedulingHints) {
+        self.scheduling_hints = hints;
+    }
 }
 
 /// Block metadata buidler
 pub struct BlockMetaBuilder {
     name: String,
     blocking: bool,
+    restart_policy: RestartPolicy,
+    scheduling_hints: SchedulingHints,
 }
 
 impl BlockMetaBuilder {
@@ -45,6 +148,8 @@ impl BlockMetaBuilder {
         BlockMetaBuilder {
             name: name.into(),
             blocking: false,
+            restart_policy: RestartPolicy::default(),
+            scheduling_hints: SchedulingHints::default(),
         }
     }
     /// Mark block as blocking
@@ -55,8 +160,37 @@ impl BlockMetaBuilder {
         self.blocking = true;
         self
     }
+    /// Set the [`RestartPolicy`] of the block
+    #[must_use]
+    pub fn restart_policy(mut self, policy: RestartPolicy) -> Self {
+        self.restart_policy = policy;
+        self
+    }
+    /// Restrict the block to the given CPU cores
+    #[must_use]
+    pub fn cores(mut self, cores: impl IntoIterator<Item = usize>) -> Self {
+        self.scheduling_hints.cores = Some(cores.into_iter().collect());
+        self
+    }
+    /// Set the [`Priority`] of the thread that runs the block
+    #[must_use]
+    pub fn priority(mut self, priority: Priority) -> Self {
+        self.scheduling_hints.priority = priority;
+        self
+    }
+    /// Run the block on its own thread
+    #[must_use]
+    pub fn dedicated_thread(mut self) -> Self {
+        self.scheduling_hints.dedicated_thread = true;
+        self
+    }
     /// Build block metadata
     pub fn build(self) -> BlockMeta {
-        BlockMeta::new(self.name, self.blocking)
+        BlockMeta::new(
+            self.name,
+            self.blocking,
+            self.restart_policy,
+            self.scheduling_hints,
+        )
     }
 }
@@ -17,6 +17,27 @@ pub trait BufferBuilder: Send + Sync + Any {
         writer_inbox: Sender<BlockMessage>,
         writer_output_id: usize,
     ) -> BufferWriter;
+
+    /// Maximum number of items in flight, i.e., produced but not consumed by all readers yet
+    ///
+    /// `None`, if the buffer is not limited.
+    fn latency_cap(&self) -> Option<usize> {
+        None
+    }
+
+    /// Build the buffer, limiting the number of items in flight
+    ///
+    /// Used to apply a [latency budget](crate::runtime::Flowgraph::set_latency_budget). Buffers
+    /// that do not support a latency cap ignore `max_items`.
+    fn build_with_latency_cap(
+        &self,
+        item_size: usize,
+        writer_inbox: Sender<BlockMessage>,
+        writer_output_id: usize,
+        _max_items: usize,
+    ) -> BufferWriter {
+        self.build(item_size, writer_inbox, writer_output_id)
+    }
 }
 
 /// CPU buffer writer
@@ -58,6 +58,7 @@ impl generic::Metadata for MyMetadata {
 #[derive(Clone, Debug, PartialEq, Hash)]
 pub struct Circular {
     min_bytes: usize,
+    max_items: Option<usize>,
 }
 
 impl Eq for Circular {}
@@ -67,11 +68,24 @@ impl Circular {
     pub fn new() -> Circular {
         Circular {
             min_bytes: config::config().buffer_size,
+            max_items: None,
         }
     }
     /// Create Circular builder with minimum size
     pub fn with_size(min_bytes: usize) -> Circular {
-        Circular { min_bytes }
+        Circular {
+            min_bytes,
+            max_items: None,
+        }
+    }
+    /// Limit the number of items in flight, i.e., produced but not consumed by all readers yet
+    ///
+    /// The writer sees less space, which bounds the latency of the connection.
+    /// A writer that needs more than `max_items` of space to produce anything stalls.
+    #[must_use]
+    pub fn max_items(mut self, max_items: usize) -> Circular {
+        self.max_items = Some(max_items);
+        self
     }
 }
 
@@ -88,12 +102,26 @@ impl BufferBuilder for Circular {
         writer_inbox: Sender<BlockMessage>,
         writer_output_id: usize,
     ) -> BufferWriter {
-        BufferWriter::Host(Box::new(Writer::new(
-            item_size,
-            self.min_bytes,
-            writer_inbox,
-            writer_output_id,
-        )))
+        let mut writer = Writer::new(item_size, self.min_bytes, writer_inbox, writer_output_id);
+        writer.max_items = self.max_items;
+        BufferWriter::Host(Box::new(writer))
+    }
+
+    fn latency_cap(&self) -> Option<usize> {
+        self.max_items
+    }
+
+    fn build_with_latency_cap(
+        &self,
+        item_size: usize,
+        writer_inbox: Sender<BlockMessage>,
+        writer_output_id: usize,
+        max_items: usize,
+    ) -> BufferWriter {
+        let max_items = self.max_items.map_or(max_items, |m| m.min(max_items));
+        self.clone()
+            .max_items(max_items)
+            .build(item_size, writer_inbox, writer_output_id)
     }
 }
 
@@ -102,6 +130,8 @@ pub struct Writer {
     writer: generic::Writer<u8, MyNotifier, MyMetadata>,
     readers: Vec<(Sender<BlockMessage>, usize)>,
     item_size: usize,
+    capacity: usize,
+    max_items: Option<usize>,
     inbox: Sender<BlockMessage>,
     output_id: usize,
     finished: bool,
@@ -126,6 +156,8 @@ impl Writer {
             writer: generic::Circular::with_capacity(buffer_size).unwrap(),
             readers: Vec::new(),
             item_size,
+            capacity: buffer_size / item_size,
+            max_items: None,
             inbox,
             output_id,
             finished: false,
@@ -137,6 +169,7 @@ impl fmt::Debug for Writer {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("circular::Writer")
             .field("item_size", &self.item_size)
+            .field("max_items", &self.max_items)
             .field("output_id", &self.output_id)
             .field("finished", &self.finished)
             .finish()
@@ -180,7 +213,12 @@ impl BufferWriterHost for Writer {
 
     fn bytes(&mut self) -> (*mut u8, usize) {
         let s = self.writer.slice(false);
-        (s.as_mut_ptr(), s.len())
+        let mut len = s.len();
+        if let Some(max_items) = self.max_items {
+            let in_flight = self.capacity.saturating_sub(len / self.item_size);
+            len = len.min(max_items.saturating_sub(in_flight) * self.item_size);
+        }
+        (s.as_mut_ptr(), len)
     }
 
     async fn notify_finished(&mut self) {
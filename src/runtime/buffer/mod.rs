@@ -13,6 +13,11 @@ pub use buffer::BufferWriterHost;
 #[cfg(not(target_arch = "wasm32"))]
 pub mod circular;
 
+// ================== SHARED MEMORY ==================
+/// POSIX shared-memory buffer
+#[cfg(all(unix, not(target_arch = "wasm32")))]
+pub mod shm;
+
 // ===================== SLAB ========================
 /// Slab buffer
 pub mod slab;
@@ -0,0 +1,929 @@
+//! POSIX shared-memory ring buffer
+//!
+//! A [`Segment`] is a named POSIX shared-memory object, which holds a header, a ring of stream
+//! tags, and a data ring that is mapped twice back-to-back (like the [`Circular`] buffer), so
+//! that readers and the writer always see contiguous slices. All state is kept in the segment
+//! itself, which allows a writer and its readers to live in different processes on the same
+//! host. Processes notify each other through futex words in the header (polling on platforms
+//! other than Linux).
+//!
+//! [`Shm`] uses a segment to back a stream connection within a flowgraph. The
+//! [`ShmSink`](crate::blocks::ShmSink) and [`ShmSource`](crate::blocks::ShmSource) blocks use a
+//! named segment to stream items and tags between flowgraphs in different processes.
+//!
+//! [`Circular`]: crate::runtime::buffer::circular::Circular
+use futures::channel::mpsc::Sender;
+use futures::prelude::*;
+use serde::Deserialize;
+use serde::Serialize;
+use std::any::Any;
+use std::ffi::CString;
+use std::fmt;
+use std::sync::atomic::AtomicI32;
+use std::sync::atomic::AtomicU32;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::anyhow::{bail, Result};
+use crate::runtime::buffer::BufferBuilder;
+use crate::runtime::buffer::BufferReader;
+use crate::runtime::buffer::BufferReaderHost;
+use crate::runtime::buffer::BufferWriter;
+use crate::runtime::buffer::BufferWriterHost;
+use crate::runtime::config;
+use crate::runtime::BlockMessage;
+use crate::runtime::ItemTag;
+use crate::runtime::Pmt;
+use crate::runtime::Tag;
+
+/// Maximum number of readers of a [`Segment`]
+pub const MAX_READERS: usize = 16;
+
+// "FSDR-SHM"
+const MAGIC: u64 = 0x4653_4452_2d53_484d;
+const TAG_SLOTS: usize = 1024;
+const TAG_SLOT_SIZE: usize = 256;
+// absolute item index (u64) and length of the encoded tag (u32)
+const TAG_SLOT_HEADER: usize = 12;
+// upper bound for a single wait, so that waiting threads do not outlive the flowgraph for long
+const WAIT_TIMEOUT: Duration = Duration::from_millis(100);
+
+const SLOT_FREE: u32 = 0;
+const SLOT_CLAIMED: u32 = 1;
+const SLOT_ACTIVE: u32 = 2;
+
+// everything is measured in bytes, except for the indices of tags, which are absolute items
+
+#[repr(C)]
+struct ReaderSlot {
+    state: AtomicU32,
+    // process of the reader, to reclaim the slot if it dies without detaching
+    pid: AtomicI32,
+    read: AtomicU64,
+    tag_read: AtomicU64,
+}
+
+#[repr(C)]
+struct Header {
+    magic: AtomicU64,
+    item_size: u64,
+    capacity: u64,
+    write: AtomicU64,
+    tag_write: AtomicU64,
+    writer_done: AtomicU32,
+    // futex words, incremented on every change
+    writer_seq: AtomicU32,
+    reader_seq: AtomicU32,
+    readers: [ReaderSlot; MAX_READERS],
+}
+
+// stream tags are serialized into the tag ring
+#[derive(Serialize, Deserialize)]
+enum ShmTag {
+    Id(u64),
+    String(String),
+    Data(Pmt),
+    NamedUsize(String, usize),
+    NamedF32(String, f32),
+    NamedF64(String, f64),
+    NamedI64(String, i64),
+}
+
+impl ShmTag {
+    fn from_tag(tag: &Tag) -> Option<ShmTag> {
+        match tag {
+            Tag::Id(v) => Some(ShmTag::Id(*v)),
+            Tag::String(s) => Some(ShmTag::String(s.clone())),
+            Tag::Data(p) => Some(ShmTag::Data(p.clone())),
+            Tag::NamedUsize(n, v) => Some(ShmTag::NamedUsize(n.clone(), *v)),
+            Tag::NamedF32(n, v) => Some(ShmTag::NamedF32(n.clone(), *v)),
+            Tag::NamedF64(n, v) => Some(ShmTag::NamedF64(n.clone(), *v)),
+            Tag::NamedI64(n, v) => Some(ShmTag::NamedI64(n.clone(), *v)),
+            _ => None,
+        }
+    }
+
+    fn into_tag(self) -> Tag {
+        match self {
+            ShmTag::Id(v) => Tag::Id(v),
+            ShmTag::String(s) => Tag::String(s),
+            ShmTag::Data(p) => Tag::Data(p),
+            ShmTag::NamedUsize(n, v) => Tag::NamedUsize(n, v),
+            ShmTag::NamedF32(n, v) => Tag::NamedF32(n, v),
+            ShmTag::NamedF64(n, v) => Tag::NamedF64(n, v),
+            ShmTag::NamedI64(n, v) => Tag::NamedI64(n, v),
+        }
+    }
+}
+
+fn pagesize() -> usize {
+    unsafe { libc::sysconf(libc::_SC_PAGESIZE) as usize }
+}
+
+fn round_up(n: usize, to: usize) -> usize {
+    (n + to - 1) / to * to
+}
+
+// header and tag ring
+fn meta_len() -> usize {
+    let ps = pagesize();
+    round_up(std::mem::size_of::<Header>(), ps) + round_up(TAG_SLOTS * TAG_SLOT_SIZE, ps)
+}
+
+fn wake(word: &AtomicU32) {
+    word.fetch_add(1, Ordering::AcqRel);
+    #[cfg(target_os = "linux")]
+    unsafe {
+        libc::syscall(
+            libc::SYS_futex,
+            word as *const AtomicU32,
+            libc::FUTEX_WAKE,
+            i32::MAX,
+            std::ptr::null::<libc::timespec>(),
+            std::ptr::null::<u32>(),
+            0,
+        );
+    }
+}
+
+fn wait(word: &AtomicU32, seen: u32, timeout: Duration) {
+    if word.load(Ordering::Acquire) != seen {
+        return;
+    }
+    #[cfg(target_os = "linux")]
+    unsafe {
+        let ts = libc::timespec {
+            tv_sec: timeout.as_secs() as libc::time_t,
+            tv_nsec: timeout.subsec_nanos() as _,
+        };
+        libc::syscall(
+            libc::SYS_futex,
+            word as *const AtomicU32,
+            libc::FUTEX_WAIT,
+            seen,
+            &ts as *const libc::timespec,
+            std::ptr::null::<u32>(),
+            0,
+        );
+    }
+    #[cfg(not(target_os = "linux"))]
+    std::thread::sleep(timeout.min(Duration::from_millis(1)));
+}
+
+/// Shared-memory segment with a ring buffer for one writer and up to [`MAX_READERS`] readers
+pub struct Segment {
+    name: String,
+    base: *mut u8,
+    meta_len: usize,
+    capacity: usize,
+    item_size: usize,
+    owner: bool,
+}
+
+// The segment is only accessed through atomics and the ring protocol.
+unsafe impl Send for Segment {}
+unsafe impl Sync for Segment {}
+
+impl Segment {
+    /// Create a named segment for items of the given size
+    ///
+    /// An existing segment with the same name is replaced. The segment is unlinked, when it is
+    /// dropped.
+    pub fn create(name: &str, item_size: usize, min_bytes: usize) -> Result<Arc<Segment>> {
+        let mut segment = Self::create_segment(name, item_size, min_bytes)?;
+        segment.owner = true;
+        Ok(Arc::new(segment))
+    }
+
+    /// Create a segment that is not visible to other processes
+    pub fn anonymous(item_size: usize, min_bytes: usize) -> Result<Arc<Segment>> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let name = format!(
+            "/futuresdr-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        );
+        let segment = Self::create_segment(&name, item_size, min_bytes)?;
+        let cname = CString::new(segment.name.clone())?;
+        unsafe {
+            libc::shm_unlink(cname.as_ptr());
+        }
+        Ok(Arc::new(segment))
+    }
+
+    fn create_segment(name: &str, item_size: usize, min_bytes: usize) -> Result<Segment> {
+        if item_size == 0 {
+            bail!("shared memory buffers do not support zero-sized items");
+        }
+        let name = Self::normalize(name);
+        let cname = CString::new(name.clone())?;
+
+        let ps = pagesize();
+        let mut capacity = round_up(std::cmp::max(min_bytes, 1), ps);
+        while capacity % item_size != 0 {
+            capacity += ps;
+        }
+        let meta_len = meta_len();
+
+        let base = unsafe {
+            libc::shm_unlink(cname.as_ptr());
+            let fd = libc::shm_open(
+                cname.as_ptr(),
+                libc::O_CREAT | libc::O_EXCL | libc::O_RDWR,
+                0o600,
+            );
+            if fd < 0 {
+                bail!(
+                    "failed to create shared memory segment {}: {}",
+                    name,
+                    std::io::Error::last_os_error()
+                );
+            }
+            if libc::ftruncate(fd, (meta_len + capacity) as libc::off_t) != 0 {
+                let e = std::io::Error::last_os_error();
+                libc::close(fd);
+                libc::shm_unlink(cname.as_ptr());
+                bail!("failed to size shared memory segment {}: {}", name, e);
+            }
+            let base = Self::map(fd, meta_len, capacity);
+            libc::close(fd);
+            match base {
+                Ok(b) => b,
+                Err(e) => {
+                    libc::shm_unlink(cname.as_ptr());
+                    return Err(e.context(format!("failed to map shared memory segment {name}")));
+                }
+            }
+        };
+
+        // the object is zero-initialized, only set what other processes check
+        unsafe {
+            let header = base as *mut Header;
+            (*header).item_size = item_size as u64;
+            (*header).capacity = capacity as u64;
+            (*header).magic.store(MAGIC, Ordering::Release);
+        }
+
+        Ok(Segment {
+            name,
+            base,
+            meta_len,
+            capacity,
+            item_size,
+            owner: false,
+        })
+    }
+
+    /// Open a named segment, created by another flowgraph or process
+    ///
+    /// Returns `None`, if the segment does not exist or is not initialized yet.
+    pub fn open(name: &str, item_size: usize) -> Result<Option<Arc<Segment>>> {
+        let name = Self::normalize(name);
+        let cname = CString::new(name.clone())?;
+        let meta_len = meta_len();
+
+        let base = unsafe {
+            let fd = libc::shm_open(cname.as_ptr(), libc::O_RDWR, 0);
+            if fd < 0 {
+                let e = std::io::Error::last_os_error();
+                if e.kind() == std::io::ErrorKind::NotFound {
+                    return Ok(None);
+                }
+                bail!("failed to open shared memory segment {}: {}", name, e);
+            }
+            let mut stat: libc::stat = std::mem::zeroed();
+            if libc::fstat(fd, &mut stat) != 0 {
+                let e = std::io::Error::last_os_error();
+                libc::close(fd);
+                bail!("failed to open shared memory segment {}: {}", name, e);
+            }
+            if (stat.st_size as usize) <= meta_len {
+                libc::close(fd);
+                return Ok(None);
+            }
+            let capacity = stat.st_size as usize - meta_len;
+            let base = Self::map(fd, meta_len, capacity);
+            libc::close(fd);
+            (base?, capacity)
+        };
+
+        let segment = Segment {
+            name,
+            base: base.0,
+            meta_len,
+            capacity: base.1,
+            item_size,
+            owner: false,
+        };
+
+        let header = segment.header();
+        if header.magic.load(Ordering::Acquire) != MAGIC {
+            return Ok(None);
+        }
+        if header.item_size as usize != item_size || header.capacity as usize != segment.capacity {
+            bail!(
+                "shared memory segment {} holds items of {} bytes, expected {}",
+                segment.name,
+                header.item_size,
+                item_size
+            );
+        }
+
+        Ok(Some(Arc::new(segment)))
+    }
+
+    fn normalize(name: &str) -> String {
+        if name.starts_with('/') {
+            name.to_string()
+        } else {
+            format!("/{name}")
+        }
+    }
+
+    // map the header and the data ring, followed by a second mapping of the data ring
+    unsafe fn map(fd: i32, meta_len: usize, capacity: usize) -> Result<*mut u8> {
+        let total = meta_len + 2 * capacity;
+        let base = libc::mmap(
+            std::ptr::null_mut(),
+            total,
+            libc::PROT_NONE,
+            libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+            -1,
+            0,
+        );
+        if base == libc::MAP_FAILED {
+            bail!("{}", std::io::Error::last_os_error());
+        }
+        let first = libc::mmap(
+            base,
+            meta_len + capacity,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_SHARED | libc::MAP_FIXED,
+            fd,
+            0,
+        );
+        let second = libc::mmap(
+            (base as *mut u8).add(meta_len + capacity) as *mut libc::c_void,
+            capacity,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_SHARED | libc::MAP_FIXED,
+            fd,
+            meta_len as libc::off_t,
+        );
+        if first == libc::MAP_FAILED || second == libc::MAP_FAILED {
+            let e = std::io::Error::last_os_error();
+            libc::munmap(base, total);
+            bail!("{}", e);
+        }
+        Ok(base as *mut u8)
+    }
+
+    fn header(&self) -> &Header {
+        unsafe { &*(self.base as *const Header) }
+    }
+
+    fn data(&self) -> *mut u8 {
+        unsafe { self.base.add(self.meta_len) }
+    }
+
+    fn tag_slot(&self, seq: u64) -> *mut u8 {
+        let offset = round_up(std::mem::size_of::<Header>(), pagesize());
+        let slot = seq as usize % TAG_SLOTS;
+        unsafe { self.base.add(offset + slot * TAG_SLOT_SIZE) }
+    }
+
+    fn tag_index(&self, seq: u64) -> u64 {
+        unsafe { std::ptr::read_unaligned(self.tag_slot(seq) as *const u64) }
+    }
+
+    fn read_tag(&self, seq: u64) -> Option<Tag> {
+        unsafe {
+            let slot = self.tag_slot(seq);
+            let len = std::ptr::read_unaligned(slot.add(8) as *const u32) as usize;
+            let bytes = std::slice::from_raw_parts(slot.add(TAG_SLOT_HEADER), len);
+            serde_json::from_slice::<ShmTag>(bytes)
+                .ok()
+                .map(ShmTag::into_tag)
+        }
+    }
+
+    /// Name of the segment
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Size of the items in the segment
+    pub fn item_size(&self) -> usize {
+        self.item_size
+    }
+
+    /// Capacity of the data ring in bytes
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    // ========================= writer =========================
+
+    // free the slots of readers whose process no longer exists
+    fn reclaim_dead_readers(&self) {
+        let header = self.header();
+        for r in header.readers.iter() {
+            if r.state.load(Ordering::Acquire) != SLOT_ACTIVE {
+                continue;
+            }
+            let pid = r.pid.load(Ordering::Acquire);
+            let dead = unsafe { libc::kill(pid, 0) } != 0
+                && std::io::Error::last_os_error().raw_os_error() == Some(libc::ESRCH);
+            if dead
+                && r.state
+                    .compare_exchange(SLOT_ACTIVE, SLOT_FREE, Ordering::AcqRel, Ordering::Acquire)
+                    .is_ok()
+            {
+                warn!(
+                    "shared memory segment {}: reclaiming slot of dead reader (pid {})",
+                    self.name, pid
+                );
+                wake(&header.reader_seq);
+            }
+        }
+    }
+
+    fn active_readers(&self) -> impl Iterator<Item = &ReaderSlot> {
+        self.header()
+            .readers
+            .iter()
+            .filter(|r| r.state.load(Ordering::Acquire) == SLOT_ACTIVE)
+    }
+
+    /// Number of attached readers
+    ///
+    /// Slots of readers whose process died without detaching are reclaimed.
+    pub fn readers(&self) -> usize {
+        self.reclaim_dead_readers();
+        self.active_readers().count()
+    }
+
+    /// Space available to the writer
+    ///
+    /// Returns a pointer and the number of bytes that can be written. Without readers, the
+    /// writer may use the complete buffer. Slots of readers whose process died without
+    /// detaching are reclaimed, so that they do not block the writer.
+    pub fn space(&self) -> (*mut u8, usize) {
+        self.reclaim_dead_readers();
+        let header = self.header();
+        let write = header.write.load(Ordering::Relaxed);
+        let read = self
+            .active_readers()
+            .map(|r| r.read.load(Ordering::Acquire))
+            .min()
+            .unwrap_or(write);
+        let used = write.saturating_sub(read) as usize;
+        let offset = (write % self.capacity as u64) as usize;
+        unsafe { (self.data().add(offset), self.capacity.saturating_sub(used)) }
+    }
+
+    /// Publish `bytes` written bytes and their tags
+    ///
+    /// The index of a tag is relative to the first published item. Tags that cannot be
+    /// serialized, that are too large for a tag slot, or that do not fit in the tag ring are
+    /// dropped.
+    pub fn produce(&self, bytes: usize, tags: &[ItemTag]) {
+        let header = self.header();
+        let write = header.write.load(Ordering::Relaxed);
+        let first_item = write / self.item_size as u64;
+
+        let mut tag_write = header.tag_write.load(Ordering::Relaxed);
+        let tag_read = self
+            .active_readers()
+            .map(|r| r.tag_read.load(Ordering::Acquire))
+            .min()
+            .unwrap_or(tag_write);
+        for t in tags.iter().filter(|t| t.index * self.item_size < bytes) {
+            let encoded = ShmTag::from_tag(&t.tag).and_then(|t| serde_json::to_vec(&t).ok());
+            let encoded = match encoded {
+                Some(e) if e.len() <= TAG_SLOT_SIZE - TAG_SLOT_HEADER => e,
+                _ => {
+                    warn!("shared memory buffer: dropping tag {:?}", t.tag);
+                    continue;
+                }
+            };
+            if tag_write - tag_read >= TAG_SLOTS as u64 {
+                warn!(
+                    "shared memory buffer: tag ring full, dropping tag {:?}",
+                    t.tag
+                );
+                continue;
+            }
+            unsafe {
+                let slot = self.tag_slot(tag_write);
+                std::ptr::write_unaligned(slot as *mut u64, first_item + t.index as u64);
+                std::ptr::write_unaligned(slot.add(8) as *mut u32, encoded.len() as u32);
+                std::ptr::copy_nonoverlapping(
+                    encoded.as_ptr(),
+                    slot.add(TAG_SLOT_HEADER),
+                    encoded.len(),
+                );
+            }
+            tag_write += 1;
+        }
+        header.tag_write.store(tag_write, Ordering::Release);
+        header.write.store(write + bytes as u64, Ordering::Release);
+        wake(&header.writer_seq);
+    }
+
+    /// Mark the writer as done
+    pub fn close(&self) {
+        self.header().writer_done.store(1, Ordering::Release);
+        wake(&self.header().writer_seq);
+    }
+
+    /// Check, if the writer is done
+    pub fn writer_done(&self) -> bool {
+        self.header().writer_done.load(Ordering::Acquire) != 0
+    }
+
+    // ========================= reader =========================
+
+    /// Attach a reader of the current process, returning its slot
+    ///
+    /// The reader starts at the current write position.
+    pub fn attach(&self) -> Result<usize> {
+        let header = self.header();
+        for (i, r) in header.readers.iter().enumerate() {
+            if r.state
+                .compare_exchange(SLOT_FREE, SLOT_CLAIMED, Ordering::AcqRel, Ordering::Acquire)
+                .is_ok()
+            {
+                r.pid.store(std::process::id() as i32, Ordering::Release);
+                r.read
+                    .store(header.write.load(Ordering::Acquire), Ordering::Release);
+                r.tag_read
+                    .store(header.tag_write.load(Ordering::Acquire), Ordering::Release);
+                r.state.store(SLOT_ACTIVE, Ordering::Release);
+                // the writer might have continued, while the slot was not active yet
+                r.read
+                    .store(header.write.load(Ordering::Acquire), Ordering::Release);
+                wake(&header.reader_seq);
+                return Ok(i);
+            }
+        }
+        bail!(
+            "shared memory segment {} supports at most {} readers",
+            self.name,
+            MAX_READERS
+        )
+    }
+
+    /// Detach the reader in the given slot
+    pub fn detach(&self, slot: usize) {
+        let header = self.header();
+        header.readers[slot]
+            .state
+            .store(SLOT_FREE, Ordering::Release);
+        wake(&header.reader_seq);
+    }
+
+    /// Data and tags available to the reader in the given slot
+    ///
+    /// Returns a pointer, the number of bytes, and the tags with indices relative to the
+    /// first available item.
+    pub fn readable(&self, slot: usize) -> (*const u8, usize, Vec<ItemTag>) {
+        let header = self.header();
+        let r = &header.readers[slot];
+        let write = header.write.load(Ordering::Acquire);
+        let read = r.read.load(Ordering::Relaxed);
+        let first_item = read / self.item_size as u64;
+        let last_item = write / self.item_size as u64;
+
+        let mut tags = Vec::new();
+        let tag_write = header.tag_write.load(Ordering::Acquire);
+        for seq in r.tag_read.load(Ordering::Relaxed)..tag_write {
+            let index = self.tag_index(seq);
+            if index >= last_item {
+                break;
+            }
+            if index < first_item {
+                continue;
+            }
+            if let Some(tag) = self.read_tag(seq) {
+                tags.push(ItemTag {
+                    index: (index - first_item) as usize,
+                    tag,
+                });
+            }
+        }
+
+        let offset = (read % self.capacity as u64) as usize;
+        unsafe {
+            (
+                self.data().add(offset) as *const u8,
+                write.saturating_sub(read) as usize,
+                tags,
+            )
+        }
+    }
+
+    /// Consume `bytes` bytes in the given reader slot
+    pub fn consume(&self, slot: usize, bytes: usize) {
+        let header = self.header();
+        let r = &header.readers[slot];
+        let read = r.read.load(Ordering::Relaxed) + bytes as u64;
+        let item = read / self.item_size as u64;
+
+        let tag_write = header.tag_write.load(Ordering::Acquire);
+        let mut tag_read = r.tag_read.load(Ordering::Relaxed);
+        while tag_read < tag_write && self.tag_index(tag_read) < item {
+            tag_read += 1;
+        }
+
+        r.tag_read.store(tag_read, Ordering::Release);
+        r.read.store(read, Ordering::Release);
+        wake(&header.reader_seq);
+    }
+
+    // ========================= notification =========================
+
+    /// Sequence number that changes, when the writer produces or finishes
+    pub fn writer_seq(&self) -> u32 {
+        self.header().writer_seq.load(Ordering::Acquire)
+    }
+
+    /// Sequence number that changes, when a reader attaches, consumes, or detaches
+    pub fn reader_seq(&self) -> u32 {
+        self.header().reader_seq.load(Ordering::Acquire)
+    }
+
+    /// Wait until the writer sequence number differs from `seen`
+    ///
+    /// Resolves at the latest after a short timeout, so callers have to check again.
+    pub fn wait_writer(self: &Arc<Self>, seen: u32) -> impl Future<Output = ()> + Send + 'static {
+        let s = self.clone();
+        blocking::unblock(move || wait(&s.header().writer_seq, seen, WAIT_TIMEOUT))
+    }
+
+    /// Wait until the reader sequence number differs from `seen`
+    ///
+    /// Resolves at the latest after a short timeout, so callers have to check again.
+    pub fn wait_reader(self: &Arc<Self>, seen: u32) -> impl Future<Output = ()> + Send + 'static {
+        let s = self.clone();
+        blocking::unblock(move || wait(&s.header().reader_seq, seen, WAIT_TIMEOUT))
+    }
+}
+
+impl Drop for Segment {
+    fn drop(&mut self) {
+        unsafe {
+            libc::munmap(
+                self.base as *mut libc::c_void,
+                self.meta_len + 2 * self.capacity,
+            );
+            if self.owner {
+                if let Ok(cname) = CString::new(self.name.clone()) {
+                    libc::shm_unlink(cname.as_ptr());
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Debug for Segment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("shm::Segment")
+            .field("name", &self.name)
+            .field("item_size", &self.item_size)
+            .field("capacity", &self.capacity)
+            .field("owner", &self.owner)
+            .finish()
+    }
+}
+
+/// Shared-memory buffer builder
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Shm {
+    min_bytes: usize,
+}
+
+impl Shm {
+    /// Create shared-memory buffer builder with default size
+    pub fn new() -> Shm {
+        Shm {
+            min_bytes: config::config().buffer_size,
+        }
+    }
+    /// Create shared-memory buffer builder with minimum size
+    pub fn with_size(min_bytes: usize) -> Shm {
+        Shm { min_bytes }
+    }
+}
+
+impl Default for Shm {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BufferBuilder for Shm {
+    fn build(
+        &self,
+        item_size: usize,
+        writer_inbox: Sender<BlockMessage>,
+        writer_output_id: usize,
+    ) -> BufferWriter {
+        BufferWriter::Host(Box::new(Writer::new(
+            item_size,
+            self.min_bytes,
+            writer_inbox,
+            writer_output_id,
+        )))
+    }
+}
+
+/// Shared-memory writer
+pub struct Writer {
+    segment: Arc<Segment>,
+    readers: Vec<(Sender<BlockMessage>, usize)>,
+    item_size: usize,
+    inbox: Sender<BlockMessage>,
+    output_id: usize,
+    finished: bool,
+}
+
+impl Writer {
+    /// Create shared-memory writer
+    pub fn new(
+        item_size: usize,
+        min_bytes: usize,
+        inbox: Sender<BlockMessage>,
+        output_id: usize,
+    ) -> Writer {
+        Writer {
+            segment: Segment::anonymous(item_size, min_bytes).unwrap(),
+            readers: Vec::new(),
+            item_size,
+            inbox,
+            output_id,
+            finished: false,
+        }
+    }
+}
+
+impl fmt::Debug for Writer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("shm::Writer")
+            .field("segment", &self.segment)
+            .field("output_id", &self.output_id)
+            .field("finished", &self.finished)
+            .finish()
+    }
+}
+
+#[async_trait]
+impl BufferWriterHost for Writer {
+    fn add_reader(&mut self, inbox: Sender<BlockMessage>, input_id: usize) -> BufferReader {
+        let slot = self.segment.attach().unwrap();
+        self.readers.push((inbox, input_id));
+
+        BufferReader::Host(Box::new(Reader {
+            segment: self.segment.clone(),
+            slot,
+            item_size: self.item_size,
+            finished: false,
+            writer_inbox: self.inbox.clone(),
+            writer_output_id: self.output_id,
+        }))
+    }
+
+    fn as_any(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn produce(&mut self, items: usize, tags: Vec<ItemTag>) {
+        self.segment.produce(items * self.item_size, &tags);
+        for r in self.readers.iter_mut() {
+            let _ = r.0.try_send(BlockMessage::Notify);
+        }
+    }
+
+    fn bytes(&mut self) -> (*mut u8, usize) {
+        self.segment.space()
+    }
+
+    async fn notify_finished(&mut self) {
+        if self.finished {
+            return;
+        }
+
+        self.segment.close();
+        for i in self.readers.iter_mut() {
+            let _ =
+                i.0.send(BlockMessage::StreamInputDone { input_id: i.1 })
+                    .await;
+        }
+    }
+
+    fn finish(&mut self) {
+        self.finished = true;
+    }
+
+    fn finished(&self) -> bool {
+        self.finished
+    }
+}
+
+/// Shared-memory reader
+pub struct Reader {
+    segment: Arc<Segment>,
+    slot: usize,
+    item_size: usize,
+    finished: bool,
+    writer_inbox: Sender<BlockMessage>,
+    writer_output_id: usize,
+}
+
+#[async_trait]
+impl BufferReaderHost for Reader {
+    fn as_any(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn bytes(&mut self) -> (*const u8, usize, Vec<ItemTag>) {
+        self.segment.readable(self.slot)
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.segment.consume(self.slot, amount * self.item_size);
+        let _ = self.writer_inbox.try_send(BlockMessage::Notify);
+    }
+
+    async fn notify_finished(&mut self) {
+        if self.finished {
+            return;
+        }
+
+        let _ = self
+            .writer_inbox
+            .send(BlockMessage::StreamOutputDone {
+                output_id: self.writer_output_id,
+            })
+            .await;
+    }
+
+    fn finish(&mut self) {
+        self.finished = true;
+    }
+
+    fn finished(&self) -> bool {
+        self.finished
+    }
+}
+
+impl Drop for Reader {
+    fn drop(&mut self) {
+        self.segment.detach(self.slot);
+        let _ = self.writer_inbox.try_send(BlockMessage::Notify);
+    }
+}
+
+impl fmt::Debug for Reader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("shm::Reader")
+            .field("segment", &self.segment.name())
+            .field("slot", &self.slot)
+            .field("writer_output_id", &self.writer_output_id)
+            .field("finished", &self.finished)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reclaim_dead_reader() {
+        let segment = Segment::anonymous(4, 4096).unwrap();
+        let live = segment.attach().unwrap();
+        let dead = segment.attach().unwrap();
+        assert_eq!(segment.readers(), 2);
+
+        let mut child = std::process::Command::new("true").spawn().unwrap();
+        let pid = child.id() as i32;
+        child.wait().unwrap();
+        segment.header().readers[dead]
+            .pid
+            .store(pid, Ordering::Release);
+
+        // the dead reader does not block the writer
+        let (_, space) = segment.space();
+        segment.produce(space, &[]);
+        assert_eq!(segment.readers(), 1);
+        assert_eq!(segment.readable(live).1, space);
+        assert_eq!(segment.space().1, 0);
+
+        // the slot can be used again
+        assert_eq!(segment.attach().unwrap(), dead);
+    }
+}
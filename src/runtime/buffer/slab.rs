@@ -14,11 +14,17 @@ use crate::runtime::BlockMessage;
 use crate::runtime::ItemTag;
 
 /// Slab buffer
+///
+/// The writer fills fixed-size chunks. Full chunks are reference-counted and shared by all
+/// readers of the output, i.e., connecting an output to multiple inputs does not copy the data.
+/// A chunk is recycled, once all readers consumed it.
 #[derive(Debug, PartialEq, Hash)]
 pub struct Slab {
     min_bytes: usize,
     n_buffer: usize,
     reserved_items: usize,
+    in_place: bool,
+    max_items: Option<usize>,
 }
 
 impl Eq for Slab {}
@@ -30,6 +36,8 @@ impl Slab {
             min_bytes: config::config().buffer_size,
             n_buffer: 2,
             reserved_items: config::config().slab_reserved,
+            in_place: false,
+            max_items: None,
         }
     }
 
@@ -39,6 +47,8 @@ impl Slab {
             min_bytes,
             n_buffer: 2,
             reserved_items: config::config().slab_reserved,
+            in_place: false,
+            max_items: None,
         }
     }
 
@@ -48,6 +58,8 @@ impl Slab {
             min_bytes: config::config().buffer_size,
             n_buffer,
             reserved_items: config::config().slab_reserved,
+            in_place: false,
+            max_items: None,
         }
     }
 
@@ -57,8 +69,32 @@ impl Slab {
             min_bytes,
             n_buffer,
             reserved_items,
+            in_place: false,
+            max_items: None,
         }
     }
+
+    /// Create Slab builder that accepts chunks for in-place processing
+    ///
+    /// A block with a Slab input, connected to this buffer, can process input chunks in place and
+    /// forward them without copying (see
+    /// [`StreamIo::process_in_place`](crate::runtime::StreamIo::process_in_place)).
+    pub fn in_place() -> Slab {
+        Slab {
+            in_place: true,
+            ..Slab::new()
+        }
+    }
+
+    /// Limit the number of items in flight, i.e., produced but not consumed by all readers yet
+    ///
+    /// The writer fills smaller chunks, which bounds the latency of the connection.
+    /// A writer that needs more than `max_items` of space to produce anything stalls.
+    #[must_use]
+    pub fn max_items(mut self, max_items: usize) -> Slab {
+        self.max_items = Some(max_items);
+        self
+    }
 }
 
 impl Default for Slab {
@@ -74,30 +110,137 @@ impl BufferBuilder for Slab {
         writer_inbox: Sender<BlockMessage>,
         writer_output_id: usize,
     ) -> BufferWriter {
-        Writer::new(
+        let mut buffer_size = self.min_bytes;
+        while buffer_size % item_size != 0 {
+            buffer_size += 1;
+        }
+
+        let mut buffers = VecDeque::new();
+        for _ in 0..self.n_buffer {
+            buffers.push_back(vec![0; buffer_size].into_boxed_slice());
+        }
+
+        BufferWriter::Host(Box::new(Writer {
+            current: None,
+            pool: Arc::new(Mutex::new(Pool {
+                buffers,
+                writer_inbox: writer_inbox.clone(),
+            })),
             item_size,
-            self.min_bytes,
-            self.n_buffer,
-            self.reserved_items,
+            reserved_items: self.reserved_items,
+            // all chunks might be in flight
+            max_chunk_items: self
+                .max_items
+                .map(|m| std::cmp::max(1, m / std::cmp::max(1, self.n_buffer))),
+            in_place: self.in_place,
+            readers: Vec::new(),
             writer_inbox,
             writer_output_id,
-        )
+            finished: false,
+        }))
+    }
+
+    fn latency_cap(&self) -> Option<usize> {
+        self.max_items
+    }
+
+    fn build_with_latency_cap(
+        &self,
+        item_size: usize,
+        writer_inbox: Sender<BlockMessage>,
+        writer_output_id: usize,
+        max_items: usize,
+    ) -> BufferWriter {
+        Slab {
+            max_items: Some(self.max_items.map_or(max_items, |m| m.min(max_items))),
+            ..*self
+        }
+        .build(item_size, writer_inbox, writer_output_id)
     }
 }
 
+// everything is measured in items, e.g., offsets, capacity, space available
+
+// empty buffers of a writer
 #[derive(Debug)]
-struct BufferEmpty {
-    buffer: Box<[u8]>,
+struct Pool {
+    buffers: VecDeque<Box<[u8]>>,
+    writer_inbox: Sender<BlockMessage>,
 }
 
+// items are in `start..start + items`, tags are relative to `start`
 #[derive(Debug)]
-struct BufferFull {
+struct ChunkInner {
     buffer: Box<[u8]>,
+    start: usize,
     items: usize,
-    tags: Vec<ItemTag>,
+    item_size: usize,
+    // chunks are shared between threads
+    tags: Mutex<Vec<ItemTag>>,
+    pool: Arc<Mutex<Pool>>,
 }
 
-// everything is measured in items, e.g., offsets, capacity, space available
+impl Drop for ChunkInner {
+    fn drop(&mut self) {
+        let mut pool = self.pool.lock().unwrap();
+        pool.buffers.push_back(std::mem::take(&mut self.buffer));
+        let _ = pool.writer_inbox.try_send(BlockMessage::Notify);
+    }
+}
+
+/// Chunk of a [`Slab`] buffer, owned by a single block
+///
+/// Taken from a [`Reader`] and forwarded to a [`Writer`] for in-place processing. The memory
+/// returns to the writer that filled the chunk, once the chunk is dropped.
+#[derive(Debug)]
+pub struct Chunk(Arc<ChunkInner>);
+
+impl Chunk {
+    fn inner_mut(&mut self) -> &mut ChunkInner {
+        Arc::get_mut(&mut self.0).unwrap()
+    }
+
+    /// Number of items
+    pub fn len(&self) -> usize {
+        self.0.items
+    }
+
+    /// Check, if the chunk holds no items
+    pub fn is_empty(&self) -> bool {
+        self.0.items == 0
+    }
+
+    /// Size of the items in bytes
+    pub fn item_size(&self) -> usize {
+        self.0.item_size
+    }
+
+    /// Get tags of the items
+    pub fn tags(&mut self) -> &mut Vec<ItemTag> {
+        self.inner_mut().tags.get_mut().unwrap()
+    }
+
+    /// Get items as mutable slice
+    ///
+    /// Panics, if the size of `T` does not match the item size.
+    pub fn slice_mut<T>(&mut self) -> &mut [T] {
+        assert_eq!(std::mem::size_of::<T>(), self.0.item_size);
+        let c = self.inner_mut();
+        unsafe {
+            std::slice::from_raw_parts_mut(
+                c.buffer.as_mut_ptr().add(c.start * c.item_size) as *mut T,
+                c.items,
+            )
+        }
+    }
+}
+
+// full chunks that are queued for a reader
+#[derive(Debug, Default)]
+struct Queue {
+    chunks: VecDeque<Arc<ChunkInner>>,
+    closed: bool,
+}
 
 #[derive(Debug)]
 struct CurrentBuffer {
@@ -111,22 +254,17 @@ struct CurrentBuffer {
 #[derive(Debug)]
 pub struct Writer {
     current: Option<CurrentBuffer>,
-    state: Arc<Mutex<State>>,
+    pool: Arc<Mutex<Pool>>,
     item_size: usize,
     reserved_items: usize,
-    reader_inbox: Option<Sender<BlockMessage>>,
-    reader_input_id: Option<usize>,
+    max_chunk_items: Option<usize>,
+    in_place: bool,
+    readers: Vec<(Sender<BlockMessage>, usize, Arc<Mutex<Queue>>)>,
     writer_inbox: Sender<BlockMessage>,
     writer_output_id: usize,
     finished: bool,
 }
 
-#[derive(Debug)]
-struct State {
-    writer_input: VecDeque<BufferEmpty>,
-    reader_input: VecDeque<BufferFull>,
-}
-
 impl Writer {
     /// Create Slab writer
     pub fn new(
@@ -137,32 +275,55 @@ impl Writer {
         writer_inbox: Sender<BlockMessage>,
         writer_output_id: usize,
     ) -> BufferWriter {
-        let mut buffer_size = min_bytes;
-        while buffer_size % item_size != 0 {
-            buffer_size += 1;
-        }
-
-        let mut writer_input = VecDeque::new();
-        for _ in 0..n_buffer {
-            writer_input.push_back(BufferEmpty {
-                buffer: vec![0; buffer_size].into_boxed_slice(),
-            });
-        }
-
-        BufferWriter::Host(Box::new(Writer {
-            current: None,
-            state: Arc::new(Mutex::new(State {
-                writer_input,
-                reader_input: VecDeque::new(),
-            })),
+        Slab::with_config(min_bytes, n_buffer, reserved_items).build(
             item_size,
-            reserved_items,
-            reader_inbox: None,
-            reader_input_id: None,
             writer_inbox,
             writer_output_id,
-            finished: false,
-        }))
+        )
+    }
+
+    /// Check, if the writer accepts chunks for in-place processing
+    pub fn in_place(&self) -> bool {
+        self.in_place
+    }
+
+    /// Forward a chunk to the readers without copying
+    ///
+    /// Items that were produced before are sent first.
+    pub fn submit(&mut self, chunk: Chunk) {
+        debug_assert_eq!(chunk.item_size(), self.item_size);
+        self.flush();
+        self.send(chunk.0);
+    }
+
+    fn send(&mut self, chunk: Arc<ChunkInner>) {
+        for (inbox, _, queue) in self.readers.iter_mut() {
+            let mut q = queue.lock().unwrap();
+            if !q.closed {
+                q.chunks.push_back(chunk.clone());
+                let _ = inbox.try_send(BlockMessage::Notify);
+            }
+        }
+        // if no reader is connected (e.g., after a runtime reconfiguration), the chunk is
+        // recycled, when it is dropped here
+    }
+
+    fn flush(&mut self) {
+        if let Some(c) = self.current.take() {
+            if c.offset > self.reserved_items {
+                let chunk = Arc::new(ChunkInner {
+                    buffer: c.buffer,
+                    start: self.reserved_items,
+                    items: c.offset - self.reserved_items,
+                    item_size: self.item_size,
+                    tags: Mutex::new(c.tags),
+                    pool: self.pool.clone(),
+                });
+                self.send(chunk);
+            } else {
+                self.pool.lock().unwrap().buffers.push_front(c.buffer);
+            }
+        }
     }
 }
 
@@ -173,15 +334,14 @@ impl BufferWriterHost for Writer {
         reader_inbox: Sender<BlockMessage>,
         reader_input_id: usize,
     ) -> BufferReader {
-        debug_assert!(self.reader_inbox.is_none());
-        debug_assert!(self.reader_input_id.is_none());
-
-        self.reader_inbox = Some(reader_inbox.clone());
-        self.reader_input_id = Some(reader_input_id);
+        let queue = Arc::new(Mutex::new(Queue::default()));
+        self.readers
+            .push((reader_inbox.clone(), reader_input_id, queue.clone()));
 
         BufferReader::Host(Box::new(Reader {
             current: None,
-            state: self.state.clone(),
+            queue,
+            scratch: None,
             item_size: self.item_size,
             reader_inbox,
             reserved_items: self.reserved_items,
@@ -197,11 +357,14 @@ impl BufferWriterHost for Writer {
 
     fn bytes(&mut self) -> (*mut u8, usize) {
         if self.current.is_none() {
-            let mut state = self.state.lock().unwrap();
-            if let Some(b) = state.writer_input.pop_front() {
-                let capacity = b.buffer.len() / self.item_size;
+            let buffer = self.pool.lock().unwrap().buffers.pop_front();
+            if let Some(buffer) = buffer {
+                let mut capacity = buffer.len() / self.item_size;
+                if let Some(m) = self.max_chunk_items {
+                    capacity = capacity.min(self.reserved_items + m);
+                }
                 self.current = Some(CurrentBuffer {
-                    buffer: b.buffer,
+                    buffer,
                     offset: self.reserved_items,
                     capacity,
                     tags: Vec::new(),
@@ -215,7 +378,7 @@ impl BufferWriterHost for Writer {
 
         unsafe {
             (
-                (c.buffer.as_mut_ptr() as *mut u8).add(c.offset * self.item_size),
+                c.buffer.as_mut_ptr().add(c.offset * self.item_size),
                 (c.capacity - c.offset) * self.item_size,
             )
         }
@@ -227,28 +390,15 @@ impl BufferWriterHost for Writer {
         let c = self.current.as_mut().unwrap();
         debug_assert!(amount <= c.capacity - c.offset);
         for t in tags.iter_mut() {
-            t.index += c.offset;
+            t.index += c.offset - self.reserved_items;
         }
         c.tags.append(&mut tags);
         c.offset += amount;
         if c.offset == c.capacity {
-            let c = self.current.take().unwrap();
-            let mut state = self.state.lock().unwrap();
-
-            state.reader_input.push_back(BufferFull {
-                buffer: c.buffer,
-                items: c.capacity - self.reserved_items,
-                tags: c.tags,
-            });
-
-            let _ = self
-                .reader_inbox
-                .as_mut()
-                .unwrap()
-                .try_send(BlockMessage::Notify);
+            self.flush();
 
             // make sure to be called again, if we have another buffer queued
-            if !state.writer_input.is_empty() {
+            if !self.pool.lock().unwrap().buffers.is_empty() {
                 let _ = self.writer_inbox.try_send(BlockMessage::Notify);
             }
         }
@@ -259,32 +409,15 @@ impl BufferWriterHost for Writer {
             return;
         }
 
-        if let Some(CurrentBuffer {
-            buffer,
-            offset,
-            tags,
-            ..
-        }) = self.current.take()
-        {
-            if offset > self.reserved_items {
-                let mut state = self.state.lock().unwrap();
+        self.flush();
 
-                state.reader_input.push_back(BufferFull {
-                    buffer,
-                    items: offset - self.reserved_items,
-                    tags,
-                });
-            }
+        for (inbox, input_id, _) in self.readers.iter_mut() {
+            let _ = inbox
+                .send(BlockMessage::StreamInputDone {
+                    input_id: *input_id,
+                })
+                .await;
         }
-
-        let _ = self
-            .reader_inbox
-            .as_mut()
-            .unwrap()
-            .send(BlockMessage::StreamInputDone {
-                input_id: self.reader_input_id.unwrap(),
-            })
-            .await;
     }
 
     fn finish(&mut self) {
@@ -296,11 +429,48 @@ impl BufferWriterHost for Writer {
     }
 }
 
+#[derive(Debug)]
+enum Data {
+    Shared(Arc<ChunkInner>),
+    Owned(Box<[u8]>),
+}
+
+impl Data {
+    fn as_ptr(&self) -> *const u8 {
+        match self {
+            Data::Shared(c) => c.buffer.as_ptr(),
+            Data::Owned(b) => b.as_ptr(),
+        }
+    }
+}
+
+// items are in `offset..end`, tags are relative to `offset`
+#[derive(Debug)]
+struct CurrentChunk {
+    data: Data,
+    offset: usize,
+    end: usize,
+    tags: Vec<ItemTag>,
+}
+
+impl CurrentChunk {
+    fn new(chunk: Arc<ChunkInner>) -> Self {
+        let tags = chunk.tags.lock().unwrap().clone();
+        CurrentChunk {
+            offset: chunk.start,
+            end: chunk.start + chunk.items,
+            tags,
+            data: Data::Shared(chunk),
+        }
+    }
+}
+
 /// Slab reader
 #[derive(Debug)]
 pub struct Reader {
-    current: Option<CurrentBuffer>,
-    state: Arc<Mutex<State>>,
+    current: Option<CurrentChunk>,
+    queue: Arc<Mutex<Queue>>,
+    scratch: Option<Box<[u8]>>,
     item_size: usize,
     reserved_items: usize,
     reader_inbox: Sender<BlockMessage>,
@@ -309,6 +479,91 @@ pub struct Reader {
     finished: bool,
 }
 
+impl Reader {
+    /// Take the next chunk for in-place processing
+    ///
+    /// Only possible, if no items of the previous chunk are left and no other reader shares the
+    /// next chunk.
+    pub fn take(&mut self) -> Option<Chunk> {
+        if self.current.is_some() {
+            return None;
+        }
+
+        let mut q = self.queue.lock().unwrap();
+        if Arc::strong_count(q.chunks.front()?) != 1 {
+            return None;
+        }
+        let chunk = q.chunks.pop_front().unwrap();
+
+        // make sure to be called again, if we have another buffer queued
+        if !q.chunks.is_empty() {
+            let _ = self.reader_inbox.try_send(BlockMessage::Notify);
+        }
+
+        Some(Chunk(chunk))
+    }
+
+    // continue the items that are left in the current chunk with the next chunk
+    fn carry_over(&mut self, mut next: Arc<ChunkInner>) {
+        let cur = self.current.take().unwrap();
+        let left = cur.end - cur.offset;
+        let is = self.item_size;
+
+        let mut tags = cur.tags;
+        tags.extend(next.tags.lock().unwrap().iter().map(|t| ItemTag {
+            index: t.index + left,
+            tag: t.tag.clone(),
+        }));
+
+        if next.start >= left && Arc::get_mut(&mut next).is_some() {
+            // sole reader, copy the items in front of the next chunk
+            let n = Arc::get_mut(&mut next).unwrap();
+            unsafe {
+                std::ptr::copy_nonoverlapping(
+                    cur.data.as_ptr().add(cur.offset * is),
+                    n.buffer.as_mut_ptr().add((n.start - left) * is),
+                    left * is,
+                );
+            }
+            self.current = Some(CurrentChunk {
+                offset: next.start - left,
+                end: next.start + next.items,
+                tags,
+                data: Data::Shared(next),
+            });
+        } else {
+            // the chunk is shared with other readers, copy both into a private buffer
+            let items = left + next.items;
+            let mut buffer = match self.scratch.take() {
+                Some(b) if b.len() >= items * is => b,
+                _ => vec![0; items * is].into_boxed_slice(),
+            };
+            unsafe {
+                std::ptr::copy_nonoverlapping(
+                    cur.data.as_ptr().add(cur.offset * is),
+                    buffer.as_mut_ptr(),
+                    left * is,
+                );
+                std::ptr::copy_nonoverlapping(
+                    next.buffer.as_ptr().add(next.start * is),
+                    buffer.as_mut_ptr().add(left * is),
+                    next.items * is,
+                );
+            }
+            self.current = Some(CurrentChunk {
+                offset: 0,
+                end: items,
+                tags,
+                data: Data::Owned(buffer),
+            });
+        }
+
+        if let Data::Owned(b) = cur.data {
+            self.scratch = Some(b);
+        }
+    }
+}
+
 #[async_trait]
 impl BufferReaderHost for Reader {
     fn as_any(&mut self) -> &mut dyn Any {
@@ -316,61 +571,30 @@ impl BufferReaderHost for Reader {
     }
 
     fn bytes(&mut self) -> (*const u8, usize, Vec<ItemTag>) {
-        if let Some(cur) = self.current.as_mut() {
-            let left = cur.capacity - cur.offset;
-            debug_assert!(left > 0);
-            if left <= self.reserved_items {
-                let mut state = self.state.lock().unwrap();
-                if let Some(BufferFull {
-                    mut buffer,
-                    mut tags,
-                    items,
-                }) = state.reader_input.pop_front()
-                {
-                    unsafe {
-                        std::ptr::copy_nonoverlapping(
-                            cur.buffer.as_ptr().add(cur.offset * self.item_size),
-                            buffer
-                                .as_mut_ptr()
-                                .add((self.reserved_items - left) * self.item_size),
-                            left * self.item_size,
-                        );
-                    }
-
-                    for t in tags.iter_mut() {
-                        t.index += left;
-                    }
-                    cur.tags.append(&mut tags);
-
-                    let old = std::mem::replace(&mut cur.buffer, buffer);
-                    state.writer_input.push_back(BufferEmpty { buffer: old });
-                    let _ = self.writer_inbox.try_send(BlockMessage::Notify);
-
-                    cur.capacity = items + self.reserved_items;
-                    cur.offset = self.reserved_items - left;
+        match self.current.as_ref().map(|c| c.end - c.offset) {
+            None => {
+                let next = self.queue.lock().unwrap().chunks.pop_front();
+                if let Some(next) = next {
+                    self.current = Some(CurrentChunk::new(next));
+                } else {
+                    return (std::ptr::null::<u8>(), 0, Vec::new());
                 }
             }
-        } else {
-            let mut state = self.state.lock().unwrap();
-            if let Some(b) = state.reader_input.pop_front() {
-                let capacity = b.items + self.reserved_items;
-                self.current = Some(CurrentBuffer {
-                    buffer: b.buffer,
-                    offset: self.reserved_items,
-                    capacity,
-                    tags: b.tags,
-                });
-            } else {
-                return (std::ptr::null::<u8>(), 0, Vec::new());
+            Some(left) if left <= self.reserved_items => {
+                let next = self.queue.lock().unwrap().chunks.pop_front();
+                if let Some(next) = next {
+                    self.carry_over(next);
+                }
             }
+            _ => {}
         }
 
-        let c = self.current.as_mut().unwrap();
+        let c = self.current.as_ref().unwrap();
 
         unsafe {
             (
-                (c.buffer.as_ptr() as *const u8).add(c.offset * self.item_size),
-                (c.capacity - c.offset) * self.item_size,
+                c.data.as_ptr().add(c.offset * self.item_size),
+                (c.end - c.offset) * self.item_size,
                 c.tags.clone(),
             )
         }
@@ -380,29 +604,23 @@ impl BufferReaderHost for Reader {
         debug_assert!(amount > 0);
 
         let c = self.current.as_mut().unwrap();
-        debug_assert!(amount <= c.capacity - c.offset);
+        debug_assert!(amount <= c.end - c.offset);
         c.offset += amount;
-
-        if c.offset == c.capacity {
-            let b = self.current.take().unwrap();
-            let mut state = self.state.lock().unwrap();
-
-            state
-                .writer_input
-                .push_back(BufferEmpty { buffer: b.buffer });
-
-            let _ = self.writer_inbox.try_send(BlockMessage::Notify);
-
-            // make sure to be called again, if we have another buffer queued
-            if !state.reader_input.is_empty() {
-                let _ = self.reader_inbox.try_send(BlockMessage::Notify);
-            }
-        } else if c.capacity - c.offset <= self.reserved_items {
-            let state = self.state.lock().unwrap();
-            if !state.reader_input.is_empty() {
-                let _ = self.reader_inbox.try_send(BlockMessage::Notify);
+        c.tags.retain(|t| t.index >= amount);
+        c.tags.iter_mut().for_each(|t| t.index -= amount);
+
+        let left = c.end - c.offset;
+        if left == 0 {
+            // a shared chunk is recycled, once all readers are done with it
+            if let Data::Owned(b) = self.current.take().unwrap().data {
+                self.scratch = Some(b);
             }
         }
+
+        // make sure to be called again, if we have another buffer queued
+        if left <= self.reserved_items && !self.queue.lock().unwrap().chunks.is_empty() {
+            let _ = self.reader_inbox.try_send(BlockMessage::Notify);
+        }
     }
 
     async fn notify_finished(&mut self) {
@@ -410,6 +628,14 @@ impl BufferReaderHost for Reader {
             return;
         }
 
+        let chunks = {
+            let mut q = self.queue.lock().unwrap();
+            q.closed = true;
+            std::mem::take(&mut q.chunks)
+        };
+        drop(chunks);
+        self.current = None;
+
         let _ = self
             .writer_inbox
             .send(BlockMessage::StreamOutputDone {
@@ -423,6 +649,18 @@ impl BufferReaderHost for Reader {
     }
 
     fn finished(&self) -> bool {
-        self.finished && self.state.lock().unwrap().reader_input.is_empty()
+        self.finished && self.queue.lock().unwrap().chunks.is_empty()
+    }
+}
+
+impl Drop for Reader {
+    fn drop(&mut self) {
+        // do not hold back chunks of the writer
+        let chunks = {
+            let mut q = self.queue.lock().unwrap();
+            q.closed = true;
+            std::mem::take(&mut q.chunks)
+        };
+        drop(chunks);
     }
 }
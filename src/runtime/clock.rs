@@ -0,0 +1,189 @@
+//! Clock of the Runtime
+//!
+//! Time-based blocks use [`now`] and [`sleep`] instead of [`Instant::now`] and
+//! [`async_io::Timer`]. By default, they use the wall-clock time. Blocks that run on the
+//! [`DeterministicScheduler`](crate::runtime::scheduler::DeterministicScheduler) use its
+//! [`VirtualClock`] instead.
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::task::Context;
+use std::task::Poll;
+use std::task::Waker;
+use std::time::Duration;
+use std::time::Instant;
+
+thread_local! {
+    static CURRENT: RefCell<Option<VirtualClock>> = const { RefCell::new(None) };
+}
+
+/// Virtual clock that only advances when told to
+#[derive(Clone, Debug)]
+pub struct VirtualClock {
+    inner: Arc<Mutex<ClockState>>,
+}
+
+#[derive(Debug)]
+struct ClockState {
+    start: Instant,
+    elapsed: Duration,
+    // (deadline, timer id)
+    timers: BTreeMap<(Duration, u64), Option<Waker>>,
+    next_id: u64,
+}
+
+impl VirtualClock {
+    /// Create virtual clock, starting at the current wall-clock time
+    pub fn new() -> Self {
+        VirtualClock {
+            inner: Arc::new(Mutex::new(ClockState {
+                start: Instant::now(),
+                elapsed: Duration::ZERO,
+                timers: BTreeMap::new(),
+                next_id: 0,
+            })),
+        }
+    }
+
+    /// Current virtual time
+    pub fn now(&self) -> Instant {
+        let s = self.inner.lock().unwrap();
+        s.start + s.elapsed
+    }
+
+    /// Virtual time since the clock was created
+    pub fn elapsed(&self) -> Duration {
+        self.inner.lock().unwrap().elapsed
+    }
+
+    /// Deadline of the next timer that did not expire yet, relative to the creation of the clock
+    pub fn next_deadline(&self) -> Option<Duration> {
+        let s = self.inner.lock().unwrap();
+        s.timers.keys().map(|(d, _)| *d).find(|d| *d > s.elapsed)
+    }
+
+    /// Advance the clock to the given time (relative to the creation of the clock), waking all
+    /// timers that expired
+    ///
+    /// The clock never goes backwards.
+    pub fn advance_to(&self, t: Duration) {
+        let wakers: Vec<Waker> = {
+            let mut s = self.inner.lock().unwrap();
+            s.elapsed = s.elapsed.max(t);
+            let elapsed = s.elapsed;
+            s.timers
+                .iter_mut()
+                .take_while(|((d, _), _)| *d <= elapsed)
+                .filter_map(|(_, w)| w.take())
+                .collect()
+        };
+        wakers.into_iter().for_each(Waker::wake);
+    }
+
+    /// Make this the clock of the current thread, until the guard is dropped
+    pub fn enter(&self) -> ClockGuard {
+        let previous = CURRENT.with(|c| c.borrow_mut().replace(self.clone()));
+        ClockGuard { previous }
+    }
+
+    fn sleep_until(&self, deadline: Instant) -> Sleep {
+        let mut s = self.inner.lock().unwrap();
+        let deadline = deadline.saturating_duration_since(s.start);
+        let id = s.next_id;
+        s.next_id += 1;
+        s.timers.insert((deadline, id), None);
+        Sleep(SleepInner::Virtual {
+            clock: self.clone(),
+            key: (deadline, id),
+        })
+    }
+}
+
+impl Default for VirtualClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Restores the previous clock of the thread, when dropped
+#[derive(Debug)]
+pub struct ClockGuard {
+    previous: Option<VirtualClock>,
+}
+
+impl Drop for ClockGuard {
+    fn drop(&mut self) {
+        CURRENT.with(|c| *c.borrow_mut() = self.previous.take());
+    }
+}
+
+/// Current time
+///
+/// Virtual time, if called from a task of a scheduler with a [`VirtualClock`], wall-clock time
+/// otherwise.
+pub fn now() -> Instant {
+    CURRENT
+        .with(|c| c.borrow().as_ref().map(|c| c.now()))
+        .unwrap_or_else(Instant::now)
+}
+
+/// Sleep for the given duration
+///
+/// Uses the clock that is current, when this function is called (see [`now`]).
+pub fn sleep(duration: Duration) -> Sleep {
+    sleep_until(now() + duration)
+}
+
+/// Sleep until the given deadline
+///
+/// Uses the clock that is current, when this function is called (see [`now`]).
+pub fn sleep_until(deadline: Instant) -> Sleep {
+    match CURRENT.with(|c| c.borrow().clone()) {
+        Some(clock) => clock.sleep_until(deadline),
+        None => Sleep(SleepInner::Real(async_io::Timer::at(deadline))),
+    }
+}
+
+/// Future returned by [`sleep`] and [`sleep_until`]
+#[derive(Debug)]
+pub struct Sleep(SleepInner);
+
+#[derive(Debug)]
+enum SleepInner {
+    Real(async_io::Timer),
+    Virtual {
+        clock: VirtualClock,
+        key: (Duration, u64),
+    },
+}
+
+impl Future for Sleep {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        match &mut self.0 {
+            SleepInner::Real(timer) => Pin::new(timer).poll(cx).map(|_| ()),
+            SleepInner::Virtual { clock, key } => {
+                let mut s = clock.inner.lock().unwrap();
+                if s.elapsed >= key.0 {
+                    s.timers.remove(key);
+                    Poll::Ready(())
+                } else {
+                    s.timers.insert(*key, Some(cx.waker().clone()));
+                    Poll::Pending
+                }
+            }
+        }
+    }
+}
+
+impl Drop for Sleep {
+    fn drop(&mut self) {
+        if let SleepInner::Virtual { clock, key } = &self.0 {
+            clock.inner.lock().unwrap().timers.remove(key);
+        }
+    }
+}
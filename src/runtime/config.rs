@@ -76,6 +76,18 @@ static CONFIG: Lazy<Config> = Lazy::new(|| {
                 "ctrlport_bind" => {
                     c.ctrlport_bind = Some(config_parse::<SocketAddr>(v));
                 }
+                "ctrlport_read_token" => {
+                    c.ctrlport_read_token = Some(config_parse::<String>(v));
+                }
+                "ctrlport_write_token" => {
+                    c.ctrlport_write_token = Some(config_parse::<String>(v));
+                }
+                "ctrlport_tls_cert" => {
+                    c.ctrlport_tls_cert = Some(config_parse::<PathBuf>(v));
+                }
+                "ctrlport_tls_key" => {
+                    c.ctrlport_tls_key = Some(config_parse::<PathBuf>(v));
+                }
                 "frontend_path" => {
                     c.frontend_path = Some(config_parse::<PathBuf>(v));
                 }
@@ -110,6 +122,19 @@ pub struct Config {
     pub ctrlport_enable: bool,
     /// Control port socket address
     pub ctrlport_bind: Option<SocketAddr>,
+    /// Token required to read from the control port
+    ///
+    /// The write token also grants read access.
+    pub ctrlport_read_token: Option<String>,
+    /// Token required to write to the control port, i.e., for requests other than `GET` and
+    /// calls of message handlers
+    ///
+    /// If only the read token is set, it is also required to write.
+    pub ctrlport_write_token: Option<String>,
+    /// Path to the PEM certificate (chain) to serve the control port over TLS
+    pub ctrlport_tls_cert: Option<PathBuf>,
+    /// Path to the PEM private key to serve the control port over TLS
+    pub ctrlport_tls_key: Option<PathBuf>,
     /// Frontend path for Webserver
     pub frontend_path: Option<PathBuf>,
     misc: HashMap<String, Value>,
@@ -122,6 +147,14 @@ impl Config {
             println!("ctrlport enabled but socket not set");
             return false;
         }
+        if self.ctrlport_tls_cert.is_some() != self.ctrlport_tls_key.is_some() {
+            println!("ctrlport TLS requires both, certificate and key");
+            return false;
+        }
+        if self.ctrlport_tls_cert.is_some() && !cfg!(feature = "ctrlport_tls") {
+            println!("ctrlport TLS requires the ctrlport_tls feature");
+            return false;
+        }
         true
     }
 }
@@ -137,6 +170,10 @@ impl Default for Config {
             log_level: LevelFilter::Debug,
             ctrlport_enable: true,
             ctrlport_bind: "127.0.0.1:1337".parse::<SocketAddr>().ok(),
+            ctrlport_read_token: None,
+            ctrlport_write_token: None,
+            ctrlport_tls_cert: None,
+            ctrlport_tls_key: None,
             frontend_path: None,
             misc: HashMap::new(),
         }
@@ -152,6 +189,10 @@ impl Default for Config {
             log_level: LevelFilter::Info,
             ctrlport_enable: false,
             ctrlport_bind: None,
+            ctrlport_read_token: None,
+            ctrlport_write_token: None,
+            ctrlport_tls_cert: None,
+            ctrlport_tls_key: None,
             frontend_path: None,
             misc: HashMap::new(),
         }
@@ -1,22 +1,42 @@
 //! Remote Control through REST API
-use axum::extract::{Path, State};
-use axum::http::{StatusCode, Uri};
-use axum::response::Redirect;
-use axum::routing::{any, get, get_service};
+use axum::extract::ws::{Message, WebSocketUpgrade};
+use axum::extract::{Extension, Path, Query, State};
+use axum::http::{header, Method, Request, StatusCode, Uri};
+use axum::middleware::{self, Next};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Redirect, Response};
+use axum::routing::{any, delete, get, get_service, post};
 use axum::Json;
 use axum::Router;
-use slab::Slab;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use futures::future;
+use futures::future::BoxFuture;
+use futures::stream::{self, Stream, StreamExt};
+use futures::SinkExt;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::collections::HashMap;
 use std::path;
 use std::sync::Arc;
 use std::sync::Mutex;
 use std::thread::JoinHandle;
+use std::time::Duration;
 use tower_http::cors::CorsLayer;
+
+#[cfg(feature = "ctrlport_tls")]
+use axum_server::tls_rustls::RustlsConfig;
 use tower_http::services::ServeDir;
 
+use crate::anyhow::Result;
 use crate::runtime::config;
 use crate::runtime::BlockDescription;
+use crate::runtime::BlockStats;
+use crate::runtime::Flowgraph;
 use crate::runtime::FlowgraphDescription;
 use crate::runtime::FlowgraphHandle;
+use crate::runtime::FlowgraphState;
+use crate::runtime::FlowgraphStats;
 use crate::runtime::Pmt;
 use crate::runtime::PortId;
 
@@ -30,16 +50,43 @@ macro_rules! relative {
     };
 }
 
-async fn flowgraphs(
-    State(flowgraphs): State<Arc<Mutex<Slab<FlowgraphHandle>>>>,
-) -> Json<Vec<usize>> {
-    let f: Vec<usize> = flowgraphs.lock().unwrap().iter().map(|x| x.0).collect();
-    Json::from(f)
+/// Flowgraphs of the control port
+///
+/// Ids are never reused, so that a client does not end up with a different flowgraph.
+#[derive(Default)]
+struct Flowgraphs {
+    handles: BTreeMap<usize, FlowgraphHandle>,
+    next_id: usize,
+}
+
+impl Flowgraphs {
+    fn insert(&mut self, handle: FlowgraphHandle) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.handles.insert(id, handle);
+        id
+    }
+
+    fn get(&self, id: usize) -> Option<&FlowgraphHandle> {
+        self.handles.get(&id)
+    }
+
+    fn remove(&mut self, id: usize) -> Option<FlowgraphHandle> {
+        self.handles.remove(&id)
+    }
+
+    fn ids(&self) -> Vec<usize> {
+        self.handles.keys().copied().collect()
+    }
+}
+
+async fn flowgraphs(State(flowgraphs): State<Arc<Mutex<Flowgraphs>>>) -> Json<Vec<usize>> {
+    Json::from(flowgraphs.lock().unwrap().ids())
 }
 
 async fn flowgraph_description(
     Path(fg): Path<usize>,
-    State(flowgraphs): State<Arc<Mutex<Slab<FlowgraphHandle>>>>,
+    State(flowgraphs): State<Arc<Mutex<Flowgraphs>>>,
 ) -> Result<Json<FlowgraphDescription>, StatusCode> {
     let fg = flowgraphs.lock().unwrap().get(fg).cloned();
     if let Some(mut fg) = fg {
@@ -52,7 +99,7 @@ async fn flowgraph_description(
 
 async fn block_description(
     Path((fg, blk)): Path<(usize, usize)>,
-    State(flowgraphs): State<Arc<Mutex<Slab<FlowgraphHandle>>>>,
+    State(flowgraphs): State<Arc<Mutex<Flowgraphs>>>,
 ) -> Result<Json<BlockDescription>, StatusCode> {
     let fg = flowgraphs.lock().unwrap().get(fg).cloned();
     if let Some(mut fg) = fg {
@@ -64,9 +111,36 @@ async fn block_description(
     Err(StatusCode::BAD_REQUEST)
 }
 
+async fn flowgraph_stats(
+    Path(fg): Path<usize>,
+    State(flowgraphs): State<Arc<Mutex<Flowgraphs>>>,
+) -> Result<Json<FlowgraphStats>, StatusCode> {
+    let fg = flowgraphs.lock().unwrap().get(fg).cloned();
+    if let Some(mut fg) = fg {
+        if let Ok(s) = fg.stats().await {
+            return Ok(Json::from(s));
+        }
+    }
+    Err(StatusCode::BAD_REQUEST)
+}
+
+async fn block_stats(
+    Path((fg, blk)): Path<(usize, usize)>,
+    State(flowgraphs): State<Arc<Mutex<Flowgraphs>>>,
+) -> Result<Json<BlockStats>, StatusCode> {
+    let fg = flowgraphs.lock().unwrap().get(fg).cloned();
+    if let Some(mut fg) = fg {
+        if let Ok(s) = fg.block_stats(blk).await {
+            return Ok(Json::from(s));
+        }
+    }
+
+    Err(StatusCode::BAD_REQUEST)
+}
+
 async fn handler_id(
     Path((fg, blk, handler)): Path<(usize, usize, String)>,
-    State(flowgraphs): State<Arc<Mutex<Slab<FlowgraphHandle>>>>,
+    State(flowgraphs): State<Arc<Mutex<Flowgraphs>>>,
 ) -> Result<Json<Pmt>, StatusCode> {
     let fg = flowgraphs.lock().unwrap().get(fg).cloned();
     let handler = match handler.parse::<usize>() {
@@ -84,7 +158,7 @@ async fn handler_id(
 
 async fn handler_id_post(
     Path((fg, blk, handler)): Path<(usize, usize, String)>,
-    State(flowgraphs): State<Arc<Mutex<Slab<FlowgraphHandle>>>>,
+    State(flowgraphs): State<Arc<Mutex<Flowgraphs>>>,
     Json(pmt): Json<Pmt>,
 ) -> Result<Json<Pmt>, StatusCode> {
     let fg = flowgraphs.lock().unwrap().get(fg).cloned();
@@ -101,15 +175,313 @@ async fn handler_id_post(
     Err(StatusCode::BAD_REQUEST)
 }
 
+/// Create a flowgraph from parameters
+type Factory = Arc<dyn Fn(Pmt) -> Result<Flowgraph> + Send + Sync>;
+/// Start a flowgraph on the scheduler of the runtime
+type Launcher = Arc<dyn Fn(Flowgraph) -> BoxFuture<'static, Result<FlowgraphHandle>> + Send + Sync>;
+
+#[derive(Default)]
+struct Factories {
+    factories: HashMap<String, Factory>,
+    launcher: Option<Launcher>,
+    // ids of the flowgraphs that were launched through the control port, which are kept until
+    // they are deleted by a client
+    launched: Vec<usize>,
+}
+
+async fn flowgraph_state(
+    Path(fg): Path<usize>,
+    State(flowgraphs): State<Arc<Mutex<Flowgraphs>>>,
+) -> Result<Json<FlowgraphState>, StatusCode> {
+    let fg = flowgraphs.lock().unwrap().get(fg).cloned();
+    if let Some(fg) = fg {
+        return Ok(Json::from(fg.state()));
+    }
+    Err(StatusCode::BAD_REQUEST)
+}
+
+async fn flowgraph_terminate(
+    Path(fg): Path<usize>,
+    State(flowgraphs): State<Arc<Mutex<Flowgraphs>>>,
+) -> Result<Json<Pmt>, StatusCode> {
+    let fg = flowgraphs.lock().unwrap().get(fg).cloned();
+    if let Some(mut fg) = fg {
+        if fg.state() != FlowgraphState::Running || fg.terminate().await.is_ok() {
+            return Ok(Json::from(Pmt::Ok));
+        }
+    }
+    Err(StatusCode::BAD_REQUEST)
+}
+
+async fn factories(Extension(factories): Extension<Arc<Mutex<Factories>>>) -> Json<Vec<String>> {
+    let mut names: Vec<String> = factories
+        .lock()
+        .unwrap()
+        .factories
+        .keys()
+        .cloned()
+        .collect();
+    names.sort();
+    Json::from(names)
+}
+
+async fn launch(
+    Path(name): Path<String>,
+    State(flowgraphs): State<Arc<Mutex<Flowgraphs>>>,
+    Extension(factories): Extension<Arc<Mutex<Factories>>>,
+    Json(params): Json<Pmt>,
+) -> Result<Json<usize>, (StatusCode, String)> {
+    let (factory, launcher) = {
+        let f = factories.lock().unwrap();
+        (f.factories.get(&name).cloned(), f.launcher.clone())
+    };
+    let (factory, launcher) = match (factory, launcher) {
+        (Some(f), Some(l)) => (f, l),
+        _ => return Err((StatusCode::NOT_FOUND, format!("no factory {name}"))),
+    };
+
+    let fg = factory(params).map_err(|e| (StatusCode::BAD_REQUEST, format!("{e:#}")))?;
+    let handle = launcher(fg)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("{e:#}")))?;
+
+    let id = flowgraphs.lock().unwrap().insert(handle);
+    factories.lock().unwrap().launched.push(id);
+    Ok(Json::from(id))
+}
+
+async fn delete_launched(
+    Path(fg): Path<usize>,
+    State(flowgraphs): State<Arc<Mutex<Flowgraphs>>>,
+    Extension(factories): Extension<Arc<Mutex<Factories>>>,
+) -> Result<Json<Pmt>, StatusCode> {
+    let mut f = factories.lock().unwrap();
+    let mut flowgraphs = flowgraphs.lock().unwrap();
+    if !f.launched.contains(&fg) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    if flowgraphs.get(fg).map(|h| h.state()) == Some(FlowgraphState::Running) {
+        return Err(StatusCode::CONFLICT);
+    }
+    flowgraphs.remove(fg);
+    f.launched.retain(|id| *id != fg);
+    Ok(Json::from(Pmt::Ok))
+}
+
+/// Push the items of a stream as JSON
+///
+/// Uses a WebSocket, if the client requests an upgrade, and Server-Sent Events otherwise.
+fn push<T, S>(ws: Option<WebSocketUpgrade>, items: S) -> Response
+where
+    T: Serialize + Send + 'static,
+    S: Stream<Item = T> + Send + 'static,
+{
+    match ws {
+        Some(ws) => ws
+            .on_upgrade(|socket| async move {
+                let (mut tx, mut rx) = socket.split();
+                let forward = async move {
+                    let mut items = Box::pin(items);
+                    while let Some(item) = items.next().await {
+                        let json = match serde_json::to_string(&item) {
+                            Ok(j) => j,
+                            Err(_) => continue,
+                        };
+                        if tx.send(Message::Text(json)).await.is_err() {
+                            return;
+                        }
+                    }
+                    let _ = tx.send(Message::Close(None)).await;
+                };
+                // stop forwarding, once the client closes the connection
+                let closed = async move {
+                    while let Some(Ok(m)) = rx.next().await {
+                        if let Message::Close(_) = m {
+                            break;
+                        }
+                    }
+                };
+                future::select(Box::pin(forward), Box::pin(closed)).await;
+            })
+            .into_response(),
+        None => Sse::new(items.map(|item| Event::default().json_data(item)))
+            .keep_alive(KeepAlive::default())
+            .into_response(),
+    }
+}
+
+async fn flowgraph_events(
+    Path(fg): Path<usize>,
+    State(flowgraphs): State<Arc<Mutex<Flowgraphs>>>,
+    ws: Option<WebSocketUpgrade>,
+) -> Result<Response, StatusCode> {
+    let fg = flowgraphs.lock().unwrap().get(fg).cloned();
+    if let Some(mut fg) = fg {
+        if let Ok(events) = fg.subscribe_events().await {
+            return Ok(push(ws, events));
+        }
+    }
+    Err(StatusCode::BAD_REQUEST)
+}
+
+#[derive(Deserialize)]
+struct StatsParams {
+    interval_ms: Option<u64>,
+}
+
+async fn flowgraph_stats_stream(
+    Path(fg): Path<usize>,
+    Query(params): Query<StatsParams>,
+    State(flowgraphs): State<Arc<Mutex<Flowgraphs>>>,
+    ws: Option<WebSocketUpgrade>,
+) -> Result<Response, StatusCode> {
+    let fg = flowgraphs.lock().unwrap().get(fg).cloned();
+    let fg = fg.ok_or(StatusCode::BAD_REQUEST)?;
+    let interval = Duration::from_millis(params.interval_ms.unwrap_or(1000).max(10));
+
+    // stats are pushed periodically, until the flowgraph is done
+    let stats = stream::unfold((fg, true), move |(mut fg, first)| async move {
+        if !first {
+            async_io::Timer::after(interval).await;
+        }
+        fg.stats().await.ok().map(|s| (s, (fg, false)))
+    });
+    Ok(push(ws, stats))
+}
+
+async fn block_subscribe(
+    Path((fg, blk, port)): Path<(usize, usize, String)>,
+    State(flowgraphs): State<Arc<Mutex<Flowgraphs>>>,
+    ws: Option<WebSocketUpgrade>,
+) -> Result<Response, StatusCode> {
+    let fg = flowgraphs.lock().unwrap().get(fg).cloned();
+    let port = match port.parse::<usize>() {
+        Ok(i) => PortId::Index(i),
+        Err(_) => PortId::Name(port),
+    };
+    if let Some(mut fg) = fg {
+        if let Ok(messages) = fg.subscribe_message(blk, port).await {
+            return Ok(push(ws, messages));
+        }
+    }
+    Err(StatusCode::BAD_REQUEST)
+}
+
+/// Tokens that clients have to present to the control port
+///
+/// Clients pass a token either as `Bearer` token or as password of a `Basic` authorization.
+#[derive(Clone, Debug, Default)]
+struct Auth {
+    read: Option<String>,
+    write: Option<String>,
+}
+
+impl Auth {
+    fn from_config() -> Self {
+        Self {
+            read: config::config().ctrlport_read_token.clone(),
+            write: config::config().ctrlport_write_token.clone(),
+        }
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.read.is_some() || self.write.is_some()
+    }
+
+    /// Extract the token from an `Authorization` header
+    fn token(value: &str) -> Option<String> {
+        let (scheme, credentials) = value.trim().split_once(' ')?;
+        if scheme.eq_ignore_ascii_case("bearer") {
+            Some(credentials.trim().to_string())
+        } else if scheme.eq_ignore_ascii_case("basic") {
+            let decoded = BASE64.decode(credentials.trim()).ok()?;
+            let decoded = String::from_utf8(decoded).ok()?;
+            decoded.split_once(':').map(|(_, p)| p.to_string())
+        } else {
+            None
+        }
+    }
+
+    /// Compare tokens in constant time
+    fn matches(expected: &Option<String>, token: &Option<String>) -> bool {
+        match (expected, token) {
+            (Some(e), Some(t)) => {
+                e.len() == t.len()
+                    && e.bytes()
+                        .zip(t.bytes())
+                        .fold(0, |acc, (a, b)| acc | (a ^ b))
+                        == 0
+            }
+            _ => false,
+        }
+    }
+
+    /// Check, if the path calls a message handler of a block
+    fn is_call(path: &str) -> bool {
+        let s: Vec<&str> = path.split('/').collect();
+        s.len() > 6 && s[1] == "api" && s[2] == "fg" && s[4] == "block" && s[6] == "call"
+    }
+
+    /// Check, if a request with the given token is allowed
+    ///
+    /// `GET` requests need the read or the write token, other requests and calls of message
+    /// handlers, which are also possible through `GET`, the write token. Without a write token,
+    /// these requests need the read token. Requests are only open, if no token is configured
+    /// that covers them. `OPTIONS` requests are always allowed to answer CORS preflights, which
+    /// do not carry credentials.
+    fn authorize(&self, method: &Method, path: &str, token: &Option<String>) -> bool {
+        if method == Method::OPTIONS {
+            return true;
+        }
+        let read = self.read.is_none()
+            || Self::matches(&self.read, token)
+            || Self::matches(&self.write, token);
+        let write = match self.write {
+            Some(_) => Self::matches(&self.write, token),
+            None => self.read.is_none() || Self::matches(&self.read, token),
+        };
+        if (method == Method::GET || method == Method::HEAD) && !Self::is_call(path) {
+            read
+        } else {
+            write
+        }
+    }
+}
+
+async fn authorize<B>(
+    Extension(auth): Extension<Arc<Auth>>,
+    req: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let token = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(Auth::token);
+    if auth.authorize(req.method(), req.uri().path(), &token) {
+        next.run(req).await
+    } else if token.is_some() {
+        StatusCode::FORBIDDEN.into_response()
+    } else {
+        (
+            StatusCode::UNAUTHORIZED,
+            [(header::WWW_AUTHENTICATE, "Basic realm=\"FutureSDR\"")],
+        )
+            .into_response()
+    }
+}
+
 pub struct ControlPort {
-    flowgraphs: Arc<Mutex<Slab<FlowgraphHandle>>>,
+    flowgraphs: Arc<Mutex<Flowgraphs>>,
+    factories: Arc<Mutex<Factories>>,
     thread: Option<JoinHandle<()>>,
 }
 
 impl ControlPort {
     pub fn new() -> Self {
         let mut cp = ControlPort {
-            flowgraphs: Arc::new(Mutex::new(Slab::new())),
+            flowgraphs: Arc::new(Mutex::new(Flowgraphs::default())),
+            factories: Arc::new(Mutex::new(Factories::default())),
             thread: None,
         };
         cp.start(None);
@@ -118,7 +490,8 @@ impl ControlPort {
 
     pub fn with_routes(routes: Router) -> Self {
         let mut cp = ControlPort {
-            flowgraphs: Arc::new(Mutex::new(Slab::new())),
+            flowgraphs: Arc::new(Mutex::new(Flowgraphs::default())),
+            factories: Arc::new(Mutex::new(Factories::default())),
             thread: None,
         };
         cp.start(Some(routes));
@@ -130,6 +503,12 @@ impl ControlPort {
         v.insert(handle)
     }
 
+    pub fn register_factory(&self, name: String, factory: Factory, launcher: Launcher) {
+        let mut f = self.factories.lock().unwrap();
+        f.factories.insert(name, factory);
+        f.launcher = Some(launcher);
+    }
+
     fn start(&mut self, custom_routes: Option<Router>) {
         if !config::config().ctrlport_enable {
             return;
@@ -142,11 +521,24 @@ impl ControlPort {
         let mut app = Router::new()
             .route("/api/fg/", get(flowgraphs))
             .route("/api/fg/:fg/", get(flowgraph_description))
+            .route("/api/fg/:fg/stats/", get(flowgraph_stats))
+            .route("/api/fg/:fg/stats/stream/", get(flowgraph_stats_stream))
+            .route("/api/fg/:fg/events/", get(flowgraph_events))
+            .route("/api/fg/:fg/state/", get(flowgraph_state))
+            .route("/api/fg/:fg/terminate/", post(flowgraph_terminate))
+            .route("/api/factory/", get(factories))
+            .route("/api/factory/:name/", post(launch))
+            .route("/api/launched/:fg/", delete(delete_launched))
             .route("/api/fg/:fg/block/:blk/", get(block_description))
+            .route("/api/fg/:fg/block/:blk/stats/", get(block_stats))
             .route(
                 "/api/fg/:fg/block/:blk/call/:handler/",
                 get(handler_id).post(handler_id_post),
             )
+            .route(
+                "/api/fg/:fg/block/:blk/subscribe/:port/",
+                get(block_subscribe),
+            )
             .route(
                 "/api/block/*foo",
                 any(|uri: Uri| async move {
@@ -154,6 +546,7 @@ impl ControlPort {
                     Redirect::permanent(&format!("/api/fg/0/block/{u}/"))
                 }),
             )
+            .layer(Extension(self.factories.clone()))
             .layer(CorsLayer::permissive())
             .with_state(self.flowgraphs.clone());
 
@@ -180,6 +573,14 @@ impl ControlPort {
             ));
         }
 
+        let auth = Auth::from_config();
+        let auth_enabled = auth.is_enabled();
+        if auth_enabled {
+            app = app
+                .layer(middleware::from_fn(authorize))
+                .layer(Extension(Arc::new(auth)));
+        }
+
         let handle = std::thread::spawn(move || {
             let runtime = tokio::runtime::Builder::new_current_thread()
                 .enable_all()
@@ -188,6 +589,32 @@ impl ControlPort {
 
             runtime.block_on(async move {
                 let addr = config::config().ctrlport_bind.unwrap();
+
+                #[cfg(feature = "ctrlport_tls")]
+                if let (Some(cert), Some(key)) = (
+                    &config::config().ctrlport_tls_cert,
+                    &config::config().ctrlport_tls_key,
+                ) {
+                    let tls = match RustlsConfig::from_pem_file(cert, key).await {
+                        Ok(tls) => tls,
+                        Err(e) => {
+                            warn!("CtrlPort cannot load TLS certificate and key ({})", e);
+                            return;
+                        }
+                    };
+                    debug!("Listening on {} (TLS)", addr);
+                    if let Err(e) = axum_server::bind_rustls(addr, tls)
+                        .serve(app.into_make_service())
+                        .await
+                    {
+                        warn!("CtrlPort cannot serve on {} ({})", addr, e);
+                    }
+                    return;
+                }
+
+                if auth_enabled && !addr.ip().is_loopback() {
+                    warn!("CtrlPort tokens are sent unencrypted, consider enabling TLS");
+                }
                 if let Ok(s) = axum::Server::try_bind(&addr) {
                     debug!("Listening on {}", addr);
                     s.serve(app.into_make_service()).await.unwrap();
@@ -1,12 +1,18 @@
+use futures::channel::mpsc;
 use futures::channel::mpsc::Sender;
 use futures::channel::oneshot;
 use futures::SinkExt;
 use std::cmp::{Eq, PartialEq};
+use std::collections::HashMap;
 use std::fmt::Debug;
 use std::hash::Hash;
+use std::path::Path;
 use std::result;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
 
-use crate::anyhow::Result;
+use crate::anyhow::{anyhow, bail, Context, Result};
 #[cfg(not(target_arch = "wasm32"))]
 use crate::runtime::buffer::circular::Circular;
 #[cfg(target_arch = "wasm32")]
@@ -16,10 +22,18 @@ use crate::runtime::buffer::BufferWriter;
 use crate::runtime::Block;
 use crate::runtime::BlockDescription;
 use crate::runtime::BlockMessage;
+use crate::runtime::BlockRegistry;
+use crate::runtime::BlockStats;
 use crate::runtime::Error;
 use crate::runtime::FlowgraphDescription;
+use crate::runtime::FlowgraphEvent;
 use crate::runtime::FlowgraphMessage;
+use crate::runtime::FlowgraphSpec;
+use crate::runtime::FlowgraphState;
+use crate::runtime::FlowgraphStats;
+use crate::runtime::HierBlock;
 use crate::runtime::Kernel;
+use crate::runtime::MessageQueueConfig;
 use crate::runtime::Pmt;
 use crate::runtime::Topology;
 
@@ -29,6 +43,7 @@ use crate::runtime::Topology;
 /// There is at least one source and one sink in every Flowgraph.
 pub struct Flowgraph {
     pub(crate) topology: Option<Topology>,
+    pub(crate) terminate_on_error: bool,
 }
 
 impl Flowgraph {
@@ -36,14 +51,62 @@ impl Flowgraph {
     pub fn new() -> Flowgraph {
         Flowgraph {
             topology: Some(Topology::new()),
+            terminate_on_error: true,
         }
     }
 
+    /// Set whether the flowgraph is terminated, if a block fails
+    ///
+    /// This is the default. If disabled, only the neighbors of the failed block shut down and the
+    /// rest of the flowgraph keeps running. In both cases, the errors are returned as
+    /// [`FlowgraphError`](crate::runtime::FlowgraphError), once the flowgraph is done.
+    pub fn set_terminate_on_error(&mut self, terminate: bool) {
+        self.terminate_on_error = terminate;
+    }
+
+    /// Limit the latency of the stream connections
+    ///
+    /// The budget is split evenly between the connections of the longest path through the
+    /// flowgraph, limiting the number of items in flight on all connections (see
+    /// [`BufferBuilder::build_with_latency_cap`]). Connections with a smaller cap keep it. The
+    /// number of items is derived from `sample_rate`, assuming that all connections run at this
+    /// rate. When the flowgraph is reconfigured at runtime, the caps are recomputed for the
+    /// buffers of outputs that are connected or disconnected.
+    ///
+    /// A cap smaller than the number of items that a block needs to produce its output (e.g.,
+    /// the length of an [`Fft`](crate::blocks::Fft)) stalls the block, since the output never
+    /// has enough space.
+    ///
+    /// The measured latency is reported in the
+    /// [`FlowgraphDescription`](crate::runtime::FlowgraphDescription) of the running flowgraph.
+    pub fn set_latency_budget(&mut self, budget: Duration, sample_rate: f64) {
+        let items = (budget.as_secs_f64() * sample_rate).round() as usize;
+        self.topology.as_mut().unwrap().latency_budget = Some(items);
+    }
+
     /// Add [`Block`] to flowgraph
     pub fn add_block(&mut self, block: Block) -> usize {
         self.topology.as_mut().unwrap().add_block(block)
     }
 
+    /// Add [`HierBlock`] to flowgraph
+    ///
+    /// The inner blocks are added to the flowgraph. The returned Id can be used to connect the
+    /// ports that are exposed by the hierarchical block.
+    pub fn add_hier_block(&mut self, hier: HierBlock) -> Result<usize> {
+        self.topology.as_mut().unwrap().add_hier_block(hier)
+    }
+
+    /// Get Id of an inner block of a [`HierBlock`]
+    ///
+    /// `inner_block` is the Id of the block in the flowgraph that was wrapped by the hierarchical
+    /// block.
+    pub fn hier_block_id(&self, hier_block: usize, inner_block: usize) -> Option<usize> {
+        self.topology
+            .as_ref()
+            .and_then(|t| t.hier_block_id(hier_block, inner_block))
+    }
+
     /// Make stream connection
     pub fn connect_stream(
         &mut self,
@@ -79,7 +142,29 @@ impl Flowgraph {
         )
     }
 
+    /// Make stream connection, reinterpreting the items of the output as items of the input
+    ///
+    /// Opt-in for connections between ports with different item types of the same size, e.g.,
+    /// `Complex32` and `[f32; 2]`. The items are not converted, i.e., the bytes are reinterpreted.
+    pub fn connect_stream_reinterpret(
+        &mut self,
+        src_block: usize,
+        src_port: impl Into<PortId>,
+        dst_block: usize,
+        dst_port: impl Into<PortId>,
+    ) -> Result<()> {
+        self.topology.as_mut().unwrap().connect_stream_reinterpret(
+            src_block,
+            src_port.into(),
+            dst_block,
+            dst_port.into(),
+            DefaultBuffer::new(),
+        )
+    }
+
     /// Make message connection
+    ///
+    /// Posting to the output waits for space in the inbox of the receiving block.
     pub fn connect_message(
         &mut self,
         src_block: usize,
@@ -95,6 +180,111 @@ impl Flowgraph {
         )
     }
 
+    /// Make message connection with a bounded queue
+    ///
+    /// With [`MessageQueueConfig::DropOldest`] or [`MessageQueueConfig::DropNewest`], posting to
+    /// the output never waits for the receiving block. Instead, messages are dropped, if the queue
+    /// is full. The number of dropped messages is reported in the [`FlowgraphDescription`].
+    ///
+    /// The queue holds up to the configured number of messages, independent of the size of the
+    /// inbox of the receiving block (the `queue_size` config).
+    pub fn connect_message_with_queue(
+        &mut self,
+        src_block: usize,
+        src_port: impl Into<PortId>,
+        dst_block: usize,
+        dst_port: impl Into<PortId>,
+        queue: MessageQueueConfig,
+    ) -> Result<()> {
+        self.topology.as_mut().unwrap().connect_message_with_queue(
+            src_block,
+            src_port.into(),
+            dst_block,
+            dst_port.into(),
+            queue,
+        )
+    }
+
+    /// Create flowgraph from a [`FlowgraphSpec`]
+    ///
+    /// The blocks are created with the constructors of the [`BlockRegistry`] and get the names
+    /// of the specification as instance names. The parameters are kept, to be able to export the
+    /// flowgraph again with [`Flowgraph::to_spec`].
+    pub fn from_description(spec: &FlowgraphSpec, registry: &BlockRegistry) -> Result<Flowgraph> {
+        let mut fg = Flowgraph::new();
+        let mut ids = HashMap::new();
+
+        for b in spec.blocks.iter() {
+            if ids.contains_key(b.name.as_str()) {
+                bail!("duplicate block name {}", b.name);
+            }
+            let mut block = registry
+                .build(&b.type_name, &Pmt::MapStrPmt(b.parameters.clone()))
+                .with_context(|| format!("block {}", b.name))?;
+            block.set_instance_name(&b.name);
+            let id = fg.add_block(block);
+            fg.topology
+                .as_mut()
+                .unwrap()
+                .parameters
+                .insert(id, b.parameters.clone());
+            ids.insert(b.name.as_str(), id);
+        }
+
+        let id = |name: &str| {
+            ids.get(name)
+                .copied()
+                .ok_or_else(|| anyhow!("edge refers to unknown block {name}"))
+        };
+        for e in spec.stream_edges.iter() {
+            let (src, dst) = (id(&e.src)?, id(&e.dst)?);
+            let (src_port, dst_port) = (e.src_port.as_str(), e.dst_port.as_str());
+            if e.reinterpret {
+                fg.connect_stream_reinterpret(src, src_port, dst, dst_port)
+            } else {
+                fg.connect_stream(src, src_port, dst, dst_port)
+            }
+            .with_context(|| {
+                format!(
+                    "stream edge {}.{} -> {}.{}",
+                    e.src, e.src_port, e.dst, e.dst_port
+                )
+            })?;
+        }
+        for e in spec.message_edges.iter() {
+            fg.connect_message_with_queue(
+                id(&e.src)?,
+                e.src_port.as_str(),
+                id(&e.dst)?,
+                e.dst_port.as_str(),
+                e.queue,
+            )
+            .with_context(|| {
+                format!(
+                    "message edge {}.{} -> {}.{}",
+                    e.src, e.src_port, e.dst, e.dst_port
+                )
+            })?;
+        }
+
+        Ok(fg)
+    }
+
+    /// Load flowgraph from a TOML, YAML, or JSON file
+    ///
+    /// See [`FlowgraphSpec::load`] and [`Flowgraph::from_description`].
+    pub fn load(path: impl AsRef<Path>, registry: &BlockRegistry) -> Result<Flowgraph> {
+        Self::from_description(&FlowgraphSpec::load(path)?, registry)
+    }
+
+    /// Export flowgraph as [`FlowgraphSpec`]
+    ///
+    /// Blocks that were not created through a [`BlockRegistry`] are exported without parameters.
+    pub fn to_spec(&self) -> Result<FlowgraphSpec> {
+        let t = self.topology.as_ref().context("flowgraph is running")?;
+        FlowgraphSpec::from_description(&t.description(), &t.parameters)
+    }
+
     /// Try to get kernel from given block
     pub fn kernel<T: Kernel + 'static>(&self, id: usize) -> Option<&T> {
         self.topology
@@ -145,15 +335,27 @@ impl From<String> for PortId {
     }
 }
 
+/// Capacity of the channels to subscribers of messages and events
+const SUBSCRIBER_QUEUE_SIZE: usize = 64;
+
 /// Handle to interact with running [`Flowgraph`]
 #[derive(Debug, Clone)]
 pub struct FlowgraphHandle {
     inbox: Sender<FlowgraphMessage>,
+    state: Arc<Mutex<FlowgraphState>>,
 }
 
 impl FlowgraphHandle {
-    pub(crate) fn new(inbox: Sender<FlowgraphMessage>) -> FlowgraphHandle {
-        FlowgraphHandle { inbox }
+    pub(crate) fn new(
+        inbox: Sender<FlowgraphMessage>,
+        state: Arc<Mutex<FlowgraphState>>,
+    ) -> FlowgraphHandle {
+        FlowgraphHandle { inbox, state }
+    }
+
+    /// Get [`FlowgraphState`]
+    pub fn state(&self) -> FlowgraphState {
+        self.state.lock().unwrap().clone()
     }
 
     /// Call message handler, ignoring the result
@@ -216,6 +418,175 @@ impl FlowgraphHandle {
         Ok(d)
     }
 
+    /// Get [`FlowgraphStats`]
+    ///
+    /// Collects the statistics of all running blocks.
+    pub async fn stats(&mut self) -> Result<FlowgraphStats> {
+        let (tx, rx) = oneshot::channel::<FlowgraphStats>();
+        self.inbox
+            .send(FlowgraphMessage::FlowgraphStats { tx })
+            .await?;
+        let s = rx.await?;
+        Ok(s)
+    }
+
+    /// Get [`BlockStats`]
+    pub async fn block_stats(&mut self, block_id: usize) -> Result<BlockStats> {
+        let (tx, rx) = oneshot::channel::<result::Result<BlockStats, Error>>();
+        self.inbox
+            .send(FlowgraphMessage::BlockStats { block_id, tx })
+            .await?;
+        let s = rx.await??;
+        Ok(s)
+    }
+
+    /// Add [`Block`] to running flowgraph
+    ///
+    /// The block is started, once all its stream ports are connected.
+    pub async fn add_block(&mut self, block: Block) -> Result<usize> {
+        let (tx, rx) = oneshot::channel::<Result<usize>>();
+        self.inbox
+            .send(FlowgraphMessage::AddBlock { block, tx })
+            .await?;
+        rx.await?
+    }
+
+    /// Remove [`Block`] from running flowgraph
+    ///
+    /// The block is disconnected and terminated. Its neighbors keep running. Returns the block,
+    /// once it is shut down.
+    pub async fn remove_block(&mut self, block_id: usize) -> Result<Block> {
+        let (tx, rx) = oneshot::channel::<Result<Block>>();
+        self.inbox
+            .send(FlowgraphMessage::RemoveBlock { block_id, tx })
+            .await?;
+        rx.await?
+    }
+
+    /// Make stream connection in running flowgraph
+    ///
+    /// The buffer of the source port is replaced, i.e., samples that were not yet consumed by
+    /// the downstream blocks are dropped.
+    pub async fn connect_stream(
+        &mut self,
+        src_block: usize,
+        src_port: impl Into<PortId>,
+        dst_block: usize,
+        dst_port: impl Into<PortId>,
+    ) -> Result<()> {
+        let (tx, rx) = oneshot::channel::<Result<()>>();
+        self.inbox
+            .send(FlowgraphMessage::ConnectStream {
+                src_block,
+                src_port: src_port.into(),
+                dst_block,
+                dst_port: dst_port.into(),
+                tx,
+            })
+            .await?;
+        rx.await?
+    }
+
+    /// Remove stream connection from running flowgraph
+    ///
+    /// The destination port does not receive samples, until it is connected again.
+    pub async fn disconnect_stream(
+        &mut self,
+        src_block: usize,
+        src_port: impl Into<PortId>,
+        dst_block: usize,
+        dst_port: impl Into<PortId>,
+    ) -> Result<()> {
+        let (tx, rx) = oneshot::channel::<Result<()>>();
+        self.inbox
+            .send(FlowgraphMessage::DisconnectStream {
+                src_block,
+                src_port: src_port.into(),
+                dst_block,
+                dst_port: dst_port.into(),
+                tx,
+            })
+            .await?;
+        rx.await?
+    }
+
+    /// Make message connection in running flowgraph
+    pub async fn connect_message(
+        &mut self,
+        src_block: usize,
+        src_port: impl Into<PortId>,
+        dst_block: usize,
+        dst_port: impl Into<PortId>,
+    ) -> Result<()> {
+        let (tx, rx) = oneshot::channel::<Result<()>>();
+        self.inbox
+            .send(FlowgraphMessage::ConnectMessage {
+                src_block,
+                src_port: src_port.into(),
+                dst_block,
+                dst_port: dst_port.into(),
+                tx,
+            })
+            .await?;
+        rx.await?
+    }
+
+    /// Remove message connection from running flowgraph
+    pub async fn disconnect_message(
+        &mut self,
+        src_block: usize,
+        src_port: impl Into<PortId>,
+        dst_block: usize,
+        dst_port: impl Into<PortId>,
+    ) -> Result<()> {
+        let (tx, rx) = oneshot::channel::<Result<()>>();
+        self.inbox
+            .send(FlowgraphMessage::DisconnectMessage {
+                src_block,
+                src_port: src_port.into(),
+                dst_block,
+                dst_port: dst_port.into(),
+                tx,
+            })
+            .await?;
+        rx.await?
+    }
+
+    /// Subscribe to the messages that a block posts to a message output port
+    ///
+    /// Messages are dropped, if the subscriber does not keep up. The stream ends, once the port
+    /// is finished.
+    pub async fn subscribe_message(
+        &mut self,
+        block_id: usize,
+        port_id: impl Into<PortId>,
+    ) -> Result<mpsc::Receiver<Pmt>> {
+        let (sender, receiver) = mpsc::channel(SUBSCRIBER_QUEUE_SIZE);
+        let (tx, rx) = oneshot::channel::<Result<()>>();
+        self.inbox
+            .send(FlowgraphMessage::SubscribeMessage {
+                block_id,
+                port_id: port_id.into(),
+                sender,
+                tx,
+            })
+            .await?;
+        rx.await??;
+        Ok(receiver)
+    }
+
+    /// Subscribe to [`FlowgraphEvent`]s
+    ///
+    /// Events are dropped, if the subscriber does not keep up. The stream ends, once the
+    /// flowgraph is done.
+    pub async fn subscribe_events(&mut self) -> Result<mpsc::Receiver<FlowgraphEvent>> {
+        let (sender, receiver) = mpsc::channel(SUBSCRIBER_QUEUE_SIZE);
+        self.inbox
+            .send(FlowgraphMessage::SubscribeEvents { sender })
+            .await?;
+        Ok(receiver)
+    }
+
     /// Terminate
     pub async fn terminate(&mut self) -> Result<()> {
         self.inbox.send(FlowgraphMessage::Terminate).await?;
@@ -244,6 +615,16 @@ impl BufferBuilder for DefaultBuffer {
     ) -> BufferWriter {
         Circular::new().build(item_size, writer_inbox, writer_output_id)
     }
+    #[cfg(not(target_arch = "wasm32"))]
+    fn build_with_latency_cap(
+        &self,
+        item_size: usize,
+        writer_inbox: Sender<BlockMessage>,
+        writer_output_id: usize,
+        max_items: usize,
+    ) -> BufferWriter {
+        Circular::new().build_with_latency_cap(item_size, writer_inbox, writer_output_id, max_items)
+    }
     #[cfg(target_arch = "wasm32")]
     fn build(
         &self,
@@ -253,4 +634,14 @@ impl BufferBuilder for DefaultBuffer {
     ) -> BufferWriter {
         Slab::new().build(item_size, writer_inbox, writer_output_id)
     }
+    #[cfg(target_arch = "wasm32")]
+    fn build_with_latency_cap(
+        &self,
+        item_size: usize,
+        writer_inbox: Sender<BlockMessage>,
+        writer_output_id: usize,
+        max_items: usize,
+    ) -> BufferWriter {
+        Slab::new().build_with_latency_cap(item_size, writer_inbox, writer_output_id, max_items)
+    }
 }
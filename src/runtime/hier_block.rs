@@ -0,0 +1,170 @@
+use crate::runtime::Flowgraph;
+use crate::runtime::PortId;
+
+/// Hierarchical block
+///
+/// Wraps a [`Flowgraph`] and exposes selected ports of its blocks as ports of the hierarchical
+/// block. When added to a parent flowgraph, the inner blocks and connections are moved into the
+/// parent [`Topology`](crate::runtime::Topology), i.e., the hierarchy only exists while the
+/// flowgraph is constructed. Schedulers and the
+/// [`FlowgraphDescription`](crate::runtime::FlowgraphDescription) only see the flattened graph.
+///
+/// Use the [`HierBlockBuilder`] to create a hierarchical block.
+pub struct HierBlock {
+    pub(crate) name: String,
+    pub(crate) flowgraph: Flowgraph,
+    pub(crate) stream_inputs: Vec<(String, usize, PortId)>,
+    pub(crate) stream_outputs: Vec<(String, usize, PortId)>,
+    pub(crate) message_inputs: Vec<(String, usize, PortId)>,
+    pub(crate) message_outputs: Vec<(String, usize, PortId)>,
+}
+
+impl HierBlock {
+    /// Instance name of the hierarchical block
+    ///
+    /// Used as prefix for the instance names of the inner blocks.
+    pub fn instance_name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Hierarchical block builder
+///
+/// ```
+/// use futuresdr::anyhow::Result;
+/// use futuresdr::blocks::Apply;
+/// use futuresdr::runtime::Flowgraph;
+/// use futuresdr::runtime::HierBlockBuilder;
+///
+/// fn main() -> Result<()> {
+///     let mut inner = Flowgraph::new();
+///     let double = inner.add_block(Apply::new(|i: &f32| i * 2.0));
+///     let offset = inner.add_block(Apply::new(|i: &f32| i + 1.0));
+///     inner.connect_stream(double, "out", offset, "in")?;
+///
+///     let hier = HierBlockBuilder::new("DoubleOffset", inner)
+///         .stream_input("in", double, "in")
+///         .stream_output("out", offset, "out")
+///         .build();
+///
+///     let mut fg = Flowgraph::new();
+///     let _h = fg.add_hier_block(hier)?;
+///     Ok(())
+/// }
+/// ```
+pub struct HierBlockBuilder {
+    hier: HierBlock,
+}
+
+impl HierBlockBuilder {
+    /// Create builder, wrapping the inner [`Flowgraph`]
+    pub fn new(name: impl Into<String>, flowgraph: Flowgraph) -> HierBlockBuilder {
+        HierBlockBuilder {
+            hier: HierBlock {
+                name: name.into(),
+                flowgraph,
+                stream_inputs: Vec::new(),
+                stream_outputs: Vec::new(),
+                message_inputs: Vec::new(),
+                message_outputs: Vec::new(),
+            },
+        }
+    }
+
+    /// Expose stream input of an inner block
+    #[must_use]
+    pub fn stream_input(
+        mut self,
+        name: &str,
+        block: usize,
+        port: impl Into<PortId>,
+    ) -> HierBlockBuilder {
+        self.hier
+            .stream_inputs
+            .push((name.to_string(), block, port.into()));
+        self
+    }
+
+    /// Expose stream output of an inner block
+    #[must_use]
+    pub fn stream_output(
+        mut self,
+        name: &str,
+        block: usize,
+        port: impl Into<PortId>,
+    ) -> HierBlockBuilder {
+        self.hier
+            .stream_outputs
+            .push((name.to_string(), block, port.into()));
+        self
+    }
+
+    /// Expose message input (i.e., handler) of an inner block
+    #[must_use]
+    pub fn message_input(
+        mut self,
+        name: &str,
+        block: usize,
+        port: impl Into<PortId>,
+    ) -> HierBlockBuilder {
+        self.hier
+            .message_inputs
+            .push((name.to_string(), block, port.into()));
+        self
+    }
+
+    /// Expose message output of an inner block
+    #[must_use]
+    pub fn message_output(
+        mut self,
+        name: &str,
+        block: usize,
+        port: impl Into<PortId>,
+    ) -> HierBlockBuilder {
+        self.hier
+            .message_outputs
+            .push((name.to_string(), block, port.into()));
+        self
+    }
+
+    /// Build hierarchical block
+    pub fn build(self) -> HierBlock {
+        self.hier
+    }
+}
+
+/// Ports of a hierarchical block, resolved to (block id, port id) in the flattened topology.
+#[derive(Debug, Default)]
+pub(crate) struct HierPorts {
+    pub(crate) blocks: Vec<(usize, usize)>,
+    pub(crate) stream_inputs: Vec<(String, usize, usize)>,
+    pub(crate) stream_outputs: Vec<(String, usize, usize)>,
+    pub(crate) message_inputs: Vec<(String, usize, usize)>,
+    pub(crate) message_outputs: Vec<(String, usize, usize)>,
+}
+
+impl HierPorts {
+    fn lookup(ports: &[(String, usize, usize)], port: &PortId) -> Option<(usize, usize)> {
+        match port {
+            PortId::Index(i) => ports.get(*i),
+            PortId::Name(n) => ports.iter().find(|x| &x.0 == n),
+        }
+        .map(|x| (x.1, x.2))
+    }
+
+    pub(crate) fn stream_input(&self, port: &PortId) -> Option<(usize, usize)> {
+        Self::lookup(&self.stream_inputs, port)
+    }
+
+    pub(crate) fn stream_output(&self, port: &PortId) -> Option<(usize, usize)> {
+        Self::lookup(&self.stream_outputs, port)
+    }
+
+    pub(crate) fn message_input(&self, port: &PortId) -> Option<(usize, usize)> {
+        Self::lookup(&self.message_inputs, port)
+    }
+
+    pub(crate) fn message_output(&self, port: &PortId) -> Option<(usize, usize)> {
+        Self::lookup(&self.message_outputs, port)
+    }
+}
@@ -1,13 +1,18 @@
 //! Message/Event/RPC-based Ports
 use futures::channel::mpsc::Sender;
 use futures::prelude::*;
+use std::collections::VecDeque;
 use std::future::Future;
 use std::pin::Pin;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
+use std::sync::Mutex;
 
 use crate::anyhow::Result;
 use crate::runtime::BlockMessage;
 use crate::runtime::BlockMeta;
+use crate::runtime::MessageQueueConfig;
 use crate::runtime::Pmt;
 use crate::runtime::PortId;
 use crate::runtime::WorkIo;
@@ -89,11 +94,103 @@ impl<T: Send + ?Sized> MessageInput<T> {
     }
 }
 
+/// Queue of a message connection that drops messages, if it is full
+///
+/// The queue holds up to the configured capacity of messages, independent of the size of the
+/// inbox of the receiving block. Once a message is queued, one [`BlockMessage::Dequeue`] is sent
+/// to the receiving block, which then takes messages from the queue until it is empty. This way,
+/// the sending block never waits for the receiving block. The [`BlockMessage::Dequeue`] is
+/// delivered even if the inbox of the receiving block is full.
+#[derive(Debug)]
+pub struct MessageQueue {
+    config: MessageQueueConfig,
+    queue: Mutex<QueueState>,
+    dropped: AtomicU64,
+}
+
+#[derive(Debug, Default)]
+struct QueueState {
+    messages: VecDeque<Pmt>,
+    // a dequeue message is on its way to the receiving block
+    scheduled: bool,
+}
+
+impl MessageQueue {
+    pub(crate) fn new(config: MessageQueueConfig) -> Self {
+        MessageQueue {
+            config,
+            queue: Mutex::new(QueueState::default()),
+            dropped: AtomicU64::new(0),
+        }
+    }
+
+    /// Queue configuration
+    pub fn config(&self) -> MessageQueueConfig {
+        self.config
+    }
+
+    /// Number of dropped messages
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Take the oldest message from the queue
+    ///
+    /// Receivers of a [`BlockMessage::Dequeue`] call this until it returns `None`.
+    pub fn pop(&self) -> Option<Pmt> {
+        let mut queue = self.queue.lock().unwrap();
+        let p = queue.messages.pop_front();
+        if p.is_none() {
+            queue.scheduled = false;
+        }
+        p
+    }
+
+    fn push(self: &Arc<Self>, port_id: usize, sender: &mut Sender<BlockMessage>, p: Pmt) {
+        let capacity = match self.config {
+            MessageQueueConfig::Block => usize::MAX,
+            MessageQueueConfig::DropOldest(c) | MessageQueueConfig::DropNewest(c) => c,
+        };
+
+        let mut queue = self.queue.lock().unwrap();
+        if queue.messages.len() >= capacity {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+            if let MessageQueueConfig::DropOldest(_) = self.config {
+                queue.messages.pop_front();
+                queue.messages.push_back(p);
+            }
+        } else {
+            queue.messages.push_back(p);
+        }
+
+        if !queue.scheduled {
+            // A fresh sender always has a slot in the inbox, even if it is full. This bounds the
+            // inbox, since there is at most one dequeue message per queue on its way.
+            queue.scheduled = sender
+                .clone()
+                .try_send(BlockMessage::Dequeue {
+                    port_id: PortId::Index(port_id),
+                    queue: self.clone(),
+                })
+                .is_ok();
+        }
+    }
+
+    /// Queue the final message, which is never dropped
+    ///
+    /// Returns `true`, if a [`BlockMessage::Dequeue`] has to be sent.
+    fn push_finished(&self) -> bool {
+        let mut queue = self.queue.lock().unwrap();
+        queue.messages.push_back(Pmt::Finished);
+        !std::mem::replace(&mut queue.scheduled, true)
+    }
+}
+
 /// Message output port
 #[derive(Debug)]
 pub struct MessageOutput {
     name: String,
-    handlers: Vec<(usize, Sender<BlockMessage>)>,
+    handlers: Vec<(usize, Sender<BlockMessage>, Option<Arc<MessageQueue>>)>,
 }
 
 impl MessageOutput {
@@ -112,30 +209,63 @@ impl MessageOutput {
 
     /// Connect port to downstream message input
     pub fn connect(&mut self, port: usize, sender: Sender<BlockMessage>) {
-        self.handlers.push((port, sender));
+        self.handlers.push((port, sender, None));
+    }
+
+    /// Connect port to downstream message input through a [`MessageQueue`]
+    pub fn connect_with_queue(
+        &mut self,
+        port: usize,
+        sender: Sender<BlockMessage>,
+        queue: Arc<MessageQueue>,
+    ) {
+        self.handlers.push((port, sender, Some(queue)));
+    }
+
+    /// Disconnect port from downstream message input
+    pub fn disconnect(&mut self, port: usize, sender: &Sender<BlockMessage>) {
+        self.handlers
+            .retain(|(p, s, _)| !(*p == port && s.same_receiver(sender)));
     }
 
     /// Notify connected downstream message ports that we are finished
     pub async fn notify_finished(&mut self) {
-        for (port_id, sender) in self.handlers.iter_mut() {
-            let _ = sender
-                .send(BlockMessage::Call {
+        for (port_id, sender, queue) in self.handlers.iter_mut() {
+            let msg = if let Some(queue) = queue {
+                // do not overtake queued messages and do not drop this one
+                if !queue.push_finished() {
+                    continue;
+                }
+                BlockMessage::Dequeue {
+                    port_id: PortId::Index(*port_id),
+                    queue: queue.clone(),
+                }
+            } else {
+                BlockMessage::Call {
                     port_id: PortId::Index(*port_id),
                     data: Pmt::Finished,
-                })
-                .await;
+                }
+            };
+            let _ = sender.send(msg).await;
         }
     }
 
     /// Post data to connected downstream message port
+    ///
+    /// Waits for space in the inboxes of the receiving blocks, unless the connection has a
+    /// [`MessageQueue`] that drops messages.
     pub async fn post(&mut self, p: Pmt) {
-        for (port_id, sender) in self.handlers.iter_mut() {
-            let _ = sender
-                .send(BlockMessage::Call {
-                    port_id: PortId::Index(*port_id),
-                    data: p.clone(),
-                })
-                .await;
+        for (port_id, sender, queue) in self.handlers.iter_mut() {
+            if let Some(queue) = queue {
+                queue.push(*port_id, sender, p.clone());
+            } else {
+                let _ = sender
+                    .send(BlockMessage::Call {
+                        port_id: PortId::Index(*port_id),
+                        data: p.clone(),
+                    })
+                    .await;
+            }
         }
     }
 }
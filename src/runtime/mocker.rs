@@ -1,56 +1,109 @@
-use futures::channel::mpsc::Sender;
+use futures::channel::mpsc::{channel, Receiver, Sender};
+use futures::future::Either;
+use futures::Future;
+use futures::FutureExt;
+use futures::StreamExt;
 use std::any::Any;
 use std::fmt::Debug;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
+use crate::anyhow::Result;
+use crate::runtime::block::TypedBlockWrapper;
 use crate::runtime::buffer::BufferReaderHost;
 use crate::runtime::buffer::BufferWriterHost;
+use crate::runtime::config;
 use crate::runtime::BlockMessage;
 use crate::runtime::BufferReader;
 use crate::runtime::BufferWriter;
 use crate::runtime::ItemTag;
 use crate::runtime::Kernel;
+use crate::runtime::Pmt;
+use crate::runtime::PortId;
 use crate::runtime::TypedBlock;
 use crate::runtime::WorkIo;
 
 /// Mocker for a block
 ///
 /// A harness to run a block without a runtime. Used for unit tests and benchmarking.
+///
+/// Messages that the block posts to its message outputs are captured and can be read with
+/// [`messages`](Self::messages).
 pub struct Mocker<K> {
     block: TypedBlock<K>,
+    io: WorkIo,
+    inputs_finished: Vec<Option<Arc<AtomicBool>>>,
+    message_receivers: Vec<Receiver<BlockMessage>>,
+    messages: Vec<Vec<Pmt>>,
 }
 
 impl<K: Kernel + 'static> Mocker<K> {
     /// Create mocker
-    pub fn new(block: TypedBlock<K>) -> Self {
-        Mocker { block }
+    pub fn new(mut block: TypedBlock<K>) -> Self {
+        let mut message_receivers = Vec::new();
+        for o in block.mio.outputs_mut().iter_mut() {
+            let (tx, rx) = channel::<BlockMessage>(config::config().queue_size);
+            o.connect(0, tx);
+            message_receivers.push(rx);
+        }
+        let n_outputs = message_receivers.len();
+
+        Mocker {
+            block,
+            io: WorkIo {
+                call_again: false,
+                finished: false,
+                block_on: None,
+            },
+            inputs_finished: Vec::new(),
+            message_receivers,
+            messages: vec![Vec::new(); n_outputs],
+        }
     }
 
     /// Add input buffer with given data
+    ///
+    /// The input is marked as finished (see [`set_input_finished`](Self::set_input_finished)).
     pub fn input<T>(&mut self, id: usize, data: Vec<T>)
     where
         T: Debug + Send + 'static,
     {
-        self.block
-            .sio
-            .input(id)
-            .set_reader(BufferReader::Host(Box::new(MockReader::new(
-                data,
-                Vec::new(),
-            ))));
+        self.input_with_tags(id, data, Vec::new());
     }
 
     /// Add input buffer with given data and tags
+    ///
+    /// The input is marked as finished (see [`set_input_finished`](Self::set_input_finished)).
     pub fn input_with_tags<T>(&mut self, id: usize, data: Vec<T>, tags: Vec<ItemTag>)
     where
         T: Debug + Send + 'static,
     {
+        let finished = Arc::new(AtomicBool::new(true));
+        if self.inputs_finished.len() <= id {
+            self.inputs_finished.resize(id + 1, None);
+        }
+        self.inputs_finished[id] = Some(finished.clone());
         self.block
             .sio
             .input(id)
-            .set_reader(BufferReader::Host(Box::new(MockReader::new(data, tags))));
+            .set_reader(BufferReader::Host(Box::new(MockReader::new(
+                data, tags, finished,
+            ))));
+    }
+
+    /// Set whether the upstream block of an input is finished, i.e., no more data will arrive
+    pub fn set_input_finished(&mut self, id: usize, finished: bool) {
+        self.inputs_finished
+            .get(id)
+            .and_then(|f| f.as_ref())
+            .expect("mocker: input not initialized")
+            .store(finished, Ordering::SeqCst);
     }
 
     /// Initialize output buffer with given size
+    ///
+    /// The buffer is full, once `size` items are produced and not read with
+    /// [`output`](Self::output).
     pub fn init_output<T>(&mut self, id: usize, size: usize)
     where
         T: Debug + Send + 'static,
@@ -61,52 +114,215 @@ impl<K: Kernel + 'static> Mocker<K> {
             .init(BufferWriter::Host(Box::new(MockWriter::<T>::new(size))));
     }
 
-    /// Get data from output buffer
-    pub fn output<T>(&mut self, id: usize) -> Vec<T>
+    fn writer<T>(&mut self, id: usize) -> &mut MockWriter<T>
     where
         T: Debug + Send + 'static,
     {
         let w = self.block.sio.output(id).writer_mut();
         if let BufferWriter::Host(w) = w {
-            w.as_any().downcast_mut::<MockWriter<T>>().unwrap().get()
+            w.as_any().downcast_mut::<MockWriter<T>>().unwrap()
         } else {
             panic!("mocker: wrong output buffer (expected CPU, got Custom)");
         }
     }
 
+    /// Get data from output buffer
+    ///
+    /// This empties the buffer.
+    pub fn output<T>(&mut self, id: usize) -> Vec<T>
+    where
+        T: Debug + Send + 'static,
+    {
+        self.writer::<T>(id).get()
+    }
+
+    /// Get tags, produced on an output
+    ///
+    /// The `index` of the tags is the absolute offset of the item in the output stream.
+    pub fn output_tags<T>(&mut self, id: usize) -> Vec<ItemTag>
+    where
+        T: Debug + Send + 'static,
+    {
+        std::mem::take(&mut self.writer::<T>(id).tags)
+    }
+
+    /// Get messages, posted to a message output
+    pub fn messages(&mut self, id: usize) -> Vec<Pmt> {
+        std::mem::take(&mut self.messages[id])
+    }
+
+    /// Check, if the block marked itself as finished
+    pub fn finished(&self) -> bool {
+        self.io.finished
+    }
+
+    /// Initialize the block
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn init(&mut self) -> Result<()> {
+        crate::async_io::block_on(self.init_async())
+    }
+
+    /// Initialize the block async
+    pub async fn init_async(&mut self) -> Result<()> {
+        let b = &mut self.block;
+        collecting(
+            &mut self.message_receivers,
+            &mut self.messages,
+            b.kernel.init(&mut b.sio, &mut b.mio, &mut b.meta),
+        )
+        .await
+    }
+
+    /// Deinitialize the block
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn deinit(&mut self) -> Result<()> {
+        crate::async_io::block_on(self.deinit_async())
+    }
+
+    /// Deinitialize the block async
+    pub async fn deinit_async(&mut self) -> Result<()> {
+        let b = &mut self.block;
+        collecting(
+            &mut self.message_receivers,
+            &mut self.messages,
+            b.kernel.deinit(&mut b.sio, &mut b.mio, &mut b.meta),
+        )
+        .await
+    }
+
+    /// Call a message handler of the block, returning its result
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn post(&mut self, port: impl Into<PortId>, p: Pmt) -> Result<Pmt> {
+        crate::async_io::block_on(self.post_async(port, p))
+    }
+
+    /// Call a message handler of the block async, returning its result
+    pub async fn post_async(&mut self, port: impl Into<PortId>, p: Pmt) -> Result<Pmt> {
+        let b = &mut self.block;
+        let ret = collecting(
+            &mut self.message_receivers,
+            &mut self.messages,
+            TypedBlockWrapper::call_handler(
+                &mut self.io,
+                &mut b.mio,
+                &mut b.meta,
+                &mut b.kernel,
+                port.into(),
+                p,
+            ),
+        )
+        .await;
+        Ok(ret??)
+    }
+
     /// Run the mocker
+    ///
+    /// Calls `work()` until the block is finished or quiescent, i.e., it neither asks to be called
+    /// again nor consumes, produces, or posts anything. Panics, if `work()` fails.
     #[cfg(not(target_arch = "wasm32"))]
     pub fn run(&mut self) {
         crate::async_io::block_on(self.run_async());
     }
 
     /// Run the mocker async
+    ///
+    /// See [`run`](Self::run).
     pub async fn run_async(&mut self) {
-        let mut io = WorkIo {
-            call_again: false,
-            finished: false,
-            block_on: None,
-        };
-
         loop {
-            self.block
-                .kernel
-                .work(
-                    &mut io,
-                    &mut self.block.sio,
-                    &mut self.block.mio,
-                    &mut self.block.meta,
-                )
-                .await
-                .unwrap();
-            self.block.sio.commit();
-            if !io.call_again {
+            let progress = self.work().await.unwrap();
+            if self.io.finished || !(progress || self.io.call_again) {
                 break;
-            } else {
-                io.call_again = false;
             }
         }
     }
+
+    /// Call `work()` the given number of times, or until the block is finished
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn run_for(&mut self, n: usize) -> Result<()> {
+        crate::async_io::block_on(self.run_for_async(n))
+    }
+
+    /// Call `work()` the given number of times, or until the block is finished, async
+    pub async fn run_for_async(&mut self, n: usize) -> Result<()> {
+        for _ in 0..n {
+            self.work().await?;
+            if self.io.finished {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Call `work()` once, returning whether the block consumed, produced, or posted anything
+    async fn work(&mut self) -> Result<bool> {
+        let before = self.items();
+        let n_messages = self.n_messages();
+
+        self.io.call_again = false;
+        self.io.block_on = None;
+        let b = &mut self.block;
+        let ret = collecting(
+            &mut self.message_receivers,
+            &mut self.messages,
+            b.kernel
+                .work(&mut self.io, &mut b.sio, &mut b.mio, &mut b.meta),
+        )
+        .await;
+        b.sio.commit();
+        ret?;
+
+        Ok(self.items() != before || self.n_messages() != n_messages)
+    }
+
+    fn items(&self) -> u64 {
+        let sio = &self.block.sio;
+        sio.inputs().iter().map(|i| i.items_consumed()).sum::<u64>()
+            + sio
+                .outputs()
+                .iter()
+                .map(|o| o.items_produced())
+                .sum::<u64>()
+    }
+
+    fn n_messages(&self) -> usize {
+        self.messages.iter().map(|m| m.len()).sum()
+    }
+}
+
+/// Run `f`, collecting the messages that it posts to the message outputs concurrently
+///
+/// The outputs are drained while `f` runs, so the block never waits for space in the channels,
+/// no matter how many messages it posts.
+async fn collecting<F: Future>(
+    receivers: &mut [Receiver<BlockMessage>],
+    messages: &mut [Vec<Pmt>],
+    f: F,
+) -> F::Output {
+    let mut incoming = futures::stream::select_all(
+        receivers
+            .iter_mut()
+            .enumerate()
+            .map(|(i, rx)| rx.map(move |m| (i, m))),
+    );
+    let mut push = |(i, m): (usize, BlockMessage)| {
+        if let BlockMessage::Call { data, .. } = m {
+            messages[i].push(data);
+        }
+    };
+    futures::pin_mut!(f);
+
+    loop {
+        match futures::future::select(f.as_mut(), incoming.next()).await {
+            Either::Left((ret, _)) => {
+                while let Some(Some(m)) = incoming.next().now_or_never() {
+                    push(m);
+                }
+                return ret;
+            }
+            Either::Right((Some(m), _)) => push(m),
+            Either::Right((None, _)) => return f.await,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -114,14 +330,16 @@ struct MockReader<T: Debug + Send + 'static> {
     data: Vec<T>,
     index: usize,
     tags: Vec<ItemTag>,
+    finished: Arc<AtomicBool>,
 }
 
 impl<T: Debug + Send + 'static> MockReader<T> {
-    pub fn new(data: Vec<T>, tags: Vec<ItemTag>) -> Self {
+    pub fn new(data: Vec<T>, tags: Vec<ItemTag>, finished: Arc<AtomicBool>) -> Self {
         MockReader {
             data,
             index: 0,
             tags,
+            finished,
         }
     }
 }
@@ -149,26 +367,34 @@ impl<T: Debug + Send + 'static> BufferReaderHost for MockReader<T> {
         }
     }
     async fn notify_finished(&mut self) {}
-    fn finish(&mut self) {}
+    fn finish(&mut self) {
+        self.finished.store(true, Ordering::SeqCst);
+    }
     fn finished(&self) -> bool {
-        true
+        self.finished.load(Ordering::SeqCst)
     }
 }
 
 #[derive(Debug)]
 struct MockWriter<T: Debug + Send + 'static> {
     data: Vec<T>,
+    size: usize,
+    produced: usize,
+    tags: Vec<ItemTag>,
 }
 
 impl<T: Debug + Send + 'static> MockWriter<T> {
     pub fn new(size: usize) -> Self {
         MockWriter::<T> {
             data: Vec::with_capacity(size),
+            size,
+            produced: 0,
+            tags: Vec::new(),
         }
     }
 
     pub fn get(&mut self) -> Vec<T> {
-        std::mem::take(&mut self.data)
+        std::mem::replace(&mut self.data, Vec::with_capacity(self.size))
     }
 }
 
@@ -185,10 +411,15 @@ impl<T: Debug + Send + 'static> BufferWriterHost for MockWriter<T> {
         self
     }
 
-    fn produce(&mut self, amount: usize, _tags: Vec<ItemTag>) {
+    fn produce(&mut self, amount: usize, tags: Vec<ItemTag>) {
         unsafe {
             self.data.set_len(self.data.len() + amount);
         }
+        for mut t in tags {
+            t.index += self.produced;
+            self.tags.push(t);
+        }
+        self.produced += amount;
     }
 
     fn bytes(&mut self) -> (*mut u8, usize) {
@@ -1,12 +1,16 @@
 //! ## SDR Runtime
 use futures::channel::mpsc;
 use futures::channel::oneshot;
+use std::fmt;
 use std::result;
+use std::sync::Arc;
 use thiserror::Error;
 
 mod block;
 mod block_meta;
 pub mod buffer;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod clock;
 pub mod config;
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -26,11 +30,14 @@ mod logging;
 mod logging;
 
 mod flowgraph;
+mod hier_block;
 pub mod message_io;
 mod mocker;
+mod registry;
 #[allow(clippy::module_inception)]
 mod runtime;
 pub mod scheduler;
+mod spec;
 pub mod stream_io;
 mod tag;
 mod topology;
@@ -41,26 +48,50 @@ pub use block::TypedBlock;
 pub use block::WorkIo;
 pub use block_meta::BlockMeta;
 pub use block_meta::BlockMetaBuilder;
+pub use block_meta::Priority;
+pub use block_meta::RestartPolicy;
+pub use block_meta::SchedulingHints;
 pub use flowgraph::Flowgraph;
 pub use flowgraph::FlowgraphHandle;
 pub use flowgraph::PortId;
+pub use hier_block::HierBlock;
+pub use hier_block::HierBlockBuilder;
 pub use message_io::MessageInput;
 pub use message_io::MessageIo;
 pub use message_io::MessageIoBuilder;
 pub use message_io::MessageOutput;
+pub use message_io::MessageQueue;
 pub use mocker::Mocker;
+pub use registry::BlockRegistry;
 pub use runtime::Runtime;
+pub use spec::BlockSpec;
+pub use spec::EdgeSpec;
+pub use spec::FlowgraphSpec;
+pub use spec::SpecFormat;
+pub use stream_io::LatencyProbe;
 pub use stream_io::StreamInput;
 pub use stream_io::StreamIo;
 pub use stream_io::StreamIoBuilder;
 pub use stream_io::StreamOutput;
 pub use tag::ItemTag;
+pub use tag::SampleClock;
 pub use tag::Tag;
+pub use tag::TagPropagation;
+pub use tag::TagPropagationFn;
 pub use topology::Topology;
 
 pub use futuresdr_types::BlockDescription;
+pub use futuresdr_types::BlockStats;
 pub use futuresdr_types::FlowgraphDescription;
+pub use futuresdr_types::FlowgraphEvent;
+pub use futuresdr_types::FlowgraphState;
+pub use futuresdr_types::FlowgraphStats;
+pub use futuresdr_types::MessageQueueConfig;
+pub use futuresdr_types::MessageQueueDescription;
 pub use futuresdr_types::Pmt;
+pub use futuresdr_types::StreamInputStats;
+pub use futuresdr_types::StreamLatencyDescription;
+pub use futuresdr_types::StreamOutputStats;
 
 use buffer::BufferReader;
 use buffer::BufferWriter;
@@ -97,6 +128,8 @@ pub enum FlowgraphMessage {
         block_id: usize,
         /// Block
         block: Block,
+        /// Error
+        error: BlockError,
     },
     /// Call handler of block (ignoring result)
     BlockCall {
@@ -132,6 +165,100 @@ pub enum FlowgraphMessage {
         /// Back channel for result
         tx: oneshot::Sender<result::Result<BlockDescription, Error>>,
     },
+    /// Get [`FlowgraphStats`]
+    FlowgraphStats {
+        /// Back channel for result
+        tx: oneshot::Sender<FlowgraphStats>,
+    },
+    /// Get [`BlockStats`]
+    BlockStats {
+        /// Block Id
+        block_id: usize,
+        /// Back channel for result
+        tx: oneshot::Sender<result::Result<BlockStats, Error>>,
+    },
+    /// Add block to running flowgraph
+    AddBlock {
+        /// Block
+        block: Block,
+        /// Back channel for Id of the block
+        tx: oneshot::Sender<anyhow::Result<usize>>,
+    },
+    /// Remove block from running flowgraph
+    RemoveBlock {
+        /// Block Id
+        block_id: usize,
+        /// Back channel for the terminated block
+        tx: oneshot::Sender<anyhow::Result<Block>>,
+    },
+    /// Connect stream ports of running flowgraph
+    ConnectStream {
+        /// Source block Id
+        src_block: usize,
+        /// Source port Id
+        src_port: PortId,
+        /// Destination block Id
+        dst_block: usize,
+        /// Destination port Id
+        dst_port: PortId,
+        /// Back channel for result
+        tx: oneshot::Sender<anyhow::Result<()>>,
+    },
+    /// Disconnect stream ports of running flowgraph
+    DisconnectStream {
+        /// Source block Id
+        src_block: usize,
+        /// Source port Id
+        src_port: PortId,
+        /// Destination block Id
+        dst_block: usize,
+        /// Destination port Id
+        dst_port: PortId,
+        /// Back channel for result
+        tx: oneshot::Sender<anyhow::Result<()>>,
+    },
+    /// Connect message ports of running flowgraph
+    ConnectMessage {
+        /// Source block Id
+        src_block: usize,
+        /// Source port Id
+        src_port: PortId,
+        /// Destination block Id
+        dst_block: usize,
+        /// Destination port Id
+        dst_port: PortId,
+        /// Back channel for result
+        tx: oneshot::Sender<anyhow::Result<()>>,
+    },
+    /// Disconnect message ports of running flowgraph
+    DisconnectMessage {
+        /// Source block Id
+        src_block: usize,
+        /// Source port Id
+        src_port: PortId,
+        /// Destination block Id
+        dst_block: usize,
+        /// Destination port Id
+        dst_port: PortId,
+        /// Back channel for result
+        tx: oneshot::Sender<anyhow::Result<()>>,
+    },
+    /// Subscribe to the messages of a message output port
+    SubscribeMessage {
+        /// Block Id
+        block_id: usize,
+        /// Message output port Id
+        port_id: PortId,
+        /// Channel for the messages
+        sender: mpsc::Sender<Pmt>,
+        /// Back channel for result
+        tx: oneshot::Sender<anyhow::Result<()>>,
+    },
+    /// Subscribe to [`FlowgraphEvent`]s
+    SubscribeEvents {
+        /// Channel for the events
+        sender: mpsc::Sender<FlowgraphEvent>,
+    },
 }
 
 /// Block inbox message type
@@ -148,12 +275,20 @@ pub enum BlockMessage {
         /// Channel for return value
         tx: oneshot::Sender<BlockDescription>,
     },
+    /// Get [`BlockStats`]
+    BlockStats {
+        /// Channel for return value
+        tx: oneshot::Sender<BlockStats>,
+    },
     /// Initialize [`StreamOutput`]
     StreamOutputInit {
         /// Stream output ID
         src_port: usize,
         /// [`BufferWriter`]
         writer: BufferWriter,
+
+        /// Latency probes of the connected edges
+        latency: Vec<Arc<LatencyProbe>>,
     },
     /// Initialize [`StreamInput`]
     StreamInputInit {
@@ -161,6 +296,9 @@ pub enum BlockMessage {
         dst_port: usize,
         /// [`BufferReader`]
         reader: BufferReader,
+
+        /// Latency probe of the connected edge
+        latency: Option<Arc<LatencyProbe>>,
     },
     /// Stream input port is done
     StreamInputDone {
@@ -180,6 +318,17 @@ pub enum BlockMessage {
         dst_port: usize,
         /// Destination block inbox
         dst_inbox: mpsc::Sender<BlockMessage>,
+        /// Queue of the connection, if it drops messages
+        queue: Option<Arc<MessageQueue>>,
+    },
+    /// Disconnect message output
+    MessageOutputDisconnect {
+        /// Message output port Id
+        src_port: usize,
+        /// Destination input port Id
+        dst_port: usize,
+        /// Destination block inbox
+        dst_inbox: mpsc::Sender<BlockMessage>,
     },
     /// Call handler (return value is ignored)
     Call {
@@ -188,6 +337,14 @@ pub enum BlockMessage {
         /// [`Pmt`] input data
         data: Pmt,
     },
+    /// Call handler with the messages of a [`MessageQueue`], until it is empty (return value is
+    /// ignored)
+    Dequeue {
+        /// Message handler Id
+        port_id: PortId,
+        /// Queue of the message connection
+        queue: Arc<MessageQueue>,
+    },
     /// Call handler
     Callback {
         /// Message handler Id
@@ -219,3 +376,61 @@ pub enum Error {
     #[error("Error in runtime")]
     RuntimeError,
 }
+
+/// Lifecycle phase of a block
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockPhase {
+    /// `init()`
+    Init,
+    /// `work()`
+    Work,
+    /// `deinit()`
+    Deinit,
+    /// Message handler
+    Handler,
+}
+
+impl fmt::Display for BlockPhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BlockPhase::Init => write!(f, "init()"),
+            BlockPhase::Work => write!(f, "work()"),
+            BlockPhase::Deinit => write!(f, "deinit()"),
+            BlockPhase::Handler => write!(f, "message handler"),
+        }
+    }
+}
+
+/// Error of a block that failed while the flowgraph was running
+#[derive(Error, Debug)]
+#[error("block {block_id} ({instance_name}) failed in {phase}: {error:#}")]
+pub struct BlockError {
+    /// Block Id
+    pub block_id: usize,
+    /// Instance name of the block
+    pub instance_name: String,
+    /// Phase, in which the block failed
+    pub phase: BlockPhase,
+    /// Error returned by the block
+    pub error: anyhow::Error,
+}
+
+/// Error returned by a flowgraph, if one or more blocks failed
+///
+/// [`Runtime::run`] returns this error wrapped in an [`anyhow::Error`]. Use `downcast_ref` to
+/// get it back.
+#[derive(Error, Debug)]
+pub struct FlowgraphError {
+    /// Errors of all failed blocks
+    pub errors: Vec<BlockError>,
+}
+
+impl fmt::Display for FlowgraphError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "flowgraph error")?;
+        for (i, e) in self.errors.iter().enumerate() {
+            write!(f, "{} {e}", if i == 0 { ":" } else { ";" })?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::anyhow::{anyhow, bail, Context, Result};
+use crate::runtime::Block;
+use crate::runtime::Pmt;
+
+type Constructor = Box<dyn Fn(&Pmt) -> Result<Block> + Send + Sync>;
+
+/// Block Registry
+///
+/// Maps type names to constructors that create a [`Block`] from a [`Pmt::MapStrPmt`] of
+/// parameters. It is used to instantiate the blocks of a
+/// [`FlowgraphSpec`](crate::runtime::FlowgraphSpec).
+///
+/// [`BlockRegistry::new`] creates a registry with the built-in blocks of
+/// [`futuresdr::blocks`](crate::blocks). Generic blocks take an `item_type` parameter (`u8`,
+/// `u16`, `u32`, `u64`, `i8`, `i16`, `i32`, `i64`, `f32`, `f64`, `Complex32`, or `Complex64`).
+///
+/// | Type | Parameters |
+/// |---|---|
+/// | `NullSource`, `NullSink`, `Copy` | `item_type` |
+/// | `CopyRand` | `item_type`, `max_copy` |
+/// | `Head` | `item_type`, `n_items` |
+/// | `VectorSource` | `item_type`, `items` (complex items as `[re, im]`) |
+/// | `VectorSink` | `item_type`, optional `capacity` |
+/// | `ConsoleSink` | `item_type`, optional `sep` |
+/// | `TagDebug` | `item_type`, `name` |
+/// | `Throttle` | `item_type`, `rate` |
+/// | `FileSource` | `item_type`, `file_name`, optional `repeat` |
+/// | `FileSink` | `item_type`, `file_name` |
+/// | `ShmSource`, `ShmSink` | `item_type`, `name` |
+/// | `Fft` | `len`, optional `inverse`, `fft_shift`, and `normalize` |
+/// | `Fir` | `item_type` (`f32` or `Complex32`), `taps` |
+/// | `FftFilter` | `item_type` (`f32` or `Complex32`), `taps`, optional `fft_size` |
+/// | `Iir` | `item_type` (`f32` or `f64`), `a_taps`, `b_taps` |
+/// | `PfbChannelizer`, `PfbSynthesizer` | `num_channels`, optional `taps` and `oversampled` |
+/// | `TcpSource`, `TcpSink` | `port` |
+/// | `BlobToUdp` | `remote` |
+/// | `MessageSink`, `MessageCopy` | |
+/// | `MessageBurst` | `n_messages`, optional `message` |
+/// | `MessageSource` | `interval` in seconds, optional `message` and `n_messages` |
+///
+/// Blocks that take closures (e.g., `Apply`, `Combine`, `Filter`, `Source`, or `Sink`),
+/// channels, or hardware handles cannot be described by parameters and are not registered.
+/// Use [`BlockRegistry::register`] to add constructors for them or for custom blocks.
+///
+/// Type names should match the [`type_name`](Block::type_name) of the created block, to be able
+/// to export a flowgraph and load it again.
+pub struct BlockRegistry {
+    constructors: HashMap<String, Constructor>,
+}
+
+impl BlockRegistry {
+    /// Create registry with the built-in blocks
+    pub fn new() -> Self {
+        let mut r = Self::empty();
+        crate::blocks::registry::register_builtin(&mut r);
+        r
+    }
+
+    /// Create empty registry
+    pub fn empty() -> Self {
+        BlockRegistry {
+            constructors: HashMap::new(),
+        }
+    }
+
+    /// Register a block constructor
+    ///
+    /// Replaces a constructor that was registered for the same type name.
+    pub fn register<F>(&mut self, type_name: impl Into<String>, constructor: F)
+    where
+        F: Fn(&Pmt) -> Result<Block> + Send + Sync + 'static,
+    {
+        self.constructors
+            .insert(type_name.into(), Box::new(constructor));
+    }
+
+    /// Check if a constructor is registered for the type name
+    pub fn contains(&self, type_name: &str) -> bool {
+        self.constructors.contains_key(type_name)
+    }
+
+    /// Registered type names
+    pub fn type_names(&self) -> Vec<&str> {
+        let mut v: Vec<&str> = self.constructors.keys().map(|x| x.as_str()).collect();
+        v.sort_unstable();
+        v
+    }
+
+    /// Create a block
+    ///
+    /// `parameters` has to be a [`Pmt::MapStrPmt`].
+    pub fn build(&self, type_name: &str, parameters: &Pmt) -> Result<Block> {
+        let c = self.constructors.get(type_name).ok_or_else(|| {
+            anyhow!(
+                "no block registered for type {type_name} (registered types: {})",
+                self.type_names().join(", ")
+            )
+        })?;
+        if !matches!(parameters, Pmt::MapStrPmt(_)) {
+            bail!("parameters of {type_name} are not a map");
+        }
+        c(parameters).with_context(|| format!("failed to create {type_name}"))
+    }
+}
+
+impl Default for BlockRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for BlockRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BlockRegistry")
+            .field("type_names", &self.type_names())
+            .finish()
+    }
+}
@@ -1,35 +1,50 @@
 #[cfg(not(target_arch = "wasm32"))]
-use async_io::block_on;
-#[cfg(not(target_arch = "wasm32"))]
 use axum::Router;
 use futures::channel::mpsc::{channel, Receiver, Sender};
 use futures::channel::oneshot;
 use futures::prelude::*;
 use futures::FutureExt;
+use slab::Slab;
+use std::collections::HashMap;
+use std::collections::HashSet;
 use std::future::Future;
 use std::pin::Pin;
 use std::result;
+use std::sync::Arc;
+use std::sync::Mutex;
 use std::task;
 use std::task::Poll;
 
-use crate::anyhow::{bail, Context, Result};
+use crate::anyhow::{anyhow, bail, Context, Result};
 use crate::runtime;
+use crate::runtime::buffer::BufferBuilder;
 use crate::runtime::config;
+use crate::runtime::flowgraph::DefaultBuffer;
 use crate::runtime::scheduler::Scheduler;
 #[cfg(not(target_arch = "wasm32"))]
 use crate::runtime::scheduler::SmolScheduler;
 use crate::runtime::scheduler::Task;
 #[cfg(target_arch = "wasm32")]
 use crate::runtime::scheduler::WasmScheduler;
+use crate::runtime::Block;
 use crate::runtime::BlockDescription;
 use crate::runtime::BlockMessage;
+use crate::runtime::BlockStats;
 use crate::runtime::ControlPort;
 use crate::runtime::Error;
 use crate::runtime::Flowgraph;
 use crate::runtime::FlowgraphDescription;
+use crate::runtime::FlowgraphError;
+use crate::runtime::FlowgraphEvent;
 use crate::runtime::FlowgraphHandle;
 use crate::runtime::FlowgraphMessage;
+use crate::runtime::FlowgraphState;
+use crate::runtime::FlowgraphStats;
+use crate::runtime::MessageQueue;
+use crate::runtime::MessageQueueConfig;
 use crate::runtime::Pmt;
+use crate::runtime::PortId;
+use crate::runtime::Topology;
 
 pub struct TaskHandle<'a, T> {
     task: Task<T>,
@@ -147,7 +162,7 @@ impl<'a, S: Scheduler> Runtime<'a, S> {
         &self,
         future: impl Future<Output = T> + Send + 'static,
     ) -> T {
-        block_on(self.scheduler.spawn(future))
+        self.scheduler.block_on(self.scheduler.spawn(future))
     }
 
     /// Spawn task on runtime in background, detaching the handle
@@ -188,29 +203,45 @@ impl<'a, S: Scheduler> Runtime<'a, S> {
     where
         'a: 'b,
     {
-        let queue_size = config::config().queue_size;
-        let (fg_inbox, fg_inbox_rx) = channel::<FlowgraphMessage>(queue_size);
-
-        let (tx, rx) = oneshot::channel::<()>();
-        let task = self.scheduler.spawn(run_flowgraph(
-            fg,
-            self.scheduler.clone(),
-            fg_inbox.clone(),
-            fg_inbox_rx,
-            tx,
-        ));
-        rx.await
+        let (task, handle) = start_flowgraph(self.scheduler.clone(), fg)
+            .await
             .expect("run_flowgraph did not signal startup completed");
-        let handle = FlowgraphHandle::new(fg_inbox);
         self.control_port.add_flowgraph(handle.clone());
         (TaskHandle::new(task), handle)
     }
 
+    /// Register a factory to launch flowgraphs through the control port
+    ///
+    /// Clients launch a flowgraph by the name of the factory, passing a [`Pmt`] with
+    /// parameters. The flowgraph runs on the scheduler of this runtime and is registered with
+    /// the control port, like flowgraphs that are started locally. Launched flowgraphs stay
+    /// registered after they are done, so that clients can query their state, until a client
+    /// deletes them with `DELETE /api/launched/<id>/`. Ids are never reused.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn register_factory(
+        &self,
+        name: impl Into<String>,
+        factory: impl Fn(Pmt) -> Result<Flowgraph> + Send + Sync + 'static,
+    ) {
+        let scheduler = Mutex::new(self.scheduler.clone());
+        let launcher = move |fg: Flowgraph| -> future::BoxFuture<'static, Result<FlowgraphHandle>> {
+            let scheduler = scheduler.lock().unwrap().clone();
+            async move {
+                let (task, handle) = start_flowgraph(scheduler, fg).await?;
+                task.detach();
+                Ok(handle)
+            }
+            .boxed()
+        };
+        self.control_port
+            .register_factory(name.into(), Arc::new(factory), Arc::new(launcher));
+    }
+
     /// Main method that kicks-off the running of a [Flowgraph].
     #[cfg(not(target_arch = "wasm32"))]
     pub fn run(&self, fg: Flowgraph) -> Result<Flowgraph> {
-        let (handle, _) = block_on(self.start(fg));
-        block_on(handle)
+        let (handle, _) = self.scheduler.block_on(self.start(fg));
+        self.scheduler.block_on(handle)
     }
 
     /// Async version of `run`, mainly for WASM.
@@ -225,6 +256,41 @@ impl<'a, S: Scheduler> Runtime<'a, S> {
     }
 }
 
+/// Spawn the flowgraph on the scheduler and wait, until it is running
+///
+/// The state of the returned handle is updated, once the flowgraph is done.
+async fn start_flowgraph<S: Scheduler>(
+    scheduler: S,
+    fg: Flowgraph,
+) -> Result<(Task<Result<Flowgraph>>, FlowgraphHandle)> {
+    let queue_size = config::config().queue_size;
+    let (fg_inbox, fg_inbox_rx) = channel::<FlowgraphMessage>(queue_size);
+    let state = Arc::new(Mutex::new(FlowgraphState::Running));
+
+    let (tx, rx) = oneshot::channel::<()>();
+    let task = {
+        let fg_inbox = fg_inbox.clone();
+        let state = state.clone();
+        let s = scheduler.clone();
+        scheduler.spawn(async move {
+            let result = run_flowgraph(fg, s, fg_inbox, fg_inbox_rx, tx).await;
+            *state.lock().unwrap() = match &result {
+                Ok(_) => FlowgraphState::Finished,
+                Err(e) => FlowgraphState::Failed(format!("{e:#}")),
+            };
+            result
+        })
+    };
+    if rx.await.is_err() {
+        // flowgraph failed during startup
+        return Err(match task.await {
+            Err(e) => e,
+            Ok(_) => anyhow!("flowgraph did not start"),
+        });
+    }
+    Ok((task, FlowgraphHandle::new(fg_inbox, state)))
+}
+
 async fn run_flowgraph<S: Scheduler>(
     mut fg: Flowgraph,
     scheduler: S,
@@ -234,20 +300,28 @@ async fn run_flowgraph<S: Scheduler>(
 ) -> Result<Flowgraph> {
     debug!("in run_flowgraph");
     let mut topology = fg.topology.take().context("flowgraph not initialized")?;
+    topology.remove_hier_placeholders();
     topology.validate()?;
+    topology.record_ports();
+    topology.apply_latency_budget();
 
     let mut inboxes = scheduler.run_topology(&mut topology, &main_channel);
 
     debug!("connect stream io");
     // connect stream IO
+    let mut probes = Vec::new();
     for ((src, src_port, buffer_builder), v) in topology.stream_edges.iter() {
         debug_assert!(!v.is_empty());
 
         let src_inbox = inboxes[*src].as_ref().unwrap().clone();
-        let mut writer = buffer_builder.build(src_inbox, *src_port);
+        let mut writer = topology.build_stream_output(buffer_builder, *src, *src_port, src_inbox);
+        let mut latency = Vec::new();
 
         for (dst, dst_port) in v.iter() {
             let dst_inbox = inboxes[*dst].as_ref().unwrap().clone();
+            let probe = topology.latency_probe(buffer_builder, *src, *src_port);
+            probes.push(((*src, *src_port, *dst, *dst_port), probe.clone()));
+            latency.push(probe.clone());
 
             inboxes[*dst]
                 .as_mut()
@@ -255,6 +329,7 @@ async fn run_flowgraph<S: Scheduler>(
                 .send(BlockMessage::StreamInputInit {
                     dst_port: *dst_port,
                     reader: writer.add_reader(dst_inbox, *dst_port),
+                    latency: Some(probe),
                 })
                 .await
                 .unwrap();
@@ -266,14 +341,16 @@ async fn run_flowgraph<S: Scheduler>(
             .send(BlockMessage::StreamOutputInit {
                 src_port: *src_port,
                 writer,
+                latency,
             })
             .await
             .unwrap();
     }
+    topology.stream_latencies.extend(probes);
 
     debug!("connect message io");
     // connect message IO
-    for (src, src_port, dst, dst_port) in topology.message_edges.iter() {
+    for edge @ (src, src_port, dst, dst_port) in topology.message_edges.iter() {
         let dst_box = inboxes[*dst].as_ref().unwrap().clone();
         inboxes[*src]
             .as_mut()
@@ -282,6 +359,7 @@ async fn run_flowgraph<S: Scheduler>(
                 src_port: *src_port,
                 dst_port: *dst_port,
                 dst_inbox: dst_box,
+                queue: topology.message_queue(edge),
             })
             .await
             .unwrap();
@@ -301,7 +379,7 @@ async fn run_flowgraph<S: Scheduler>(
     // wait until all blocks are initialized
     let mut i = active_blocks;
     let mut queue = Vec::new();
-    let mut block_error = false;
+    let mut block_errors = Vec::new();
     loop {
         if i == 0 {
             break;
@@ -310,12 +388,16 @@ async fn run_flowgraph<S: Scheduler>(
         let m = main_rx.next().await.context("no msg")?;
         match m {
             FlowgraphMessage::Initialized => i -= 1,
-            FlowgraphMessage::BlockError { block_id, block } => {
+            FlowgraphMessage::BlockError {
+                block_id,
+                block,
+                error,
+            } => {
                 *topology.blocks.get_mut(block_id).unwrap() = Some(block);
                 inboxes[block_id] = None;
                 i -= 1;
                 active_blocks -= 1;
-                block_error = true;
+                block_errors.push(error);
             }
             x => {
                 debug!(
@@ -346,13 +428,19 @@ async fn run_flowgraph<S: Scheduler>(
         .send(())
         .expect("failed to signal flowgraph startup complete.");
 
-    if block_error {
+    if !block_errors.is_empty() && fg.terminate_on_error {
         main_channel
             .try_send(FlowgraphMessage::Terminate)
             .expect("main inbox exceeded capacity during startup");
     }
 
     let mut terminated = false;
+    // blocks added at runtime, waiting for their stream ports to be connected
+    let mut pending = HashSet::new();
+    // blocks that are removed at runtime, waiting for them to terminate
+    let mut removing: HashMap<usize, oneshot::Sender<Result<Block>>> = HashMap::new();
+    // subscribers to flowgraph events
+    let mut subscribers: Vec<Sender<FlowgraphEvent>> = Vec::new();
 
     // main loop
     loop {
@@ -411,16 +499,157 @@ async fn run_flowgraph<S: Scheduler>(
                 }
             }
             FlowgraphMessage::BlockDone { block_id, block } => {
-                *topology.blocks.get_mut(block_id).unwrap() = Some(block);
                 inboxes[block_id] = None;
                 active_blocks -= 1;
+                emit(&mut subscribers, FlowgraphEvent::BlockDone { block_id });
+                if let Some(tx) = removing.remove(&block_id) {
+                    topology.delete_block(block_id);
+                    let _ = tx.send(Ok(block));
+                } else {
+                    *topology.blocks.get_mut(block_id).unwrap() = Some(block);
+                }
             }
-            FlowgraphMessage::BlockError { block_id, block } => {
-                *topology.blocks.get_mut(block_id).unwrap() = Some(block);
+            FlowgraphMessage::BlockError {
+                block_id,
+                block,
+                error,
+            } => {
                 inboxes[block_id] = None;
-                block_error = true;
                 active_blocks -= 1;
-                let _ = main_channel.send(FlowgraphMessage::Terminate).await;
+                emit(
+                    &mut subscribers,
+                    FlowgraphEvent::BlockError {
+                        block_id,
+                        error: error.to_string(),
+                    },
+                );
+                if let Some(tx) = removing.remove(&block_id) {
+                    warn!("block terminated with error, while being removed: {error}");
+                    topology.delete_block(block_id);
+                    let _ = tx.send(Ok(block));
+                } else {
+                    *topology.blocks.get_mut(block_id).unwrap() = Some(block);
+                    block_errors.push(error);
+                    if fg.terminate_on_error {
+                        let _ = main_channel.send(FlowgraphMessage::Terminate).await;
+                    }
+                }
+            }
+            FlowgraphMessage::Initialized => {}
+            FlowgraphMessage::AddBlock { block, tx } => {
+                if terminated {
+                    let _ = tx.send(Err(anyhow!("flowgraph is terminating")));
+                    continue;
+                }
+                let id = topology.add_block(block);
+                topology.record_ports();
+                let block = topology.blocks[id].take().unwrap();
+                while inboxes.len() <= id {
+                    inboxes.insert(None);
+                }
+                inboxes[id] = Some(scheduler.spawn_block(id, block, &main_channel));
+                active_blocks += 1;
+                pending.insert(id);
+                let r = start_if_connected(&mut topology, &mut inboxes, &mut pending, id).await;
+                let _ = tx.send(r.map(|_| id));
+            }
+            FlowgraphMessage::RemoveBlock { block_id, tx } => {
+                if terminated {
+                    let _ = tx.send(Err(anyhow!("flowgraph is terminating")));
+                    continue;
+                }
+                match remove_block(&mut topology, &mut inboxes, &mut pending, block_id).await {
+                    Ok(Some(block)) => {
+                        let _ = tx.send(Ok(block));
+                    }
+                    Ok(None) => {
+                        removing.insert(block_id, tx);
+                    }
+                    Err(e) => {
+                        let _ = tx.send(Err(e));
+                    }
+                }
+            }
+            FlowgraphMessage::ConnectStream {
+                src_block,
+                src_port,
+                dst_block,
+                dst_port,
+                tx,
+            } => {
+                let r = if terminated {
+                    Err(anyhow!("flowgraph is terminating"))
+                } else {
+                    connect_stream(
+                        &mut topology,
+                        &mut inboxes,
+                        &mut pending,
+                        (src_block, src_port),
+                        (dst_block, dst_port),
+                    )
+                    .await
+                };
+                let _ = tx.send(r);
+            }
+            FlowgraphMessage::DisconnectStream {
+                src_block,
+                src_port,
+                dst_block,
+                dst_port,
+                tx,
+            } => {
+                let r = if terminated {
+                    Err(anyhow!("flowgraph is terminating"))
+                } else {
+                    disconnect_stream(
+                        &mut topology,
+                        &mut inboxes,
+                        (src_block, src_port),
+                        (dst_block, dst_port),
+                    )
+                    .await
+                };
+                let _ = tx.send(r);
+            }
+            FlowgraphMessage::ConnectMessage {
+                src_block,
+                src_port,
+                dst_block,
+                dst_port,
+                tx,
+            } => {
+                let r = if terminated {
+                    Err(anyhow!("flowgraph is terminating"))
+                } else {
+                    connect_message(
+                        &mut topology,
+                        &mut inboxes,
+                        (src_block, src_port),
+                        (dst_block, dst_port),
+                    )
+                    .await
+                };
+                let _ = tx.send(r);
+            }
+            FlowgraphMessage::DisconnectMessage {
+                src_block,
+                src_port,
+                dst_block,
+                dst_port,
+                tx,
+            } => {
+                let r = if terminated {
+                    Err(anyhow!("flowgraph is terminating"))
+                } else {
+                    disconnect_message(
+                        &mut topology,
+                        &mut inboxes,
+                        (src_block, src_port),
+                        (dst_block, dst_port),
+                    )
+                    .await
+                };
+                let _ = tx.send(r);
             }
             FlowgraphMessage::BlockDescription { block_id, tx } => {
                 if let Some(Some(ref mut b)) = inboxes.get_mut(block_id) {
@@ -445,32 +674,99 @@ async fn run_flowgraph<S: Scheduler>(
                 let mut blocks = Vec::new();
                 let ids: Vec<usize> = topology.blocks.iter().map(|x| x.0).collect();
                 for id in ids {
-                    let (b_tx, rx) = oneshot::channel::<BlockDescription>();
-                    inboxes[id]
-                        .as_mut()
-                        .unwrap()
-                        .send(BlockMessage::BlockDescription { tx: b_tx })
-                        .await
-                        .unwrap();
-                    blocks.push(rx.await.unwrap());
+                    // failed or finished blocks have no inbox anymore
+                    if let Some(Some(inbox)) = inboxes.get_mut(id) {
+                        let (b_tx, rx) = oneshot::channel::<BlockDescription>();
+                        if inbox
+                            .send(BlockMessage::BlockDescription { tx: b_tx })
+                            .await
+                            .is_ok()
+                        {
+                            if let Ok(d) = rx.await {
+                                blocks.push(d);
+                            }
+                        }
+                    }
                 }
 
-                let stream_edges = topology
-                    .stream_edges
-                    .iter()
-                    .flat_map(|x| x.1.iter().map(|y| (x.0 .0, x.0 .1, y.0, y.1)))
-                    .collect();
+                let (stream_edges, stream_latencies) = topology.stream_edge_descriptions();
                 let message_edges = topology.message_edges.clone();
+                let message_queues = topology.message_queue_descriptions();
 
                 tx.send(FlowgraphDescription {
                     blocks,
                     stream_edges,
                     message_edges,
+                    message_queues,
+                    stream_latencies,
                 })
                 .unwrap();
             }
+            FlowgraphMessage::BlockStats { block_id, tx } => {
+                if let Some(Some(ref mut b)) = inboxes.get_mut(block_id) {
+                    let (b_tx, rx) = oneshot::channel::<BlockStats>();
+                    if b.send(BlockMessage::BlockStats { tx: b_tx }).await.is_ok() {
+                        if let Ok(b) = rx.await {
+                            let _ = tx.send(Ok(b));
+                        } else {
+                            let _ = tx.send(Err(Error::RuntimeError));
+                        }
+                    } else {
+                        let _ = tx.send(Err(Error::BlockTerminated));
+                    }
+                } else {
+                    let _ = tx.send(Err(Error::InvalidBlock));
+                }
+            }
+            FlowgraphMessage::FlowgraphStats { tx } => {
+                let mut blocks = Vec::new();
+                for (_, inbox) in inboxes.iter_mut() {
+                    if let Some(inbox) = inbox {
+                        let (b_tx, rx) = oneshot::channel::<BlockStats>();
+                        if inbox
+                            .send(BlockMessage::BlockStats { tx: b_tx })
+                            .await
+                            .is_ok()
+                        {
+                            if let Ok(s) = rx.await {
+                                blocks.push(s);
+                            }
+                        }
+                    }
+                }
+                let _ = tx.send(FlowgraphStats { blocks });
+            }
+            FlowgraphMessage::SubscribeMessage {
+                block_id,
+                port_id,
+                sender,
+                tx,
+            } => {
+                let r = subscribe_message(
+                    scheduler.clone(),
+                    &mut topology,
+                    &mut inboxes,
+                    (block_id, port_id),
+                    sender,
+                )
+                .await;
+                let _ = tx.send(r);
+            }
+            FlowgraphMessage::SubscribeEvents { sender } => {
+                subscribers.push(sender);
+            }
             FlowgraphMessage::Terminate => {
                 if !terminated {
+                    emit(&mut subscribers, FlowgraphEvent::Terminate);
+                    // blocks that were never started have to be initialized to shut down
+                    for id in pending.drain() {
+                        if let Err(e) = init_unconnected(&mut topology, &mut inboxes, id).await {
+                            warn!("failed to shut down pending block {id}: {e:?}");
+                        }
+                        if let Some(ref mut chan) = inboxes[id] {
+                            let _ = chan.send(BlockMessage::Initialize).await;
+                        }
+                    }
                     for (_, opt) in inboxes.iter_mut() {
                         if let Some(ref mut chan) = opt {
                             if chan.send(BlockMessage::Terminate).await.is_err() {
@@ -483,14 +779,399 @@ async fn run_flowgraph<S: Scheduler>(
                     terminated = true;
                 }
             }
-            _ => warn!("main loop received unhandled message"),
         }
     }
 
     fg.topology = Some(topology);
-    if block_error {
-        bail!("flowgraph error");
+    if !block_errors.is_empty() {
+        return Err(FlowgraphError {
+            errors: block_errors,
+        }
+        .into());
     }
 
     Ok(fg)
 }
+
+type Inboxes = Slab<Option<Sender<BlockMessage>>>;
+
+fn inbox(inboxes: &Inboxes, block_id: usize) -> Result<Sender<BlockMessage>> {
+    inboxes
+        .get(block_id)
+        .and_then(|i| i.clone())
+        .with_context(|| format!("block {block_id} is not running"))
+}
+
+/// Initialize a stream output with a new buffer, connecting all its readers
+///
+/// Samples in the previous buffer are dropped. If there are no readers, the block writes into a
+/// buffer that is not read. The latency caps are recomputed for the current topology, but only
+/// apply to the new buffer; outputs that are not initialized again keep their cap.
+async fn init_stream_output(
+    topology: &mut Topology,
+    inboxes: &mut Inboxes,
+    src: usize,
+    src_port: usize,
+) -> Result<()> {
+    let mut src_inbox = inbox(inboxes, src)?;
+    topology.apply_latency_budget();
+
+    let edge = topology
+        .stream_edges
+        .iter()
+        .find(|(k, _)| k.0 == src && k.1 == src_port);
+    let mut probes = Vec::new();
+    let writer = if let Some(((_, _, buffer_builder), v)) = edge {
+        let mut writer =
+            topology.build_stream_output(buffer_builder, src, src_port, src_inbox.clone());
+        for (dst, dst_port) in v.iter() {
+            if let Ok(mut dst_inbox) = inbox(inboxes, *dst) {
+                let reader = writer.add_reader(dst_inbox.clone(), *dst_port);
+                let probe = topology.latency_probe(buffer_builder, src, src_port);
+                probes.push(((src, src_port, *dst, *dst_port), probe.clone()));
+                dst_inbox
+                    .send(BlockMessage::StreamInputInit {
+                        dst_port: *dst_port,
+                        reader,
+                        latency: Some(probe),
+                    })
+                    .await?;
+            }
+        }
+        writer
+    } else {
+        let item_size = topology.block_ports(src, "src")?.stream_outputs[src_port].item_size;
+        DefaultBuffer.build(item_size, src_inbox.clone(), src_port)
+    };
+    let latency = probes.iter().map(|(_, p)| p.clone()).collect();
+    topology.stream_latencies.extend(probes);
+
+    src_inbox
+        .send(BlockMessage::StreamOutputInit {
+            src_port,
+            writer,
+            latency,
+        })
+        .await?;
+    Ok(())
+}
+
+/// Set a reader without writer for a stream input
+///
+/// The input does not receive samples, until it is connected again.
+async fn disconnect_stream_input(
+    topology: &mut Topology,
+    inboxes: &mut Inboxes,
+    dst: usize,
+    dst_port: usize,
+) -> Result<()> {
+    let mut dst_inbox = inbox(inboxes, dst)?;
+    let item_size = topology.block_ports(dst, "dst")?.stream_inputs[dst_port].item_size;
+
+    // notifications of the reader go nowhere and the writer is dropped right away
+    let (tx, _) = channel::<BlockMessage>(0);
+    let mut writer = DefaultBuffer.build(item_size, tx, 0);
+    let reader = writer.add_reader(dst_inbox.clone(), dst_port);
+
+    dst_inbox
+        .send(BlockMessage::StreamInputInit {
+            dst_port,
+            reader,
+            latency: None,
+        })
+        .await?;
+    Ok(())
+}
+
+/// Set buffers for all stream ports of a block that are not connected
+async fn init_unconnected(
+    topology: &mut Topology,
+    inboxes: &mut Inboxes,
+    block_id: usize,
+) -> Result<()> {
+    let (inputs, outputs) = topology.unconnected_stream_ports(block_id);
+    for i in inputs {
+        disconnect_stream_input(topology, inboxes, block_id, i).await?;
+    }
+    for o in outputs {
+        init_stream_output(topology, inboxes, block_id, o).await?;
+    }
+    Ok(())
+}
+
+/// Start a block that was added at runtime, once all its stream ports are connected
+async fn start_if_connected(
+    topology: &mut Topology,
+    inboxes: &mut Inboxes,
+    pending: &mut HashSet<usize>,
+    block_id: usize,
+) -> Result<()> {
+    if !pending.contains(&block_id) {
+        return Ok(());
+    }
+
+    let (inputs, outputs) = topology.unconnected_stream_ports(block_id);
+    if inputs.is_empty() && outputs.is_empty() {
+        pending.remove(&block_id);
+        let mut i = inbox(inboxes, block_id)?;
+        i.send(BlockMessage::Initialize).await?;
+        i.send(BlockMessage::Notify).await?;
+    }
+    Ok(())
+}
+
+async fn connect_stream(
+    topology: &mut Topology,
+    inboxes: &mut Inboxes,
+    pending: &mut HashSet<usize>,
+    (src_block, src_port): (usize, PortId),
+    (dst_block, dst_port): (usize, PortId),
+) -> Result<()> {
+    let (src, src_port) = topology.resolve_stream_output(src_block, src_port)?;
+    let (dst, dst_port) = topology.resolve_stream_input(dst_block, dst_port)?;
+    inbox(inboxes, src)?;
+    inbox(inboxes, dst)?;
+
+    if topology
+        .stream_edges
+        .values()
+        .any(|v| v.contains(&(dst, dst_port)))
+    {
+        bail!("stream input is already connected");
+    }
+
+    topology.connect_stream(src, src_port.into(), dst, dst_port.into(), DefaultBuffer)?;
+    init_stream_output(topology, inboxes, src, src_port).await?;
+
+    start_if_connected(topology, inboxes, pending, src).await?;
+    start_if_connected(topology, inboxes, pending, dst).await
+}
+
+async fn disconnect_stream(
+    topology: &mut Topology,
+    inboxes: &mut Inboxes,
+    (src_block, src_port): (usize, PortId),
+    (dst_block, dst_port): (usize, PortId),
+) -> Result<()> {
+    let (src, src_port) = topology.resolve_stream_output(src_block, src_port)?;
+    let (dst, dst_port) = topology.resolve_stream_input(dst_block, dst_port)?;
+
+    topology.disconnect_stream(src, src_port.into(), dst, dst_port.into())?;
+
+    if inbox(inboxes, src).is_ok() {
+        init_stream_output(topology, inboxes, src, src_port).await?;
+    }
+    if inbox(inboxes, dst).is_ok() {
+        disconnect_stream_input(topology, inboxes, dst, dst_port).await?;
+    }
+    Ok(())
+}
+
+async fn connect_message(
+    topology: &mut Topology,
+    inboxes: &mut Inboxes,
+    (src_block, src_port): (usize, PortId),
+    (dst_block, dst_port): (usize, PortId),
+) -> Result<()> {
+    let (src, src_port) = topology.resolve_message_output(src_block, src_port)?;
+    let (dst, dst_port) = topology.resolve_message_input(dst_block, dst_port)?;
+    let mut src_inbox = inbox(inboxes, src)?;
+    let dst_inbox = inbox(inboxes, dst)?;
+
+    topology.connect_message(src, src_port.into(), dst, dst_port.into())?;
+    src_inbox
+        .send(BlockMessage::MessageOutputConnect {
+            src_port,
+            dst_port,
+            dst_inbox,
+            queue: None,
+        })
+        .await?;
+    Ok(())
+}
+
+async fn disconnect_message(
+    topology: &mut Topology,
+    inboxes: &mut Inboxes,
+    (src_block, src_port): (usize, PortId),
+    (dst_block, dst_port): (usize, PortId),
+) -> Result<()> {
+    let (src, src_port) = topology.resolve_message_output(src_block, src_port)?;
+    let (dst, dst_port) = topology.resolve_message_input(dst_block, dst_port)?;
+
+    topology.disconnect_message(src, src_port.into(), dst, dst_port.into())?;
+
+    if let (Ok(mut src_inbox), Ok(dst_inbox)) = (inbox(inboxes, src), inbox(inboxes, dst)) {
+        src_inbox
+            .send(BlockMessage::MessageOutputDisconnect {
+                src_port,
+                dst_port,
+                dst_inbox,
+            })
+            .await?;
+    }
+    Ok(())
+}
+
+/// Push an event to all subscribers, dropping it for subscribers that do not keep up
+fn emit(subscribers: &mut Vec<Sender<FlowgraphEvent>>, event: FlowgraphEvent) {
+    for s in subscribers.iter_mut() {
+        let _ = s.try_send(event.clone());
+    }
+    subscribers.retain(|s| !s.is_closed());
+}
+
+/// Forward the messages of a message output port to a subscriber
+///
+/// The port is connected through a [`MessageQueue`] that drops the oldest messages, so that a
+/// slow subscriber does not stall the block. The subscription ends, once the port is finished or
+/// the subscriber is dropped.
+async fn subscribe_message<S: Scheduler>(
+    scheduler: S,
+    topology: &mut Topology,
+    inboxes: &mut Inboxes,
+    (block, port): (usize, PortId),
+    mut subscriber: Sender<Pmt>,
+) -> Result<()> {
+    let (src, src_port) = topology.resolve_message_output(block, port)?;
+    let mut src_inbox = inbox(inboxes, src)?;
+
+    let (tap, mut tap_rx) = channel::<BlockMessage>(config::config().queue_size);
+    let queue = Arc::new(MessageQueue::new(MessageQueueConfig::DropOldest(
+        config::config().queue_size,
+    )));
+    src_inbox
+        .send(BlockMessage::MessageOutputConnect {
+            src_port,
+            dst_port: 0,
+            dst_inbox: tap.clone(),
+            queue: Some(queue),
+        })
+        .await?;
+
+    scheduler
+        .spawn(async move {
+            'tap: while let Some(m) = tap_rx.next().await {
+                let (queue, mut next) = match m {
+                    BlockMessage::Dequeue { queue, .. } => {
+                        let p = queue.pop();
+                        (Some(queue), p)
+                    }
+                    BlockMessage::Call { data, .. } => (None, Some(data)),
+                    _ => continue,
+                };
+                while let Some(p) = next {
+                    if matches!(p, Pmt::Finished) {
+                        break 'tap;
+                    }
+                    if subscriber.send(p).await.is_err() {
+                        // subscriber is gone
+                        let _ = src_inbox
+                            .send(BlockMessage::MessageOutputDisconnect {
+                                src_port,
+                                dst_port: 0,
+                                dst_inbox: tap,
+                            })
+                            .await;
+                        break 'tap;
+                    }
+                    next = queue.as_ref().and_then(|q| q.pop());
+                }
+            }
+        })
+        .detach();
+    Ok(())
+}
+
+/// Disconnect a block and ask it to terminate
+///
+/// Returns the block right away, if it is already terminated. Otherwise, it is handed out, once
+/// it is done.
+async fn remove_block(
+    topology: &mut Topology,
+    inboxes: &mut Inboxes,
+    pending: &mut HashSet<usize>,
+    block_id: usize,
+) -> Result<Option<Block>> {
+    if topology.hier_blocks.contains_key(&block_id) {
+        bail!("hierarchical blocks cannot be removed from a running flowgraph");
+    }
+
+    let mut block_inbox = match inbox(inboxes, block_id) {
+        Ok(i) => i,
+        Err(_) => {
+            let block = topology
+                .blocks
+                .get_mut(block_id)
+                .and_then(|b| b.take())
+                .context("invalid block")?;
+            topology.delete_block(block_id);
+            return Ok(Some(block));
+        }
+    };
+
+    // remove connections
+    let upstream: HashSet<(usize, usize)> = topology
+        .stream_edges
+        .iter()
+        .filter(|(k, v)| k.0 != block_id && v.iter().any(|x| x.0 == block_id))
+        .map(|(k, _)| (k.0, k.1))
+        .collect();
+    let downstream: Vec<(usize, usize)> = topology
+        .stream_edges
+        .iter()
+        .filter(|(k, _)| k.0 == block_id)
+        .flat_map(|(_, v)| v.iter().copied())
+        .filter(|x| x.0 != block_id)
+        .collect();
+    let message_edges: Vec<(usize, usize, usize, usize)> = topology
+        .message_edges
+        .iter()
+        .filter(|x| x.0 == block_id || x.2 == block_id)
+        .copied()
+        .collect();
+
+    topology.stream_edges.retain(|k, _| k.0 != block_id);
+    for v in topology.stream_edges.values_mut() {
+        v.retain(|x| x.0 != block_id);
+    }
+    topology.stream_edges.retain(|_, v| !v.is_empty());
+    topology
+        .message_edges
+        .retain(|x| x.0 != block_id && x.2 != block_id);
+    topology
+        .message_queues
+        .retain(|x, _| x.0 != block_id && x.2 != block_id);
+
+    // make sure that neither the neighbors nor the block itself get notified, when the block
+    // shuts down
+    for (src, src_port) in upstream {
+        if inbox(inboxes, src).is_ok() {
+            init_stream_output(topology, inboxes, src, src_port).await?;
+        }
+    }
+    for (dst, dst_port) in downstream {
+        if inbox(inboxes, dst).is_ok() {
+            disconnect_stream_input(topology, inboxes, dst, dst_port).await?;
+        }
+    }
+    init_unconnected(topology, inboxes, block_id).await?;
+    for (src, src_port, dst, dst_port) in message_edges {
+        if let (Ok(mut src_inbox), Ok(dst_inbox)) = (inbox(inboxes, src), inbox(inboxes, dst)) {
+            src_inbox
+                .send(BlockMessage::MessageOutputDisconnect {
+                    src_port,
+                    dst_port,
+                    dst_inbox,
+                })
+                .await?;
+        }
+    }
+
+    if pending.remove(&block_id) {
+        block_inbox.send(BlockMessage::Initialize).await?;
+    }
+    block_inbox.send(BlockMessage::Terminate).await?;
+
+    Ok(None)
+}
@@ -0,0 +1,566 @@
+use async_executor::{Executor, Task};
+use futures::channel::mpsc::{
+    channel, unbounded, Receiver, Sender, UnboundedReceiver, UnboundedSender,
+};
+use futures::channel::oneshot;
+use futures::future::Future;
+use futures::task::{waker_ref, ArcWake, AtomicWaker};
+use futures::StreamExt;
+use log::debug;
+use slab::Slab;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::task::{Context, Poll};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::runtime::config;
+use crate::runtime::scheduler::hints;
+use crate::runtime::scheduler::Scheduler;
+use crate::runtime::Block;
+use crate::runtime::BlockMessage;
+use crate::runtime::FlowgraphMessage;
+use crate::runtime::Topology;
+
+/// Maximum number of polling rounds of a group, before it yields to other tasks.
+const BATCH: usize = 32;
+
+use hints::BlockFuture;
+
+/// Configuration of the [`ChainScheduler`]
+#[derive(Clone, Debug)]
+pub struct ChainConfig {
+    /// Maximum number of blocks that are fused into one task
+    pub max_fused: usize,
+    /// Interval, in which the load of the tasks is checked
+    pub rebalance_interval: Duration,
+    /// Load of a task (busy time / interval), above which its most expensive block is split off
+    pub split_load: f64,
+}
+
+impl Default for ChainConfig {
+    fn default() -> Self {
+        Self {
+            max_fused: 8,
+            rebalance_interval: Duration::from_millis(100),
+            split_load: 0.5,
+        }
+    }
+}
+
+/// Chain Scheduler
+///
+/// Work-stealing scheduler for long linear chains of blocks. When the flowgraph starts,
+/// consecutive blocks of a chain (blocks with exactly one upstream and one downstream block) are
+/// fused into one task, which polls them in batches on the same worker. Fusion is not gated on
+/// the cost of the blocks, i.e., all blocks are assumed to be lightweight at first. The time
+/// spent in each block is measured and, if a fused task gets too busy, its most expensive block
+/// is moved to a task of its own, which can then be stolen by another worker. Blocks that were
+/// split off are not fused again.
+///
+/// Blocking blocks and blocks with [`SchedulingHints`](crate::runtime::SchedulingHints) are not
+/// fused and run like in the [`SmolScheduler`](super::SmolScheduler).
+#[derive(Clone, Debug)]
+pub struct ChainScheduler {
+    inner: Arc<ChainSchedulerInner>,
+}
+
+struct ChainSchedulerInner {
+    executor: Arc<Executor<'static>>,
+    config: ChainConfig,
+    groups: Arc<Mutex<Vec<GroupHandle>>>,
+    workers: Vec<(thread::JoinHandle<()>, oneshot::Sender<()>)>,
+    _monitor: Task<()>,
+}
+
+impl fmt::Debug for ChainSchedulerInner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ChainSchedulerInner")
+            .field("config", &self.config)
+            .field("workers", &self.workers.len())
+            .finish()
+    }
+}
+
+impl Drop for ChainSchedulerInner {
+    fn drop(&mut self) {
+        for i in self.workers.drain(..) {
+            i.1.send(()).unwrap();
+            i.0.join().unwrap();
+        }
+    }
+}
+
+impl ChainScheduler {
+    /// Create chain scheduler
+    ///
+    /// ## Parameter
+    /// - `n_executors`: number of worker threads
+    /// - `pin_executors`: pin worker threads to CPUs?
+    pub fn new(n_executors: usize, pin_executors: bool) -> ChainScheduler {
+        Self::with_config(n_executors, pin_executors, ChainConfig::default())
+    }
+
+    /// Create chain scheduler with custom configuration
+    pub fn with_config(
+        n_executors: usize,
+        pin_executors: bool,
+        config: ChainConfig,
+    ) -> ChainScheduler {
+        let executor = Arc::new(Executor::new());
+        let mut workers = Vec::new();
+
+        let core_ids = if let Some(core_ids) = core_affinity::get_core_ids() {
+            core_ids
+        } else {
+            (0..n_executors)
+                .map(|i| core_affinity::CoreId { id: i })
+                .collect()
+        };
+
+        for c in core_ids.iter().cycle().take(n_executors).cloned() {
+            let e = executor.clone();
+            let (sender, receiver) = oneshot::channel::<()>();
+
+            let handle = thread::Builder::new()
+                .stack_size(config::config().stack_size)
+                .name(format!("chain-{}", &c.id))
+                .spawn(move || {
+                    if pin_executors {
+                        debug!("starting executor thread on core id {}", &c.id);
+                        core_affinity::set_for_current(c);
+                    }
+                    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                        async_io::block_on(e.run(receiver)).unwrap();
+                    }));
+                    if result.is_err() {
+                        eprintln!("chain worker panicked {result:?}");
+                        std::process::exit(1);
+                    }
+                })
+                .expect("failed to spawn executor thread");
+
+            workers.push((handle, sender));
+        }
+
+        let groups = Arc::new(Mutex::new(Vec::new()));
+        let monitor = executor.spawn(monitor(
+            Arc::downgrade(&executor),
+            groups.clone(),
+            config.clone(),
+        ));
+
+        ChainScheduler {
+            inner: Arc::new(ChainSchedulerInner {
+                executor,
+                config,
+                groups,
+                workers,
+                _monitor: monitor,
+            }),
+        }
+    }
+
+    /// Ids of the blocks that are currently fused into one task
+    ///
+    /// Blocks that run on a thread of their own are not listed.
+    pub fn groups(&self) -> Vec<Vec<usize>> {
+        self.inner
+            .groups
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|g| !g.shared.done.load(Ordering::Acquire))
+            .map(|g| {
+                g.shared
+                    .members
+                    .lock()
+                    .unwrap()
+                    .iter()
+                    .map(|m| m.0)
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn fusable(block: &Block) -> bool {
+        !block.is_blocking() && !hints::needs_thread(&block.scheduling_hints())
+    }
+
+    // Blocks that are spawned on their own form a group of one block.
+    fn spawn_block_task(
+        &self,
+        block_id: usize,
+        block: Block,
+        main_channel: Sender<FlowgraphMessage>,
+        receiver: Receiver<BlockMessage>,
+    ) {
+        hints::spawn_block_task(self, block_id, block, main_channel, receiver, |f| {
+            spawn_group(
+                &self.inner.executor,
+                &self.inner.groups,
+                vec![Member::new(block_id, f)],
+            )
+        });
+    }
+}
+
+/// Split the fusable blocks of the topology in chains of at most `max` blocks
+fn chains(topology: &Topology, fusable: &HashSet<usize>, max: usize) -> Vec<Vec<usize>> {
+    let mut succ: HashMap<usize, HashSet<usize>> = HashMap::new();
+    let mut pred: HashMap<usize, HashSet<usize>> = HashMap::new();
+    for ((src, _, _), dsts) in topology.stream_edges.iter() {
+        for (dst, _) in dsts {
+            succ.entry(*src).or_default().insert(*dst);
+            pred.entry(*dst).or_default().insert(*src);
+        }
+    }
+
+    let next = |b: usize| -> Option<usize> {
+        let s = succ.get(&b)?;
+        let n = *s.iter().next()?;
+        if s.len() == 1 && pred[&n].len() == 1 && fusable.contains(&n) && n != b {
+            Some(n)
+        } else {
+            None
+        }
+    };
+
+    let mut ids: Vec<usize> = fusable.iter().copied().collect();
+    ids.sort_unstable();
+    let linked: HashSet<usize> = ids.iter().filter_map(|b| next(*b)).collect();
+
+    let mut visited = HashSet::new();
+    let mut chains = Vec::new();
+    // start at the heads of the chains, then handle blocks in cycles
+    let heads = ids.iter().filter(|b| !linked.contains(b));
+    for start in heads.chain(ids.iter()).copied() {
+        if !visited.insert(start) {
+            continue;
+        }
+        let mut chain = vec![start];
+        let mut current = start;
+        while let Some(n) = next(current) {
+            if !visited.insert(n) {
+                break;
+            }
+            if chain.len() == max {
+                chains.push(std::mem::take(&mut chain));
+            }
+            chain.push(n);
+            current = n;
+        }
+        chains.push(chain);
+    }
+    chains
+}
+
+impl Scheduler for ChainScheduler {
+    fn run_topology(
+        &self,
+        topology: &mut Topology,
+        main_channel: &Sender<FlowgraphMessage>,
+    ) -> Slab<Option<Sender<BlockMessage>>> {
+        let mut inboxes = Slab::new();
+        let max = topology.blocks.iter().map(|(i, _)| i).max().unwrap_or(0);
+        for _ in 0..=max {
+            inboxes.insert(None);
+        }
+        let queue_size = config::config().queue_size;
+
+        let fusable: HashSet<usize> = topology
+            .blocks
+            .iter()
+            .filter(|(_, b)| Self::fusable(b.as_ref().unwrap()))
+            .map(|(id, _)| id)
+            .collect();
+
+        for chain in chains(topology, &fusable, self.inner.config.max_fused.max(1)) {
+            let mut members = Vec::new();
+            for id in chain {
+                let block = topology.blocks[id].take().unwrap();
+                let (sender, receiver) = channel::<BlockMessage>(queue_size);
+                inboxes[id] = Some(sender);
+                members.push(Member::new(
+                    id,
+                    Box::pin(block.run(id, main_channel.clone(), receiver)),
+                ));
+            }
+            debug!(
+                "fusing blocks {:?}",
+                members.iter().map(|m| m.block_id).collect::<Vec<_>>()
+            );
+            spawn_group(&self.inner.executor, &self.inner.groups, members);
+        }
+
+        // blocking blocks and blocks with scheduling hints
+        for (id, block_o) in topology.blocks.iter_mut() {
+            if let Some(block) = block_o.take() {
+                let (sender, receiver) = channel::<BlockMessage>(queue_size);
+                inboxes[id] = Some(sender);
+                self.spawn_block_task(id, block, main_channel.clone(), receiver);
+            }
+        }
+
+        inboxes
+    }
+
+    fn spawn_block(
+        &self,
+        block_id: usize,
+        block: Block,
+        main_channel: &Sender<FlowgraphMessage>,
+    ) -> Sender<BlockMessage> {
+        let (sender, receiver) = channel::<BlockMessage>(config::config().queue_size);
+        self.spawn_block_task(block_id, block, main_channel.clone(), receiver);
+        sender
+    }
+
+    fn spawn<T: Send + 'static>(
+        &self,
+        future: impl Future<Output = T> + Send + 'static,
+    ) -> Task<T> {
+        self.inner.executor.spawn(future)
+    }
+
+    fn spawn_blocking<T: Send + 'static>(
+        &self,
+        future: impl Future<Output = T> + Send + 'static,
+    ) -> Task<T> {
+        self.inner
+            .executor
+            .spawn(blocking::unblock(|| async_io::block_on(future)))
+    }
+}
+
+impl Default for ChainScheduler {
+    fn default() -> Self {
+        let n_executors = core_affinity::get_core_ids().map(|c| c.len()).unwrap_or(1);
+        Self::new(n_executors, false)
+    }
+}
+
+/// Wakes a block of a group and, thereby, the group
+struct MemberWaker {
+    woken: AtomicBool,
+    group: Arc<AtomicWaker>,
+}
+
+impl ArcWake for MemberWaker {
+    fn wake_by_ref(arc_self: &Arc<Self>) {
+        arc_self.woken.store(true, Ordering::Release);
+        arc_self.group.wake();
+    }
+}
+
+/// Block of a group
+struct Member {
+    block_id: usize,
+    future: BlockFuture,
+    waker: Option<Arc<MemberWaker>>,
+    /// Time spent polling the block (ns)
+    cost: Arc<AtomicU64>,
+}
+
+impl Member {
+    fn new(block_id: usize, future: BlockFuture) -> Self {
+        Self {
+            block_id,
+            future,
+            waker: None,
+            cost: Arc::new(AtomicU64::new(0)),
+        }
+    }
+}
+
+/// State of a group, shared with the monitor
+#[derive(Default)]
+struct GroupShared {
+    members: Mutex<Vec<(usize, Arc<AtomicU64>)>>,
+    done: AtomicBool,
+}
+
+struct GroupHandle {
+    shared: Arc<GroupShared>,
+    commands: UnboundedSender<(usize, oneshot::Sender<Member>)>,
+}
+
+/// Task that polls a group of blocks
+///
+/// Blocks are only polled, when they were woken. Commands ask the group to hand out one of its
+/// blocks, which is moved to another group.
+struct Group {
+    members: Vec<Member>,
+    waker: Arc<AtomicWaker>,
+    shared: Arc<GroupShared>,
+    commands: UnboundedReceiver<(usize, oneshot::Sender<Member>)>,
+}
+
+impl Group {
+    fn add(&mut self, mut member: Member) {
+        // poll once, to register the wakers of the new group
+        member.waker = Some(Arc::new(MemberWaker {
+            woken: AtomicBool::new(true),
+            group: self.waker.clone(),
+        }));
+        self.shared
+            .members
+            .lock()
+            .unwrap()
+            .push((member.block_id, member.cost.clone()));
+        self.members.push(member);
+    }
+
+    fn remove(&mut self, index: usize) -> Member {
+        let member = self.members.remove(index);
+        self.shared
+            .members
+            .lock()
+            .unwrap()
+            .retain(|m| m.0 != member.block_id);
+        member
+    }
+}
+
+impl Future for Group {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let this = self.get_mut();
+        this.waker.register(cx.waker());
+
+        while let Poll::Ready(Some((block_id, tx))) = this.commands.poll_next_unpin(cx) {
+            if let Some(i) = this.members.iter().position(|m| m.block_id == block_id) {
+                let member = this.remove(i);
+                if let Err(member) = tx.send(member) {
+                    this.add(member);
+                }
+            }
+        }
+
+        for _ in 0..BATCH {
+            let mut progress = false;
+            let mut i = 0;
+            while i < this.members.len() {
+                let member = &mut this.members[i];
+                let waker = member.waker.as_ref().unwrap();
+                if waker.woken.swap(false, Ordering::AcqRel) {
+                    progress = true;
+                    let w = waker_ref(waker);
+                    let mut member_cx = Context::from_waker(&w);
+                    let start = Instant::now();
+                    let ready = member.future.as_mut().poll(&mut member_cx).is_ready();
+                    member
+                        .cost
+                        .fetch_add(start.elapsed().as_nanos() as u64, Ordering::Relaxed);
+                    if ready {
+                        this.remove(i);
+                        continue;
+                    }
+                }
+                i += 1;
+            }
+            if !progress {
+                break;
+            }
+        }
+
+        if this.members.is_empty() {
+            return Poll::Ready(());
+        }
+
+        // yield to other tasks, if there is still work to do
+        if this
+            .members
+            .iter()
+            .any(|m| m.waker.as_ref().unwrap().woken.load(Ordering::Acquire))
+        {
+            cx.waker().wake_by_ref();
+        }
+        Poll::Pending
+    }
+}
+
+impl Drop for Group {
+    fn drop(&mut self) {
+        self.shared.done.store(true, Ordering::Release);
+    }
+}
+
+fn spawn_group(
+    executor: &Executor<'static>,
+    groups: &Mutex<Vec<GroupHandle>>,
+    members: Vec<Member>,
+) {
+    let (tx, rx) = unbounded();
+    let mut group = Group {
+        members: Vec::new(),
+        waker: Arc::new(AtomicWaker::new()),
+        shared: Arc::new(GroupShared::default()),
+        commands: rx,
+    };
+    for m in members {
+        group.add(m);
+    }
+    groups.lock().unwrap().push(GroupHandle {
+        shared: group.shared.clone(),
+        commands: tx,
+    });
+    executor.spawn(group).detach();
+}
+
+/// Periodically check the load of the groups and split off expensive blocks of busy groups
+async fn monitor(
+    executor: Weak<Executor<'static>>,
+    groups: Arc<Mutex<Vec<GroupHandle>>>,
+    config: ChainConfig,
+) {
+    let mut last: HashMap<usize, u64> = HashMap::new();
+    let interval = config.rebalance_interval.as_nanos() as f64;
+
+    loop {
+        async_io::Timer::after(config.rebalance_interval).await;
+
+        let mut evict = Vec::new();
+        {
+            let mut groups = groups.lock().unwrap();
+            groups.retain(|g| !g.shared.done.load(Ordering::Acquire));
+
+            let mut costs = HashMap::new();
+            for g in groups.iter() {
+                let members = g.shared.members.lock().unwrap();
+                let deltas: Vec<(usize, u64)> = members
+                    .iter()
+                    .map(|(id, cost)| {
+                        let cost = cost.load(Ordering::Relaxed);
+                        costs.insert(*id, cost);
+                        (*id, cost.saturating_sub(last.get(id).copied().unwrap_or(0)))
+                    })
+                    .collect();
+                let load = deltas.iter().map(|d| d.1).sum::<u64>() as f64 / interval;
+
+                if deltas.len() > 1 && load > config.split_load {
+                    let (id, _) = deltas.iter().max_by_key(|d| d.1).unwrap();
+                    let (tx, rx) = oneshot::channel();
+                    if g.commands.unbounded_send((*id, tx)).is_ok() {
+                        debug!("group {deltas:?} has load {load:.2}, splitting off block {id}");
+                        evict.push(rx);
+                    }
+                }
+            }
+            last = costs;
+        }
+
+        for rx in evict {
+            if let Ok(member) = rx.await {
+                match executor.upgrade() {
+                    Some(executor) => spawn_group(&executor, &groups, vec![member]),
+                    None => return,
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,273 @@
+//! Deterministic Scheduler
+use async_task::Runnable;
+use async_task::Task;
+use futures::channel::mpsc::{channel, Sender};
+use futures::future::Future;
+use slab::Slab;
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::task::{Context, Poll, Wake, Waker};
+use std::time::Duration;
+use std::time::Instant;
+
+use crate::runtime::clock::VirtualClock;
+use crate::runtime::config;
+use crate::runtime::scheduler::Scheduler;
+use crate::runtime::Block;
+use crate::runtime::BlockMessage;
+use crate::runtime::FlowgraphMessage;
+use crate::runtime::Topology;
+
+/// Deterministic Scheduler
+///
+/// Runs all tasks, including blocking tasks, on the thread that drives the scheduler through
+/// [`block_on`](Scheduler::block_on), [`step`](Self::step), or [`advance`](Self::advance).
+/// Tasks are polled in the order in which they are woken up, i.e., runs are reproducible.
+///
+/// Time-based blocks use the [`VirtualClock`] of the scheduler (see
+/// [`clock`](crate::runtime::clock)). When all tasks are idle, `block_on` advances the clock to
+/// the next timer, i.e., a flowgraph with a [`Throttle`](crate::blocks::Throttle) set to 1 item/s
+/// processes 1000 items without waiting 1000 s.
+///
+/// ```
+/// # use futuresdr::anyhow::Result;
+/// # use futuresdr::blocks::{Head, NullSink, NullSource, Throttle};
+/// # use futuresdr::runtime::scheduler::{DeterministicScheduler, Scheduler};
+/// # use futuresdr::runtime::{Flowgraph, Runtime};
+/// # use std::time::Duration;
+/// # fn main() -> Result<()> {
+/// let mut fg = Flowgraph::new();
+/// let src = fg.add_block(NullSource::<u8>::new());
+/// let throttle = fg.add_block(Throttle::<u8>::new(100.0));
+/// let snk = fg.add_block(NullSink::<u8>::new());
+/// fg.connect_stream(src, "out", throttle, "in")?;
+/// fg.connect_stream(throttle, "out", snk, "in")?;
+///
+/// let scheduler = DeterministicScheduler::new();
+/// let rt = Runtime::with_scheduler(scheduler.clone());
+/// let (task, mut handle) = scheduler.block_on(rt.start(fg));
+///
+/// // run for 10 s of virtual time and inspect the graph
+/// scheduler.advance(Duration::from_secs(10));
+/// let stats = scheduler.block_on(handle.block_stats(snk))?;
+/// assert!(stats.stream_inputs[0].items_consumed <= 1000);
+///
+/// scheduler.block_on(handle.terminate())?;
+/// scheduler.block_on(task)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct DeterministicScheduler {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    queue: Mutex<VecDeque<Runnable>>,
+    // signaled, when a task is scheduled or the future of block_on is woken up
+    cond: Condvar,
+    clock: VirtualClock,
+}
+
+impl fmt::Debug for DeterministicScheduler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DeterministicScheduler")
+            .field("elapsed", &self.inner.clock.elapsed())
+            .finish()
+    }
+}
+
+struct BlockOnWaker {
+    inner: Arc<Inner>,
+    woken: AtomicBool,
+}
+
+impl Wake for BlockOnWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.woken.store(true, Ordering::SeqCst);
+        let _q = self.inner.queue.lock().unwrap();
+        self.inner.cond.notify_all();
+    }
+}
+
+impl DeterministicScheduler {
+    /// Create deterministic scheduler
+    pub fn new() -> DeterministicScheduler {
+        DeterministicScheduler {
+            inner: Arc::new(Inner {
+                queue: Mutex::new(VecDeque::new()),
+                cond: Condvar::new(),
+                clock: VirtualClock::new(),
+            }),
+        }
+    }
+
+    /// Virtual clock of the scheduler
+    pub fn clock(&self) -> VirtualClock {
+        self.inner.clock.clone()
+    }
+
+    /// Current virtual time
+    pub fn now(&self) -> Instant {
+        self.inner.clock.now()
+    }
+
+    /// Virtual time since the scheduler was created
+    pub fn elapsed(&self) -> Duration {
+        self.inner.clock.elapsed()
+    }
+
+    /// Poll the next task that is ready
+    ///
+    /// Returns `false`, if no task was ready.
+    pub fn step(&self) -> bool {
+        let runnable = self.inner.queue.lock().unwrap().pop_front();
+        match runnable {
+            Some(r) => {
+                let _clock = self.inner.clock.enter();
+                r.run();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Poll tasks until none of them is ready, without advancing the virtual time
+    ///
+    /// Returns the number of polled tasks.
+    pub fn run_until_stalled(&self) -> usize {
+        let mut n = 0;
+        while self.step() {
+            n += 1;
+        }
+        n
+    }
+
+    /// Run tasks, advancing the virtual time by the given duration
+    ///
+    /// Timers expire in order, running all tasks that are ready in between.
+    pub fn advance(&self, duration: Duration) {
+        let target = self.inner.clock.elapsed() + duration;
+        loop {
+            self.run_until_stalled();
+            match self.inner.clock.next_deadline() {
+                Some(t) if t <= target => self.inner.clock.advance_to(t),
+                _ => break,
+            }
+        }
+        self.inner.clock.advance_to(target);
+        self.run_until_stalled();
+    }
+
+    fn schedule(inner: &Arc<Inner>) -> impl Fn(Runnable) + Send + Sync + 'static {
+        let inner = Arc::downgrade(inner);
+        move |runnable| {
+            if let Some(inner) = inner.upgrade() {
+                inner.queue.lock().unwrap().push_back(runnable);
+                inner.cond.notify_all();
+            }
+        }
+    }
+}
+
+impl Scheduler for DeterministicScheduler {
+    fn run_topology(
+        &self,
+        topology: &mut Topology,
+        main_channel: &Sender<FlowgraphMessage>,
+    ) -> Slab<Option<Sender<BlockMessage>>> {
+        let mut inboxes = Slab::new();
+        let max = topology.blocks.iter().map(|(i, _)| i).max().unwrap_or(0);
+        for _ in 0..=max {
+            inboxes.insert(None);
+        }
+        let queue_size = config::config().queue_size;
+
+        // spawn block executors
+        for (id, block_o) in topology.blocks.iter_mut() {
+            let block = block_o.take().unwrap();
+
+            let (sender, receiver) = channel::<BlockMessage>(queue_size);
+            inboxes[id] = Some(sender);
+
+            self.spawn(block.run(id, main_channel.clone(), receiver))
+                .detach();
+        }
+
+        inboxes
+    }
+
+    fn spawn_block(
+        &self,
+        block_id: usize,
+        block: Block,
+        main_channel: &Sender<FlowgraphMessage>,
+    ) -> Sender<BlockMessage> {
+        let (sender, receiver) = channel::<BlockMessage>(config::config().queue_size);
+
+        self.spawn(block.run(block_id, main_channel.clone(), receiver))
+            .detach();
+
+        sender
+    }
+
+    fn spawn<T: Send + 'static>(
+        &self,
+        future: impl Future<Output = T> + Send + 'static,
+    ) -> Task<T> {
+        let (runnable, task) = async_task::spawn(future, Self::schedule(&self.inner));
+        runnable.schedule();
+        task
+    }
+
+    fn spawn_blocking<T: Send + 'static>(
+        &self,
+        future: impl Future<Output = T> + Send + 'static,
+    ) -> Task<T> {
+        self.spawn(future)
+    }
+
+    fn block_on<T>(&self, future: impl Future<Output = T>) -> T {
+        let waker = Arc::new(BlockOnWaker {
+            inner: self.inner.clone(),
+            woken: AtomicBool::new(true),
+        });
+        let w = Waker::from(waker.clone());
+        let mut cx = Context::from_waker(&w);
+        futures::pin_mut!(future);
+
+        loop {
+            if waker.woken.swap(false, Ordering::SeqCst) {
+                let _clock = self.inner.clock.enter();
+                if let Poll::Ready(t) = future.as_mut().poll(&mut cx) {
+                    return t;
+                }
+            }
+            if self.step() || waker.woken.load(Ordering::SeqCst) {
+                continue;
+            }
+            if let Some(t) = self.inner.clock.next_deadline() {
+                self.inner.clock.advance_to(t);
+                continue;
+            }
+
+            // all tasks are idle and no timers are pending, wait for external events
+            let mut q = self.inner.queue.lock().unwrap();
+            while q.is_empty() && !waker.woken.load(Ordering::SeqCst) {
+                q = self.inner.cond.wait(q).unwrap();
+            }
+        }
+    }
+}
+
+impl Default for DeterministicScheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
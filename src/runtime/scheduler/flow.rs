@@ -4,7 +4,7 @@ use async_task::Runnable;
 use async_task::Task;
 use concurrent_queue::ConcurrentQueue;
 use core_affinity;
-use futures::channel::mpsc::{channel, Sender};
+use futures::channel::mpsc::{channel, Receiver, Sender};
 use futures::channel::oneshot;
 use futures_lite::future::{self, Future, FutureExt};
 use slab::Slab;
@@ -17,14 +17,22 @@ use std::task::{Poll, Waker};
 use std::thread;
 
 use crate::runtime::config;
+use crate::runtime::scheduler::hints;
 use crate::runtime::scheduler::Scheduler;
+use crate::runtime::Block;
 use crate::runtime::BlockMessage;
 use crate::runtime::FlowgraphMessage;
+use crate::runtime::Priority;
 use crate::runtime::Topology;
 
 /// Flow scheduler
 ///
 /// Groups blocks and puts them fixed in local queues of worker threads.
+///
+/// Worker threads are pinned to CPU cores. Blocks with a core set in their
+/// [`SchedulingHints`](crate::runtime::SchedulingHints) are put in the queue of a worker on one
+/// of these cores. Blocks that should run on a dedicated thread or with a different priority get
+/// a thread of their own.
 #[derive(Clone, Debug)]
 pub struct FlowScheduler {
     inner: Arc<FlowSchedulerInner>,
@@ -33,6 +41,8 @@ pub struct FlowScheduler {
 struct FlowSchedulerInner {
     executor: Arc<FlowExecutor>,
     workers: Vec<(thread::JoinHandle<()>, oneshot::Sender<()>)>,
+    // core id of each worker
+    cores: Vec<usize>,
 }
 
 impl fmt::Debug for FlowSchedulerInner {
@@ -60,6 +70,7 @@ impl FlowScheduler {
         debug!("flowsched: core ids {}", core_ids.len());
 
         let barrier = Arc::new(Barrier::new(core_ids.len() + 1));
+        let cores = core_ids.iter().map(|c| c.id).collect();
 
         for id in core_ids {
             let b = barrier.clone();
@@ -92,7 +103,11 @@ impl FlowScheduler {
         async_io::block_on(barrier.wait());
 
         FlowScheduler {
-            inner: Arc::new(FlowSchedulerInner { executor, workers }),
+            inner: Arc::new(FlowSchedulerInner {
+                executor,
+                workers,
+                cores,
+            }),
         }
     }
 
@@ -108,6 +123,68 @@ impl FlowScheduler {
 
         n_cores - 1
     }
+
+    fn spawn_block_task(
+        &self,
+        block_id: usize,
+        block: Block,
+        main_channel: Sender<FlowgraphMessage>,
+        receiver: Receiver<BlockMessage>,
+        mut executor: usize,
+    ) {
+        let block_hints = block.scheduling_hints();
+        let name = block.instance_name().unwrap_or("block").to_string();
+
+        // threads of blocking blocks are not the pinned worker threads
+        if block_hints.dedicated_thread
+            || block_hints.priority != Priority::Normal
+            || (block.is_blocking() && block_hints.cores.is_some())
+        {
+            hints::spawn_thread(
+                name,
+                block_hints,
+                block.run(block_id, main_channel, receiver),
+            );
+            return;
+        }
+
+        if let Some(cores) = &block_hints.cores {
+            let matching: Vec<usize> = self
+                .inner
+                .cores
+                .iter()
+                .enumerate()
+                .filter(|(_, c)| cores.contains(c))
+                .map(|(i, _)| i)
+                .collect();
+            if matching.is_empty() {
+                warn!(
+                    "{name}: no worker on cores {cores:?}, using core {}",
+                    self.inner.cores[executor]
+                );
+            } else if !matching.contains(&executor) {
+                executor = matching[block_id % matching.len()];
+            }
+        }
+
+        if block.is_blocking() {
+            debug!("spawing block on executor");
+            self.inner
+                .executor
+                .spawn_executor(
+                    blocking::unblock(move || {
+                        block_on(block.run(block_id, main_channel, receiver))
+                    }),
+                    executor,
+                )
+                .detach();
+        } else {
+            self.inner
+                .executor
+                .spawn_executor(block.run(block_id, main_channel, receiver), executor)
+                .detach();
+        }
+    }
 }
 
 impl Scheduler for FlowScheduler {
@@ -133,30 +210,38 @@ impl Scheduler for FlowScheduler {
             let (sender, receiver) = channel::<BlockMessage>(queue_size);
             inboxes[id] = Some(sender.clone());
 
-            if block.is_blocking() {
-                let main = main_channel.clone();
-                debug!("spawing block on executor");
-                self.inner
-                    .executor
-                    .spawn_executor(
-                        blocking::unblock(move || block_on(block.run(id, main, receiver))),
-                        FlowScheduler::map_block(id, n_blocks, n_cores),
-                    )
-                    .detach();
-            } else {
-                self.inner
-                    .executor
-                    .spawn_executor(
-                        block.run(id, main_channel.clone(), receiver),
-                        FlowScheduler::map_block(id, n_blocks, n_cores),
-                    )
-                    .detach();
-            }
+            self.spawn_block_task(
+                id,
+                block,
+                main_channel.clone(),
+                receiver,
+                FlowScheduler::map_block(id, n_blocks, n_cores),
+            );
         }
 
         inboxes
     }
 
+    fn spawn_block(
+        &self,
+        block_id: usize,
+        block: Block,
+        main_channel: &Sender<FlowgraphMessage>,
+    ) -> Sender<BlockMessage> {
+        let (sender, receiver) = channel::<BlockMessage>(config::config().queue_size);
+        let n_cores = self.inner.workers.len();
+
+        self.spawn_block_task(
+            block_id,
+            block,
+            main_channel.clone(),
+            receiver,
+            block_id % n_cores,
+        );
+
+        sender
+    }
+
     fn spawn<T: Send + 'static>(
         &self,
         future: impl Future<Output = T> + Send + 'static,
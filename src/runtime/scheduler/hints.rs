@@ -0,0 +1,173 @@
+//! Apply [`SchedulingHints`] to threads
+use futures::channel::mpsc::{Receiver, Sender};
+use futures::future::Future;
+use std::pin::Pin;
+use std::thread;
+
+use crate::runtime::config;
+use crate::runtime::scheduler::Scheduler;
+use crate::runtime::Block;
+use crate::runtime::BlockMessage;
+use crate::runtime::FlowgraphMessage;
+use crate::runtime::Priority;
+use crate::runtime::SchedulingHints;
+
+pub(crate) type BlockFuture = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// Check, if the block needs a thread of its own to honor the hints
+///
+/// Threads of executors are shared between blocks, so they cannot be pinned or prioritized for
+/// a single block.
+pub(crate) fn needs_thread(hints: &SchedulingHints) -> bool {
+    hints.dedicated_thread || hints.priority != Priority::Normal || hints.cores.is_some()
+}
+
+/// Spawn the task of a block on a scheduler, whose executor threads are shared by all blocks
+///
+/// Blocks with hints run on a thread of their own and blocking blocks on the blocking pool of
+/// the scheduler. All other blocks are passed to `spawn`.
+pub(crate) fn spawn_block_task<S: Scheduler>(
+    scheduler: &S,
+    block_id: usize,
+    block: Block,
+    main_channel: Sender<FlowgraphMessage>,
+    receiver: Receiver<BlockMessage>,
+    spawn: impl FnOnce(BlockFuture),
+) {
+    let block_hints = block.scheduling_hints();
+    if needs_thread(&block_hints) {
+        let name = block.instance_name().unwrap_or("block").to_string();
+        spawn_thread(
+            name,
+            block_hints,
+            block.run(block_id, main_channel, receiver),
+        );
+    } else if block.is_blocking() {
+        scheduler
+            .spawn_blocking(block.run(block_id, main_channel, receiver))
+            .detach();
+    } else {
+        spawn(Box::pin(block.run(block_id, main_channel, receiver)));
+    }
+}
+
+/// Run a block on a thread of its own, applying the hints
+pub(crate) fn spawn_thread(
+    name: String,
+    hints: SchedulingHints,
+    future: impl Future<Output = ()> + Send + 'static,
+) {
+    thread::Builder::new()
+        .stack_size(config::config().stack_size)
+        .name(name.clone())
+        .spawn(move || {
+            apply(&name, &hints);
+            async_io::block_on(future);
+        })
+        .expect("failed to spawn block thread");
+}
+
+/// Pin the current thread and set its priority
+///
+/// Logs a warning, if a hint cannot be satisfied.
+pub(crate) fn apply(name: &str, hints: &SchedulingHints) {
+    if let Some(cores) = &hints.cores {
+        if let Err(e) = pin(cores) {
+            warn!("{name}: cannot pin to cores {cores:?} ({e})");
+        }
+    }
+    if let Err(e) = set_priority(hints.priority) {
+        warn!("{name}: cannot set priority {:?} ({e})", hints.priority);
+    }
+}
+
+/// Available cores of the given set
+fn available_cores(cores: &[usize]) -> Vec<usize> {
+    let available: Vec<usize> = core_affinity::get_core_ids()
+        .unwrap_or_default()
+        .iter()
+        .map(|c| c.id)
+        .collect();
+    cores
+        .iter()
+        .copied()
+        .filter(|c| available.contains(c))
+        .collect()
+}
+
+fn pin(cores: &[usize]) -> Result<(), String> {
+    let valid = available_cores(cores);
+    if valid.is_empty() {
+        return Err("cores not available".to_string());
+    }
+    if valid.len() < cores.len() {
+        warn!("cores {cores:?} partially not available, using {valid:?}");
+    }
+
+    #[cfg(target_os = "linux")]
+    unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        libc::CPU_ZERO(&mut set);
+        for c in valid.iter() {
+            libc::CPU_SET(*c, &mut set);
+        }
+        if libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set) != 0 {
+            return Err(std::io::Error::last_os_error().to_string());
+        }
+        Ok(())
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        if valid.len() > 1 {
+            warn!(
+                "core sets are only supported on Linux, pinning to core {}",
+                valid[0]
+            );
+        }
+        if core_affinity::set_for_current(core_affinity::CoreId { id: valid[0] }) {
+            Ok(())
+        } else {
+            Err("setting affinity failed".to_string())
+        }
+    }
+}
+
+#[cfg(unix)]
+fn set_priority(priority: Priority) -> Result<(), String> {
+    match priority {
+        Priority::Normal => Ok(()),
+        Priority::High => {
+            #[cfg(target_os = "linux")]
+            unsafe {
+                // the nice value of a thread is set through its thread id
+                let tid = libc::syscall(libc::SYS_gettid) as libc::id_t;
+                if libc::setpriority(libc::PRIO_PROCESS, tid, -10) != 0 {
+                    return Err(std::io::Error::last_os_error().to_string());
+                }
+                Ok(())
+            }
+            #[cfg(not(target_os = "linux"))]
+            Err("not supported on this platform".to_string())
+        }
+        Priority::Realtime => unsafe {
+            let min = libc::sched_get_priority_min(libc::SCHED_FIFO);
+            let max = libc::sched_get_priority_max(libc::SCHED_FIFO);
+            let mut param: libc::sched_param = std::mem::zeroed();
+            param.sched_priority = min + (max - min) / 2;
+            let ret = libc::pthread_setschedparam(libc::pthread_self(), libc::SCHED_FIFO, &param);
+            if ret != 0 {
+                return Err(std::io::Error::from_raw_os_error(ret).to_string());
+            }
+            Ok(())
+        },
+    }
+}
+
+#[cfg(not(unix))]
+fn set_priority(priority: Priority) -> Result<(), String> {
+    match priority {
+        Priority::Normal => Ok(()),
+        _ => Err("not supported on this platform".to_string()),
+    }
+}
@@ -1,4 +1,19 @@
 //! Flowgraph Scheduler Trait and Implementations
+#[cfg(feature = "chain_scheduler")]
+mod chain;
+#[cfg(feature = "chain_scheduler")]
+pub use crate::runtime::scheduler::chain::ChainConfig;
+#[cfg(feature = "chain_scheduler")]
+pub use crate::runtime::scheduler::chain::ChainScheduler;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod deterministic;
+#[cfg(not(target_arch = "wasm32"))]
+pub use crate::runtime::scheduler::deterministic::DeterministicScheduler;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod hints;
+
 #[cfg(feature = "flow_scheduler")]
 mod flow;
 #[cfg(feature = "flow_scheduler")]
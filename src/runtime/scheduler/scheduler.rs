@@ -3,6 +3,7 @@ use futures::future::Future;
 use slab::Slab;
 
 use crate::runtime::scheduler::Task;
+use crate::runtime::Block;
 use crate::runtime::BlockMessage;
 use crate::runtime::FlowgraphMessage;
 use crate::runtime::Topology;
@@ -19,6 +20,16 @@ pub trait Scheduler: Clone + Send + 'static {
         main_channel: &Sender<FlowgraphMessage>,
     ) -> Slab<Option<Sender<BlockMessage>>>;
 
+    /// Spawn a single block, returning its inbox
+    ///
+    /// Used to add blocks to a running [`Flowgraph`](crate::runtime::Flowgraph).
+    fn spawn_block(
+        &self,
+        block_id: usize,
+        block: Block,
+        main_channel: &Sender<FlowgraphMessage>,
+    ) -> Sender<BlockMessage>;
+
     /// Spawn a task
     fn spawn<T: Send + 'static>(&self, future: impl Future<Output = T> + Send + 'static)
         -> Task<T>;
@@ -28,4 +39,10 @@ pub trait Scheduler: Clone + Send + 'static {
         &self,
         future: impl Future<Output = T> + Send + 'static,
     ) -> Task<T>;
+
+    /// Block the current thread, until the future completes
+    #[cfg(not(target_arch = "wasm32"))]
+    fn block_on<T>(&self, future: impl Future<Output = T>) -> T {
+        async_io::block_on(future)
+    }
 }
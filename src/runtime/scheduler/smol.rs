@@ -1,5 +1,5 @@
 use async_executor::{Executor, Task};
-use futures::channel::mpsc::{channel, Sender};
+use futures::channel::mpsc::{channel, Receiver, Sender};
 use futures::channel::oneshot;
 use futures::future::Future;
 use log::debug;
@@ -10,7 +10,9 @@ use std::sync::{Arc, Mutex};
 use std::thread;
 
 use crate::runtime::config;
+use crate::runtime::scheduler::hints;
 use crate::runtime::scheduler::Scheduler;
+use crate::runtime::Block;
 use crate::runtime::BlockMessage;
 use crate::runtime::FlowgraphMessage;
 use crate::runtime::Topology;
@@ -20,6 +22,9 @@ static SMOL: Lazy<Mutex<Slab<Arc<Executor<'_>>>>> = Lazy::new(|| Mutex::new(Slab
 /// Smol Scheduler
 ///
 /// Default scheduler of the smol async runtime
+///
+/// Blocks with [`SchedulingHints`](crate::runtime::SchedulingHints) run on a thread of their
+/// own, which is pinned and prioritized according to the hints.
 #[derive(Clone, Debug)]
 pub struct SmolScheduler {
     inner: Arc<SmolSchedulerInner>,
@@ -97,6 +102,18 @@ impl SmolScheduler {
             inner: Arc::new(SmolSchedulerInner { id, workers }),
         }
     }
+
+    fn spawn_block_task(
+        &self,
+        block_id: usize,
+        block: Block,
+        main_channel: Sender<FlowgraphMessage>,
+        receiver: Receiver<BlockMessage>,
+    ) {
+        hints::spawn_block_task(self, block_id, block, main_channel, receiver, |f| {
+            self.spawn(f).detach()
+        });
+    }
 }
 
 impl Scheduler for SmolScheduler {
@@ -119,18 +136,23 @@ impl Scheduler for SmolScheduler {
             let (sender, receiver) = channel::<BlockMessage>(queue_size);
             inboxes[id] = Some(sender);
 
-            if block.is_blocking() {
-                self.spawn_blocking(block.run(id, main_channel.clone(), receiver))
-                    .detach();
-            } else {
-                self.spawn(block.run(id, main_channel.clone(), receiver))
-                    .detach();
-            }
+            self.spawn_block_task(id, block, main_channel.clone(), receiver);
         }
 
         inboxes
     }
 
+    fn spawn_block(
+        &self,
+        block_id: usize,
+        block: Block,
+        main_channel: &Sender<FlowgraphMessage>,
+    ) -> Sender<BlockMessage> {
+        let (sender, receiver) = channel::<BlockMessage>(config::config().queue_size);
+        self.spawn_block_task(block_id, block, main_channel.clone(), receiver);
+        sender
+    }
+
     fn spawn<T: Send + 'static>(
         &self,
         future: impl Future<Output = T> + Send + 'static,
@@ -1,5 +1,5 @@
 use async_executor::{Executor, Task};
-use futures::channel::mpsc::{channel, Sender};
+use futures::channel::mpsc::{channel, Receiver, Sender};
 use futures::channel::oneshot;
 use futures::future::Future;
 use once_cell::sync::Lazy;
@@ -9,9 +9,12 @@ use std::sync::{Arc, Mutex};
 use std::thread;
 
 use crate::runtime::config;
+use crate::runtime::scheduler::hints;
 use crate::runtime::scheduler::Scheduler;
+use crate::runtime::Block;
 use crate::runtime::BlockMessage;
 use crate::runtime::FlowgraphMessage;
+use crate::runtime::Priority;
 use crate::runtime::Topology;
 
 static TPB: Lazy<Mutex<Slab<Arc<Executor<'_>>>>> = Lazy::new(|| Mutex::new(Slab::new()));
@@ -76,6 +79,29 @@ impl TpbScheduler {
             inner: Arc::new(TpbSchedulerInner { id, workers }),
         }
     }
+
+    // Every block has a thread of its own. Blocks that should be pinned or prioritized get a
+    // new thread instead of one from the thread pool.
+    fn spawn_block_task(
+        &self,
+        block_id: usize,
+        block: Block,
+        main_channel: Sender<FlowgraphMessage>,
+        receiver: Receiver<BlockMessage>,
+    ) {
+        let block_hints = block.scheduling_hints();
+        if block_hints.cores.is_some() || block_hints.priority != Priority::Normal {
+            let name = block.instance_name().unwrap_or("block").to_string();
+            hints::spawn_thread(
+                name,
+                block_hints,
+                block.run(block_id, main_channel, receiver),
+            );
+        } else {
+            self.spawn_blocking(block.run(block_id, main_channel, receiver))
+                .detach();
+        }
+    }
 }
 
 impl Scheduler for TpbScheduler {
@@ -100,13 +126,23 @@ impl Scheduler for TpbScheduler {
             let (sender, receiver) = channel::<BlockMessage>(queue_size);
             inboxes[id] = Some(sender);
 
-            self.spawn_blocking(block.run(id, main_channel.clone(), receiver))
-                .detach();
+            self.spawn_block_task(id, block, main_channel.clone(), receiver);
         }
 
         inboxes
     }
 
+    fn spawn_block(
+        &self,
+        block_id: usize,
+        block: Block,
+        main_channel: &Sender<FlowgraphMessage>,
+    ) -> Sender<BlockMessage> {
+        let (sender, receiver) = channel::<BlockMessage>(config::config().queue_size);
+        self.spawn_block_task(block_id, block, main_channel.clone(), receiver);
+        sender
+    }
+
     fn spawn<T: Send + 'static>(
         &self,
         future: impl Future<Output = T> + Send + 'static,
@@ -10,6 +10,7 @@ use std::pin::Pin;
 
 use crate::runtime::config;
 use crate::runtime::scheduler::Scheduler;
+use crate::runtime::Block;
 use crate::runtime::BlockMessage;
 use crate::runtime::FlowgraphMessage;
 use crate::runtime::Topology;
@@ -55,6 +56,23 @@ impl Scheduler for WasmScheduler {
         inboxes
     }
 
+    fn spawn_block(
+        &self,
+        block_id: usize,
+        block: Block,
+        main_channel: &Sender<FlowgraphMessage>,
+    ) -> Sender<BlockMessage> {
+        let (sender, receiver) = channel::<BlockMessage>(config::config().queue_size);
+
+        if block.is_blocking() {
+            self.spawn_blocking(block.run(block_id, main_channel.clone(), receiver));
+        } else {
+            self.spawn(block.run(block_id, main_channel.clone(), receiver));
+        }
+
+        sender
+    }
+
     fn spawn<T: Send + 'static>(
         &self,
         future: impl Future<Output = T> + Send + 'static,
@@ -0,0 +1,386 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Number, Value};
+use std::collections::HashMap;
+use std::path::Path;
+use yaml_rust::{Yaml, YamlEmitter, YamlLoader};
+
+use crate::anyhow::{anyhow, bail, Context, Result};
+use crate::runtime::FlowgraphDescription;
+use crate::runtime::MessageQueueConfig;
+use crate::runtime::Pmt;
+
+/// File format of a [`FlowgraphSpec`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpecFormat {
+    /// JSON
+    Json,
+    /// TOML
+    Toml,
+    /// YAML
+    Yaml,
+}
+
+impl SpecFormat {
+    /// Get format from the extension of a file (`json`, `toml`, `yaml`, or `yml`)
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => Ok(SpecFormat::Json),
+            Some("toml") => Ok(SpecFormat::Toml),
+            Some("yaml") | Some("yml") => Ok(SpecFormat::Yaml),
+            _ => bail!("unknown flowgraph file format ({})", path.display()),
+        }
+    }
+}
+
+/// Serializable specification of a [`Flowgraph`](crate::runtime::Flowgraph)
+///
+/// Lists the blocks with their type and parameters and the stream and message connections. Blocks
+/// are instantiated through a [`BlockRegistry`](crate::runtime::BlockRegistry), using
+/// [`Flowgraph::from_description`](crate::runtime::Flowgraph::from_description).
+///
+/// ```toml
+/// [[blocks]]
+/// name = "src"
+/// type = "NullSource"
+/// parameters = { item_type = "f32" }
+///
+/// [[blocks]]
+/// name = "head"
+/// type = "Head"
+/// parameters = { item_type = "f32", n_items = 1024 }
+///
+/// [[blocks]]
+/// name = "snk"
+/// type = "NullSink"
+/// parameters = { item_type = "f32" }
+///
+/// [[stream_edges]]
+/// src = "src"
+/// src_port = "out"
+/// dst = "head"
+/// dst_port = "in"
+///
+/// [[stream_edges]]
+/// src = "head"
+/// src_port = "out"
+/// dst = "snk"
+/// dst_port = "in"
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FlowgraphSpec {
+    /// Blocks
+    #[serde(default)]
+    pub blocks: Vec<BlockSpec>,
+    /// Stream connections
+    #[serde(default)]
+    pub stream_edges: Vec<EdgeSpec>,
+    /// Message connections
+    #[serde(default)]
+    pub message_edges: Vec<EdgeSpec>,
+}
+
+/// Block of a [`FlowgraphSpec`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockSpec {
+    /// Instance name
+    pub name: String,
+    /// Type name, used to look up the constructor in the registry
+    #[serde(rename = "type")]
+    pub type_name: String,
+    /// Parameters, passed to the constructor as [`Pmt::MapStrPmt`]
+    ///
+    /// Parameters are plain values in the file. Integers are mapped to [`Pmt::U64`] (negative
+    /// integers to [`Pmt::F64`]), floats to [`Pmt::F64`], arrays to [`Pmt::VecPmt`], and tables to
+    /// [`Pmt::MapStrPmt`].
+    #[serde(
+        default,
+        with = "parameters",
+        skip_serializing_if = "HashMap::is_empty"
+    )]
+    pub parameters: HashMap<String, Pmt>,
+}
+
+/// Connection of a [`FlowgraphSpec`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EdgeSpec {
+    /// Source block
+    pub src: String,
+    /// Source port
+    pub src_port: String,
+    /// Destination block
+    pub dst: String,
+    /// Destination port
+    pub dst_port: String,
+    /// Reinterpret items of the output as items of the input (only for stream connections)
+    ///
+    /// See [`Flowgraph::connect_stream_reinterpret`](crate::runtime::Flowgraph::connect_stream_reinterpret).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub reinterpret: bool,
+    /// Queue of the connection (only for message connections)
+    ///
+    /// See [`Flowgraph::connect_message_with_queue`](crate::runtime::Flowgraph::connect_message_with_queue).
+    #[serde(default, skip_serializing_if = "is_block")]
+    pub queue: MessageQueueConfig,
+}
+
+fn is_block(q: &MessageQueueConfig) -> bool {
+    *q == MessageQueueConfig::Block
+}
+
+impl FlowgraphSpec {
+    /// Parse specification
+    pub fn parse(s: &str, format: SpecFormat) -> Result<Self> {
+        let spec = match format {
+            SpecFormat::Json => serde_json::from_str(s)?,
+            SpecFormat::Toml => toml::from_str(s)?,
+            SpecFormat::Yaml => {
+                let docs = YamlLoader::load_from_str(s)?;
+                let doc = docs.into_iter().next().unwrap_or(Yaml::Null);
+                serde_json::from_value(yaml_to_value(doc)?)?
+            }
+        };
+        Ok(spec)
+    }
+
+    /// Serialize specification
+    pub fn dump(&self, format: SpecFormat) -> Result<String> {
+        let s = match format {
+            SpecFormat::Json => serde_json::to_string_pretty(self)?,
+            // go through a toml::Value, which emits plain values before tables
+            SpecFormat::Toml => toml::to_string_pretty(&toml::Value::try_from(self)?)?,
+            SpecFormat::Yaml => {
+                let mut s = String::new();
+                YamlEmitter::new(&mut s).dump(&value_to_yaml(serde_json::to_value(self)?))?;
+                s.push('\n');
+                s
+            }
+        };
+        Ok(s)
+    }
+
+    /// Load specification from file
+    ///
+    /// The format is determined by the file extension.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let format = SpecFormat::from_path(path)?;
+        let s = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        Self::parse(&s, format).with_context(|| format!("failed to parse {}", path.display()))
+    }
+
+    /// Save specification to file
+    ///
+    /// The format is determined by the file extension.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let s = self.dump(SpecFormat::from_path(path)?)?;
+        std::fs::write(path, s).with_context(|| format!("failed to write {}", path.display()))
+    }
+
+    /// Create specification from a [`FlowgraphDescription`] and the parameters of the blocks
+    ///
+    /// Blocks without parameters get an empty parameter map.
+    pub fn from_description(
+        description: &FlowgraphDescription,
+        parameters: &HashMap<usize, HashMap<String, Pmt>>,
+    ) -> Result<Self> {
+        let mut blocks: Vec<_> = description.blocks.iter().collect();
+        blocks.sort_by_key(|b| b.id);
+
+        let block = |id: usize| {
+            description
+                .blocks
+                .iter()
+                .find(|b| b.id == id)
+                .ok_or_else(|| anyhow!("edge refers to unknown block {id}"))
+        };
+        let port = |ports: &Vec<String>, id: usize| {
+            ports
+                .get(id)
+                .cloned()
+                .ok_or_else(|| anyhow!("edge refers to unknown port {id}"))
+        };
+
+        let mut stream_edges = Vec::new();
+        for (src, src_port, dst, dst_port) in description.stream_edges.iter().copied() {
+            let (s, d) = (block(src)?, block(dst)?);
+            let reinterpret = matches!(
+                (s.stream_output_types.get(src_port), d.stream_input_types.get(dst_port)),
+                (Some(a), Some(b)) if a != b
+            );
+            stream_edges.push(EdgeSpec {
+                src: s.instance_name.clone(),
+                src_port: port(&s.stream_outputs, src_port)?,
+                dst: d.instance_name.clone(),
+                dst_port: port(&d.stream_inputs, dst_port)?,
+                reinterpret,
+                queue: MessageQueueConfig::Block,
+            });
+        }
+        stream_edges.sort_by(|a, b| (&a.src, &a.src_port).cmp(&(&b.src, &b.src_port)));
+
+        let mut message_edges = Vec::new();
+        for (i, (src, src_port, dst, dst_port)) in
+            description.message_edges.iter().copied().enumerate()
+        {
+            let (s, d) = (block(src)?, block(dst)?);
+            message_edges.push(EdgeSpec {
+                src: s.instance_name.clone(),
+                src_port: port(&s.message_outputs, src_port)?,
+                dst: d.instance_name.clone(),
+                dst_port: port(&d.message_inputs, dst_port)?,
+                reinterpret: false,
+                queue: description
+                    .message_queues
+                    .get(i)
+                    .map(|q| q.config)
+                    .unwrap_or_default(),
+            });
+        }
+
+        Ok(FlowgraphSpec {
+            blocks: blocks
+                .into_iter()
+                .map(|b| BlockSpec {
+                    name: b.instance_name.clone(),
+                    type_name: b.type_name.clone(),
+                    parameters: parameters.get(&b.id).cloned().unwrap_or_default(),
+                })
+                .collect(),
+            stream_edges,
+            message_edges,
+        })
+    }
+}
+
+mod parameters {
+    use serde::de::Deserializer;
+    use serde::ser::{Error, Serializer};
+    use serde::{Deserialize, Serialize};
+    use serde_json::{Map, Value};
+    use std::collections::HashMap;
+
+    use crate::runtime::Pmt;
+
+    pub fn serialize<S: Serializer>(
+        parameters: &HashMap<String, Pmt>,
+        s: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut m = Map::new();
+        for (k, v) in parameters {
+            m.insert(k.clone(), super::pmt_to_value(v).map_err(S::Error::custom)?);
+        }
+        m.serialize(s)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<HashMap<String, Pmt>, D::Error> {
+        let m = Map::<String, Value>::deserialize(d)?;
+        Ok(m.into_iter()
+            .map(|(k, v)| (k, super::value_to_pmt(v)))
+            .collect())
+    }
+}
+
+fn value_to_pmt(v: Value) -> Pmt {
+    match v {
+        Value::Null => Pmt::Null,
+        Value::Bool(b) => Pmt::Bool(b),
+        Value::Number(n) => match n.as_u64() {
+            Some(u) => Pmt::U64(u),
+            None => Pmt::F64(n.as_f64().unwrap_or(f64::NAN)),
+        },
+        Value::String(s) => Pmt::String(s),
+        Value::Array(a) => Pmt::VecPmt(a.into_iter().map(value_to_pmt).collect()),
+        Value::Object(m) => {
+            Pmt::MapStrPmt(m.into_iter().map(|(k, v)| (k, value_to_pmt(v))).collect())
+        }
+    }
+}
+
+fn pmt_to_value(p: &Pmt) -> Result<Value> {
+    let float = |f: f64| {
+        Number::from_f64(f)
+            .map(Value::Number)
+            .ok_or_else(|| anyhow!("cannot export non-finite float {f}"))
+    };
+
+    let v = match p {
+        Pmt::Null => Value::Null,
+        Pmt::Bool(b) => Value::Bool(*b),
+        Pmt::String(s) => Value::String(s.clone()),
+        Pmt::Usize(u) => Value::from(*u),
+        Pmt::U32(u) => Value::from(*u),
+        Pmt::U64(u) => Value::from(*u),
+        Pmt::F32(f) => float(*f as f64)?,
+        Pmt::F64(f) => float(*f)?,
+        Pmt::VecF32(v) => Value::Array(
+            v.iter()
+                .map(|f| float(*f as f64))
+                .collect::<Result<Vec<_>>>()?,
+        ),
+        Pmt::VecU64(v) => Value::from(v.clone()),
+        Pmt::Blob(v) => Value::from(v.clone()),
+        Pmt::VecPmt(v) => Value::Array(v.iter().map(pmt_to_value).collect::<Result<Vec<_>>>()?),
+        Pmt::MapStrPmt(m) => Value::Object(
+            m.iter()
+                .map(|(k, v)| Ok((k.clone(), pmt_to_value(v)?)))
+                .collect::<Result<Map<_, _>>>()?,
+        ),
+        p => bail!("cannot export parameter {p:?}"),
+    };
+    Ok(v)
+}
+
+fn yaml_to_value(y: Yaml) -> Result<Value> {
+    let v = match y {
+        Yaml::Null => Value::Null,
+        Yaml::Boolean(b) => Value::Bool(b),
+        Yaml::Integer(i) => Value::from(i),
+        Yaml::Real(s) => {
+            let f: f64 = s.parse().with_context(|| format!("invalid float {s}"))?;
+            Number::from_f64(f)
+                .map(Value::Number)
+                .ok_or_else(|| anyhow!("non-finite float {s}"))?
+        }
+        Yaml::String(s) => Value::String(s),
+        Yaml::Array(a) => Value::Array(
+            a.into_iter()
+                .map(yaml_to_value)
+                .collect::<Result<Vec<_>>>()?,
+        ),
+        Yaml::Hash(h) => {
+            let mut m = Map::new();
+            for (k, v) in h {
+                let k = match k {
+                    Yaml::String(s) => s,
+                    Yaml::Integer(i) => i.to_string(),
+                    k => bail!("unsupported YAML key {k:?}"),
+                };
+                m.insert(k, yaml_to_value(v)?);
+            }
+            Value::Object(m)
+        }
+        y => bail!("unsupported YAML value {y:?}"),
+    };
+    Ok(v)
+}
+
+fn value_to_yaml(v: Value) -> Yaml {
+    match v {
+        Value::Null => Yaml::Null,
+        Value::Bool(b) => Yaml::Boolean(b),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => Yaml::Integer(i),
+            None => Yaml::Real(format!("{:?}", n.as_f64().unwrap_or(f64::NAN))),
+        },
+        Value::String(s) => Yaml::String(s),
+        Value::Array(a) => Yaml::Array(a.into_iter().map(value_to_yaml).collect()),
+        Value::Object(m) => Yaml::Hash(
+            m.into_iter()
+                .map(|(k, v)| (Yaml::String(k), value_to_yaml(v)))
+                .collect(),
+        ),
+    }
+}
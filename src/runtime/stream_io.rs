@@ -2,16 +2,112 @@
 use futures::channel::mpsc::Sender;
 use std::any::Any;
 use std::any::TypeId;
+#[cfg(not(target_arch = "wasm32"))]
+use std::collections::VecDeque;
 use std::fmt;
 use std::mem;
 use std::slice;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
 
+use crate::runtime::buffer::slab;
 use crate::runtime::buffer::BufferReader;
 use crate::runtime::buffer::BufferWriter;
 use crate::runtime::tag::default_tag_propagation;
+use crate::runtime::tag::TagPropagationFn;
 use crate::runtime::BlockMessage;
 use crate::runtime::ItemTag;
+use crate::runtime::StreamInputStats;
+use crate::runtime::StreamLatencyDescription;
+use crate::runtime::StreamOutputStats;
 use crate::runtime::Tag;
+use crate::runtime::TagPropagation;
+
+/// Latency measurement of a stream edge
+///
+/// The writer marks produced items with a timestamp; the reader measures the time until the
+/// marked items are consumed.
+#[derive(Debug)]
+pub struct LatencyProbe {
+    max_items: Option<usize>,
+    state: Mutex<ProbeState>,
+}
+
+#[derive(Debug, Default)]
+struct ProbeState {
+    #[cfg(not(target_arch = "wasm32"))]
+    marks: VecDeque<(u64, Instant)>,
+    produced: u64,
+    consumed: u64,
+    measurements: u64,
+    sum: Duration,
+    max: Duration,
+    last: Duration,
+}
+
+impl LatencyProbe {
+    const MAX_MARKS: usize = 1024;
+
+    /// Create latency probe for an edge with the given latency cap
+    pub fn new(max_items: Option<usize>) -> Self {
+        Self {
+            max_items,
+            state: Mutex::new(ProbeState::default()),
+        }
+    }
+
+    /// Writer produced `n` items
+    pub fn produced(&self, n: usize) {
+        let mut s = self.state.lock().unwrap();
+        s.produced += n as u64;
+        #[cfg(not(target_arch = "wasm32"))]
+        if s.marks.len() < Self::MAX_MARKS {
+            let mark = (s.produced, crate::runtime::clock::now());
+            s.marks.push_back(mark);
+        }
+    }
+
+    /// Reader consumed `n` items
+    pub fn consumed(&self, n: usize) {
+        let mut s = self.state.lock().unwrap();
+        s.consumed += n as u64;
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let now = crate::runtime::clock::now();
+            while let Some((items, time)) = s.marks.front().copied() {
+                if items > s.consumed {
+                    break;
+                }
+                s.marks.pop_front();
+                let latency = now.saturating_duration_since(time);
+                s.measurements += 1;
+                s.sum += latency;
+                s.max = s.max.max(latency);
+                s.last = latency;
+            }
+        }
+    }
+
+    /// Get description of the measured latency
+    pub fn description(&self) -> StreamLatencyDescription {
+        let s = self.state.lock().unwrap();
+        let mean = if s.measurements > 0 {
+            s.sum / s.measurements as u32
+        } else {
+            Duration::ZERO
+        };
+        StreamLatencyDescription {
+            max_items: self.max_items,
+            measurements: s.measurements,
+            mean,
+            max: s.max,
+            last: s.last,
+        }
+    }
+}
 
 #[derive(Debug)]
 struct CurrentInput {
@@ -30,9 +126,13 @@ pub struct StreamInput {
     name: String,
     item_size: usize,
     type_id: TypeId,
+    type_name: &'static str,
     reader: Option<BufferReader>,
     current: Option<CurrentInput>,
     tags: Vec<ItemTag>,
+    items_consumed: u64,
+    items_available: usize,
+    probe: Option<Arc<LatencyProbe>>,
 }
 
 impl StreamInput {
@@ -42,9 +142,13 @@ impl StreamInput {
             name: name.to_string(),
             item_size: std::mem::size_of::<T>(),
             type_id: TypeId::of::<T>(),
+            type_name: std::any::type_name::<T>(),
             reader: None,
             current: None,
             tags: Vec::new(),
+            items_consumed: 0,
+            items_available: 0,
+            probe: None,
         }
     }
 
@@ -58,6 +162,13 @@ impl StreamInput {
         self.type_id
     }
 
+    /// Get name of the type of items, handled by the port
+    ///
+    /// The name is only meant for diagnostics (see [`std::any::type_name`]).
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
     /// Get name of port
     pub fn name(&self) -> &str {
         &self.name
@@ -103,6 +214,9 @@ impl StreamInput {
         }
 
         let c = self.current.as_ref().unwrap();
+        if c.len == 0 {
+            return &[];
+        }
         unsafe { slice::from_raw_parts(c.ptr as *const T, c.len / mem::size_of::<T>()) }
     }
 
@@ -134,7 +248,12 @@ impl StreamInput {
             let amount = c.index / self.item_size;
             if amount != 0 {
                 self.reader.as_mut().unwrap().consume(amount);
+                if let Some(p) = &self.probe {
+                    p.consumed(amount);
+                }
             }
+            self.items_consumed += amount as u64;
+            self.items_available = c.len / self.item_size - amount;
             self.current = None;
         }
     }
@@ -148,12 +267,37 @@ impl StreamInput {
         }
     }
 
+    /// Number of items consumed in previous calls to `work()`
+    ///
+    /// This is the absolute offset of the first item in the current input buffer, i.e., an
+    /// [`ItemTag`] with `index` refers to item `items_consumed() + index` of the stream.
+    pub fn items_consumed(&self) -> u64 {
+        self.items_consumed
+    }
+
+    /// Get runtime statistics of the port
+    pub fn stats(&self) -> StreamInputStats {
+        StreamInputStats {
+            name: self.name.clone(),
+            items_consumed: self.items_consumed,
+            items_available: self.items_available,
+        }
+    }
+
     /// Set the buffer reader
+    ///
+    /// If the flowgraph is reconfigured at runtime, this replaces the current reader.
     pub fn set_reader(&mut self, reader: BufferReader) {
-        debug_assert!(self.reader.is_none());
+        debug_assert!(self.current.is_none());
+        self.tags.clear();
         self.reader = Some(reader);
     }
 
+    /// Set the latency probe of the connected edge
+    pub fn set_latency_probe(&mut self, probe: Option<Arc<LatencyProbe>>) {
+        self.probe = probe;
+    }
+
     /// Notify connected, upstream writer that we are finished
     pub async fn notify_finished(&mut self) {
         self.reader.as_mut().unwrap().notify_finished().await;
@@ -178,9 +322,14 @@ pub struct StreamOutput {
     name: String,
     item_size: usize,
     type_id: TypeId,
+    type_name: &'static str,
     writer: Option<BufferWriter>,
     tags: Vec<ItemTag>,
     offset: usize,
+    items_produced: u64,
+    capacity: Option<usize>,
+    space_available: usize,
+    probes: Vec<Arc<LatencyProbe>>,
 }
 
 impl StreamOutput {
@@ -190,9 +339,14 @@ impl StreamOutput {
             name: name.to_string(),
             item_size: std::mem::size_of::<T>(),
             type_id: TypeId::of::<T>(),
+            type_name: std::any::type_name::<T>(),
             writer: None,
             tags: Vec::new(),
             offset: 0,
+            items_produced: 0,
+            capacity: None,
+            space_available: 0,
+            probes: Vec::new(),
         }
     }
 
@@ -206,14 +360,23 @@ impl StreamOutput {
         self.type_id
     }
 
+    /// Get name of the type of items, handled by the port
+    ///
+    /// The name is only meant for diagnostics (see [`std::any::type_name`]).
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
     /// Get name of port
     pub fn name(&self) -> &str {
         &self.name
     }
 
     /// Initialize port, setting the writer
+    ///
+    /// If the flowgraph is reconfigured at runtime, this replaces the current writer.
     pub fn init(&mut self, writer: BufferWriter) {
-        debug_assert!(self.writer.is_none());
+        debug_assert_eq!(self.offset, 0);
         self.writer = Some(writer);
     }
 
@@ -265,6 +428,10 @@ impl StreamOutput {
     /// Get buffer content as slice without checking the type
     pub fn slice_unchecked<T>(&mut self) -> &'static mut [T] {
         let (ptr, len) = self.writer.as_mut().unwrap().bytes();
+        self.capacity = Some(len / self.item_size);
+        if len == 0 {
+            return &mut [];
+        }
 
         unsafe {
             slice::from_raw_parts_mut(
@@ -275,15 +442,25 @@ impl StreamOutput {
     }
 
     fn commit(&mut self) {
+        if let Some(c) = self.capacity.take() {
+            self.space_available = c.saturating_sub(self.offset);
+        }
         if self.offset == 0 {
             return;
         }
+        self.items_produced += self.offset as u64;
 
         let mut tmp = self.tags.clone();
         tmp.retain(|x| x.index < self.offset);
         self.tags.retain(|x| x.index >= self.offset);
+        // keep tags of items that are not produced yet, relative to the next window
+        let offset = self.offset;
+        self.tags.iter_mut().for_each(|x| x.index -= offset);
 
         self.writer.as_mut().unwrap().produce(self.offset, tmp);
+        for p in self.probes.iter() {
+            p.produced(self.offset);
+        }
         self.offset = 0;
     }
 
@@ -292,6 +469,22 @@ impl StreamOutput {
         self.offset
     }
 
+    /// Number of items produced in previous calls to `work()`
+    ///
+    /// This is the absolute offset of the first item in the current output buffer.
+    pub fn items_produced(&self) -> u64 {
+        self.items_produced
+    }
+
+    /// Get runtime statistics of the port
+    pub fn stats(&self) -> StreamOutputStats {
+        StreamOutputStats {
+            name: self.name.clone(),
+            items_produced: self.items_produced,
+            space_available: self.space_available,
+        }
+    }
+
     /// Notify downstream readers that we are finished
     pub async fn notify_finished(&mut self) {
         self.writer.as_mut().unwrap().notify_finished().await;
@@ -307,6 +500,11 @@ impl StreamOutput {
         self.writer.as_ref().unwrap().finished()
     }
 
+    /// Set the latency probes of the connected edges
+    pub fn set_latency_probes(&mut self, probes: Vec<Arc<LatencyProbe>>) {
+        self.probes = probes;
+    }
+
     /// Get a mutable reference to the buffer writer
     pub(super) fn writer_mut(&mut self) -> &mut BufferWriter {
         let w = self.writer.as_mut().unwrap();
@@ -318,8 +516,7 @@ impl StreamOutput {
 pub struct StreamIo {
     inputs: Vec<StreamInput>,
     outputs: Vec<StreamOutput>,
-    #[allow(clippy::type_complexity)]
-    tag_propagation: Box<dyn FnMut(&mut [StreamInput], &mut [StreamOutput]) + Send + 'static>,
+    tag_propagation: TagPropagationFn,
 }
 
 impl fmt::Debug for StreamIo {
@@ -332,11 +529,10 @@ impl fmt::Debug for StreamIo {
 }
 
 impl StreamIo {
-    #[allow(clippy::type_complexity)]
     fn new(
         inputs: Vec<StreamInput>,
         outputs: Vec<StreamOutput>,
-        tag_propagation: Box<dyn FnMut(&mut [StreamInput], &mut [StreamOutput]) + Send + 'static>,
+        tag_propagation: TagPropagationFn,
     ) -> StreamIo {
         StreamIo {
             inputs,
@@ -423,6 +619,49 @@ impl StreamIo {
             .map(|(i, _)| i)
     }
 
+    /// Process the next input chunk in place and forward it to an output, without copying
+    ///
+    /// Requires a [`Slab`](crate::runtime::buffer::slab::Slab) input and an
+    /// [in-place](crate::runtime::buffer::slab::Slab::in_place) Slab output that were not
+    /// accessed in this call to `work()` yet. Tags are forwarded unchanged.
+    ///
+    /// Returns the number of processed items or `None`, if in-place processing is not possible.
+    /// In this case, the block has to fall back to copying from the input to the output.
+    pub fn process_in_place<T: 'static>(
+        &mut self,
+        input: usize,
+        output: usize,
+        f: impl FnOnce(&mut [T]),
+    ) -> Option<usize> {
+        let i = &mut self.inputs[input];
+        let o = &mut self.outputs[output];
+        assert_eq!(i.type_id, TypeId::of::<T>());
+        assert_eq!(o.type_id, TypeId::of::<T>());
+
+        if i.current.is_some() || o.offset != 0 {
+            return None;
+        }
+        let writer = o.writer.as_mut()?.try_as::<slab::Writer>()?;
+        if !writer.in_place() {
+            return None;
+        }
+        let mut chunk = i.reader.as_mut()?.try_as::<slab::Reader>()?.take()?;
+
+        f(chunk.slice_mut::<T>());
+        let n = chunk.len();
+        writer.submit(chunk);
+
+        if let Some(p) = &i.probe {
+            p.consumed(n);
+        }
+        for p in o.probes.iter() {
+            p.produced(n);
+        }
+        i.items_consumed += n as u64;
+        o.items_produced += n as u64;
+        Some(n)
+    }
+
     /// Commit all consume/produce calls after `work()` call
     pub fn commit(&mut self) {
         (self.tag_propagation)(&mut self.inputs, &mut self.outputs);
@@ -435,21 +674,16 @@ impl StreamIo {
     }
 
     /// Set tag propagation
-    #[allow(clippy::type_complexity)]
-    pub fn set_tag_propagation(
-        &mut self,
-        f: Box<dyn FnMut(&mut [StreamInput], &mut [StreamOutput]) + Send + 'static>,
-    ) {
+    pub fn set_tag_propagation(&mut self, f: TagPropagationFn) {
         self.tag_propagation = f;
     }
 }
 
 /// Stream IO builder
-#[allow(clippy::type_complexity)]
 pub struct StreamIoBuilder {
     inputs: Vec<StreamInput>,
     outputs: Vec<StreamOutput>,
-    tag_propagation: Box<dyn FnMut(&mut [StreamInput], &mut [StreamOutput]) + Send + 'static>,
+    tag_propagation: TagPropagationFn,
 }
 
 impl StreamIoBuilder {
@@ -486,6 +720,13 @@ impl StreamIoBuilder {
         self
     }
 
+    /// Configure tag propagation, using a built-in [`TagPropagation`] policy
+    #[must_use]
+    pub fn tag_propagation_policy(mut self, policy: TagPropagation) -> StreamIoBuilder {
+        self.tag_propagation = policy.into_fn();
+        self
+    }
+
     /// Build Stream IO
     pub fn build(self) -> StreamIo {
         StreamIo::new(self.inputs, self.outputs, self.tag_propagation)
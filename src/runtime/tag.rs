@@ -50,10 +50,29 @@ pub enum Tag {
     NamedUsize(String, usize),
     /// An `f32` with a name
     NamedF32(String, f32),
+    /// An `f64` with a name
+    NamedF64(String, f64),
+    /// An `i64` with a name
+    NamedI64(String, i64),
     /// Arbitrary data with a name
     NamedAny(String, Box<dyn TagAny>),
 }
 
+impl Tag {
+    /// Hardware time of a received item (`NamedI64`, nanoseconds)
+    pub const RX_TIME: &'static str = "rx_time";
+    /// Sample rate of received items (`NamedF64`, Hz)
+    pub const RX_RATE: &'static str = "rx_rate";
+    /// Center frequency of received items (`NamedF64`, Hz)
+    pub const RX_FREQ: &'static str = "rx_freq";
+    /// Hardware time to transmit an item (`NamedI64`, nanoseconds)
+    pub const TX_TIME: &'static str = "tx_time";
+    /// Start of a burst (`String`)
+    pub const TX_SOB: &'static str = "tx_sob";
+    /// End of a burst (`String`)
+    pub const TX_EOB: &'static str = "tx_eob";
+}
+
 /// Item tag
 #[derive(Clone, Debug)]
 pub struct ItemTag {
@@ -64,3 +83,178 @@ pub struct ItemTag {
 }
 
 pub fn default_tag_propagation(_inputs: &mut [StreamInput], _outputs: &mut [StreamOutput]) {}
+
+/// Tag propagation function
+pub type TagPropagationFn =
+    Box<dyn FnMut(&mut [StreamInput], &mut [StreamOutput]) + Send + 'static>;
+
+/// Built-in tag propagation policies
+///
+/// Tags of consumed input items are forwarded to the outputs, using the absolute item offsets of
+/// the ports ([`StreamInput::items_consumed`], [`StreamOutput::items_produced`]) to map them to
+/// output items. Tags that map to output items that are not yet produced are kept, until the
+/// items are produced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TagPropagation {
+    /// Drop all tags
+    None,
+    /// Forward the tags of every input to every output
+    AllToAll,
+    /// Forward the tags of input `i` to output `i`
+    OneToOne,
+    /// Forward the tags of every input to every output, scaling offsets by `interp / decim`
+    RateChange {
+        /// Interpolation factor
+        interp: usize,
+        /// Decimation factor
+        decim: usize,
+    },
+}
+
+impl TagPropagation {
+    /// Create propagation function for the policy
+    pub fn into_fn(self) -> TagPropagationFn {
+        match self {
+            TagPropagation::None => Box::new(default_tag_propagation),
+            TagPropagation::AllToAll => Box::new(|inputs, outputs| {
+                for i in inputs.iter() {
+                    for o in outputs.iter_mut() {
+                        forward_tags(i, o, 1, 1);
+                    }
+                }
+            }),
+            TagPropagation::OneToOne => Box::new(|inputs, outputs| {
+                for (i, o) in inputs.iter().zip(outputs.iter_mut()) {
+                    forward_tags(i, o, 1, 1);
+                }
+            }),
+            TagPropagation::RateChange { interp, decim } => {
+                assert!(
+                    interp > 0 && decim > 0,
+                    "interp and decim have to be positive"
+                );
+                Box::new(move |inputs, outputs| {
+                    for i in inputs.iter() {
+                        for o in outputs.iter_mut() {
+                            forward_tags(i, o, interp as u64, decim as u64);
+                        }
+                    }
+                })
+            }
+        }
+    }
+}
+
+fn forward_tags(input: &StreamInput, output: &mut StreamOutput, interp: u64, decim: u64) {
+    let (n, tags) = input.consumed();
+    let start = output.items_produced();
+    for t in tags.iter().filter(|x| x.index < n) {
+        let offset = (input.items_consumed() + t.index as u64) * interp / decim;
+        output.add_tag_abs(offset.saturating_sub(start) as usize, t.tag.clone());
+    }
+}
+
+/// Compute the time of items from [`Tag::RX_TIME`] and [`Tag::RX_RATE`] tags
+///
+/// Call [`update`](SampleClock::update) in every call to `work()`, before consuming items, to
+/// track the tags of the input. Time and rate changes within the current window are taken into
+/// account.
+///
+/// ```
+/// # use futuresdr::runtime::SampleClock;
+/// # use futuresdr::runtime::StreamInput;
+/// fn work(clock: &mut SampleClock, input: &mut StreamInput) {
+///     clock.update(input);
+///     // time of the first item in the current window
+///     let t = clock.time_ns(input.items_consumed());
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SampleClock {
+    // (absolute offset, time), sorted by offset
+    times: Vec<(u64, i64)>,
+    // (absolute offset, rate), sorted by offset
+    rates: Vec<(u64, f64)>,
+}
+
+impl SampleClock {
+    /// Create clock without time reference
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Process the tags in the current window of the input
+    pub fn update(&mut self, input: &mut StreamInput) {
+        let start = input.items_consumed();
+
+        // collapse the history before the current window
+        if let (Some(t), Some(r)) = (self.time_ns(start), self.sample_rate(start)) {
+            self.times.retain(|x| x.0 > start);
+            self.times.insert(0, (start, t));
+            self.rates.retain(|x| x.0 > start);
+            self.rates.insert(0, (start, r));
+        }
+
+        for t in input.tags().iter() {
+            let offset = start + t.index as u64;
+            match &t.tag {
+                Tag::NamedI64(n, time) if n == Tag::RX_TIME => {
+                    Self::insert(&mut self.times, offset, *time);
+                }
+                Tag::NamedF64(n, rate) if n == Tag::RX_RATE => {
+                    Self::insert(&mut self.rates, offset, *rate);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn insert<T>(v: &mut Vec<(u64, T)>, offset: u64, value: T) {
+        match v.last_mut() {
+            Some(l) if l.0 == offset => l.1 = value,
+            // tag was already processed
+            Some(l) if l.0 > offset => {}
+            _ => v.push((offset, value)),
+        }
+    }
+
+    /// Sample rate at the item with the given absolute offset
+    pub fn sample_rate(&self, offset: u64) -> Option<f64> {
+        self.rates
+            .iter()
+            .rev()
+            .find(|x| x.0 <= offset)
+            .or_else(|| self.rates.first())
+            .map(|x| x.1)
+    }
+
+    /// Time (in nanoseconds) of the item with the given absolute offset
+    ///
+    /// Returns `None`, if no time and sample rate tags were received yet.
+    pub fn time_ns(&self, offset: u64) -> Option<i64> {
+        let &(mut pos, time) = self
+            .times
+            .iter()
+            .rev()
+            .find(|x| x.0 <= offset)
+            .or_else(|| self.times.first())?;
+
+        if offset < pos {
+            let delta = (pos - offset) as f64 * 1e9 / self.sample_rate(pos)?;
+            return Some(time - delta.round() as i64);
+        }
+
+        let mut delta = 0.0;
+        let time_pos = pos;
+        for &(o, _) in self
+            .rates
+            .iter()
+            .filter(|x| x.0 > time_pos && x.0 <= offset)
+        {
+            delta += (o - pos) as f64 * 1e9 / self.sample_rate(pos)?;
+            pos = o;
+        }
+        delta += (offset - pos) as f64 * 1e9 / self.sample_rate(pos)?;
+        Some(time + delta.round() as i64)
+    }
+}
@@ -1,14 +1,27 @@
 use futures::channel::mpsc::Sender;
 use std::collections::HashMap;
+use std::collections::HashSet;
+use std::sync::Arc;
 
 use crate::anyhow::{bail, Context, Result};
 use crate::runtime::buffer::BufferBuilder;
 use crate::runtime::buffer::BufferWriter;
+use crate::runtime::hier_block::HierPorts;
 use crate::runtime::Block;
+use crate::runtime::BlockDescription;
 use crate::runtime::BlockMessage;
+use crate::runtime::FlowgraphDescription;
+use crate::runtime::HierBlock;
+use crate::runtime::LatencyProbe;
+use crate::runtime::MessageQueue;
+use crate::runtime::MessageQueueConfig;
+use crate::runtime::MessageQueueDescription;
+use crate::runtime::Pmt;
 use crate::runtime::PortId;
+use crate::runtime::StreamLatencyDescription;
 use slab::Slab;
 use std::any::{Any, TypeId};
+use std::borrow::Cow;
 use std::cmp::{Eq, PartialEq};
 use std::fmt::Debug;
 use std::hash::{Hash, Hasher};
@@ -59,6 +72,24 @@ impl BufferBuilderEntry {
             .builder()
             .build(self.item_size, writer_inbox, writer_output_id)
     }
+
+    pub(crate) fn build_with_latency_cap(
+        &self,
+        writer_inbox: Sender<BlockMessage>,
+        writer_output_id: usize,
+        max_items: usize,
+    ) -> BufferWriter {
+        self.builder.builder().build_with_latency_cap(
+            self.item_size,
+            writer_inbox,
+            writer_output_id,
+            max_items,
+        )
+    }
+
+    pub(crate) fn latency_cap(&self) -> Option<usize> {
+        self.builder.builder().latency_cap()
+    }
 }
 
 impl PartialEq for BufferBuilderEntry {
@@ -76,6 +107,65 @@ impl Hash for BufferBuilderEntry {
     }
 }
 
+/// Stream port of a block
+#[derive(Debug, Clone)]
+pub(crate) struct StreamPort {
+    pub(crate) name: String,
+    pub(crate) type_id: TypeId,
+    pub(crate) type_name: &'static str,
+    pub(crate) item_size: usize,
+}
+
+/// Ports of a block
+///
+/// Kept in the [Topology], while the block is owned by its task, i.e., while the flowgraph is
+/// running.
+#[derive(Debug, Clone)]
+pub(crate) struct BlockPorts {
+    pub(crate) instance_name: Option<String>,
+    pub(crate) stream_inputs: Vec<StreamPort>,
+    pub(crate) stream_outputs: Vec<StreamPort>,
+    pub(crate) message_inputs: Vec<String>,
+    pub(crate) message_outputs: Vec<String>,
+}
+
+impl BlockPorts {
+    fn new(block: &Block) -> Self {
+        BlockPorts {
+            instance_name: block.instance_name().map(|x| x.to_string()),
+            stream_inputs: block
+                .stream_inputs()
+                .iter()
+                .map(|x| StreamPort {
+                    name: x.name().to_string(),
+                    type_id: x.type_id(),
+                    type_name: x.type_name(),
+                    item_size: x.item_size(),
+                })
+                .collect(),
+            stream_outputs: block
+                .stream_outputs()
+                .iter()
+                .map(|x| StreamPort {
+                    name: x.name().to_string(),
+                    type_id: x.type_id(),
+                    type_name: x.type_name(),
+                    item_size: x.item_size(),
+                })
+                .collect(),
+            message_inputs: block.message_input_names(),
+            message_outputs: block
+                .message_outputs()
+                .iter()
+                .map(|x| x.name().to_string())
+                .collect(),
+        }
+    }
+}
+
+// (src block, src port, dst block, dst port)
+type StreamEdge = (usize, usize, usize, usize);
+
 /// The actual graph that backs a [Flowgraph](crate::runtime::Flowgraph).
 #[derive(Debug)]
 pub struct Topology {
@@ -83,6 +173,18 @@ pub struct Topology {
     pub(crate) stream_edges: HashMap<(usize, usize, BufferBuilderEntry), Vec<(usize, usize)>>,
     // src blk, src port, dst blk, dst port
     pub(crate) message_edges: Vec<(usize, usize, usize, usize)>,
+    // queues of message edges that drop messages
+    pub(crate) message_queues: HashMap<(usize, usize, usize, usize), Arc<MessageQueue>>,
+    pub(crate) hier_blocks: HashMap<usize, HierPorts>,
+    pub(crate) ports: HashMap<usize, BlockPorts>,
+    // parameters of blocks that were created through a BlockRegistry
+    pub(crate) parameters: HashMap<usize, HashMap<String, Pmt>>,
+    // maximum number of items in flight on a path through the flowgraph
+    pub(crate) latency_budget: Option<usize>,
+    // latency caps of stream outputs, derived from the latency budget
+    pub(crate) latency_caps: HashMap<(usize, usize), usize>,
+    // latency measurements of the stream edges of a running flowgraph
+    pub(crate) stream_latencies: HashMap<StreamEdge, Arc<LatencyProbe>>,
 }
 
 impl Topology {
@@ -92,13 +194,24 @@ impl Topology {
             blocks: Slab::new(),
             stream_edges: HashMap::new(),
             message_edges: Vec::new(),
+            message_queues: HashMap::new(),
+            hier_blocks: HashMap::new(),
+            ports: HashMap::new(),
+            parameters: HashMap::new(),
+            latency_budget: None,
+            latency_caps: HashMap::new(),
+            stream_latencies: HashMap::new(),
         }
     }
 
     /// Get Id of a block, given its name
     pub fn block_id(&self, name: &str) -> Option<usize> {
         for (i, b) in self.blocks.iter() {
-            if b.as_ref()?.instance_name()? == name {
+            let n = match b {
+                Some(b) => b.instance_name(),
+                None => self.ports.get(&i).and_then(|p| p.instance_name.as_deref()),
+            };
+            if n == Some(name) {
                 return Some(i);
             }
         }
@@ -108,10 +221,10 @@ impl Topology {
 
     /// Get name of a block, given its Id
     pub fn block_name(&self, id: usize) -> Option<&str> {
-        if let Some(Some(b)) = &self.blocks.get(id) {
-            b.instance_name()
-        } else {
-            None
+        match self.blocks.get(id) {
+            Some(Some(b)) => b.instance_name(),
+            Some(None) => self.ports.get(&id)?.instance_name.as_deref(),
+            None => None,
         }
     }
 
@@ -137,16 +250,35 @@ impl Topology {
         }
 
         block.set_instance_name(block_name);
-        self.blocks.insert(Some(block))
+        let id = self.blocks.insert(Some(block));
+        // the Id might have been reserved by a hier block that was already flattened
+        self.hier_blocks.remove(&id);
+        id
     }
 
     /// Removes a [Block] and all edges connected to the [Block] from the [Topology].
+    ///
+    /// If the Id refers to a [`HierBlock`], all its inner blocks are removed.
     pub fn delete_block(&mut self, id: usize) {
+        if let Some(h) = self.hier_blocks.remove(&id) {
+            if matches!(self.blocks.get(id), Some(None)) {
+                self.blocks.remove(id);
+            }
+            for (_, b) in h.blocks {
+                self.delete_block(b);
+            }
+            return;
+        }
+
         // remove from registry
         self.blocks.remove(id);
+        self.ports.remove(&id);
+        self.parameters.remove(&id);
 
         // delete associated stream edges
         self.stream_edges.retain(|k, _| k.0 != id);
+        self.latency_caps.retain(|k, _| k.0 != id);
+        self.stream_latencies.retain(|k, _| k.0 != id && k.2 != id);
         for (_, vec) in self.stream_edges.iter_mut() {
             *vec = vec.iter().filter(|x| x.0 != id).copied().collect();
         }
@@ -158,9 +290,12 @@ impl Topology {
 
         // delete associated message edges
         self.message_edges.retain(|x| x.0 != id && x.2 != id);
+        self.message_queues.retain(|x, _| x.0 != id && x.2 != id);
     }
 
     /// Connect stream ports
+    ///
+    /// The item types of the ports have to match.
     pub fn connect_stream<B: BufferBuilder + Debug + Eq + Hash>(
         &mut self,
         src_block: usize,
@@ -169,55 +304,83 @@ impl Topology {
         dst_port: PortId,
         buffer_builder: B,
     ) -> Result<()> {
-        let src = self
-            .blocks
-            .get(src_block)
-            .context("src block invalid")?
-            .as_ref()
-            .context("src block not present")?;
-        let dst = self
-            .blocks
-            .get(dst_block)
-            .context("dst block invalid")?
-            .as_ref()
-            .context("dst block not present")?;
+        self.connect_stream_inner(
+            src_block,
+            src_port,
+            dst_block,
+            dst_port,
+            buffer_builder,
+            false,
+        )
+    }
 
-        let src_port_id = match src_port {
-            PortId::Name(s) => src
-                .stream_output_name_to_id(&s)
-                .context("invalid src port name")?,
-            PortId::Index(i) => {
-                if i < src.stream_outputs().len() {
-                    i
-                } else {
-                    bail!("invalid src port id {}", i)
-                }
-            }
-        };
-        let sp = src.stream_output(src_port_id);
+    /// Connect stream ports, reinterpreting the items of the output as items of the input
+    ///
+    /// The item types may differ but have to have the same size, e.g., `Complex32` and `[f32; 2]`.
+    pub fn connect_stream_reinterpret<B: BufferBuilder + Debug + Eq + Hash>(
+        &mut self,
+        src_block: usize,
+        src_port: PortId,
+        dst_block: usize,
+        dst_port: PortId,
+        buffer_builder: B,
+    ) -> Result<()> {
+        self.connect_stream_inner(
+            src_block,
+            src_port,
+            dst_block,
+            dst_port,
+            buffer_builder,
+            true,
+        )
+    }
 
-        let dst_port_id = match dst_port {
-            PortId::Name(s) => dst
-                .stream_input_name_to_id(&s)
-                .context("invalid dst port name")?,
-            PortId::Index(i) => {
-                if i < dst.stream_inputs().len() {
-                    i
-                } else {
-                    bail!("invalid dst port id {}", i)
-                }
-            }
+    fn connect_stream_inner<B: BufferBuilder + Debug + Eq + Hash>(
+        &mut self,
+        src_block: usize,
+        src_port: PortId,
+        dst_block: usize,
+        dst_port: PortId,
+        buffer_builder: B,
+        reinterpret: bool,
+    ) -> Result<()> {
+        let (src_block, src_port_id) = self.resolve_stream_output(src_block, src_port)?;
+        let (dst_block, dst_port_id) = self.resolve_stream_input(dst_block, dst_port)?;
+
+        let src_ports = self.block_ports(src_block, "src")?;
+        let dst_ports = self.block_ports(dst_block, "dst")?;
+        let src = &src_ports.stream_outputs[src_port_id];
+        let dst = &dst_ports.stream_inputs[dst_port_id];
+        let edge = || {
+            format!(
+                "cannot connect {}.{} ({}) to {}.{} ({})",
+                src_ports.instance_name.as_deref().unwrap_or("?"),
+                src.name,
+                src.type_name,
+                dst_ports.instance_name.as_deref().unwrap_or("?"),
+                dst.name,
+                dst.type_name
+            )
         };
-        let dp = dst.stream_input(dst_port_id);
 
-        if sp.type_id() != dp.type_id() {
-            bail!("item types do not match");
+        if reinterpret {
+            if src.item_size != dst.item_size {
+                bail!(
+                    "{}: item sizes do not match ({} vs. {} bytes)",
+                    edge(),
+                    src.item_size,
+                    dst.item_size
+                );
+            }
+        } else if src.type_id != dst.type_id {
+            bail!("{}: item types do not match", edge());
         }
 
         let buffer_entry = BufferBuilderEntry {
-            item_size: sp.item_size(),
+            item_size: src.item_size,
             builder: Box::new(buffer_builder),
         };
+
         let id = (src_block, src_port_id, buffer_entry);
         if let Some(v) = self.stream_edges.get_mut(&id) {
             v.push((dst_block, dst_port_id));
@@ -227,6 +390,36 @@ impl Topology {
         Ok(())
     }
 
+    /// Disconnect stream ports
+    pub fn disconnect_stream(
+        &mut self,
+        src_block: usize,
+        src_port: PortId,
+        dst_block: usize,
+        dst_port: PortId,
+    ) -> Result<()> {
+        let (src_block, src_port_id) = self.resolve_stream_output(src_block, src_port)?;
+        let dst = self.resolve_stream_input(dst_block, dst_port)?;
+
+        let mut found = false;
+        for (k, v) in self.stream_edges.iter_mut() {
+            if k.0 == src_block && k.1 == src_port_id {
+                if let Some(i) = v.iter().position(|x| *x == dst) {
+                    v.remove(i);
+                    found = true;
+                }
+            }
+        }
+        if !found {
+            bail!("stream ports are not connected");
+        }
+        self.stream_edges.retain(|_, v| !v.is_empty());
+        self.stream_latencies
+            .remove(&(src_block, src_port_id, dst.0, dst.1));
+
+        Ok(())
+    }
+
     /// Connect message ports
     pub fn connect_message(
         &mut self,
@@ -235,48 +428,477 @@ impl Topology {
         dst_block: usize,
         dst_port: PortId,
     ) -> Result<()> {
-        let src = self
+        self.connect_message_with_queue(
+            src_block,
+            src_port,
+            dst_block,
+            dst_port,
+            MessageQueueConfig::Block,
+        )
+    }
+
+    /// Connect message ports with the given queue configuration
+    pub fn connect_message_with_queue(
+        &mut self,
+        src_block: usize,
+        src_port: PortId,
+        dst_block: usize,
+        dst_port: PortId,
+        queue: MessageQueueConfig,
+    ) -> Result<()> {
+        let (src_block, src_port_id) = self.resolve_message_output(src_block, src_port)?;
+        let (dst_block, dst_port_id) = self.resolve_message_input(dst_block, dst_port)?;
+
+        let edge = (src_block, src_port_id, dst_block, dst_port_id);
+        match queue {
+            MessageQueueConfig::Block => {}
+            MessageQueueConfig::DropOldest(0) | MessageQueueConfig::DropNewest(0) => {
+                bail!("message queue capacity has to be at least one");
+            }
+            _ => {
+                self.message_queues
+                    .insert(edge, Arc::new(MessageQueue::new(queue)));
+            }
+        }
+        self.message_edges.push(edge);
+
+        Ok(())
+    }
+
+    /// Queue of a message edge, if it drops messages
+    pub(crate) fn message_queue(
+        &self,
+        edge: &(usize, usize, usize, usize),
+    ) -> Option<Arc<MessageQueue>> {
+        self.message_queues.get(edge).cloned()
+    }
+
+    /// Split the latency budget evenly between the stream edges of the longest path
+    pub(crate) fn apply_latency_budget(&mut self) {
+        let budget = match self.latency_budget {
+            Some(b) => b,
+            None => return,
+        };
+
+        let mut next: HashMap<usize, Vec<usize>> = HashMap::new();
+        for ((src, _, _), v) in self.stream_edges.iter() {
+            next.entry(*src).or_default().extend(v.iter().map(|x| x.0));
+        }
+
+        // number of edges on the longest path, starting at the block
+        fn depth(
+            block: usize,
+            next: &HashMap<usize, Vec<usize>>,
+            memo: &mut HashMap<usize, usize>,
+            visiting: &mut HashSet<usize>,
+        ) -> usize {
+            if let Some(d) = memo.get(&block) {
+                return *d;
+            }
+            if !visiting.insert(block) {
+                // cycle
+                return 0;
+            }
+            let d = next
+                .get(&block)
+                .and_then(|v| v.iter().map(|n| 1 + depth(*n, next, memo, visiting)).max())
+                .unwrap_or(0);
+            visiting.remove(&block);
+            memo.insert(block, d);
+            d
+        }
+
+        let mut memo = HashMap::new();
+        let mut visiting = HashSet::new();
+        let longest = next
+            .keys()
+            .map(|b| depth(*b, &next, &mut memo, &mut visiting))
+            .max()
+            .unwrap_or(1);
+
+        let cap = std::cmp::max(1, budget / std::cmp::max(1, longest));
+        self.latency_caps = self
+            .stream_edges
+            .keys()
+            .map(|(src, src_port, _)| ((*src, *src_port), cap))
+            .collect();
+    }
+
+    /// Maximum number of items in flight on a stream output
+    fn latency_cap(
+        &self,
+        entry: &BufferBuilderEntry,
+        src: usize,
+        src_port: usize,
+    ) -> Option<usize> {
+        match (
+            entry.latency_cap(),
+            self.latency_caps.get(&(src, src_port)).copied(),
+        ) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
+    }
+
+    /// Build the buffer of a stream output, applying its latency cap
+    pub(crate) fn build_stream_output(
+        &self,
+        entry: &BufferBuilderEntry,
+        src: usize,
+        src_port: usize,
+        src_inbox: Sender<BlockMessage>,
+    ) -> BufferWriter {
+        match self.latency_caps.get(&(src, src_port)) {
+            Some(cap) => entry.build_with_latency_cap(src_inbox, src_port, *cap),
+            None => entry.build(src_inbox, src_port),
+        }
+    }
+
+    /// Create a latency probe for a stream output
+    pub(crate) fn latency_probe(
+        &self,
+        entry: &BufferBuilderEntry,
+        src: usize,
+        src_port: usize,
+    ) -> Arc<LatencyProbe> {
+        Arc::new(LatencyProbe::new(self.latency_cap(entry, src, src_port)))
+    }
+
+    /// Describe the stream edges and their latency
+    pub(crate) fn stream_edge_descriptions(
+        &self,
+    ) -> (Vec<StreamEdge>, Vec<StreamLatencyDescription>) {
+        self.stream_edges
+            .iter()
+            .flat_map(|((src, src_port, entry), v)| {
+                v.iter().map(move |(dst, dst_port)| {
+                    let edge = (*src, *src_port, *dst, *dst_port);
+                    let latency = match self.stream_latencies.get(&edge) {
+                        Some(p) => p.description(),
+                        None => StreamLatencyDescription {
+                            max_items: self.latency_cap(entry, *src, *src_port),
+                            ..Default::default()
+                        },
+                    };
+                    (edge, latency)
+                })
+            })
+            .unzip()
+    }
+
+    /// Describe the queues of the message edges
+    pub(crate) fn message_queue_descriptions(&self) -> Vec<MessageQueueDescription> {
+        self.message_edges
+            .iter()
+            .map(|e| match self.message_queues.get(e) {
+                Some(q) => MessageQueueDescription {
+                    config: q.config(),
+                    dropped: q.dropped(),
+                },
+                None => MessageQueueDescription::default(),
+            })
+            .collect()
+    }
+
+    /// Disconnect message ports
+    pub fn disconnect_message(
+        &mut self,
+        src_block: usize,
+        src_port: PortId,
+        dst_block: usize,
+        dst_port: PortId,
+    ) -> Result<()> {
+        let (src_block, src_port_id) = self.resolve_message_output(src_block, src_port)?;
+        let (dst_block, dst_port_id) = self.resolve_message_input(dst_block, dst_port)?;
+
+        let i = self
+            .message_edges
+            .iter()
+            .position(|x| *x == (src_block, src_port_id, dst_block, dst_port_id))
+            .context("message ports are not connected")?;
+        let edge = self.message_edges.remove(i);
+        if !self.message_edges.contains(&edge) {
+            self.message_queues.remove(&edge);
+        }
+
+        Ok(())
+    }
+
+    /// Add a [`HierBlock`], moving its blocks and connections into the [Topology].
+    ///
+    /// Returns an Id that can be used like a block Id to connect the ports, exposed by the
+    /// hierarchical block.
+    pub fn add_hier_block(&mut self, mut hier: HierBlock) -> Result<usize> {
+        let mut inner = hier
+            .flowgraph
+            .topology
+            .take()
+            .context("hier block flowgraph not initialized")?;
+
+        // resolve exposed ports, while the inner Ids are still valid
+        let mut ports = HierPorts::default();
+        for (name, block, port) in hier.stream_inputs.drain(..) {
+            let (b, p) = inner
+                .resolve_stream_input(block, port)
+                .with_context(|| format!("hier block {}: stream input {name}", hier.name))?;
+            ports.stream_inputs.push((name, b, p));
+        }
+        for (name, block, port) in hier.stream_outputs.drain(..) {
+            let (b, p) = inner
+                .resolve_stream_output(block, port)
+                .with_context(|| format!("hier block {}: stream output {name}", hier.name))?;
+            ports.stream_outputs.push((name, b, p));
+        }
+        for (name, block, port) in hier.message_inputs.drain(..) {
+            let (b, p) = inner
+                .resolve_message_input(block, port)
+                .with_context(|| format!("hier block {}: message input {name}", hier.name))?;
+            ports.message_inputs.push((name, b, p));
+        }
+        for (name, block, port) in hier.message_outputs.drain(..) {
+            let (b, p) = inner
+                .resolve_message_output(block, port)
+                .with_context(|| format!("hier block {}: message output {name}", hier.name))?;
+            ports.message_outputs.push((name, b, p));
+        }
+
+        // nested hier blocks are already flattened
+        inner.remove_hier_placeholders();
+        inner.hier_blocks.clear();
+
+        // move blocks, prefixing instance names with the name of the hier block
+        let mut map = HashMap::new();
+        let ids: Vec<usize> = inner.blocks.iter().map(|x| x.0).collect();
+        for id in ids {
+            let mut block = inner
+                .blocks
+                .remove(id)
+                .context("block not owned by topology")?;
+            let name = format!(
+                "{}/{}",
+                hier.name,
+                block.instance_name().unwrap_or(block.type_name())
+            );
+            block.set_instance_name(name);
+            let new_id = self.add_block(block);
+            if let Some(p) = inner.parameters.remove(&id) {
+                self.parameters.insert(new_id, p);
+            }
+            map.insert(id, new_id);
+            ports.blocks.push((id, new_id));
+        }
+
+        for p in ports
+            .stream_inputs
+            .iter_mut()
+            .chain(ports.stream_outputs.iter_mut())
+            .chain(ports.message_inputs.iter_mut())
+            .chain(ports.message_outputs.iter_mut())
+        {
+            p.1 = map[&p.1];
+        }
+
+        // move connections
+        for ((src, src_port, buffer), v) in inner.stream_edges.drain() {
+            self.stream_edges
+                .entry((map[&src], src_port, buffer))
+                .or_default()
+                .extend(v.into_iter().map(|(dst, dst_port)| (map[&dst], dst_port)));
+        }
+        for (src, src_port, dst, dst_port) in inner.message_edges.drain(..) {
+            self.message_edges
+                .push((map[&src], src_port, map[&dst], dst_port));
+        }
+        for ((src, src_port, dst, dst_port), q) in inner.message_queues.drain() {
+            self.message_queues
+                .insert((map[&src], src_port, map[&dst], dst_port), q);
+        }
+
+        // reserve Id for the hier block, it is removed, once the flowgraph is started
+        let id = self.blocks.insert(None);
+        self.hier_blocks.insert(id, ports);
+        Ok(id)
+    }
+
+    /// Get Id of an inner block of a [`HierBlock`] in the flattened [Topology]
+    pub fn hier_block_id(&self, hier_block: usize, inner_block: usize) -> Option<usize> {
+        self.hier_blocks
+            .get(&hier_block)?
             .blocks
-            .get(src_block)
-            .context("invalid src block")?
-            .as_ref()
-            .context("src block not present")?;
-        let dst = self
+            .iter()
+            .find(|x| x.0 == inner_block)
+            .map(|x| x.1)
+    }
+
+    /// Remove the Ids that were reserved for hierarchical blocks
+    ///
+    /// Their blocks and connections are already part of the topology. The port mapping is kept
+    /// to allow looking up inner blocks with [`Topology::hier_block_id`].
+    pub(crate) fn remove_hier_placeholders(&mut self) {
+        for id in self.hier_blocks.keys() {
+            if matches!(self.blocks.get(*id), Some(None)) {
+                self.blocks.remove(*id);
+            }
+        }
+    }
+
+    /// Record the ports of all blocks
+    ///
+    /// Called before the blocks are moved into their tasks.
+    pub(crate) fn record_ports(&mut self) {
+        for (id, b) in self.blocks.iter() {
+            if let Some(b) = b {
+                self.ports.insert(id, BlockPorts::new(b));
+            }
+        }
+    }
+
+    /// Ports of a block, no matter if the block is owned by the topology or running
+    pub(crate) fn block_ports(&self, block: usize, dir: &str) -> Result<Cow<'_, BlockPorts>> {
+        match self
             .blocks
-            .get(dst_block)
-            .context("invalid dst block")?
-            .as_ref()
-            .context("dst block not present")?;
+            .get(block)
+            .with_context(|| format!("{dir} block invalid"))?
+        {
+            Some(b) => Ok(Cow::Owned(BlockPorts::new(b))),
+            None => self
+                .ports
+                .get(&block)
+                .map(Cow::Borrowed)
+                .with_context(|| format!("{dir} block not present")),
+        }
+    }
 
-        let src_port_id = match src_port {
+    /// Stream inputs and outputs of a block that are not connected
+    pub(crate) fn unconnected_stream_ports(&self, block: usize) -> (Vec<usize>, Vec<usize>) {
+        let ports = match self.block_ports(block, "") {
+            Ok(p) => p,
+            Err(_) => return (Vec::new(), Vec::new()),
+        };
+        let inputs = (0..ports.stream_inputs.len())
+            .filter(|i| !self.stream_edges.values().any(|v| v.contains(&(block, *i))))
+            .collect();
+        let outputs = (0..ports.stream_outputs.len())
+            .filter(|i| {
+                !self
+                    .stream_edges
+                    .iter()
+                    .any(|(k, v)| k.0 == block && k.1 == *i && !v.is_empty())
+            })
+            .collect();
+        (inputs, outputs)
+    }
+
+    pub(crate) fn resolve_stream_output(
+        &self,
+        block: usize,
+        port: PortId,
+    ) -> Result<(usize, usize)> {
+        if let Some(h) = self.hier_blocks.get(&block) {
+            return h
+                .stream_output(&port)
+                .with_context(|| format!("invalid src port {port:?}"));
+        }
+        let src = self.block_ports(block, "src")?;
+        let id = match port {
             PortId::Name(s) => src
-                .message_output_name_to_id(&s)
+                .stream_outputs
+                .iter()
+                .position(|x| x.name == s)
                 .context("invalid src port name")?,
             PortId::Index(i) => {
-                if i < src.message_outputs().len() {
+                if i < src.stream_outputs.len() {
                     i
                 } else {
-                    bail!("wrong src port id {}", i)
+                    bail!("invalid src port id {}", i)
                 }
             }
         };
-        let dst_port_id = match dst_port {
+        Ok((block, id))
+    }
+
+    pub(crate) fn resolve_stream_input(
+        &self,
+        block: usize,
+        port: PortId,
+    ) -> Result<(usize, usize)> {
+        if let Some(h) = self.hier_blocks.get(&block) {
+            return h
+                .stream_input(&port)
+                .with_context(|| format!("invalid dst port {port:?}"));
+        }
+        let dst = self.block_ports(block, "dst")?;
+        let id = match port {
             PortId::Name(s) => dst
-                .message_input_name_to_id(&s)
+                .stream_inputs
+                .iter()
+                .position(|x| x.name == s)
                 .context("invalid dst port name")?,
             PortId::Index(i) => {
-                if i < dst.message_outputs().len() {
+                if i < dst.stream_inputs.len() {
                     i
                 } else {
-                    bail!("wrong dst port id {}", i)
+                    bail!("invalid dst port id {}", i)
                 }
             }
         };
+        Ok((block, id))
+    }
 
-        self.message_edges
-            .push((src_block, src_port_id, dst_block, dst_port_id));
+    pub(crate) fn resolve_message_output(
+        &self,
+        block: usize,
+        port: PortId,
+    ) -> Result<(usize, usize)> {
+        if let Some(h) = self.hier_blocks.get(&block) {
+            return h
+                .message_output(&port)
+                .with_context(|| format!("invalid src port {port:?}"));
+        }
+        let src = self.block_ports(block, "src")?;
+        let id = match port {
+            PortId::Name(s) => src
+                .message_outputs
+                .iter()
+                .position(|x| *x == s)
+                .context("invalid src port name")?,
+            PortId::Index(i) => {
+                if i < src.message_outputs.len() {
+                    i
+                } else {
+                    bail!("wrong src port id {}", i)
+                }
+            }
+        };
+        Ok((block, id))
+    }
 
-        Ok(())
+    pub(crate) fn resolve_message_input(
+        &self,
+        block: usize,
+        port: PortId,
+    ) -> Result<(usize, usize)> {
+        if let Some(h) = self.hier_blocks.get(&block) {
+            return h
+                .message_input(&port)
+                .with_context(|| format!("invalid dst port {port:?}"));
+        }
+        let dst = self.block_ports(block, "dst")?;
+        let id = match port {
+            PortId::Name(s) => dst
+                .message_inputs
+                .iter()
+                .position(|x| *x == s)
+                .context("invalid dst port name")?,
+            PortId::Index(i) => {
+                if i < dst.message_inputs.len() {
+                    i
+                } else {
+                    bail!("wrong dst port id {}", i)
+                }
+            }
+        };
+        Ok((block, id))
     }
 
     /// Validate flowgraph topology
@@ -326,7 +948,15 @@ impl Topology {
                 let dst_block = self.block_ref(*dst).expect("dst block not found");
                 let input = dst_block.stream_input(*dst_port);
                 if output.item_size() != input.item_size() {
-                    bail!("item size of stream connection does not match");
+                    bail!(
+                        "item size of stream connection {}.{} ({}) -> {}.{} ({}) does not match",
+                        src_block.instance_name().unwrap_or("?"),
+                        output.name(),
+                        output.type_name(),
+                        dst_block.instance_name().unwrap_or("?"),
+                        input.name(),
+                        input.type_name()
+                    );
                 }
             }
         }
@@ -350,6 +980,57 @@ impl Topology {
         Ok(())
     }
 
+    /// Describe the blocks and connections of a flowgraph that is not running
+    pub(crate) fn description(&self) -> FlowgraphDescription {
+        let blocks = self
+            .blocks
+            .iter()
+            .filter_map(|(id, b)| b.as_ref().map(|b| (id, b)))
+            .map(|(id, b)| BlockDescription {
+                id,
+                type_name: b.type_name().to_string(),
+                instance_name: b.instance_name().unwrap_or_default().to_string(),
+                stream_inputs: b
+                    .stream_inputs()
+                    .iter()
+                    .map(|x| x.name().to_string())
+                    .collect(),
+                stream_outputs: b
+                    .stream_outputs()
+                    .iter()
+                    .map(|x| x.name().to_string())
+                    .collect(),
+                stream_input_types: b
+                    .stream_inputs()
+                    .iter()
+                    .map(|x| x.type_name().to_string())
+                    .collect(),
+                stream_output_types: b
+                    .stream_outputs()
+                    .iter()
+                    .map(|x| x.type_name().to_string())
+                    .collect(),
+                message_inputs: b.message_input_names(),
+                message_outputs: b
+                    .message_outputs()
+                    .iter()
+                    .map(|x| x.name().to_string())
+                    .collect(),
+                blocking: b.is_blocking(),
+            })
+            .collect();
+
+        let (stream_edges, stream_latencies) = self.stream_edge_descriptions();
+
+        FlowgraphDescription {
+            blocks,
+            stream_edges,
+            message_edges: self.message_edges.clone(),
+            message_queues: self.message_queue_descriptions(),
+            stream_latencies,
+        }
+    }
+
     /// Get reference to a block
     pub fn block_ref(&self, id: usize) -> Option<&Block> {
         self.blocks.get(id).and_then(|v| v.as_ref())
@@ -0,0 +1,156 @@
+use futures::executor::block_on;
+use std::iter::repeat_with;
+use std::time::Duration;
+
+use futuresdr::anyhow::Result;
+use futuresdr::async_io::Timer;
+use futuresdr::blocks::Copy;
+use futuresdr::blocks::Head;
+use futuresdr::blocks::NullSink;
+use futuresdr::blocks::NullSource;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::runtime::scheduler::ChainConfig;
+use futuresdr::runtime::scheduler::ChainScheduler;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Runtime;
+
+/// NullSource -> Copy -> Copy -> NullSink
+fn copy_chain(fg: &mut Flowgraph) -> Result<Vec<usize>> {
+    let src = fg.add_block(NullSource::<f32>::new());
+    let copy0 = fg.add_block(Copy::<f32>::new());
+    let copy1 = fg.add_block(Copy::<f32>::new());
+    let snk = fg.add_block(NullSink::<f32>::new());
+
+    fg.connect_stream(src, "out", copy0, "in")?;
+    fg.connect_stream(copy0, "out", copy1, "in")?;
+    fg.connect_stream(copy1, "out", snk, "in")?;
+    Ok(vec![src, copy0, copy1, snk])
+}
+
+/// Run the flowgraph for a while and return the groups of the scheduler
+fn groups_after(config: ChainConfig, wait: Duration) -> Result<(Vec<usize>, Vec<Vec<usize>>)> {
+    let mut fg = Flowgraph::new();
+    let blocks = copy_chain(&mut fg)?;
+
+    let scheduler = ChainScheduler::with_config(2, false, config);
+    let rt = Runtime::with_scheduler(scheduler.clone());
+    let (task, mut handle) = block_on(rt.start(fg));
+
+    let groups = block_on(async move {
+        Timer::after(wait).await;
+        let groups = scheduler.groups();
+        handle.terminate().await?;
+        task.await?;
+        Ok::<_, futuresdr::anyhow::Error>(groups)
+    })?;
+    Ok((blocks, groups))
+}
+
+#[test]
+fn flowgraph_chain() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let null_source = fg.add_block(NullSource::<f32>::new());
+    let head = fg.add_block(Head::<f32>::new(1_000_000));
+    let mut last = head;
+    for _ in 0..10 {
+        let copy = fg.add_block(Copy::<f32>::new());
+        fg.connect_stream(last, "out", copy, "in")?;
+        last = copy;
+    }
+    let vect_sink = fg.add_block(VectorSinkBuilder::<f32>::new().build());
+
+    fg.connect_stream(null_source, "out", head, "in")?;
+    fg.connect_stream(last, "out", vect_sink, "in")?;
+
+    fg = Runtime::with_scheduler(ChainScheduler::default()).run(fg)?;
+
+    let snk = fg.kernel::<VectorSink<f32>>(vect_sink).unwrap();
+    let v = snk.items();
+
+    assert_eq!(v.len(), 1_000_000);
+    for i in v {
+        assert!(i.abs() < f32::EPSILON);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn fan_out() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let orig: Vec<u32> = repeat_with(rand::random::<u32>).take(123_456).collect();
+    let src = fg.add_block(VectorSource::<u32>::new(orig.clone()));
+    let copy = fg.add_block(Copy::<u32>::new());
+    let snk0 = fg.add_block(VectorSinkBuilder::<u32>::new().build());
+    let snk1 = fg.add_block(VectorSinkBuilder::<u32>::new().build());
+
+    fg.connect_stream(src, "out", copy, "in")?;
+    fg.connect_stream(copy, "out", snk0, "in")?;
+    fg.connect_stream(copy, "out", snk1, "in")?;
+
+    let scheduler = ChainScheduler::with_config(
+        2,
+        false,
+        ChainConfig {
+            max_fused: 2,
+            ..ChainConfig::default()
+        },
+    );
+    fg = Runtime::with_scheduler(scheduler).run(fg)?;
+
+    for snk in [snk0, snk1] {
+        let snk = fg.kernel::<VectorSink<u32>>(snk).unwrap();
+        assert_eq!(snk.items(), &orig);
+    }
+    Ok(())
+}
+
+#[test]
+fn fused() -> Result<()> {
+    let (blocks, groups) = groups_after(
+        ChainConfig {
+            rebalance_interval: Duration::from_secs(3600),
+            ..ChainConfig::default()
+        },
+        Duration::from_millis(200),
+    )?;
+    assert_eq!(groups, vec![blocks]);
+    Ok(())
+}
+
+#[test]
+fn max_fused() -> Result<()> {
+    let (blocks, groups) = groups_after(
+        ChainConfig {
+            max_fused: 3,
+            rebalance_interval: Duration::from_secs(3600),
+            ..ChainConfig::default()
+        },
+        Duration::from_millis(200),
+    )?;
+    assert_eq!(groups, vec![blocks[..3].to_vec(), blocks[3..].to_vec()]);
+    Ok(())
+}
+
+#[test]
+fn split_busy_group() -> Result<()> {
+    let (blocks, groups) = groups_after(
+        ChainConfig {
+            rebalance_interval: Duration::from_millis(20),
+            split_load: 0.1,
+            ..ChainConfig::default()
+        },
+        Duration::from_millis(500),
+    )?;
+    assert!(groups.len() > 1);
+
+    // every block is still in exactly one group
+    let mut ids: Vec<usize> = groups.concat();
+    ids.sort_unstable();
+    assert_eq!(ids, blocks);
+    Ok(())
+}
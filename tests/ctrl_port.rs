@@ -0,0 +1,157 @@
+use std::io::Read;
+use std::io::Write;
+use std::net::TcpStream;
+use std::time::Duration;
+
+use futuresdr::anyhow::Result;
+use futuresdr::async_io::block_on;
+use futuresdr::async_io::Timer;
+use futuresdr::blocks::Head;
+use futuresdr::blocks::MessageSink;
+use futuresdr::blocks::NullSink;
+use futuresdr::blocks::NullSource;
+use futuresdr::runtime::config;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Runtime;
+
+const ADDR: &str = "127.0.0.1:13370";
+
+/// Send a request and return the HTTP status code and the body
+fn request(method: &str, path: &str, auth: Option<&str>) -> Result<(u16, String)> {
+    let mut stream = TcpStream::connect(ADDR)?;
+    let mut req = format!("{method} {path} HTTP/1.1\r\nHost: {ADDR}\r\nConnection: close\r\n");
+    if let Some(auth) = auth {
+        req.push_str(&format!("Authorization: {auth}\r\n"));
+    }
+    if method == "POST" {
+        req.push_str("Content-Type: application/json\r\nContent-Length: 6\r\n\r\n\"Null\"");
+    } else {
+        req.push_str("\r\n");
+    }
+    stream.write_all(req.as_bytes())?;
+
+    let mut res = String::new();
+    stream.read_to_string(&mut res)?;
+    let code = res.split(' ').nth(1).unwrap().parse()?;
+    let body = res.split("\r\n\r\n").nth(1).unwrap_or_default().to_string();
+    Ok((code, body))
+}
+
+/// Send a request and return the HTTP status code
+fn status(method: &str, path: &str, auth: Option<&str>) -> Result<u16> {
+    Ok(request(method, path, auth)?.0)
+}
+
+#[test]
+fn auth() -> Result<()> {
+    // the config is global, so this has to happen before it is accessed
+    std::env::set_var("FUTURESDR_CTRLPORT_ENABLE", "true");
+    std::env::set_var("FUTURESDR_CTRLPORT_BIND", ADDR);
+    std::env::set_var("FUTURESDR_CTRLPORT_READ_TOKEN", "reader");
+    std::env::set_var("FUTURESDR_CTRLPORT_WRITE_TOKEN", "writer");
+    assert_eq!(
+        config::config().ctrlport_write_token.as_deref(),
+        Some("writer")
+    );
+
+    let mut fg = Flowgraph::new();
+    let snk = fg.add_block(MessageSink::new());
+
+    let rt = Runtime::new();
+    rt.register_factory("head", |_| {
+        let mut fg = Flowgraph::new();
+        let src = fg.add_block(NullSource::<f32>::new());
+        let head = fg.add_block(Head::<f32>::new(10));
+        let snk = fg.add_block(NullSink::<f32>::new());
+        fg.connect_stream(src, "out", head, "in")?;
+        fg.connect_stream(head, "out", snk, "in")?;
+        Ok(fg)
+    });
+    rt.register_factory("endless", |_| {
+        let mut fg = Flowgraph::new();
+        let src = fg.add_block(NullSource::<f32>::new());
+        let snk = fg.add_block(NullSink::<f32>::new());
+        fg.connect_stream(src, "out", snk, "in")?;
+        Ok(fg)
+    });
+    let (task, mut handle) = block_on(rt.start(fg));
+    block_on(Timer::after(Duration::from_millis(100)));
+
+    let get = "/api/fg/0/";
+    let call = format!("/api/fg/0/block/{snk}/call/0/");
+
+    assert_eq!(status("GET", get, None)?, 401);
+    assert_eq!(status("GET", get, Some("Bearer foo"))?, 403);
+    assert_eq!(status("GET", get, Some("Bearer reader"))?, 200);
+    // user:reader
+    assert_eq!(status("GET", get, Some("Basic dXNlcjpyZWFkZXI="))?, 200);
+    assert_eq!(status("GET", get, Some("Bearer writer"))?, 200);
+
+    assert_eq!(status("POST", &call, None)?, 401);
+    assert_eq!(status("POST", &call, Some("Bearer reader"))?, 403);
+    assert_eq!(status("POST", &call, Some("Bearer writer"))?, 200);
+    // calling a handler through GET needs the write token, too
+    assert_eq!(status("GET", &call, None)?, 401);
+    assert_eq!(status("GET", &call, Some("Bearer reader"))?, 403);
+    assert_eq!(status("GET", &call, Some("Bearer writer"))?, 200);
+
+    // launched flowgraphs are kept until they are deleted and ids are not reused
+    let launch = "/api/factory/head/";
+    assert_eq!(status("POST", launch, Some("Bearer reader"))?, 403);
+    for i in 1..=3 {
+        let (code, id) = request("POST", launch, Some("Bearer writer"))?;
+        assert_eq!(code, 200);
+        assert_eq!(id, i.to_string());
+        let state = format!("/api/fg/{id}/state/");
+        while request("GET", &state, Some("Bearer reader"))?.1 == "\"Running\"" {
+            block_on(Timer::after(Duration::from_millis(10)));
+        }
+        assert_eq!(
+            request("GET", &state, Some("Bearer reader"))?.1,
+            "\"Finished\""
+        );
+    }
+    let (_, fgs) = request("GET", "/api/fg/", Some("Bearer reader"))?;
+    assert_eq!(fgs, "[0,1,2,3]");
+
+    assert_eq!(
+        status("DELETE", "/api/launched/1/", Some("Bearer reader"))?,
+        403
+    );
+    assert_eq!(
+        status("DELETE", "/api/launched/1/", Some("Bearer writer"))?,
+        200
+    );
+    assert_eq!(
+        status("DELETE", "/api/launched/1/", Some("Bearer writer"))?,
+        400
+    );
+    // only launched flowgraphs can be deleted
+    assert_eq!(
+        status("DELETE", "/api/launched/0/", Some("Bearer writer"))?,
+        400
+    );
+    let (_, fgs) = request("GET", "/api/fg/", Some("Bearer reader"))?;
+    assert_eq!(fgs, "[0,2,3]");
+
+    // running flowgraphs have to be terminated first
+    let (_, id) = request("POST", "/api/factory/endless/", Some("Bearer writer"))?;
+    assert_eq!(id, "4");
+    let delete = format!("/api/launched/{id}/");
+    assert_eq!(status("DELETE", &delete, Some("Bearer writer"))?, 409);
+    let terminate = format!("/api/fg/{id}/terminate/");
+    assert_eq!(status("POST", &terminate, Some("Bearer writer"))?, 200);
+    let state = format!("/api/fg/{id}/state/");
+    while request("GET", &state, Some("Bearer reader"))?.1 == "\"Running\"" {
+        block_on(Timer::after(Duration::from_millis(10)));
+    }
+    assert_eq!(status("DELETE", &delete, Some("Bearer writer"))?, 200);
+    let (_, fgs) = request("GET", "/api/fg/", Some("Bearer reader"))?;
+    assert_eq!(fgs, "[0,2,3]");
+
+    block_on(async move {
+        handle.terminate().await?;
+        task.await?;
+        Ok(())
+    })
+}
@@ -0,0 +1,70 @@
+use std::io::Read;
+use std::io::Write;
+use std::net::TcpStream;
+use std::time::Duration;
+
+use futuresdr::anyhow::Result;
+use futuresdr::async_io::block_on;
+use futuresdr::async_io::Timer;
+use futuresdr::blocks::MessageSink;
+use futuresdr::runtime::config;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Runtime;
+
+const ADDR: &str = "127.0.0.1:13371";
+
+/// Send a request and return the HTTP status code
+fn status(method: &str, path: &str, auth: Option<&str>) -> Result<u16> {
+    let mut stream = TcpStream::connect(ADDR)?;
+    let mut req = format!("{method} {path} HTTP/1.1\r\nHost: {ADDR}\r\nConnection: close\r\n");
+    if let Some(auth) = auth {
+        req.push_str(&format!("Authorization: {auth}\r\n"));
+    }
+    if method == "POST" {
+        req.push_str("Content-Type: application/json\r\nContent-Length: 6\r\n\r\n\"Null\"");
+    } else {
+        req.push_str("\r\n");
+    }
+    stream.write_all(req.as_bytes())?;
+
+    let mut res = String::new();
+    stream.read_to_string(&mut res)?;
+    Ok(res.split(' ').nth(1).unwrap().parse()?)
+}
+
+#[test]
+fn read_token_only() -> Result<()> {
+    // the config is global, so this has to happen before it is accessed
+    std::env::set_var("FUTURESDR_CTRLPORT_ENABLE", "true");
+    std::env::set_var("FUTURESDR_CTRLPORT_BIND", ADDR);
+    std::env::set_var("FUTURESDR_CTRLPORT_READ_TOKEN", "reader");
+    std::env::remove_var("FUTURESDR_CTRLPORT_WRITE_TOKEN");
+    assert_eq!(config::config().ctrlport_write_token, None);
+
+    let mut fg = Flowgraph::new();
+    let snk = fg.add_block(MessageSink::new());
+
+    let rt = Runtime::new();
+    let (task, mut handle) = block_on(rt.start(fg));
+    block_on(Timer::after(Duration::from_millis(100)));
+
+    let get = "/api/fg/0/";
+    let call = format!("/api/fg/0/block/{snk}/call/0/");
+
+    assert_eq!(status("GET", get, None)?, 401);
+    assert_eq!(status("GET", get, Some("Bearer foo"))?, 403);
+    assert_eq!(status("GET", get, Some("Bearer reader"))?, 200);
+
+    // without a write token, writing needs the read token
+    assert_eq!(status("POST", &call, None)?, 401);
+    assert_eq!(status("GET", &call, None)?, 401);
+    assert_eq!(status("POST", &call, Some("Bearer foo"))?, 403);
+    assert_eq!(status("POST", &call, Some("Bearer reader"))?, 200);
+    assert_eq!(status("POST", "/api/fg/0/terminate/", None)?, 401);
+
+    block_on(async move {
+        handle.terminate().await?;
+        task.await?;
+        Ok(())
+    })
+}
@@ -0,0 +1,249 @@
+use futuresdr::anyhow::Result;
+use futuresdr::blocks::MessageSink;
+use futuresdr::blocks::NullSink;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::BlockRegistry;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::FlowgraphSpec;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+use futuresdr::runtime::SpecFormat;
+
+const FLOWGRAPH: &str = r#"
+[[blocks]]
+name = "src"
+type = "NullSource"
+parameters = { item_type = "f32" }
+
+[[blocks]]
+name = "head"
+type = "Head"
+parameters = { item_type = "f32", n_items = 1234 }
+
+[[blocks]]
+name = "snk"
+type = "NullSink"
+parameters = { item_type = "f32" }
+
+[[blocks]]
+name = "burst"
+type = "MessageBurst"
+parameters = { message = "foo", n_messages = 10 }
+
+[[blocks]]
+name = "msg_snk"
+type = "MessageSink"
+
+[[stream_edges]]
+src = "src"
+src_port = "out"
+dst = "head"
+dst_port = "in"
+
+[[stream_edges]]
+src = "head"
+src_port = "out"
+dst = "snk"
+dst_port = "in"
+
+[[message_edges]]
+src = "burst"
+src_port = "out"
+dst = "msg_snk"
+dst_port = "in"
+"#;
+
+#[test]
+fn load_and_run() -> Result<()> {
+    let spec = FlowgraphSpec::parse(FLOWGRAPH, SpecFormat::Toml)?;
+    let fg = Flowgraph::from_description(&spec, &BlockRegistry::new())?;
+
+    let fg = Runtime::new().run(fg)?;
+
+    let spec = fg.to_spec()?;
+    let id = |name: &str| spec.blocks.iter().position(|b| b.name == name).unwrap();
+    let snk = fg.kernel::<NullSink<f32>>(id("snk")).unwrap();
+    assert_eq!(snk.n_received(), 1234);
+    let msg_snk = fg.kernel::<MessageSink>(id("msg_snk")).unwrap();
+    assert_eq!(msg_snk.received(), 10);
+
+    Ok(())
+}
+
+#[test]
+fn export_round_trip() -> Result<()> {
+    let spec = FlowgraphSpec::parse(FLOWGRAPH, SpecFormat::Toml)?;
+    let fg = Flowgraph::from_description(&spec, &BlockRegistry::new())?;
+    let exported = fg.to_spec()?;
+
+    for format in [SpecFormat::Toml, SpecFormat::Yaml, SpecFormat::Json] {
+        let s = exported.dump(format)?;
+        let parsed = FlowgraphSpec::parse(&s, format)?;
+
+        assert_eq!(parsed.blocks.len(), 5);
+        for (a, b) in spec.blocks.iter().zip(parsed.blocks.iter()) {
+            assert_eq!(a.name, b.name);
+            assert_eq!(a.type_name, b.type_name);
+            assert_eq!(a.parameters.len(), b.parameters.len());
+            for (k, v) in a.parameters.iter() {
+                assert_eq!(Some(v), b.parameters.get(k));
+            }
+        }
+        assert_eq!(parsed.stream_edges, exported.stream_edges);
+        assert_eq!(parsed.message_edges, spec.message_edges);
+        let mut edges = spec.stream_edges.clone();
+        edges.sort_by(|a, b| a.src.cmp(&b.src));
+        assert_eq!(parsed.stream_edges, edges);
+
+        Flowgraph::from_description(&parsed, &BlockRegistry::new())?;
+    }
+
+    Ok(())
+}
+
+#[test]
+fn custom_block() -> Result<()> {
+    let mut registry = BlockRegistry::new();
+    registry.register("VectorSink", |p| match p {
+        Pmt::MapStrPmt(m) => match m.get("capacity") {
+            Some(Pmt::U64(c)) => Ok(VectorSinkBuilder::<f32>::new()
+                .init_capacity(*c as usize)
+                .build()),
+            _ => futuresdr::anyhow::bail!("capacity missing"),
+        },
+        _ => unreachable!(),
+    });
+
+    let yaml = r#"
+blocks:
+  - name: src
+    type: NullSource
+    parameters:
+      item_type: f32
+  - name: head
+    type: Head
+    parameters:
+      item_type: f32
+      n_items: 100
+  - name: snk
+    type: VectorSink
+    parameters:
+      capacity: 100
+stream_edges:
+  - { src: src, src_port: out, dst: head, dst_port: in }
+  - { src: head, src_port: out, dst: snk, dst_port: in }
+"#;
+    let spec = FlowgraphSpec::parse(yaml, SpecFormat::Yaml)?;
+    let fg = Flowgraph::from_description(&spec, &registry)?;
+    let fg = Runtime::new().run(fg)?;
+
+    let snk = fg.kernel::<VectorSink<f32>>(2).unwrap();
+    assert_eq!(snk.items().len(), 100);
+
+    Ok(())
+}
+
+#[test]
+fn builtin_dsp_blocks() -> Result<()> {
+    let toml = r#"
+[[blocks]]
+name = "src"
+type = "VectorSource"
+parameters = { item_type = "f32", items = [1, 2, 3, 4, 5] }
+
+[[blocks]]
+name = "fir"
+type = "Fir"
+parameters = { item_type = "f32", taps = [1.0, 1.0] }
+
+[[blocks]]
+name = "snk"
+type = "VectorSink"
+parameters = { item_type = "f32" }
+
+[[blocks]]
+name = "complex_src"
+type = "VectorSource"
+parameters = { item_type = "Complex32", items = [[1, 2], 3] }
+
+[[blocks]]
+name = "complex_snk"
+type = "VectorSink"
+parameters = { item_type = "Complex32", capacity = 2 }
+
+[[stream_edges]]
+src = "src"
+src_port = "out"
+dst = "fir"
+dst_port = "in"
+
+[[stream_edges]]
+src = "fir"
+src_port = "out"
+dst = "snk"
+dst_port = "in"
+
+[[stream_edges]]
+src = "complex_src"
+src_port = "out"
+dst = "complex_snk"
+dst_port = "in"
+"#;
+    let spec = FlowgraphSpec::parse(toml, SpecFormat::Toml)?;
+    let fg = Flowgraph::from_description(&spec, &BlockRegistry::new())?;
+    let fg = Runtime::new().run(fg)?;
+
+    let snk = fg.kernel::<VectorSink<f32>>(2).unwrap();
+    assert_eq!(snk.items(), &vec![3.0, 5.0, 7.0, 9.0]);
+    let snk = fg.kernel::<VectorSink<Complex32>>(4).unwrap();
+    assert_eq!(
+        snk.items(),
+        &vec![Complex32::new(1.0, 2.0), Complex32::new(3.0, 0.0)]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn invalid_description() -> Result<()> {
+    let registry = BlockRegistry::new();
+    let block = |t: &str, p: &str| {
+        format!(r#"{{ "blocks": [ {{ "name": "a", "type": "{t}", "parameters": {p} }} ] }}"#)
+    };
+
+    // unknown type
+    let spec = FlowgraphSpec::parse(&block("Foo", "{}"), SpecFormat::Json)?;
+    let e = match Flowgraph::from_description(&spec, &registry) {
+        Ok(_) => panic!("unknown type should fail"),
+        Err(e) => format!("{e:#}"),
+    };
+    assert!(e.contains("no block registered for type Foo"));
+    assert!(e.contains("VectorSource"));
+    // invalid array
+    let spec = FlowgraphSpec::parse(
+        &block("Fir", r#"{ "item_type": "f32", "taps": [1.0, "a"] }"#),
+        SpecFormat::Json,
+    )?;
+    assert!(Flowgraph::from_description(&spec, &registry).is_err());
+    // unknown item type
+    let spec = FlowgraphSpec::parse(
+        &block("NullSink", r#"{ "item_type": "foo" }"#),
+        SpecFormat::Json,
+    )?;
+    assert!(Flowgraph::from_description(&spec, &registry).is_err());
+    // missing parameter
+    let spec = FlowgraphSpec::parse(&block("NullSink", "{}"), SpecFormat::Json)?;
+    assert!(Flowgraph::from_description(&spec, &registry).is_err());
+    // unknown block in edge
+    let mut spec = FlowgraphSpec::parse(FLOWGRAPH, SpecFormat::Toml)?;
+    spec.stream_edges[0].dst = "foo".to_string();
+    assert!(Flowgraph::from_description(&spec, &registry).is_err());
+    // duplicate name
+    let mut spec = FlowgraphSpec::parse(FLOWGRAPH, SpecFormat::Toml)?;
+    spec.blocks[1].name = "src".to_string();
+    assert!(Flowgraph::from_description(&spec, &registry).is_err());
+
+    Ok(())
+}
@@ -0,0 +1,117 @@
+use std::time::Duration;
+use std::time::Instant;
+
+use futuresdr::anyhow::Result;
+use futuresdr::blocks::Head;
+use futuresdr::blocks::MessageSink;
+use futuresdr::blocks::MessageSourceBuilder;
+use futuresdr::blocks::NullSink;
+use futuresdr::blocks::NullSource;
+use futuresdr::blocks::Throttle;
+use futuresdr::runtime::scheduler::DeterministicScheduler;
+use futuresdr::runtime::scheduler::Scheduler;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+
+fn throttled(n_items: u64, rate: f64) -> Result<(Flowgraph, usize, usize)> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(NullSource::<f32>::new());
+    let head = fg.add_block(Head::<f32>::new(n_items));
+    let throttle = fg.add_block(Throttle::<f32>::new(rate));
+    let snk = fg.add_block(NullSink::<f32>::new());
+
+    fg.connect_stream(src, "out", head, "in")?;
+    fg.connect_stream(head, "out", throttle, "in")?;
+    fg.connect_stream(throttle, "out", snk, "in")?;
+
+    Ok((fg, throttle, snk))
+}
+
+#[test]
+fn virtual_time() -> Result<()> {
+    let (fg, _, snk) = throttled(1000, 10.0)?;
+
+    let scheduler = DeterministicScheduler::new();
+    let start = Instant::now();
+    let fg = Runtime::with_scheduler(scheduler.clone()).run(fg)?;
+
+    let snk = fg.kernel::<NullSink<f32>>(snk).unwrap();
+    assert_eq!(snk.n_received(), 1000);
+    assert!(scheduler.elapsed() >= Duration::from_secs(99));
+    assert!(scheduler.elapsed() <= Duration::from_secs(101));
+    assert!(start.elapsed() < Duration::from_secs(30));
+
+    Ok(())
+}
+
+#[test]
+fn message_source() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(
+        MessageSourceBuilder::new(Pmt::Null, Duration::from_secs(60))
+            .n_messages(5)
+            .build(),
+    );
+    let snk = fg.add_block(MessageSink::new());
+    fg.connect_message(src, "out", snk, "in")?;
+
+    let scheduler = DeterministicScheduler::new();
+    let fg = Runtime::with_scheduler(scheduler.clone()).run(fg)?;
+
+    let snk = fg.kernel::<MessageSink>(snk).unwrap();
+    assert_eq!(snk.received(), 5);
+    assert_eq!(scheduler.elapsed(), Duration::from_secs(300));
+
+    Ok(())
+}
+
+#[test]
+fn step() -> Result<()> {
+    let (fg, throttle, snk) = throttled(10_000, 100.0)?;
+
+    let scheduler = DeterministicScheduler::new();
+    let rt = Runtime::with_scheduler(scheduler.clone());
+    let (task, mut handle) = scheduler.block_on(rt.start(fg));
+
+    let mut consumed = 0;
+    for i in 1..=5 {
+        scheduler.advance(Duration::from_secs(1));
+        assert_eq!(scheduler.elapsed(), Duration::from_secs(i));
+
+        let s = scheduler.block_on(handle.block_stats(snk))?;
+        let c = s.stream_inputs[0].items_consumed;
+        assert!(c > consumed);
+        assert!(c <= 100 * i);
+        consumed = c;
+
+        // the throttle is the bottleneck, i.e., its input buffer is filled
+        let s = scheduler.block_on(handle.block_stats(throttle))?;
+        assert!(s.stream_inputs[0].items_available > 0);
+    }
+
+    scheduler.block_on(handle.terminate())?;
+    scheduler.block_on(task)?;
+
+    Ok(())
+}
+
+#[test]
+fn reproducible() -> Result<()> {
+    let run = || -> Result<Vec<u64>> {
+        let (fg, _, _) = throttled(1000, 100.0)?;
+        let scheduler = DeterministicScheduler::new();
+        let rt = Runtime::with_scheduler(scheduler.clone());
+        let (task, mut handle) = scheduler.block_on(rt.start(fg));
+        scheduler.advance(Duration::from_secs(5));
+        let stats = scheduler.block_on(handle.stats())?;
+        scheduler.block_on(task)?;
+        Ok(stats.blocks.iter().map(|b| b.work_calls).collect())
+    };
+
+    assert_eq!(run()?, run()?);
+
+    Ok(())
+}
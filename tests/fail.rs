@@ -1,17 +1,33 @@
+use futures::StreamExt;
+
 use futuresdr::anyhow::{bail, Result};
+use futuresdr::async_io::block_on;
+use futuresdr::async_io::Timer;
 use futuresdr::async_trait::async_trait;
 use futuresdr::blocks::MessageSink;
+use futuresdr::blocks::MessageSourceBuilder;
+use futuresdr::blocks::NullSink;
+use futuresdr::blocks::NullSource;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
 use futuresdr::runtime::Block;
 use futuresdr::runtime::BlockMeta;
 use futuresdr::runtime::BlockMetaBuilder;
+use futuresdr::runtime::BlockPhase;
 use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::FlowgraphError;
+use futuresdr::runtime::FlowgraphEvent;
+use futuresdr::runtime::FlowgraphState;
 use futuresdr::runtime::Kernel;
 use futuresdr::runtime::MessageIo;
 use futuresdr::runtime::MessageIoBuilder;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::RestartPolicy;
 use futuresdr::runtime::Runtime;
 use futuresdr::runtime::StreamIo;
 use futuresdr::runtime::StreamIoBuilder;
 use futuresdr::runtime::WorkIo;
+use std::time::Duration;
 
 struct FailInit;
 
@@ -25,6 +41,18 @@ impl FailInit {
             Self,
         )
     }
+
+    pub fn with_stream() -> Block {
+        Block::new(
+            BlockMetaBuilder::new("FailInit").build(),
+            StreamIoBuilder::new()
+                .add_input::<f32>("in")
+                .add_output::<f32>("out")
+                .build(),
+            MessageIoBuilder::new().build(),
+            Self,
+        )
+    }
 }
 
 #[async_trait]
@@ -103,16 +131,141 @@ impl Kernel for FailDeinit {
     }
 }
 
+struct Flaky {
+    failures: usize,
+    inits: usize,
+    deinits: usize,
+}
+
+impl Flaky {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(failures: usize, policy: RestartPolicy) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("Flaky")
+                .restart_policy(policy)
+                .build(),
+            StreamIoBuilder::new().build(),
+            MessageIoBuilder::new().build(),
+            Self {
+                failures,
+                inits: 0,
+                deinits: 0,
+            },
+        )
+    }
+}
+
+#[async_trait]
+impl Kernel for Flaky {
+    async fn init(
+        &mut self,
+        _s: &mut StreamIo,
+        _m: &mut MessageIo<Self>,
+        _b: &mut BlockMeta,
+    ) -> Result<()> {
+        self.inits += 1;
+        Ok(())
+    }
+
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        _s: &mut StreamIo,
+        _m: &mut MessageIo<Self>,
+        _b: &mut BlockMeta,
+    ) -> Result<()> {
+        if self.failures > 0 {
+            self.failures -= 1;
+            bail!("Flaky, failed work()")
+        }
+        io.finished = true;
+        Ok(())
+    }
+
+    async fn deinit(
+        &mut self,
+        _s: &mut StreamIo,
+        _m: &mut MessageIo<Self>,
+        _b: &mut BlockMeta,
+    ) -> Result<()> {
+        self.deinits += 1;
+        Ok(())
+    }
+}
+
+struct FailAfterProduce;
+
+impl FailAfterProduce {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(policy: RestartPolicy) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("FailAfterProduce")
+                .restart_policy(policy)
+                .build(),
+            StreamIoBuilder::new().add_output::<f32>("out").build(),
+            MessageIoBuilder::new().build(),
+            Self,
+        )
+    }
+}
+
+#[async_trait]
+impl Kernel for FailAfterProduce {
+    async fn work(
+        &mut self,
+        _io: &mut WorkIo,
+        s: &mut StreamIo,
+        _m: &mut MessageIo<Self>,
+        _b: &mut BlockMeta,
+    ) -> Result<()> {
+        let o = s.output(0).slice::<f32>();
+        o[..10].fill(1.0);
+        s.output(0).produce(10);
+        bail!("FailAfterProduce, failed work()")
+    }
+}
+
 #[test]
 fn fail_init() -> Result<()> {
     let mut fg = Flowgraph::new();
 
     fg.add_block(MessageSink::new());
-    fg.add_block(FailInit::new());
+    let fail = fg.add_block(FailInit::new());
 
-    if Runtime::new().run(fg).is_ok() {
-        panic!("flowgraph should fail")
-    }
+    let e = match Runtime::new().run(fg) {
+        Ok(_) => panic!("flowgraph should fail"),
+        Err(e) => e,
+    };
+    let e = e.downcast_ref::<FlowgraphError>().unwrap();
+    assert_eq!(e.errors.len(), 1);
+    assert_eq!(e.errors[0].block_id, fail);
+    assert_eq!(e.errors[0].instance_name, "FailInit_0");
+    assert_eq!(e.errors[0].phase, BlockPhase::Init);
+    assert_eq!(e.errors[0].error.to_string(), "FailInit, failed init()");
+
+    Ok(())
+}
+
+#[test]
+fn fail_init_keep_running() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    fg.set_terminate_on_error(false);
+
+    let src = fg.add_block(NullSource::<f32>::new());
+    let fail = fg.add_block(FailInit::with_stream());
+    let snk = fg.add_block(NullSink::<f32>::new());
+    fg.connect_stream(src, "out", fail, "in")?;
+    fg.connect_stream(fail, "out", snk, "in")?;
+
+    // the neighbors are notified and the flowgraph finishes
+    let e = match Runtime::new().run(fg) {
+        Ok(_) => panic!("flowgraph should fail"),
+        Err(e) => e,
+    };
+    let e = e.downcast_ref::<FlowgraphError>().unwrap();
+    assert_eq!(e.errors.len(), 1);
+    assert_eq!(e.errors[0].block_id, fail);
+    assert_eq!(e.errors[0].phase, BlockPhase::Init);
 
     Ok(())
 }
@@ -124,9 +277,14 @@ fn fail_work() -> Result<()> {
     fg.add_block(MessageSink::new());
     fg.add_block(FailWork::new());
 
-    if Runtime::new().run(fg).is_ok() {
-        panic!("flowgraph should fail")
-    }
+    let e = match Runtime::new().run(fg) {
+        Ok(_) => panic!("flowgraph should fail"),
+        Err(e) => e,
+    };
+    let e = e.downcast_ref::<FlowgraphError>().unwrap();
+    assert_eq!(e.errors.len(), 1);
+    assert_eq!(e.errors[0].phase, BlockPhase::Work);
+    assert!(e.to_string().contains("FailWork, failed work()"));
 
     Ok(())
 }
@@ -138,9 +296,179 @@ fn fail_deinit() -> Result<()> {
     fg.add_block(MessageSink::new());
     fg.add_block(FailDeinit::new());
 
-    if Runtime::new().run(fg).is_ok() {
-        panic!("flowgraph should fail")
+    let e = match Runtime::new().run(fg) {
+        Ok(_) => panic!("flowgraph should fail"),
+        Err(e) => e,
+    };
+    let e = e.downcast_ref::<FlowgraphError>().unwrap();
+    assert_eq!(e.errors.len(), 1);
+    assert_eq!(e.errors[0].phase, BlockPhase::Deinit);
+
+    Ok(())
+}
+
+#[test]
+fn fail_keep_running() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    fg.set_terminate_on_error(false);
+
+    let src = fg.add_block(MessageSourceBuilder::new(Pmt::Null, Duration::from_millis(10)).build());
+    let snk = fg.add_block(MessageSink::new());
+    fg.connect_message(src, "out", snk, "in")?;
+    fg.add_block(FailWork::new());
+    fg.add_block(FailWork::new());
+
+    let rt = Runtime::new();
+    let (task, mut handle) = block_on(rt.start(fg));
+
+    let e = block_on(async move {
+        Timer::after(Duration::from_millis(100)).await;
+        let stats = handle.block_stats(snk).await.unwrap();
+        assert!(stats.messages_handled > 0);
+        handle.terminate().await.unwrap();
+        task.await
+    });
+
+    let e = match e {
+        Ok(_) => panic!("flowgraph should fail"),
+        Err(e) => e,
+    };
+    let e = e.downcast_ref::<FlowgraphError>().unwrap();
+    assert_eq!(e.errors.len(), 2);
+
+    Ok(())
+}
+
+#[test]
+fn fail_state() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    fg.set_terminate_on_error(false);
+
+    fg.add_block(MessageSourceBuilder::new(Pmt::Null, Duration::from_millis(10)).build());
+
+    let rt = Runtime::new();
+    let (task, mut handle) = block_on(rt.start(fg));
+    assert_eq!(handle.state(), FlowgraphState::Running);
+
+    let state = handle.clone();
+    assert!(block_on(async move {
+        // add the block after subscribing, so that its error is not missed
+        let mut events = handle.subscribe_events().await.unwrap();
+        let fail = handle.add_block(FailWork::new()).await.unwrap();
+        while let Some(e) = events.next().await {
+            if matches!(e, FlowgraphEvent::BlockError { block_id, .. } if block_id == fail) {
+                break;
+            }
+        }
+        assert_eq!(handle.state(), FlowgraphState::Running);
+
+        handle.terminate().await.unwrap();
+        task.await
+    })
+    .is_err());
+
+    match state.state() {
+        FlowgraphState::Failed(e) => assert!(e.contains("FailWork, failed work()")),
+        s => panic!("unexpected state {s:?}"),
     }
 
     Ok(())
 }
+
+#[test]
+fn description_after_failure() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    fg.set_terminate_on_error(false);
+
+    let src = fg.add_block(MessageSourceBuilder::new(Pmt::Null, Duration::from_millis(10)).build());
+
+    let rt = Runtime::new();
+    let (task, mut handle) = block_on(rt.start(fg));
+
+    block_on(async move {
+        let mut events = handle.subscribe_events().await.unwrap();
+        let fail = handle.add_block(FailWork::new()).await.unwrap();
+        while let Some(e) = events.next().await {
+            if matches!(e, FlowgraphEvent::BlockError { block_id, .. } if block_id == fail) {
+                break;
+            }
+        }
+
+        let desc = handle.description().await.unwrap();
+        assert_eq!(desc.blocks.len(), 1);
+        assert_eq!(desc.blocks[0].id, src);
+
+        handle.terminate().await.unwrap();
+        assert!(task.await.is_err());
+    });
+
+    Ok(())
+}
+
+#[test]
+fn restart() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let policy = RestartPolicy::Restart {
+        max_restarts: 3,
+        backoff: Duration::from_millis(1),
+    };
+    let flaky = fg.add_block(Flaky::new(2, policy));
+
+    let fg = Runtime::new().run(fg)?;
+    let flaky = fg.kernel::<Flaky>(flaky).unwrap();
+    assert_eq!(flaky.failures, 0);
+    assert_eq!(flaky.inits, 3);
+    assert_eq!(flaky.deinits, 3);
+
+    Ok(())
+}
+
+#[test]
+fn restart_limit() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let policy = RestartPolicy::Restart {
+        max_restarts: 2,
+        backoff: Duration::from_millis(1),
+    };
+    fg.add_block(Flaky::new(10, policy));
+
+    let e = match Runtime::new().run(fg) {
+        Ok(_) => panic!("flowgraph should fail"),
+        Err(e) => e,
+    };
+    let e = e.downcast_ref::<FlowgraphError>().unwrap();
+    assert_eq!(e.errors.len(), 1);
+    assert_eq!(e.errors[0].phase, BlockPhase::Work);
+
+    Ok(())
+}
+
+#[test]
+fn restart_finish() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let mut fail = FailWork::new();
+    fail.set_restart_policy(RestartPolicy::Finish);
+    fg.add_block(fail);
+
+    Runtime::new().run(fg)?;
+
+    Ok(())
+}
+
+#[test]
+fn restart_finish_commits() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(FailAfterProduce::new(RestartPolicy::Finish));
+    let snk = fg.add_block(VectorSinkBuilder::<f32>::new().build());
+    fg.connect_stream(src, "out", snk, "in")?;
+
+    let fg = Runtime::new().run(fg)?;
+    let v = fg.kernel::<VectorSink<f32>>(snk).unwrap().items();
+    assert_eq!(v, &vec![1.0; 10]);
+
+    Ok(())
+}
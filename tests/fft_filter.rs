@@ -0,0 +1,112 @@
+use futuresdr::anyhow::Result;
+use futuresdr::async_io::block_on;
+use futuresdr::blocks::ChannelSource;
+use futuresdr::blocks::FftFilter;
+use futuresdr::blocks::FirBuilder;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::futures::channel::mpsc;
+use futuresdr::futures::prelude::*;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+
+#[test]
+fn fft_filter_matches_fir() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let orig: Vec<f32> = (0..100_000)
+        .map(|i| ((i * 7919) % 1000) as f32 / 1000.0)
+        .collect();
+    let taps: Vec<f32> = (0..511).map(|i| ((i * 31) % 17) as f32 / 100.0).collect();
+
+    let src = fg.add_block(VectorSource::<f32>::new(orig));
+    let fft = fg.add_block(FftFilter::<f32, f32>::new(taps.clone()));
+    let fir = fg.add_block(FirBuilder::new::<f32, f32, f32, _>(taps));
+    let fft_snk = fg.add_block(VectorSinkBuilder::<f32>::new().build());
+    let fir_snk = fg.add_block(VectorSinkBuilder::<f32>::new().build());
+
+    fg.connect_stream(src, "out", fft, "in")?;
+    fg.connect_stream(src, "out", fir, "in")?;
+    fg.connect_stream(fft, "out", fft_snk, "in")?;
+    fg.connect_stream(fir, "out", fir_snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let fft = fg.kernel::<VectorSink<f32>>(fft_snk).unwrap().items();
+    let fir = fg.kernel::<VectorSink<f32>>(fir_snk).unwrap().items();
+
+    assert_eq!(fft.len(), 100_000 - 510);
+    assert_eq!(fft.len(), fir.len());
+    for (have, want) in fft.iter().zip(fir) {
+        assert!((have - want).abs() < 1e-3);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn fft_filter_complex_taps() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let orig = vec![Complex32::new(1.0, 0.0); 1000];
+    let taps = vec![Complex32::new(0.0, 0.5); 100];
+
+    let src = fg.add_block(VectorSource::<Complex32>::new(orig));
+    let fft = fg.add_block(FftFilter::<Complex32, Complex32>::with_fft_size(taps, 128));
+    let snk = fg.add_block(VectorSinkBuilder::<Complex32>::new().build());
+
+    fg.connect_stream(src, "out", fft, "in")?;
+    fg.connect_stream(fft, "out", snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let v = fg.kernel::<VectorSink<Complex32>>(snk).unwrap().items();
+    assert_eq!(v.len(), 901);
+    for x in v {
+        assert!((x - Complex32::new(0.0, 50.0)).norm() < 1e-3);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn fft_filter_set_taps() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    let (mut tx, rx) = mpsc::channel(10);
+
+    let src = fg.add_block(ChannelSource::<f32>::new(rx));
+    let fft = fg.add_block(FftFilter::<f32, f32>::new(vec![1.0; 4]));
+    let snk = fg.add_block(VectorSinkBuilder::<f32>::new().build());
+
+    fg.connect_stream(src, "out", fft, "in")?;
+    fg.connect_stream(fft, "out", snk, "in")?;
+
+    let rt = Runtime::new();
+    let fg = block_on(async move {
+        let (fg, mut handle) = rt.start(fg).await;
+        assert!(matches!(
+            handle.callback(fft, "taps", Pmt::U32(1)).await?,
+            Pmt::InvalidValue
+        ));
+        assert!(matches!(
+            handle
+                .callback(fft, "taps", Pmt::VecF32(vec![2.0; 8]))
+                .await?,
+            Pmt::Ok
+        ));
+        tx.send(vec![1.0; 100].into_boxed_slice()).await?;
+        tx.close().await?;
+        fg.await as Result<Flowgraph>
+    })?;
+
+    let v = fg.kernel::<VectorSink<f32>>(snk).unwrap().items();
+    assert_eq!(v.len(), 93);
+    for x in v {
+        assert!((x - 16.0).abs() < 1e-4);
+    }
+
+    Ok(())
+}
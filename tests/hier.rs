@@ -0,0 +1,109 @@
+use futuresdr::anyhow::Result;
+use futuresdr::blocks::Apply;
+use futuresdr::blocks::MessageBurst;
+use futuresdr::blocks::MessageCopy;
+use futuresdr::blocks::MessageSink;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::HierBlock;
+use futuresdr::runtime::HierBlockBuilder;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+
+fn double_offset() -> Result<HierBlock> {
+    let mut fg = Flowgraph::new();
+    let double = fg.add_block(Apply::new(|i: &f32| i * 2.0));
+    let offset = fg.add_block(Apply::new(|i: &f32| i + 1.0));
+    fg.connect_stream(double, "out", offset, "in")?;
+
+    Ok(HierBlockBuilder::new("DoubleOffset", fg)
+        .stream_input("in", double, "in")
+        .stream_output("out", offset, "out")
+        .build())
+}
+
+#[test]
+fn hier_stream() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let orig: Vec<f32> = (0..1000).map(|x| x as f32).collect();
+    let src = fg.add_block(VectorSource::<f32>::new(orig.clone()));
+    let hier = fg.add_hier_block(double_offset()?)?;
+    let snk = fg.add_block(VectorSinkBuilder::<f32>::new().build());
+
+    fg.connect_stream(src, "out", hier, "in")?;
+    fg.connect_stream(hier, 0, snk, 0)?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let snk = fg.kernel::<VectorSink<f32>>(snk).unwrap();
+    let v = snk.items();
+
+    assert_eq!(v.len(), orig.len());
+    for (o, i) in v.iter().zip(orig.iter()) {
+        assert!((o - (i * 2.0 + 1.0)).abs() < f32::EPSILON);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn hier_nested() -> Result<()> {
+    let mut inner = Flowgraph::new();
+    let a = inner.add_hier_block(double_offset()?)?;
+    let b = inner.add_hier_block(double_offset()?)?;
+    inner.connect_stream(a, "out", b, "in")?;
+    let hier = HierBlockBuilder::new("Nested", inner)
+        .stream_input("in", a, "in")
+        .stream_output("out", b, "out")
+        .build();
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(VectorSource::<f32>::new(vec![1.0, 2.0, 3.0]));
+    let hier = fg.add_hier_block(hier)?;
+    let snk = fg.add_block(VectorSinkBuilder::<f32>::new().build());
+    fg.connect_stream(src, "out", hier, "in")?;
+    fg.connect_stream(hier, "out", snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let snk = fg.kernel::<VectorSink<f32>>(snk).unwrap();
+    assert_eq!(snk.items(), &vec![7.0, 11.0, 15.0]);
+
+    Ok(())
+}
+
+#[test]
+fn hier_message() -> Result<()> {
+    let mut inner = Flowgraph::new();
+    let copy = inner.add_block(MessageCopy::new());
+    let sink = inner.add_block(MessageSink::new());
+    inner.connect_message(copy, "out", sink, "in")?;
+    let hier = HierBlockBuilder::new("Forward", inner)
+        .message_input("in", copy, "in")
+        .build();
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(MessageBurst::new(Pmt::Null, 10));
+    let hier = fg.add_hier_block(hier)?;
+    fg.connect_message(src, "out", hier, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let sink = fg.hier_block_id(hier, sink).unwrap();
+    let sink = fg.kernel::<MessageSink>(sink).unwrap();
+    assert_eq!(sink.received(), 10);
+
+    Ok(())
+}
+
+#[test]
+fn hier_invalid_port() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(VectorSource::<f32>::new(vec![1.0]));
+    let hier = fg.add_hier_block(double_offset()?)?;
+    assert!(fg.connect_stream(src, "out", hier, "foo").is_err());
+    Ok(())
+}
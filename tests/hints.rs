@@ -0,0 +1,125 @@
+use std::thread;
+
+use futuresdr::anyhow::Result;
+use futuresdr::async_trait::async_trait;
+use futuresdr::runtime::scheduler::Scheduler;
+use futuresdr::runtime::Block;
+use futuresdr::runtime::BlockMeta;
+use futuresdr::runtime::BlockMetaBuilder;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Kernel;
+use futuresdr::runtime::MessageIo;
+use futuresdr::runtime::MessageIoBuilder;
+use futuresdr::runtime::Priority;
+use futuresdr::runtime::Runtime;
+use futuresdr::runtime::StreamIo;
+use futuresdr::runtime::StreamIoBuilder;
+use futuresdr::runtime::WorkIo;
+
+/// Records the name of the thread that runs it and finishes.
+struct ThreadProbe {
+    thread: Option<String>,
+}
+
+impl ThreadProbe {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(meta: BlockMetaBuilder) -> Block {
+        let mut block = Block::new(
+            meta.build(),
+            StreamIoBuilder::new().build(),
+            MessageIoBuilder::new().build(),
+            Self { thread: None },
+        );
+        block.set_instance_name("probe");
+        block
+    }
+}
+
+#[async_trait]
+impl Kernel for ThreadProbe {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        _s: &mut StreamIo,
+        _m: &mut MessageIo<Self>,
+        _b: &mut BlockMeta,
+    ) -> Result<()> {
+        self.thread = thread::current().name().map(String::from);
+        io.finished = true;
+        Ok(())
+    }
+}
+
+fn thread_name<S: Scheduler>(rt: Runtime<S>, meta: BlockMetaBuilder) -> Result<String> {
+    let mut fg = Flowgraph::new();
+    let probe = fg.add_block(ThreadProbe::new(meta));
+    let fg = rt.run(fg)?;
+    Ok(fg
+        .kernel::<ThreadProbe>(probe)
+        .unwrap()
+        .thread
+        .clone()
+        .unwrap())
+}
+
+#[test]
+fn smol_executor() -> Result<()> {
+    let name = thread_name(Runtime::new(), BlockMetaBuilder::new("ThreadProbe"))?;
+    assert!(name.starts_with("smol-"));
+    Ok(())
+}
+
+#[test]
+fn smol_dedicated_thread() -> Result<()> {
+    let name = thread_name(
+        Runtime::new(),
+        BlockMetaBuilder::new("ThreadProbe").dedicated_thread(),
+    )?;
+    assert_eq!(name, "probe");
+    Ok(())
+}
+
+#[test]
+fn smol_unsatisfiable_hints() -> Result<()> {
+    // hints that cannot be satisfied only cause a warning
+    let name = thread_name(
+        Runtime::new(),
+        BlockMetaBuilder::new("ThreadProbe")
+            .cores([100_000])
+            .priority(Priority::High),
+    )?;
+    assert_eq!(name, "probe");
+    Ok(())
+}
+
+#[cfg(feature = "flow_scheduler")]
+#[test]
+fn flow_cores() -> Result<()> {
+    use futuresdr::runtime::scheduler::FlowScheduler;
+
+    let name = thread_name(
+        Runtime::with_scheduler(FlowScheduler::new()),
+        BlockMetaBuilder::new("ThreadProbe").cores([0]),
+    )?;
+    assert_eq!(name, "flow-0");
+
+    let name = thread_name(
+        Runtime::with_scheduler(FlowScheduler::new()),
+        BlockMetaBuilder::new("ThreadProbe").dedicated_thread(),
+    )?;
+    assert_eq!(name, "probe");
+    Ok(())
+}
+
+#[cfg(feature = "tpb_scheduler")]
+#[test]
+fn tpb_cores() -> Result<()> {
+    use futuresdr::runtime::scheduler::TpbScheduler;
+
+    let name = thread_name(
+        Runtime::with_scheduler(TpbScheduler::new()),
+        BlockMetaBuilder::new("ThreadProbe").cores([0]),
+    )?;
+    assert_eq!(name, "probe");
+    Ok(())
+}
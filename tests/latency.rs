@@ -0,0 +1,232 @@
+use std::time::Duration;
+
+use futuresdr::anyhow::Result;
+use futuresdr::async_io::block_on;
+use futuresdr::async_io::Timer;
+use futuresdr::async_trait::async_trait;
+use futuresdr::blocks::ApplyInPlace;
+use futuresdr::blocks::Copy;
+use futuresdr::blocks::NullSink;
+use futuresdr::blocks::NullSource;
+use futuresdr::blocks::VectorSource;
+use futuresdr::runtime::buffer::circular::Circular;
+use futuresdr::runtime::buffer::slab::Slab;
+use futuresdr::runtime::Block;
+use futuresdr::runtime::BlockMeta;
+use futuresdr::runtime::BlockMetaBuilder;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Kernel;
+use futuresdr::runtime::MessageIo;
+use futuresdr::runtime::MessageIoBuilder;
+use futuresdr::runtime::Runtime;
+use futuresdr::runtime::StreamIo;
+use futuresdr::runtime::StreamIoBuilder;
+use futuresdr::runtime::WorkIo;
+
+/// Checks that it receives `0, 1, 2, ...` and records the largest input it ever sees.
+struct MaxSink {
+    received: usize,
+    max: usize,
+}
+
+impl MaxSink {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> Block {
+        Block::new(
+            BlockMetaBuilder::new("MaxSink").build(),
+            StreamIoBuilder::new().add_input::<f32>("in").build(),
+            MessageIoBuilder::new().build(),
+            Self {
+                received: 0,
+                max: 0,
+            },
+        )
+    }
+}
+
+#[async_trait]
+impl Kernel for MaxSink {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _m: &mut MessageIo<Self>,
+        _b: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<f32>();
+        self.max = self.max.max(i.len());
+        for (n, v) in i.iter().enumerate() {
+            assert_eq!(*v, (self.received + n) as f32);
+        }
+        self.received += i.len();
+        sio.input(0).consume(i.len());
+
+        if sio.input(0).finished() {
+            io.finished = true;
+        }
+        Ok(())
+    }
+}
+
+fn run_capped(connect: impl FnOnce(&mut Flowgraph, usize, usize) -> Result<()>) -> Result<()> {
+    let n = 100_000;
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(VectorSource::<f32>::new((0..n).map(|x| x as f32).collect()));
+    let snk = fg.add_block(MaxSink::new());
+    connect(&mut fg, src, snk)?;
+
+    let fg = Runtime::new().run(fg)?;
+
+    let snk = fg.kernel::<MaxSink>(snk).unwrap();
+    assert_eq!(snk.received, n);
+    assert!(snk.max > 0);
+    assert!(snk.max <= 64);
+    Ok(())
+}
+
+#[test]
+fn circular_cap() -> Result<()> {
+    run_capped(|fg, src, snk| {
+        fg.connect_stream_with_type(src, "out", snk, "in", Circular::new().max_items(64))
+    })
+}
+
+#[test]
+fn slab_cap() -> Result<()> {
+    run_capped(|fg, src, snk| {
+        fg.connect_stream_with_type(src, "out", snk, "in", Slab::new().max_items(64))
+    })
+}
+
+#[test]
+fn latency_budget() -> Result<()> {
+    let n = 100_000;
+    let mut fg = Flowgraph::new();
+    // 100 items, split between 4 edges
+    fg.set_latency_budget(Duration::from_millis(1), 100_000.0);
+
+    let src = fg.add_block(VectorSource::<f32>::new((0..n).map(|x| x as f32).collect()));
+    let copy0 = fg.add_block(Copy::<f32>::new());
+    let copy1 = fg.add_block(Copy::<f32>::new());
+    let copy2 = fg.add_block(Copy::<f32>::new());
+    let snk = fg.add_block(MaxSink::new());
+
+    fg.connect_stream(src, "out", copy0, "in")?;
+    fg.connect_stream(copy0, "out", copy1, "in")?;
+    fg.connect_stream(copy1, "out", copy2, "in")?;
+    fg.connect_stream(copy2, "out", snk, "in")?;
+
+    let fg = Runtime::new().run(fg)?;
+
+    let snk = fg.kernel::<MaxSink>(snk).unwrap();
+    assert_eq!(snk.received, n);
+    assert!(snk.max <= 25);
+    Ok(())
+}
+
+#[test]
+fn latency_budget_reconfigure() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    // 100 items, split between 2 edges
+    fg.set_latency_budget(Duration::from_millis(1), 100_000.0);
+
+    let src = fg.add_block(NullSource::<f32>::new());
+    let copy0 = fg.add_block(Copy::<f32>::new());
+    let snk = fg.add_block(NullSink::<f32>::new());
+
+    fg.connect_stream(src, "out", copy0, "in")?;
+    fg.connect_stream(copy0, "out", snk, "in")?;
+
+    let rt = Runtime::new();
+    let (task, mut handle) = block_on(rt.start(fg));
+
+    block_on(async move {
+        // 3 edges on the longest path
+        let copy1 = handle.add_block(Copy::<f32>::new()).await?;
+        handle.disconnect_stream(copy0, "out", snk, "in").await?;
+        handle.connect_stream(copy0, "out", copy1, "in").await?;
+        handle.connect_stream(copy1, "out", snk, "in").await?;
+
+        let desc = handle.description().await?;
+        for (edge, l) in desc.stream_edges.iter().zip(desc.stream_latencies.iter()) {
+            if edge.0 == src {
+                // not initialized again
+                assert_eq!(l.max_items, Some(50));
+            } else if edge.0 == copy1 {
+                // initialized with the final topology
+                assert_eq!(l.max_items, Some(33));
+            }
+        }
+
+        handle.terminate().await?;
+        task.await
+    })?;
+
+    Ok(())
+}
+
+#[test]
+fn measured_latency() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    fg.set_latency_budget(Duration::from_millis(1), 100_000.0);
+
+    let src = fg.add_block(NullSource::<f32>::new());
+    let copy = fg.add_block(Copy::<f32>::new());
+    let snk = fg.add_block(NullSink::<f32>::new());
+
+    fg.connect_stream(src, "out", copy, "in")?;
+    fg.connect_stream(copy, "out", snk, "in")?;
+
+    let rt = Runtime::new();
+    let (task, mut handle) = block_on(rt.start(fg));
+
+    block_on(async move {
+        Timer::after(Duration::from_millis(100)).await;
+        let desc = handle.description().await?;
+
+        assert_eq!(desc.stream_edges.len(), 2);
+        assert_eq!(desc.stream_latencies.len(), 2);
+        for l in desc.stream_latencies.iter() {
+            assert_eq!(l.max_items, Some(50));
+            assert!(l.measurements > 0);
+            assert!(l.max >= l.mean);
+        }
+
+        handle.terminate().await?;
+        task.await
+    })?;
+
+    Ok(())
+}
+
+#[test]
+fn measured_latency_in_place() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    fg.set_latency_budget(Duration::from_millis(1), 100_000.0);
+
+    let src = fg.add_block(NullSource::<f32>::new());
+    let apply = fg.add_block(ApplyInPlace::new(|x: &mut f32| *x += 1.0));
+    let snk = fg.add_block(NullSink::<f32>::new());
+
+    fg.connect_stream_with_type(src, "out", apply, "in", Slab::new())?;
+    fg.connect_stream_with_type(apply, "out", snk, "in", Slab::in_place())?;
+
+    let rt = Runtime::new();
+    let (task, mut handle) = block_on(rt.start(fg));
+
+    block_on(async move {
+        Timer::after(Duration::from_millis(100)).await;
+        let desc = handle.description().await?;
+
+        assert_eq!(desc.stream_latencies.len(), 2);
+        for l in desc.stream_latencies.iter() {
+            assert!(l.measurements > 0);
+            assert!(l.max >= l.mean);
+        }
+
+        handle.terminate().await?;
+        task.await
+    })?;
+
+    Ok(())
+}
@@ -0,0 +1,295 @@
+use std::time::Duration;
+
+use futuresdr::anyhow::Result;
+use futuresdr::async_io::block_on;
+use futuresdr::async_io::Timer;
+use futuresdr::async_trait::async_trait;
+use futuresdr::blocks::MessageSourceBuilder;
+use futuresdr::macros::message_handler;
+use futuresdr::runtime::Block;
+use futuresdr::runtime::BlockMeta;
+use futuresdr::runtime::BlockMetaBuilder;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::FlowgraphSpec;
+use futuresdr::runtime::Kernel;
+use futuresdr::runtime::MessageIo;
+use futuresdr::runtime::MessageIoBuilder;
+use futuresdr::runtime::MessageQueueConfig;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+use futuresdr::runtime::SpecFormat;
+use futuresdr::runtime::StreamIo;
+use futuresdr::runtime::StreamIoBuilder;
+use futuresdr::runtime::WorkIo;
+
+/// Posts `n` numbered messages at once and keeps running.
+struct Burst {
+    n: u64,
+    done: bool,
+}
+
+impl Burst {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(n: u64) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("Burst").build(),
+            StreamIoBuilder::new().build(),
+            MessageIoBuilder::new().add_output("out").build(),
+            Self { n, done: false },
+        )
+    }
+}
+
+#[async_trait]
+impl Kernel for Burst {
+    async fn work(
+        &mut self,
+        _io: &mut WorkIo,
+        _sio: &mut StreamIo,
+        mio: &mut MessageIo<Self>,
+        _b: &mut BlockMeta,
+    ) -> Result<()> {
+        if !self.done {
+            for i in 0..self.n {
+                mio.post(0, Pmt::U64(i)).await;
+            }
+            self.done = true;
+        }
+        Ok(())
+    }
+}
+
+/// Handles one message per millisecond.
+struct SlowSink {
+    received: Vec<u64>,
+}
+
+impl SlowSink {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> Block {
+        Block::new(
+            BlockMetaBuilder::new("SlowSink").build(),
+            StreamIoBuilder::new().build(),
+            MessageIoBuilder::new()
+                .add_input("in", Self::handler)
+                .build(),
+            Self {
+                received: Vec::new(),
+            },
+        )
+    }
+
+    #[message_handler]
+    async fn handler(
+        &mut self,
+        _io: &mut WorkIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        if let Pmt::U64(i) = p {
+            std::thread::sleep(Duration::from_millis(1));
+            self.received.push(i);
+        }
+        Ok(Pmt::Ok)
+    }
+}
+
+#[async_trait]
+impl Kernel for SlowSink {}
+
+/// Stalls on the first message, then handles messages as fast as possible.
+struct StallingSink {
+    received: Vec<u64>,
+}
+
+impl StallingSink {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> Block {
+        Block::new(
+            BlockMetaBuilder::new("StallingSink").build(),
+            StreamIoBuilder::new().build(),
+            MessageIoBuilder::new()
+                .add_input("in", Self::handler)
+                .build(),
+            Self {
+                received: Vec::new(),
+            },
+        )
+    }
+
+    #[message_handler]
+    async fn handler(
+        &mut self,
+        _io: &mut WorkIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        if let Pmt::U64(i) = p {
+            if self.received.is_empty() {
+                std::thread::sleep(Duration::from_millis(200));
+            }
+            self.received.push(i);
+        }
+        Ok(Pmt::Ok)
+    }
+}
+
+#[async_trait]
+impl Kernel for StallingSink {}
+
+/// Run the flowgraph for a while and return the received messages and the dropped count.
+fn run(queue: MessageQueueConfig) -> Result<(Vec<u64>, u64)> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(Burst::new(1000));
+    let snk = fg.add_block(SlowSink::new());
+    fg.connect_message_with_queue(src, "out", snk, "in", queue)?;
+
+    let rt = Runtime::new();
+    let (task, mut handle) = block_on(rt.start(fg));
+
+    block_on(async move {
+        Timer::after(Duration::from_millis(1500)).await;
+
+        let d = handle.description().await?;
+        assert_eq!(d.message_edges.len(), 1);
+        assert_eq!(d.message_queues.len(), 1);
+        assert_eq!(d.message_queues[0].config, queue);
+        let dropped = d.message_queues[0].dropped;
+
+        handle.terminate().await?;
+        let fg = task.await?;
+        let snk = fg.kernel::<SlowSink>(snk).unwrap();
+        Ok((snk.received.clone(), dropped))
+    })
+}
+
+#[test]
+fn block() -> Result<()> {
+    let (received, dropped) = run(MessageQueueConfig::Block)?;
+    assert_eq!(received, (0..1000).collect::<Vec<u64>>());
+    assert_eq!(dropped, 0);
+    Ok(())
+}
+
+#[test]
+fn drop_newest() -> Result<()> {
+    let (received, dropped) = run(MessageQueueConfig::DropNewest(10))?;
+    assert!(received.len() >= 10);
+    assert!(dropped > 0);
+    assert_eq!(received.len() as u64 + dropped, 1000);
+    assert_eq!(received[..10], (0..10).collect::<Vec<u64>>());
+    Ok(())
+}
+
+#[test]
+fn drop_oldest() -> Result<()> {
+    let (received, dropped) = run(MessageQueueConfig::DropOldest(10))?;
+    assert!(received.len() >= 10);
+    assert!(dropped > 0);
+    assert_eq!(received.len() as u64 + dropped, 1000);
+    assert_eq!(
+        received[received.len() - 10..],
+        (990..1000).collect::<Vec<u64>>()
+    );
+    Ok(())
+}
+
+#[test]
+fn drop_oldest_larger_than_inbox() -> Result<()> {
+    // larger than the inbox of the receiving block (`queue_size` config)
+    let capacity = 2 * futuresdr::runtime::config::config().queue_size;
+    let n = 2 * capacity as u64;
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(Burst::new(n));
+    let snk = fg.add_block(StallingSink::new());
+    fg.connect_message_with_queue(
+        src,
+        "out",
+        snk,
+        "in",
+        MessageQueueConfig::DropOldest(capacity),
+    )?;
+
+    let rt = Runtime::new();
+    let (task, mut handle) = block_on(rt.start(fg));
+
+    let (received, dropped) = block_on(async move {
+        Timer::after(Duration::from_millis(1500)).await;
+        let dropped = handle.description().await?.message_queues[0].dropped;
+        handle.terminate().await?;
+        let fg = task.await?;
+        let snk = fg.kernel::<StallingSink>(snk).unwrap();
+        Ok::<_, futuresdr::anyhow::Error>((snk.received.clone(), dropped))
+    })?;
+
+    // the queue keeps the newest messages, at most the first one was handled before
+    assert!(received.len() >= capacity);
+    assert!(received.len() <= capacity + 1);
+    assert_eq!(received.len() as u64 + dropped, n);
+    assert_eq!(
+        received[received.len() - capacity..],
+        (n - capacity as u64..n).collect::<Vec<u64>>()
+    );
+    Ok(())
+}
+
+#[test]
+fn single_message_full_inbox() -> Result<()> {
+    // more than the inbox of the receiving block (`queue_size` config) can hold
+    let n = 2 * futuresdr::runtime::config::config().queue_size as u64;
+
+    let mut fg = Flowgraph::new();
+    let burst = fg.add_block(Burst::new(n));
+    let single = fg.add_block(
+        MessageSourceBuilder::new(Pmt::U64(u64::MAX), Duration::from_millis(50))
+            .n_messages(1)
+            .build(),
+    );
+    let snk = fg.add_block(StallingSink::new());
+    fg.connect_message(burst, "out", snk, "in")?;
+    // posted while the sink stalls with a full inbox
+    fg.connect_message_with_queue(single, "out", snk, "in", MessageQueueConfig::DropOldest(1))?;
+
+    let rt = Runtime::new();
+    let (task, mut handle) = block_on(rt.start(fg));
+
+    let received = block_on(async move {
+        Timer::after(Duration::from_millis(1000)).await;
+        handle.terminate().await?;
+        let fg = task.await?;
+        let snk = fg.kernel::<StallingSink>(snk).unwrap();
+        Ok::<_, futuresdr::anyhow::Error>(snk.received.clone())
+    })?;
+
+    assert_eq!(received.len() as u64, n + 1);
+    assert!(received.contains(&u64::MAX));
+    Ok(())
+}
+
+#[test]
+fn queue_config() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(Burst::new(1));
+    let snk = fg.add_block(SlowSink::new());
+
+    assert!(fg
+        .connect_message_with_queue(src, "out", snk, "in", MessageQueueConfig::DropOldest(0))
+        .is_err());
+    fg.connect_message_with_queue(src, "out", snk, "in", MessageQueueConfig::DropOldest(16))?;
+
+    let spec = fg.to_spec()?;
+    assert_eq!(
+        spec.message_edges[0].queue,
+        MessageQueueConfig::DropOldest(16)
+    );
+    for format in [SpecFormat::Toml, SpecFormat::Yaml, SpecFormat::Json] {
+        let parsed = FlowgraphSpec::parse(&spec.dump(format)?, format)?;
+        assert_eq!(parsed.message_edges, spec.message_edges);
+    }
+
+    Ok(())
+}
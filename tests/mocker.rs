@@ -0,0 +1,235 @@
+use futuresdr::anyhow::Result;
+use futuresdr::async_trait::async_trait;
+use futuresdr::blocks::Apply;
+use futuresdr::macros::message_handler;
+use futuresdr::runtime::BlockMeta;
+use futuresdr::runtime::BlockMetaBuilder;
+use futuresdr::runtime::ItemTag;
+use futuresdr::runtime::Kernel;
+use futuresdr::runtime::MessageIo;
+use futuresdr::runtime::MessageIoBuilder;
+use futuresdr::runtime::Mocker;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::StreamIo;
+use futuresdr::runtime::StreamIoBuilder;
+use futuresdr::runtime::Tag;
+use futuresdr::runtime::TypedBlock;
+use futuresdr::runtime::WorkIo;
+
+/// Counts messages, reports its lifecycle on `out`, and produces one item per `work()` call.
+struct Counter {
+    n: u64,
+}
+
+impl Counter {
+    fn new() -> TypedBlock<Self> {
+        TypedBlock::new(
+            BlockMetaBuilder::new("Counter").build(),
+            StreamIoBuilder::new().add_output::<u64>("out").build(),
+            MessageIoBuilder::new()
+                .add_input("in", Self::handler)
+                .add_output("out")
+                .build(),
+            Self { n: 0 },
+        )
+    }
+
+    #[message_handler]
+    async fn handler(
+        &mut self,
+        _io: &mut WorkIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        match p {
+            Pmt::U64(n) => {
+                self.n += n;
+                mio.post(0, Pmt::U64(self.n)).await;
+                Ok(Pmt::U64(self.n))
+            }
+            _ => Ok(Pmt::InvalidValue),
+        }
+    }
+}
+
+#[async_trait]
+impl Kernel for Counter {
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        mio.post(0, Pmt::String("init".to_string())).await;
+        Ok(())
+    }
+
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let o = sio.output(0).slice::<u64>();
+        if !o.is_empty() {
+            o[0] = self.n;
+            sio.output(0).produce(1);
+            io.call_again = true;
+        }
+        Ok(())
+    }
+
+    async fn deinit(
+        &mut self,
+        _sio: &mut StreamIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        mio.post(0, Pmt::String("deinit".to_string())).await;
+        Ok(())
+    }
+}
+
+#[test]
+fn message_ports() -> Result<()> {
+    let mut mocker = Mocker::new(Counter::new());
+    mocker.init_output::<u64>(0, 16);
+
+    mocker.init()?;
+    assert_eq!(mocker.messages(0), vec![Pmt::String("init".to_string())]);
+
+    assert_eq!(mocker.post("in", Pmt::U64(2))?, Pmt::U64(2));
+    assert_eq!(mocker.post(0, Pmt::U64(3))?, Pmt::U64(5));
+    assert!(matches!(mocker.post("in", Pmt::Null)?, Pmt::InvalidValue));
+    assert!(mocker.post("foo", Pmt::Null).is_err());
+    assert_eq!(mocker.messages(0), vec![Pmt::U64(2), Pmt::U64(5)]);
+    assert!(mocker.messages(0).is_empty());
+
+    mocker.deinit()?;
+    assert_eq!(mocker.messages(0), vec![Pmt::String("deinit".to_string())]);
+
+    Ok(())
+}
+
+#[test]
+fn repeated_work() -> Result<()> {
+    let mut mocker = Mocker::new(Counter::new());
+    mocker.init_output::<u64>(0, 16);
+
+    mocker.run_for(3)?;
+    assert_eq!(mocker.output::<u64>(0), vec![0; 3]);
+
+    mocker.post("in", Pmt::U64(1))?;
+    mocker.run();
+    assert_eq!(mocker.output::<u64>(0), vec![1; 16]);
+    assert!(!mocker.finished());
+
+    Ok(())
+}
+
+#[test]
+fn output_full() {
+    let mut mocker = Mocker::new(Apply::new_typed(|x: &u32| x + 1));
+    mocker.input(0, (0..10).collect::<Vec<u32>>());
+    mocker.init_output::<u32>(0, 4);
+
+    mocker.run();
+    assert!(!mocker.finished());
+    assert_eq!(mocker.output::<u32>(0), vec![1, 2, 3, 4]);
+
+    mocker.run();
+    assert_eq!(mocker.output::<u32>(0), vec![5, 6, 7, 8]);
+
+    mocker.run();
+    assert!(mocker.finished());
+    assert_eq!(mocker.output::<u32>(0), vec![9, 10]);
+}
+
+#[test]
+fn input_finished() {
+    let mut mocker = Mocker::new(Apply::new_typed(|x: &u32| x + 1));
+    mocker.input(0, vec![1u32, 2, 3]);
+    mocker.set_input_finished(0, false);
+    mocker.init_output::<u32>(0, 16);
+
+    mocker.run();
+    assert_eq!(mocker.output::<u32>(0), vec![2, 3, 4]);
+    assert!(!mocker.finished());
+
+    mocker.set_input_finished(0, true);
+    mocker.run();
+    assert!(mocker.output::<u32>(0).is_empty());
+    assert!(mocker.finished());
+}
+
+#[test]
+fn output_tags() {
+    let tags = (0..10)
+        .step_by(3)
+        .map(|i| ItemTag {
+            index: i,
+            tag: Tag::Id(i as u64),
+        })
+        .collect();
+
+    let mut mocker = Mocker::new(Apply::new_typed(|x: &f32| x * 2.0));
+    mocker.input_with_tags(0, vec![0.0f32; 10], tags);
+    mocker.init_output::<f32>(0, 5);
+
+    mocker.run();
+    mocker.output::<f32>(0);
+    mocker.run();
+    assert_eq!(mocker.output::<f32>(0).len(), 5);
+
+    let tags = mocker.output_tags::<f32>(0);
+    assert_eq!(
+        tags.iter().map(|t| t.index).collect::<Vec<_>>(),
+        vec![0, 3, 6, 9]
+    );
+    for t in tags {
+        assert!(matches!(t.tag, Tag::Id(id) if id == t.index as u64));
+    }
+    assert!(mocker.output_tags::<f32>(0).is_empty());
+}
+
+/// Posts `n` messages in one `work()` call.
+struct Flood {
+    n: usize,
+}
+
+#[async_trait]
+impl Kernel for Flood {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        _sio: &mut StreamIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        for i in 0..self.n {
+            mio.post(0, Pmt::Usize(i)).await;
+        }
+        io.finished = true;
+        Ok(())
+    }
+}
+
+#[test]
+fn more_messages_than_queue_size() {
+    // more than the channel holds, including the sender's guaranteed slot
+    let n = 2 * futuresdr::runtime::config::config().queue_size;
+    let mut mocker = Mocker::new(TypedBlock::new(
+        BlockMetaBuilder::new("Flood").build(),
+        StreamIoBuilder::new().build(),
+        MessageIoBuilder::new().add_output("out").build(),
+        Flood { n },
+    ));
+
+    mocker.run();
+    assert!(mocker.finished());
+    let messages = mocker.messages(0);
+    assert_eq!(messages.len(), n);
+    assert!(matches!(messages[n - 1], Pmt::Usize(i) if i == n - 1));
+}
@@ -0,0 +1,100 @@
+use std::f32::consts::PI;
+
+use futuresdr::anyhow::Result;
+use futuresdr::blocks::NullSink;
+use futuresdr::blocks::PfbChannelizerBuilder;
+use futuresdr::blocks::PfbSynthesizerBuilder;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Runtime;
+
+/// Tones close to the centers of channels 1 and 6 of an 8-channel filterbank.
+fn tones(n: usize) -> Vec<Complex32> {
+    (0..n)
+        .map(|t| {
+            let t = t as f32;
+            Complex32::from_polar(1.0, 2.0 * PI * (1.0 / 8.0 + 0.003) * t)
+                + Complex32::from_polar(0.5, 2.0 * PI * (6.0 / 8.0 - 0.002) * t)
+        })
+        .collect()
+}
+
+fn power(v: &[Complex32]) -> f32 {
+    v.iter().map(|x| x.norm_sqr()).sum::<f32>() / v.len() as f32
+}
+
+#[test]
+fn pfb_channelizer() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(VectorSource::<Complex32>::new(tones(80_000)));
+    let pfb = fg.add_block(
+        PfbChannelizerBuilder::new(8)
+            .oversampled()
+            .disable(3)
+            .build(),
+    );
+    fg.connect_stream(src, "out", pfb, "in")?;
+
+    // disabled channels do not produce samples
+    let null = fg.add_block(NullSink::<Complex32>::new());
+    fg.connect_stream(pfb, "out3", null, "in")?;
+
+    let mut snks = Vec::new();
+    for c in [0, 1, 2, 4, 5, 6, 7] {
+        let snk = fg.add_block(VectorSinkBuilder::<Complex32>::new().build());
+        fg.connect_stream(pfb, format!("out{c}").as_str(), snk, "in")?;
+        snks.push((c, snk));
+    }
+
+    fg = Runtime::new().run(fg)?;
+
+    assert_eq!(
+        fg.kernel::<NullSink<Complex32>>(null).unwrap().n_received(),
+        0
+    );
+    for (c, snk) in snks {
+        let v = fg.kernel::<VectorSink<Complex32>>(snk).unwrap().items();
+        assert_eq!(v.len(), (80_000 - 192) / 4 + 1);
+        let want = match c {
+            1 => 1.0,
+            6 => 0.25,
+            _ => 0.0,
+        };
+        assert!((power(v) - want).abs() < 1e-3);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn pfb_round_trip() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(VectorSource::<Complex32>::new(tones(80_000)));
+    let channelizer = fg.add_block(PfbChannelizerBuilder::new(8).disable(3).build());
+    let synthesizer = fg.add_block(PfbSynthesizerBuilder::new(8).disable(3).build());
+    let snk = fg.add_block(VectorSinkBuilder::<Complex32>::new().build());
+
+    fg.connect_stream(src, "out", channelizer, "in")?;
+    for c in 0..8 {
+        fg.connect_stream(
+            channelizer,
+            format!("out{c}").as_str(),
+            synthesizer,
+            format!("in{c}").as_str(),
+        )?;
+    }
+    fg.connect_stream(synthesizer, "out", snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let v = fg.kernel::<VectorSink<Complex32>>(snk).unwrap().items();
+    assert_eq!(v.len(), ((80_000 - 192) / 8 + 1) * 8);
+    assert!((power(&v[1000..]) - 1.25).abs() < 1e-2);
+
+    Ok(())
+}
@@ -0,0 +1,174 @@
+use std::time::Duration;
+
+use futuresdr::anyhow::Result;
+use futuresdr::async_io::block_on;
+use futuresdr::async_io::Timer;
+use futuresdr::blocks::Apply;
+use futuresdr::blocks::MessageSink;
+use futuresdr::blocks::MessageSourceBuilder;
+use futuresdr::blocks::NullSink;
+use futuresdr::blocks::NullSource;
+use futuresdr::blocks::Throttle;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+
+#[test]
+fn add_remove_stream() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(NullSource::<f32>::new());
+    let throttle = fg.add_block(Throttle::<f32>::new(1e6));
+    let snk = fg.add_block(NullSink::<f32>::new());
+
+    fg.connect_stream(src, "out", throttle, "in")?;
+    fg.connect_stream(throttle, "out", snk, "in")?;
+
+    let rt = Runtime::new();
+    let (task, mut handle) = block_on(rt.start(fg));
+
+    let fg = block_on(async move {
+        let recorder = handle.add_block(NullSink::<f32>::new()).await?;
+        handle
+            .connect_stream(throttle, "out", recorder, "in")
+            .await?;
+        Timer::after(Duration::from_millis(200)).await;
+
+        let recorder = handle.remove_block(recorder).await?;
+        let recorder = recorder.kernel::<NullSink<f32>>().unwrap();
+        assert!(recorder.n_received() > 0);
+
+        Timer::after(Duration::from_millis(50)).await;
+        handle.terminate().await?;
+        task.await
+    })?;
+
+    let snk = fg.kernel::<NullSink<f32>>(snk).unwrap();
+    assert!(snk.n_received() > 0);
+
+    Ok(())
+}
+
+#[test]
+fn swap_block() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(NullSource::<f32>::new());
+    let throttle = fg.add_block(Throttle::<f32>::new(1e5));
+    let double = fg.add_block(Apply::new(|i: &f32| i * 2.0));
+    let snk = fg.add_block(VectorSinkBuilder::<f32>::new().build());
+
+    fg.connect_stream(src, "out", throttle, "in")?;
+    fg.connect_stream(throttle, "out", double, "in")?;
+    fg.connect_stream(double, "out", snk, "in")?;
+
+    let rt = Runtime::new();
+    let (task, mut handle) = block_on(rt.start(fg));
+
+    let fg = block_on(async move {
+        Timer::after(Duration::from_millis(50)).await;
+
+        let offset = handle.add_block(Apply::new(|i: &f32| i + 1.0)).await?;
+        handle
+            .disconnect_stream(throttle, "out", double, "in")
+            .await?;
+        handle.connect_stream(throttle, "out", offset, "in").await?;
+        handle.disconnect_stream(double, "out", snk, "in").await?;
+        handle.connect_stream(offset, "out", snk, "in").await?;
+        handle.remove_block(double).await?;
+
+        Timer::after(Duration::from_millis(200)).await;
+        handle.terminate().await?;
+        task.await
+    })?;
+
+    let snk = fg.kernel::<VectorSink<f32>>(snk).unwrap();
+    let v = snk.items();
+    assert!(!v.is_empty());
+    assert!((v.last().unwrap() - 1.0).abs() < f32::EPSILON);
+    assert!(v
+        .iter()
+        .all(|x| x.abs() < f32::EPSILON || (x - 1.0).abs() < f32::EPSILON));
+
+    Ok(())
+}
+
+#[test]
+fn add_remove_message() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(MessageSourceBuilder::new(Pmt::Null, Duration::from_millis(10)).build());
+    let snk = fg.add_block(MessageSink::new());
+    fg.connect_message(src, "out", snk, "in")?;
+
+    let rt = Runtime::new();
+    let (task, mut handle) = block_on(rt.start(fg));
+
+    let fg = block_on(async move {
+        let recorder = handle.add_block(MessageSink::new()).await?;
+        handle.connect_message(src, "out", recorder, "in").await?;
+        Timer::after(Duration::from_millis(200)).await;
+        handle
+            .disconnect_message(src, "out", recorder, "in")
+            .await?;
+
+        let recorder = handle.remove_block(recorder).await?;
+        let recorder = recorder.kernel::<MessageSink>().unwrap();
+        assert!(recorder.received() > 0);
+
+        handle.terminate().await?;
+        task.await
+    })?;
+
+    let snk = fg.kernel::<MessageSink>(snk).unwrap();
+    assert!(snk.received() > 0);
+
+    Ok(())
+}
+
+#[test]
+fn invalid_reconfiguration() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(NullSource::<f32>::new());
+    let throttle = fg.add_block(Throttle::<f32>::new(1e5));
+    let snk = fg.add_block(NullSink::<f32>::new());
+
+    fg.connect_stream(src, "out", throttle, "in")?;
+    fg.connect_stream(throttle, "out", snk, "in")?;
+
+    let rt = Runtime::new();
+    let (task, mut handle) = block_on(rt.start(fg));
+
+    block_on(async move {
+        // input is already connected
+        assert!(handle.connect_stream(src, "out", snk, "in").await.is_err());
+        // invalid port
+        let other = handle.add_block(NullSink::<f32>::new()).await?;
+        assert!(handle
+            .connect_stream(throttle, "foo", other, "in")
+            .await
+            .is_err());
+        // item types do not match
+        let wrong = handle.add_block(NullSink::<u8>::new()).await?;
+        assert!(handle
+            .connect_stream(throttle, "out", wrong, "in")
+            .await
+            .is_err());
+        // not connected
+        assert!(handle
+            .disconnect_stream(src, "out", snk, "in")
+            .await
+            .is_err());
+        // invalid block
+        assert!(handle.remove_block(1234).await.is_err());
+
+        // blocks that were never started are shut down on terminate
+        handle.terminate().await?;
+        task.await
+    })?;
+
+    Ok(())
+}
@@ -0,0 +1,204 @@
+#![cfg(unix)]
+use std::iter::repeat_with;
+
+use futuresdr::anyhow::Result;
+use futuresdr::async_trait::async_trait;
+use futuresdr::blocks::Copy;
+use futuresdr::blocks::ShmSink;
+use futuresdr::blocks::ShmSource;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::runtime::buffer::shm::Shm;
+use futuresdr::runtime::Block;
+use futuresdr::runtime::BlockMeta;
+use futuresdr::runtime::BlockMetaBuilder;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Kernel;
+use futuresdr::runtime::MessageIo;
+use futuresdr::runtime::MessageIoBuilder;
+use futuresdr::runtime::Runtime;
+use futuresdr::runtime::StreamIo;
+use futuresdr::runtime::StreamIoBuilder;
+use futuresdr::runtime::Tag;
+use futuresdr::runtime::WorkIo;
+
+/// Produces `0..n`, tagging every 100th item with its offset.
+struct TagSource {
+    n: u64,
+}
+
+impl TagSource {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(n: u64) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("TagSource").build(),
+            StreamIoBuilder::new().add_output::<u32>("out").build(),
+            MessageIoBuilder::new().build(),
+            Self { n },
+        )
+    }
+}
+
+#[async_trait]
+impl Kernel for TagSource {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _m: &mut MessageIo<Self>,
+        _b: &mut BlockMeta,
+    ) -> Result<()> {
+        let o = sio.output(0).slice::<u32>();
+        let start = sio.output(0).items_produced();
+        let n = std::cmp::min(o.len() as u64, self.n - start) as usize;
+
+        for (i, v) in o[..n].iter_mut().enumerate() {
+            let offset = start + i as u64;
+            *v = offset as u32;
+            if offset % 100 == 0 {
+                sio.output(0).add_tag(i, Tag::Id(offset));
+            }
+        }
+        sio.output(0).produce(n);
+
+        if sio.output(0).items_produced() + n as u64 == self.n {
+            io.finished = true;
+        }
+        Ok(())
+    }
+}
+
+/// Checks that items are `0, 1, 2, ...` and that every 100th item is tagged with its offset.
+struct TagSink {
+    received: u64,
+    tags: u64,
+}
+
+impl TagSink {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> Block {
+        Block::new(
+            BlockMetaBuilder::new("TagSink").build(),
+            StreamIoBuilder::new().add_input::<u32>("in").build(),
+            MessageIoBuilder::new().build(),
+            Self {
+                received: 0,
+                tags: 0,
+            },
+        )
+    }
+}
+
+#[async_trait]
+impl Kernel for TagSink {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _m: &mut MessageIo<Self>,
+        _b: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<u32>();
+        for (n, v) in i.iter().enumerate() {
+            assert_eq!(*v as u64, self.received + n as u64);
+        }
+        for t in sio.input(0).tags().iter().filter(|t| t.index < i.len()) {
+            let offset = self.received + t.index as u64;
+            assert_eq!(offset % 100, 0);
+            assert!(matches!(t.tag, Tag::Id(o) if o == offset));
+            self.tags += 1;
+        }
+        self.received += i.len() as u64;
+        sio.input(0).consume(i.len());
+
+        if sio.input(0).finished() {
+            io.finished = true;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn shm_buffer() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let orig: Vec<f32> = repeat_with(rand::random::<f32>).take(123_456).collect();
+    let src = fg.add_block(VectorSource::<f32>::new(orig.clone()));
+    let copy = fg.add_block(Copy::<f32>::new());
+    let snk0 = fg.add_block(VectorSinkBuilder::<f32>::new().build());
+    let snk1 = fg.add_block(VectorSinkBuilder::<f32>::new().build());
+
+    fg.connect_stream_with_type(src, "out", copy, "in", Shm::with_size(4096))?;
+    fg.connect_stream_with_type(copy, "out", snk0, "in", Shm::new())?;
+    fg.connect_stream_with_type(copy, "out", snk1, "in", Shm::new())?;
+
+    let fg = Runtime::new().run(fg)?;
+
+    for snk in [snk0, snk1] {
+        let snk = fg.kernel::<VectorSink<f32>>(snk).unwrap();
+        assert_eq!(snk.items(), &orig);
+    }
+    Ok(())
+}
+
+#[test]
+fn shm_buffer_tags() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(TagSource::new(100_000));
+    let snk = fg.add_block(TagSink::new());
+    fg.connect_stream_with_type(src, "out", snk, "in", Shm::with_size(4096))?;
+
+    let fg = Runtime::new().run(fg)?;
+
+    let snk = fg.kernel::<TagSink>(snk).unwrap();
+    assert_eq!(snk.received, 100_000);
+    assert_eq!(snk.tags, 1_000);
+    Ok(())
+}
+
+#[test]
+fn shm_sink_source() -> Result<()> {
+    let name = format!("futuresdr-test-{}", std::process::id());
+
+    // the flowgraphs only share the name of the segment, like flowgraphs in separate processes
+    let writer = {
+        let name = name.clone();
+        std::thread::spawn(move || -> Result<()> {
+            let mut fg = Flowgraph::new();
+            let src = fg.add_block(TagSource::new(100_000));
+            let snk = fg.add_block(ShmSink::<u32>::with_size(name, 4096));
+            fg.connect_stream(src, "out", snk, "in")?;
+            Runtime::new().run(fg)?;
+            Ok(())
+        })
+    };
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(ShmSource::<u32>::new(name));
+    let snk = fg.add_block(TagSink::new());
+    fg.connect_stream(src, "out", snk, "in")?;
+    let fg = Runtime::new().run(fg)?;
+
+    writer.join().unwrap()?;
+
+    let snk = fg.kernel::<TagSink>(snk).unwrap();
+    assert_eq!(snk.received, 100_000);
+    assert_eq!(snk.tags, 1_000);
+    Ok(())
+}
+
+#[test]
+fn shm_sink_without_source() -> Result<()> {
+    let name = format!("futuresdr-test-nosrc-{}", std::process::id());
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(VectorSource::<f32>::new(vec![0.0; 1000]));
+    let snk = fg.add_block(ShmSink::<f32>::new(name));
+    fg.connect_stream(src, "out", snk, "in")?;
+
+    // finishes, although no source ever attaches
+    Runtime::new().run(fg)?;
+    Ok(())
+}
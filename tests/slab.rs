@@ -1,15 +1,124 @@
 use std::iter::repeat_with;
 
 use futuresdr::anyhow::Result;
+use futuresdr::async_trait::async_trait;
+use futuresdr::blocks::ApplyInPlace;
 use futuresdr::blocks::Copy;
+use futuresdr::blocks::FirBuilder;
 use futuresdr::blocks::Head;
 use futuresdr::blocks::NullSource;
 use futuresdr::blocks::VectorSink;
 use futuresdr::blocks::VectorSinkBuilder;
 use futuresdr::blocks::VectorSource;
 use futuresdr::runtime::buffer::slab::Slab;
+use futuresdr::runtime::Block;
+use futuresdr::runtime::BlockMeta;
+use futuresdr::runtime::BlockMetaBuilder;
 use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Kernel;
+use futuresdr::runtime::MessageIo;
+use futuresdr::runtime::MessageIoBuilder;
 use futuresdr::runtime::Runtime;
+use futuresdr::runtime::StreamIo;
+use futuresdr::runtime::StreamIoBuilder;
+use futuresdr::runtime::Tag;
+use futuresdr::runtime::WorkIo;
+
+/// Produces `n` items, tagging every 100th item with its absolute offset.
+struct TagSource {
+    n: u64,
+}
+
+impl TagSource {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(n: u64) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("TagSource").build(),
+            StreamIoBuilder::new().add_output::<f32>("out").build(),
+            MessageIoBuilder::new().build(),
+            Self { n },
+        )
+    }
+}
+
+#[async_trait]
+impl Kernel for TagSource {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _m: &mut MessageIo<Self>,
+        _b: &mut BlockMeta,
+    ) -> Result<()> {
+        let o = sio.output(0).slice::<f32>();
+        let start = sio.output(0).items_produced();
+        let n = std::cmp::min(o.len() as u64, self.n - start) as usize;
+
+        for (i, v) in o[..n].iter_mut().enumerate() {
+            let offset = start + i as u64;
+            *v = offset as f32;
+            if offset % 100 == 0 {
+                sio.output(0).add_tag(i, Tag::Id(offset));
+            }
+        }
+        sio.output(0).produce(n);
+
+        if start + n as u64 == self.n {
+            io.finished = true;
+        }
+        Ok(())
+    }
+}
+
+/// Checks the items and records the absolute offsets of the received tags.
+struct TagSink {
+    n_items: u64,
+    tags: Vec<(u64, u64)>,
+}
+
+impl TagSink {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> Block {
+        Block::new(
+            BlockMetaBuilder::new("TagSink").build(),
+            StreamIoBuilder::new().add_input::<f32>("in").build(),
+            MessageIoBuilder::new().build(),
+            Self {
+                n_items: 0,
+                tags: Vec::new(),
+            },
+        )
+    }
+}
+
+#[async_trait]
+impl Kernel for TagSink {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _m: &mut MessageIo<Self>,
+        _b: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<f32>();
+        let start = sio.input(0).items_consumed();
+        for (k, v) in i.iter().enumerate() {
+            assert_eq!(*v, (start + k as u64) as f32 * 2.0);
+        }
+        for t in sio.input(0).tags().iter().filter(|x| x.index < i.len()) {
+            if let Tag::Id(id) = t.tag {
+                self.tags.push((start + t.index as u64, id));
+            }
+        }
+        self.n_items += i.len() as u64;
+        sio.input(0).consume(i.len());
+
+        if sio.input(0).finished() {
+            io.finished = true;
+        }
+        Ok(())
+    }
+}
 
 #[test]
 fn flowgraph() -> Result<()> {
@@ -72,3 +181,104 @@ fn fg_rand_vec() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn fan_out() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let n_items = 1_000_000;
+    let orig: Vec<f32> = repeat_with(rand::random::<f32>).take(n_items).collect();
+    let mut taps = vec![0.0f32; 16];
+    taps[0] = 1.0;
+
+    let src = fg.add_block(VectorSource::<f32>::new(orig.clone()));
+    let fir = fg.add_block(FirBuilder::new::<f32, f32, f32, _>(taps));
+    let fir_snk = fg.add_block(VectorSinkBuilder::<f32>::new().build());
+    fg.connect_stream_with_type(src, "out", fir, "in", Slab::new())?;
+    fg.connect_stream_with_type(fir, "out", fir_snk, "in", Slab::new())?;
+
+    let mut snks = Vec::new();
+    for _ in 0..3 {
+        let snk = fg.add_block(VectorSinkBuilder::<f32>::new().build());
+        fg.connect_stream_with_type(src, "out", snk, "in", Slab::new())?;
+        snks.push(snk);
+    }
+
+    fg = Runtime::new().run(fg)?;
+
+    for snk in snks {
+        let snk = fg.kernel::<VectorSink<f32>>(snk).unwrap();
+        assert_eq!(snk.items(), &orig);
+    }
+
+    // the fir keeps samples in the buffer for its history, i.e., it reads across chunks
+    let snk = fg.kernel::<VectorSink<f32>>(fir_snk).unwrap();
+    assert_eq!(snk.items().len(), n_items - 15);
+    assert_eq!(snk.items()[..], orig[15..]);
+
+    Ok(())
+}
+
+#[test]
+fn in_place() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let n_items = 1_000_000;
+    let orig: Vec<u32> = repeat_with(rand::random::<u32>)
+        .map(|x| x % 1000)
+        .take(n_items)
+        .collect();
+
+    let src = fg.add_block(VectorSource::<u32>::new(orig.clone()));
+    let add = fg.add_block(ApplyInPlace::new(|x: &mut u32| *x += 1));
+    let mul = fg.add_block(ApplyInPlace::new(|x: &mut u32| *x *= 2));
+    let snk = fg.add_block(VectorSinkBuilder::<u32>::new().build());
+    let copy_snk = fg.add_block(VectorSinkBuilder::<u32>::new().build());
+
+    fg.connect_stream_with_type(src, "out", add, "in", Slab::new())?;
+    fg.connect_stream_with_type(add, "out", mul, "in", Slab::in_place())?;
+    fg.connect_stream_with_type(mul, "out", snk, "in", Slab::in_place())?;
+    // a second reader shares the chunks, i.e., `mul` has to copy
+    fg.connect_stream_with_type(add, "out", copy_snk, "in", Slab::in_place())?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let snk = fg.kernel::<VectorSink<u32>>(snk).unwrap();
+    assert_eq!(snk.items().len(), n_items);
+    for (o, i) in snk.items().iter().zip(orig.iter()) {
+        assert_eq!(*o, (i + 1) * 2);
+    }
+
+    let snk = fg.kernel::<VectorSink<u32>>(copy_snk).unwrap();
+    assert_eq!(snk.items().len(), n_items);
+    for (o, i) in snk.items().iter().zip(orig.iter()) {
+        assert_eq!(*o, i + 1);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn in_place_tags() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let n_items = 100_000;
+    let src = fg.add_block(TagSource::new(n_items));
+    let apply = fg.add_block(ApplyInPlace::new(|x: &mut f32| *x *= 2.0));
+    let snk = fg.add_block(TagSink::new());
+
+    fg.connect_stream_with_type(src, "out", apply, "in", Slab::new())?;
+    fg.connect_stream_with_type(apply, "out", snk, "in", Slab::in_place())?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let snk = fg.kernel::<TagSink>(snk).unwrap();
+    assert_eq!(snk.n_items, n_items);
+    assert_eq!(snk.tags.len(), n_items as usize / 100);
+    for (i, (offset, id)) in snk.tags.iter().enumerate() {
+        assert_eq!(*offset, i as u64 * 100);
+        assert_eq!(offset, id);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,63 @@
+use std::time::Duration;
+
+use futuresdr::anyhow::Result;
+use futuresdr::async_io::block_on;
+use futuresdr::async_io::Timer;
+use futuresdr::blocks::MessageSink;
+use futuresdr::blocks::MessageSourceBuilder;
+use futuresdr::blocks::NullSink;
+use futuresdr::blocks::NullSource;
+use futuresdr::blocks::Throttle;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+
+#[test]
+fn block_stats() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(NullSource::<f32>::new());
+    let throttle = fg.add_block(Throttle::<f32>::new(1e6));
+    let snk = fg.add_block(NullSink::<f32>::new());
+    let msg_src =
+        fg.add_block(MessageSourceBuilder::new(Pmt::Null, Duration::from_millis(10)).build());
+    let msg_snk = fg.add_block(MessageSink::new());
+
+    fg.connect_stream(src, "out", throttle, "in")?;
+    fg.connect_stream(throttle, "out", snk, "in")?;
+    fg.connect_message(msg_src, "out", msg_snk, "in")?;
+
+    let rt = Runtime::new();
+    let (task, mut handle) = block_on(rt.start(fg));
+
+    block_on(async move {
+        Timer::after(Duration::from_millis(200)).await;
+
+        let s = handle.block_stats(throttle).await?;
+        assert_eq!(s.id, throttle);
+        assert!(s.work_calls > 0);
+        assert!(s.work_time > Duration::ZERO);
+        assert_eq!(s.stream_inputs.len(), 1);
+        assert_eq!(s.stream_outputs.len(), 1);
+        assert_eq!(s.stream_inputs[0].name, "in");
+        assert!(s.stream_inputs[0].items_consumed > 0);
+        assert!(s.stream_outputs[0].items_produced > 0);
+
+        let s = handle.block_stats(snk).await?;
+        assert!(s.stream_inputs[0].items_consumed > 0);
+        assert!(s.stream_outputs.is_empty());
+
+        let s = handle.block_stats(msg_snk).await?;
+        assert!(s.messages_handled > 0);
+
+        let s = handle.stats().await?;
+        assert_eq!(s.blocks.len(), 5);
+
+        assert!(handle.block_stats(1234).await.is_err());
+
+        handle.terminate().await?;
+        task.await
+    })?;
+
+    Ok(())
+}
@@ -0,0 +1,76 @@
+use futures::StreamExt;
+use std::time::Duration;
+
+use futuresdr::anyhow::Result;
+use futuresdr::async_io::block_on;
+use futuresdr::async_io::Timer;
+use futuresdr::blocks::MessageSink;
+use futuresdr::blocks::MessageSource;
+use futuresdr::blocks::MessageSourceBuilder;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::FlowgraphEvent;
+use futuresdr::runtime::FlowgraphState;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+
+#[test]
+fn subscribe_message() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(MessageSource::new(
+        Pmt::U32(123),
+        Duration::from_millis(20),
+        Some(10),
+    ));
+    let snk = fg.add_block(MessageSink::new());
+    fg.connect_message(src, "out", snk, "in")?;
+
+    let rt = Runtime::new();
+    let (task, mut handle) = block_on(rt.start(fg));
+
+    block_on(async move {
+        let messages = handle.subscribe_message(src, "out").await?;
+        // the stream ends, once the port is finished
+        let messages: Vec<Pmt> = messages.collect().await;
+        assert!(!messages.is_empty());
+        assert!(messages.iter().all(|p| matches!(p, Pmt::U32(123))));
+
+        assert!(handle.subscribe_message(src, "foo").await.is_err());
+        assert!(handle.subscribe_message(1234, "out").await.is_err());
+
+        let fg = task.await?;
+        let snk = fg.kernel::<MessageSink>(snk).unwrap();
+        assert_eq!(snk.received(), 10);
+        Ok(())
+    })
+}
+
+#[test]
+fn subscribe_events() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(MessageSourceBuilder::new(Pmt::Null, Duration::from_millis(10)).build());
+    let snk = fg.add_block(MessageSink::new());
+    fg.connect_message(src, "out", snk, "in")?;
+
+    let rt = Runtime::new();
+    let (task, mut handle) = block_on(rt.start(fg));
+
+    block_on(async move {
+        let events = handle.subscribe_events().await?;
+        assert_eq!(handle.state(), FlowgraphState::Running);
+        Timer::after(Duration::from_millis(50)).await;
+        handle.terminate().await?;
+
+        // the stream ends, once the flowgraph is done
+        let events: Vec<FlowgraphEvent> = events.collect().await;
+        assert_eq!(events[0], FlowgraphEvent::Terminate);
+        for block_id in [src, snk] {
+            assert!(events.contains(&FlowgraphEvent::BlockDone { block_id }));
+        }
+
+        task.await?;
+        assert_eq!(handle.state(), FlowgraphState::Finished);
+        Ok(())
+    })
+}
@@ -0,0 +1,283 @@
+use futuresdr::anyhow::Result;
+use futuresdr::async_trait::async_trait;
+use futuresdr::blocks::Apply;
+use futuresdr::blocks::Copy;
+use futuresdr::blocks::FirBuilder;
+use futuresdr::runtime::Block;
+use futuresdr::runtime::BlockMeta;
+use futuresdr::runtime::BlockMetaBuilder;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Kernel;
+use futuresdr::runtime::MessageIo;
+use futuresdr::runtime::MessageIoBuilder;
+use futuresdr::runtime::Runtime;
+use futuresdr::runtime::SampleClock;
+use futuresdr::runtime::StreamIo;
+use futuresdr::runtime::StreamIoBuilder;
+use futuresdr::runtime::Tag;
+use futuresdr::runtime::WorkIo;
+
+/// Produces `n` items, tagging every 100th item with its absolute offset.
+struct TagSource {
+    n: u64,
+}
+
+impl TagSource {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(n: u64) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("TagSource").build(),
+            StreamIoBuilder::new().add_output::<f32>("out").build(),
+            MessageIoBuilder::new().build(),
+            Self { n },
+        )
+    }
+}
+
+#[async_trait]
+impl Kernel for TagSource {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _m: &mut MessageIo<Self>,
+        _b: &mut BlockMeta,
+    ) -> Result<()> {
+        let o = sio.output(0).slice::<f32>();
+        let start = sio.output(0).items_produced();
+        let n = std::cmp::min(o.len() as u64, self.n - start) as usize;
+
+        for (i, v) in o[..n].iter_mut().enumerate() {
+            let offset = start + i as u64;
+            *v = offset as f32;
+            if offset % 100 == 0 {
+                sio.output(0).add_tag(i, Tag::Id(offset));
+            }
+        }
+        sio.output(0).produce(n);
+
+        if start + n as u64 == self.n {
+            io.finished = true;
+        }
+        Ok(())
+    }
+}
+
+/// Records the absolute offsets of the received tags.
+struct TagSink {
+    tags: Vec<(u64, u64)>,
+}
+
+impl TagSink {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> Block {
+        Block::new(
+            BlockMetaBuilder::new("TagSink").build(),
+            StreamIoBuilder::new().add_input::<f32>("in").build(),
+            MessageIoBuilder::new().build(),
+            Self { tags: Vec::new() },
+        )
+    }
+}
+
+#[async_trait]
+impl Kernel for TagSink {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _m: &mut MessageIo<Self>,
+        _b: &mut BlockMeta,
+    ) -> Result<()> {
+        let n = sio.input(0).slice::<f32>().len();
+        let start = sio.input(0).items_consumed();
+        for t in sio.input(0).tags().iter().filter(|x| x.index < n) {
+            if let Tag::Id(id) = t.tag {
+                self.tags.push((start + t.index as u64, id));
+            }
+        }
+        sio.input(0).consume(n);
+
+        if sio.input(0).finished() {
+            io.finished = true;
+        }
+        Ok(())
+    }
+}
+
+/// Produces `n` items with a time tag at the start and a rate change at `n / 2`.
+struct ClockSource {
+    n: u64,
+}
+
+impl ClockSource {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(n: u64) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("ClockSource").build(),
+            StreamIoBuilder::new().add_output::<f32>("out").build(),
+            MessageIoBuilder::new().build(),
+            Self { n },
+        )
+    }
+}
+
+#[async_trait]
+impl Kernel for ClockSource {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _m: &mut MessageIo<Self>,
+        _b: &mut BlockMeta,
+    ) -> Result<()> {
+        let o = sio.output(0).slice::<f32>();
+        let start = sio.output(0).items_produced();
+        let n = std::cmp::min(o.len() as u64, self.n - start) as usize;
+
+        for i in 0..n {
+            let offset = start + i as u64;
+            if offset == 0 {
+                sio.output(0)
+                    .add_tag(i, Tag::NamedI64(Tag::RX_TIME.to_string(), 1_000_000_000));
+                sio.output(0)
+                    .add_tag(i, Tag::NamedF64(Tag::RX_RATE.to_string(), 1e6));
+            } else if offset == self.n / 2 {
+                sio.output(0)
+                    .add_tag(i, Tag::NamedF64(Tag::RX_RATE.to_string(), 2e6));
+            }
+        }
+        sio.output(0).produce(n);
+
+        if start + n as u64 == self.n {
+            io.finished = true;
+        }
+        Ok(())
+    }
+}
+
+/// Records the time of every item.
+struct ClockSink {
+    clock: SampleClock,
+    times: Vec<Option<i64>>,
+}
+
+impl ClockSink {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> Block {
+        Block::new(
+            BlockMetaBuilder::new("ClockSink").build(),
+            StreamIoBuilder::new().add_input::<f32>("in").build(),
+            MessageIoBuilder::new().build(),
+            Self {
+                clock: SampleClock::new(),
+                times: Vec::new(),
+            },
+        )
+    }
+}
+
+#[async_trait]
+impl Kernel for ClockSink {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _m: &mut MessageIo<Self>,
+        _b: &mut BlockMeta,
+    ) -> Result<()> {
+        let n = sio.input(0).slice::<f32>().len();
+        let start = sio.input(0).items_consumed();
+        self.clock.update(sio.input(0));
+        for i in 0..n as u64 {
+            self.times.push(self.clock.time_ns(start + i));
+        }
+        sio.input(0).consume(n);
+
+        if sio.input(0).finished() {
+            io.finished = true;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn one_to_one() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(TagSource::new(10_000));
+    let copy = fg.add_block(Copy::<f32>::new());
+    let apply = fg.add_block(Apply::new(|x: &f32| x + 1.0));
+    let snk = fg.add_block(TagSink::new());
+
+    fg.connect_stream(src, "out", copy, "in")?;
+    fg.connect_stream(copy, "out", apply, "in")?;
+    fg.connect_stream(apply, "out", snk, "in")?;
+
+    let fg = Runtime::new().run(fg)?;
+
+    let snk = fg.kernel::<TagSink>(snk).unwrap();
+    assert_eq!(snk.tags.len(), 100);
+    for (i, (offset, id)) in snk.tags.iter().enumerate() {
+        assert_eq!(*offset, i as u64 * 100);
+        assert_eq!(offset, id);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn rate_change() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(TagSource::new(10_000));
+    let fir = fg.add_block(FirBuilder::new_resampling_with_taps::<f32, f32, f32, _>(
+        1,
+        4,
+        vec![1.0f32],
+    ));
+    let snk = fg.add_block(TagSink::new());
+
+    fg.connect_stream(src, "out", fir, "in")?;
+    fg.connect_stream(fir, "out", snk, "in")?;
+
+    let fg = Runtime::new().run(fg)?;
+
+    let snk = fg.kernel::<TagSink>(snk).unwrap();
+    assert_eq!(snk.tags.len(), 100);
+    for (i, (offset, id)) in snk.tags.iter().enumerate() {
+        assert_eq!(*id, i as u64 * 100);
+        assert_eq!(*offset, id / 4);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn sample_clock() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(ClockSource::new(10_000));
+    let copy = fg.add_block(Copy::<f32>::new());
+    let snk = fg.add_block(ClockSink::new());
+
+    fg.connect_stream(src, "out", copy, "in")?;
+    fg.connect_stream(copy, "out", snk, "in")?;
+
+    let fg = Runtime::new().run(fg)?;
+
+    let snk = fg.kernel::<ClockSink>(snk).unwrap();
+    assert_eq!(snk.times.len(), 10_000);
+    for (i, t) in snk.times.iter().enumerate() {
+        let i = i as i64;
+        let expected = if i < 5_000 {
+            1_000_000_000 + i * 1_000
+        } else {
+            1_005_000_000 + (i - 5_000) * 500
+        };
+        assert_eq!(*t, Some(expected));
+    }
+    assert_eq!(snk.clock.sample_rate(9_999), Some(2e6));
+
+    Ok(())
+}
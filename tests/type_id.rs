@@ -1,6 +1,12 @@
+use futuresdr::anyhow::Result;
 use futuresdr::blocks::NullSink;
 use futuresdr::blocks::NullSource;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::num_complex::Complex32;
 use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Runtime;
 
 #[test]
 #[should_panic]
@@ -12,3 +18,45 @@ fn type_id() {
 
     fg.connect_stream(src, "out", snk, "in").unwrap();
 }
+
+#[test]
+fn type_mismatch_error() {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(NullSource::<f32>::new());
+    let snk = fg.add_block(NullSink::<u32>::new());
+
+    let e = fg.connect_stream(src, "out", snk, "in").unwrap_err();
+    let e = e.to_string();
+    assert!(e.contains("NullSource_0.out (f32)"));
+    assert!(e.contains("NullSink_0.in (u32)"));
+}
+
+#[test]
+fn reinterpret() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let items = vec![Complex32::new(1.0, 2.0), Complex32::new(3.0, 4.0)];
+    let src = fg.add_block(VectorSource::new(items));
+    let snk = fg.add_block(VectorSinkBuilder::<[f32; 2]>::new().build());
+
+    assert!(fg.connect_stream(src, "out", snk, "in").is_err());
+    fg.connect_stream_reinterpret(src, "out", snk, "in")?;
+
+    let fg = Runtime::new().run(fg)?;
+    let snk = fg.kernel::<VectorSink<[f32; 2]>>(snk).unwrap();
+    assert_eq!(snk.items(), &vec![[1.0, 2.0], [3.0, 4.0]]);
+
+    let spec = fg.to_spec()?;
+    assert!(spec.stream_edges[0].reinterpret);
+
+    // item sizes have to match
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(NullSource::<f32>::new());
+    let snk = fg.add_block(NullSink::<f64>::new());
+    assert!(fg
+        .connect_stream_reinterpret(src, "out", snk, "in")
+        .is_err());
+
+    Ok(())
+}